@@ -0,0 +1,59 @@
+//! Benchmark of the decoding of `PGS` images.
+
+// The functions generated by `criterion_group` are not documented.
+#![expect(missing_docs)]
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::{fs::File, hint::black_box, io::BufReader};
+use subtile::{
+    image::{luma_a_to_luma, ToImage as _, ToOcrImage as _, ToOcrImageOpt},
+    pgs::{DecodeTimeImage, RleEncodedImage, RleToImage, SupParser},
+};
+
+fn load_image() -> RleEncodedImage {
+    let mut parser =
+        SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+            .unwrap();
+    parser.next().unwrap().unwrap().1
+}
+
+fn decode(c: &mut Criterion) {
+    let image = load_image();
+
+    let mut group = c.benchmark_group("pgs_decode");
+    group.bench_function("pixel_iterator", |b| {
+        b.iter(|| black_box(&image).iter().collect::<Vec<_>>());
+    });
+    group.bench_function("decode_indexed", |b| {
+        b.iter(|| black_box(&image).decode_indexed());
+    });
+    group.bench_function("decode_indexed_lut", |b| {
+        b.iter(|| {
+            let image = black_box(&image);
+            let lut = image.palette_lut(|pixel| pixel);
+            image
+                .decode_indexed()
+                .into_iter()
+                .map(|index| lut[usize::from(index)])
+                .collect::<Vec<_>>()
+        });
+    });
+    group.finish();
+}
+
+fn convert(c: &mut Criterion) {
+    let image = load_image();
+    let opt = ToOcrImageOpt::default();
+
+    let mut group = c.benchmark_group("pgs_convert");
+    group.bench_function("to_image", |b| {
+        b.iter(|| RleToImage::new(black_box(&image), |pixel| pixel).to_image());
+    });
+    group.bench_function("to_ocr_image", |b| {
+        b.iter(|| RleToImage::new(black_box(&image), luma_a_to_luma::<_, _, 100, 100>).image(&opt));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode, convert);
+criterion_main!(benches);
@@ -0,0 +1,1062 @@
+//! Advanced `SubStation` Alpha (`ASS`) and `SubStation` Alpha (`SSA`) functionality.
+//!
+//! A script is made of the `[Script Info]`, `[V4+ Styles]` (`[V4 Styles]` for `SSA`)
+//! and `[Events]` sections. Other sections (like `[Fonts]` or `[Graphics]`) are kept verbatim
+//! and written back after the events.
+//!
+//! Specification: <http://www.tcax.org/docs/ass-specs.htm>
+mod tags;
+
+pub use tags::{escape_text, plain_text, tokenize, OverrideTag, TextToken};
+
+pub(crate) use tags::cue_rows;
+
+use crate::{
+    content::{Area, Size},
+    text::{self, CuePosition},
+    time::{TimePoint, TimeSpan},
+};
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// Script type of the written scripts, matching the styles format.
+const SCRIPT_TYPE: &str = "v4.00+";
+/// Fields of the styles, in the order used when writing.
+const STYLE_FORMAT: &str = "Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, \
+    OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, \
+    Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding";
+/// Fields of the events, in the order used when writing.
+const EVENT_FORMAT: &str =
+    "Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+/// Name of the default style.
+const DEFAULT_STYLE: &str = "Default";
+
+/// Error for `ASS` handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AssError {
+    /// If an error happen during the file opening.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("failed to read the script")]
+    Read(#[source] io::Error),
+
+    /// A line of a section is used before the `Format` line of the section.
+    #[error("line {line}: missing 'Format' line before the section content")]
+    MissingFormat {
+        /// Line number (starting at 1).
+        line: usize,
+    },
+
+    /// A line has not the number of fields of the `Format` line.
+    #[error("line {line}: expected {expected} fields")]
+    MissingFields {
+        /// Line number (starting at 1).
+        line: usize,
+        /// Number of fields of the `Format` line.
+        expected: usize,
+    },
+
+    /// A field has an invalid value.
+    #[error("line {line}: invalid value '{value}' for field '{field}'")]
+    InvalidField {
+        /// Line number (starting at 1).
+        line: usize,
+        /// Name of the field.
+        field: String,
+        /// Value of the field.
+        value: String,
+    },
+}
+
+/// Extend `TimePoint` for implement `ASS` specific `Display` (`H:MM:SS.cc`).
+///
+/// The time is rounded to centiseconds, and negative times are written as zero.
+#[repr(transparent)]
+pub struct TimePointAss(TimePoint);
+
+impl From<TimePoint> for TimePointAss {
+    fn from(value: TimePoint) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for TimePointAss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let centis = (self.0.msecs().max(0) + 5) / 10;
+        write!(
+            f,
+            "{}:{:02}:{:02}.{:02}",
+            centis / 360_000,
+            centis / 6000 % 60,
+            centis / 100 % 60,
+            centis % 100
+        )
+    }
+}
+
+/// Parse a time in `H:MM:SS.cc` format.
+fn parse_time(value: &str) -> Option<TimePoint> {
+    let (hours, rest) = value.trim().split_once(':')?;
+    let (mins, secs) = rest.split_once(':')?;
+    let (secs, fraction) = secs.split_once('.').unwrap_or((secs, "0"));
+    let number = |value: &str| {
+        (!value.is_empty() && value.bytes().all(|c| c.is_ascii_digit()))
+            .then(|| value.parse::<i64>().ok())
+            .flatten()
+    };
+    // The fraction is in centiseconds, but some tools write more or less digits.
+    let fraction_msecs = number(fraction)?.checked_mul(1000)?
+        / 10_i64.checked_pow(u32::try_from(fraction.len()).ok()?)?;
+    let msecs = number(hours)?
+        .checked_mul(60)?
+        .checked_add(number(mins)?)?
+        .checked_mul(60)?
+        .checked_add(number(secs)?)?
+        .checked_mul(1000)?
+        .checked_add(fraction_msecs)?;
+    Some(TimePoint::from_msecs(msecs))
+}
+
+/// A color, as used in `ASS` styles and tags (`&HAABBGGRR`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    /// Red component.
+    pub red: u8,
+    /// Green component.
+    pub green: u8,
+    /// Blue component.
+    pub blue: u8,
+    /// Transparency, `0` is opaque and `255` fully transparent.
+    pub alpha: u8,
+}
+
+impl Color {
+    /// Create an opaque color.
+    #[must_use]
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha: 0,
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = std::num::ParseIntError;
+
+    /// Parse a color in `&HAABBGGRR` format, or as a decimal number as in `SSA` files.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().trim_end_matches('&');
+        let value = if let Some(hex) = value
+            .strip_prefix("&H")
+            .or_else(|| value.strip_prefix("&h"))
+        {
+            u32::from_str_radix(hex, 16)?
+        } else {
+            // Decimal values can be negative, to have the alpha bits set.
+            value.parse::<u32>().or_else(|_| {
+                value
+                    .parse::<i32>()
+                    .map(|value| u32::from_be_bytes(value.to_be_bytes()))
+            })?
+        };
+        let [alpha, blue, green, red] = value.to_be_bytes();
+        Ok(Self {
+            red,
+            green,
+            blue,
+            alpha,
+        })
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "&H{:02X}{:02X}{:02X}{:02X}",
+            self.alpha, self.blue, self.green, self.red
+        )
+    }
+}
+
+/// A style of a script, from the `[V4+ Styles]` section.
+#[derive(Debug, Clone, PartialEq)]
+#[expect(clippy::struct_excessive_bools)]
+pub struct Style {
+    /// Name of the style, used by the events.
+    pub name: String,
+    /// Name of the font.
+    pub font_name: String,
+    /// Size of the font.
+    pub font_size: f64,
+    /// Color of the text.
+    pub primary_color: Color,
+    /// Color of the text before the karaoke highlighting.
+    pub secondary_color: Color,
+    /// Color of the outline.
+    pub outline_color: Color,
+    /// Color of the shadow (or of the box with opaque box border style).
+    pub back_color: Color,
+    /// If the text is bold.
+    pub bold: bool,
+    /// If the text is italic.
+    pub italic: bool,
+    /// If the text is underlined.
+    pub underline: bool,
+    /// If the text is striked out.
+    pub strike_out: bool,
+    /// Horizontal scale, in percent.
+    pub scale_x: f64,
+    /// Vertical scale, in percent.
+    pub scale_y: f64,
+    /// Extra space between characters, in pixels.
+    pub spacing: f64,
+    /// Rotation around the `z` axis, in degrees.
+    pub angle: f64,
+    /// `1` for outline and drop shadow, `3` for opaque box.
+    pub border_style: u8,
+    /// Width of the outline, in pixels.
+    pub outline: f64,
+    /// Depth of the shadow, in pixels.
+    pub shadow: f64,
+    /// Alignment, in numpad layout (`1` to `9`).
+    pub alignment: u8,
+    /// Left margin, in pixels.
+    pub margin_l: i32,
+    /// Right margin, in pixels.
+    pub margin_r: i32,
+    /// Vertical margin, in pixels.
+    pub margin_v: i32,
+    /// Font character set.
+    pub encoding: i32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_STYLE.into(),
+            font_name: "Arial".into(),
+            font_size: 20.,
+            primary_color: Color::rgb(255, 255, 255),
+            secondary_color: Color::rgb(255, 0, 0),
+            outline_color: Color::rgb(0, 0, 0),
+            back_color: Color::rgb(0, 0, 0),
+            bold: false,
+            italic: false,
+            underline: false,
+            strike_out: false,
+            scale_x: 100.,
+            scale_y: 100.,
+            spacing: 0.,
+            angle: 0.,
+            border_style: 1,
+            outline: 2.,
+            shadow: 2.,
+            alignment: 2,
+            margin_l: 10,
+            margin_r: 10,
+            margin_v: 10,
+            encoding: 1,
+        }
+    }
+}
+
+impl Style {
+    /// Create a style from the fields of a `Style` line.
+    fn parse(fields: &Fields<'_>, legacy: bool) -> Result<Self, AssError> {
+        let default = Self::default();
+        let flag = |name| fields.parse_or(name, 0_i32).map(|value| value != 0);
+        let alignment = fields.parse_or("alignment", default.alignment)?;
+        Ok(Self {
+            name: fields.get("name").unwrap_or(DEFAULT_STYLE).into(),
+            font_name: fields.get("fontname").unwrap_or(&default.font_name).into(),
+            font_size: fields.parse_or("fontsize", default.font_size)?,
+            primary_color: fields.parse_or("primarycolour", default.primary_color)?,
+            secondary_color: fields.parse_or("secondarycolour", default.secondary_color)?,
+            // `SSA` has a `TertiaryColour` instead of the outline color.
+            outline_color: fields.parse_or(
+                "outlinecolour",
+                fields.parse_or("tertiarycolour", default.outline_color)?,
+            )?,
+            back_color: fields.parse_or("backcolour", default.back_color)?,
+            bold: flag("bold")?,
+            italic: flag("italic")?,
+            underline: flag("underline")?,
+            strike_out: flag("strikeout")?,
+            scale_x: fields.parse_or("scalex", default.scale_x)?,
+            scale_y: fields.parse_or("scaley", default.scale_y)?,
+            spacing: fields.parse_or("spacing", default.spacing)?,
+            angle: fields.parse_or("angle", default.angle)?,
+            border_style: fields.parse_or("borderstyle", default.border_style)?,
+            outline: fields.parse_or("outline", default.outline)?,
+            shadow: fields.parse_or("shadow", default.shadow)?,
+            alignment: if legacy {
+                legacy_alignment(alignment)
+            } else {
+                alignment
+            },
+            margin_l: fields.parse_or("marginl", default.margin_l)?,
+            margin_r: fields.parse_or("marginr", default.margin_r)?,
+            margin_v: fields.parse_or("marginv", default.margin_v)?,
+            encoding: fields.parse_or("encoding", default.encoding)?,
+        })
+    }
+}
+
+/// Convert an `SSA` alignment (`1` to `3` bottom, `+4` top, `+8` middle) to numpad layout.
+const fn legacy_alignment(alignment: u8) -> u8 {
+    let column = alignment.saturating_sub(1) % 4;
+    match alignment {
+        5..=7 => 7 + column,
+        9..=11 => 4 + column,
+        _ => 1 + column,
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |value: bool| if value { -1 } else { 0 };
+        write!(
+            f,
+            "Style: {},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.name,
+            self.font_name,
+            self.font_size,
+            self.primary_color,
+            self.secondary_color,
+            self.outline_color,
+            self.back_color,
+            flag(self.bold),
+            flag(self.italic),
+            flag(self.underline),
+            flag(self.strike_out),
+            self.scale_x,
+            self.scale_y,
+            self.spacing,
+            self.angle,
+            self.border_style,
+            self.outline,
+            self.shadow,
+            self.alignment,
+            self.margin_l,
+            self.margin_r,
+            self.margin_v,
+            self.encoding
+        )
+    }
+}
+
+/// Kind of an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A displayed line (`Dialogue:`).
+    Dialogue,
+    /// A line which is not displayed (`Comment:`).
+    Comment,
+}
+
+/// An event of the `[Events]` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Kind of the event.
+    pub kind: EventKind,
+    /// Layer of the event, higher layers are drawn over lower ones.
+    pub layer: i32,
+    /// Time span of the event.
+    pub time_span: TimeSpan,
+    /// Name of the style of the event.
+    pub style: String,
+    /// Name of the character speaking.
+    pub name: String,
+    /// Left margin, `0` to use the style margin.
+    pub margin_l: i32,
+    /// Right margin, `0` to use the style margin.
+    pub margin_r: i32,
+    /// Vertical margin, `0` to use the style margin.
+    pub margin_v: i32,
+    /// Transition effect.
+    pub effect: String,
+    /// Text, with override blocks.
+    pub text: String,
+}
+
+impl Event {
+    /// Create a dialogue event with the default style from a plain text.
+    #[must_use]
+    pub fn dialogue(time_span: TimeSpan, text: &str) -> Self {
+        Self {
+            kind: EventKind::Dialogue,
+            layer: 0,
+            time_span,
+            style: DEFAULT_STYLE.into(),
+            name: String::new(),
+            margin_l: 0,
+            margin_r: 0,
+            margin_v: 0,
+            effect: String::new(),
+            text: escape_text(text),
+        }
+    }
+
+    /// Place the event at the position of a bitmap subtitle.
+    ///
+    /// The text is centered horizontally on the `area` and aligned on its bottom,
+    /// with an `{\an2\pos(x,y)}` override block. Positions are in script
+    /// resolution coordinates, which should match the video size used for the `area`.
+    #[must_use]
+    pub fn at_area(mut self, area: &Area) -> Self {
+        let x = u32::from(area.left()) + u32::from(area.width()) / 2;
+        let y = u32::from(area.top()) + u32::from(area.height());
+        self.text = format!("{{\\an2\\pos({x},{y})}}{}", self.text);
+        self
+    }
+
+    /// Split the text in tokens.
+    #[must_use]
+    pub fn tokens(&self) -> Vec<TextToken<'_>> {
+        tokenize(&self.text)
+    }
+
+    /// Get the text without override blocks, with line breaks as `\n`.
+    #[must_use]
+    pub fn plain_text(&self) -> String {
+        plain_text(&self.text)
+    }
+
+    /// Create an event from the fields of a `Dialogue` or `Comment` line.
+    fn parse(kind: EventKind, fields: &Fields<'_>) -> Result<Self, AssError> {
+        let time = |name| {
+            let value = fields.get(name).unwrap_or_default();
+            parse_time(value).ok_or_else(|| fields.invalid(name, value))
+        };
+        // `SSA` has a `Marked` field instead of the layer.
+        let layer = match fields.get("layer") {
+            Some(value) if value.starts_with("Marked=") => 0,
+            _ => fields.parse_or("layer", 0)?,
+        };
+        Ok(Self {
+            kind,
+            layer,
+            time_span: TimeSpan::new(time("start")?, time("end")?),
+            style: fields
+                .get("style")
+                .map_or(DEFAULT_STYLE, |style| style.trim_start_matches('*'))
+                .into(),
+            name: fields.get("name").unwrap_or_default().into(),
+            margin_l: fields.parse_or("marginl", 0)?,
+            margin_r: fields.parse_or("marginr", 0)?,
+            margin_v: fields.parse_or("marginv", 0)?,
+            effect: fields.get("effect").unwrap_or_default().into(),
+            text: fields.get("text").unwrap_or_default().into(),
+        })
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            EventKind::Dialogue => "Dialogue",
+            EventKind::Comment => "Comment",
+        };
+        write!(
+            f,
+            "{kind}: {},{},{},{},{},{},{},{},{},{}",
+            self.layer,
+            TimePointAss(self.time_span.start),
+            TimePointAss(self.time_span.end),
+            self.style,
+            self.name,
+            self.margin_l,
+            self.margin_r,
+            self.margin_v,
+            self.effect,
+            self.text
+        )
+    }
+}
+
+/// Values of a line, associated with the field names of the `Format` line.
+struct Fields<'a> {
+    line: usize,
+    names: &'a [String],
+    values: Vec<&'a str>,
+}
+
+impl<'a> Fields<'a> {
+    /// Split the values of a line. The last field (the text of events) can contain commas.
+    ///
+    /// The values are trimmed, except the text whose spaces are significant.
+    fn split(line: usize, names: &'a [String], values: &'a str) -> Result<Self, AssError> {
+        let values: Vec<_> = values
+            .splitn(names.len(), ',')
+            .zip(names)
+            .map(|(value, name)| if name == "text" { value } else { value.trim() })
+            .collect();
+        if values.len() != names.len() {
+            return Err(AssError::MissingFields {
+                line,
+                expected: names.len(),
+            });
+        }
+        Ok(Self {
+            line,
+            names,
+            values,
+        })
+    }
+
+    /// Get the value of a field.
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.names
+            .iter()
+            .position(|field| field == name)
+            .map(|idx| self.values[idx])
+    }
+
+    /// Parse the value of a field, use `default` if the field is missing.
+    fn parse_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, AssError> {
+        self.get(name).map_or(Ok(default), |value| {
+            value.parse().ok().ok_or_else(|| self.invalid(name, value))
+        })
+    }
+
+    /// Create the error of an invalid field value.
+    fn invalid(&self, name: &str, value: &str) -> AssError {
+        AssError::InvalidField {
+            line: self.line,
+            field: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// Convert to the shared text model, from the override tags of the text.
+/// The style of the event, defined in the script, is not applied.
+impl From<&Event> for text::Cue {
+    fn from(event: &Event) -> Self {
+        let (rows, align) = cue_rows(&event.text);
+        Self {
+            time_span: event.time_span,
+            position: CuePosition { line: None, align },
+            rows,
+        }
+    }
+}
+
+/// Section of the script being parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    ScriptInfo,
+    Styles {
+        legacy: bool,
+    },
+    Events,
+    /// Section kept verbatim in [`Script::extra_sections`].
+    Extra,
+    /// Before the first section.
+    Other,
+}
+
+/// A section of a script not interpreted by the parser, like `[Fonts]` or `[Graphics]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraSection {
+    /// Name of the section, without the brackets.
+    pub name: String,
+    /// Non-empty lines of the section, verbatim.
+    pub lines: Vec<String>,
+}
+
+/// An `ASS` or `SSA` script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    /// Properties of the `[Script Info]` section, in file order.
+    pub info: Vec<(String, String)>,
+    /// Styles of the script.
+    pub styles: Vec<Style>,
+    /// Events of the script.
+    pub events: Vec<Event>,
+    /// Other sections of the script, in file order.
+    pub extra_sections: Vec<ExtraSection>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self {
+            info: vec![("ScriptType".into(), SCRIPT_TYPE.into())],
+            styles: vec![Style::default()],
+            events: Vec::new(),
+            extra_sections: Vec::new(),
+        }
+    }
+}
+
+impl Script {
+    /// Create a script with the default style, for a video of size `play_res`.
+    #[must_use]
+    pub fn new(play_res: Size) -> Self {
+        let mut script = Self::default();
+        script.set_info("PlayResX", play_res.w.to_string());
+        script.set_info("PlayResY", play_res.h.to_string());
+        script
+    }
+
+    /// Create a script from subtitles, with the default style.
+    #[must_use]
+    pub fn from_subtitles(play_res: Size, subtitles: &[(TimeSpan, String)]) -> Self {
+        let mut script = Self::new(play_res);
+        script.events = subtitles
+            .iter()
+            .map(|(time_span, text)| Event::dialogue(*time_span, text))
+            .collect();
+        script
+    }
+
+    /// Read an `ASS` or `SSA` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `AssError::Io` if not able to read the file.
+    /// Will return an other `AssError` if the content is not a valid script.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AssError> {
+        let path = path.as_ref();
+        let content = fs::read(path).map_err(|source| AssError::Io {
+            source,
+            path: path.into(),
+        })?;
+        Self::parse(&String::from_utf8_lossy(&content))
+    }
+
+    /// Read a script from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `AssError::Read` if failed to read from `reader`.
+    /// Will return an other `AssError` if the content is not a valid script.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, AssError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(AssError::Read)?;
+        Self::parse(&String::from_utf8_lossy(&content))
+    }
+
+    /// Parse the content of a script.
+    ///
+    /// # Errors
+    ///
+    /// Will return `AssError::MissingFormat` if a style or an event is before the `Format`
+    /// line of its section.
+    /// Will return `AssError::MissingFields` or `AssError::InvalidField` if a style or
+    /// an event is invalid.
+    pub fn parse(content: &str) -> Result<Self, AssError> {
+        let mut script = Self {
+            info: Vec::new(),
+            styles: Vec::new(),
+            events: Vec::new(),
+            extra_sections: Vec::new(),
+        };
+        let mut section = Section::Other;
+        let mut format: Option<Vec<String>> = None;
+
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        for (idx, raw_line) in content.lines().enumerate() {
+            let line_num = idx + 1;
+            let line = raw_line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = match name.to_ascii_lowercase().as_str() {
+                    "script info" => Section::ScriptInfo,
+                    "v4+ styles" => Section::Styles { legacy: false },
+                    "v4 styles" => Section::Styles { legacy: true },
+                    "events" => Section::Events,
+                    _ => {
+                        script.extra_sections.push(ExtraSection {
+                            name: name.into(),
+                            lines: Vec::new(),
+                        });
+                        Section::Extra
+                    }
+                };
+                format = None;
+                continue;
+            }
+            if let (Section::Extra, Some(extra)) = (section, script.extra_sections.last_mut()) {
+                // Lines of embedded fonts and graphics can start with `;`.
+                extra.lines.push(raw_line.into());
+                continue;
+            }
+            if line.starts_with(';') {
+                continue;
+            }
+            // The end of the line is kept, for the spaces at the end of the events text.
+            let Some((key, value)) = raw_line.trim_start().split_once(':') else {
+                continue;
+            };
+            let value = value.trim_start();
+            match section {
+                Section::ScriptInfo => script
+                    .info
+                    .push((key.trim().into(), value.trim_end().into())),
+                Section::Styles { .. } | Section::Events if key == "Format" => {
+                    format = Some(
+                        value
+                            .split(',')
+                            .map(|name| name.trim().to_ascii_lowercase())
+                            .collect(),
+                    );
+                }
+                Section::Styles { legacy } if key == "Style" => {
+                    let names = format
+                        .as_deref()
+                        .ok_or(AssError::MissingFormat { line: line_num })?;
+                    let fields = Fields::split(line_num, names, value)?;
+                    script.styles.push(Style::parse(&fields, legacy)?);
+                }
+                Section::Events if key == "Dialogue" || key == "Comment" => {
+                    let names = format
+                        .as_deref()
+                        .ok_or(AssError::MissingFormat { line: line_num })?;
+                    let kind = if key == "Dialogue" {
+                        EventKind::Dialogue
+                    } else {
+                        EventKind::Comment
+                    };
+                    let fields = Fields::split(line_num, names, value)?;
+                    script.events.push(Event::parse(kind, &fields)?);
+                }
+                Section::Styles { .. } | Section::Events | Section::Extra | Section::Other => {}
+            }
+        }
+        Ok(script)
+    }
+
+    /// Get the value of a property of the `[Script Info]` section.
+    #[must_use]
+    pub fn info(&self, key: &str) -> Option<&str> {
+        self.info
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the value of a property of the `[Script Info]` section.
+    pub fn set_info(&mut self, key: &str, value: String) {
+        if let Some((_, current)) = self
+            .info
+            .iter_mut()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        {
+            *current = value;
+        } else {
+            self.info.push((key.into(), value));
+        }
+    }
+
+    /// Resolution of the script coordinates (`PlayResX` and `PlayResY`), if defined.
+    #[must_use]
+    pub fn play_res(&self) -> Option<Size> {
+        Some(Size {
+            w: self.info("PlayResX")?.trim().parse().ok()?,
+            h: self.info("PlayResY")?.trim().parse().ok()?,
+        })
+    }
+
+    /// Get a style by name.
+    #[must_use]
+    pub fn style(&self, name: &str) -> Option<&Style> {
+        self.styles.iter().find(|style| style.name == name)
+    }
+
+    /// Write the script in `ASS` format.
+    ///
+    /// The [`Script::extra_sections`] are written after the events.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing in `writer` return an `Err`.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        // The styles are always written in `V4+` format, the script type of `SSA` is replaced.
+        writeln!(writer, "[Script Info]")?;
+        if self.info("ScriptType").is_none() {
+            writeln!(writer, "ScriptType: {SCRIPT_TYPE}")?;
+        }
+        self.info.iter().try_for_each(|(key, value)| {
+            if key.eq_ignore_ascii_case("ScriptType") {
+                writeln!(writer, "{key}: {SCRIPT_TYPE}")
+            } else {
+                writeln!(writer, "{key}: {value}")
+            }
+        })?;
+
+        writeln!(writer, "\n[V4+ Styles]\nFormat: {STYLE_FORMAT}")?;
+        self.styles
+            .iter()
+            .try_for_each(|style| writeln!(writer, "{style}"))?;
+
+        writeln!(writer, "\n[Events]\nFormat: {EVENT_FORMAT}")?;
+        self.events
+            .iter()
+            .try_for_each(|event| writeln!(writer, "{event}"))?;
+
+        self.extra_sections.iter().try_for_each(|section| {
+            writeln!(writer, "\n[{}]", section.name)?;
+            section
+                .lines
+                .iter()
+                .try_for_each(|line| writeln!(writer, "{line}"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::AreaValues;
+    use assert_matches2::assert_matches;
+
+    const SCRIPT: &str = "\u{feff}[Script Info]
+; Comment
+Title: Example
+ScriptType: v4.00+
+PlayResX: 1920
+PlayResY: 1080
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,-1,0,0,0,100,100,0,0,1,2.5,1,2,20,20,30,1
+Style: Sign,Verdana,36,&H0000FFFF,&H000000FF,&H00000000,&H00000000,0,-1,0,0,100,100,0,0,1,2,0,8,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:03.50,Default,Alice,0,0,0,,{\\i1}Hello,\\Nworld{\\i0}
+Comment: 0,0:00:02.00,0:00:04.00,Default,,0,0,0,,Translator note
+Dialogue: 1,0:01:02.05,1:00:00.99,Sign,,0,0,0,,{\\an8\\pos(960,40)}Sign text
+
+[Fonts]
+fontname: font.ttf
+;/[BC
+
+[Aegisub Project Garbage]
+Video File: video.mkv
+";
+
+    #[test]
+    fn parse_script() {
+        let script = Script::parse(SCRIPT).unwrap();
+        assert_eq!(script.info("title"), Some("Example"));
+        assert_eq!(script.play_res(), Some(Size { w: 1920, h: 1080 }));
+
+        assert_eq!(script.styles.len(), 2);
+        let default = script.style("Default").unwrap();
+        assert!((default.font_size - 48.).abs() < f64::EPSILON);
+        assert!(default.bold);
+        assert!((default.outline - 2.5).abs() < f64::EPSILON);
+        assert_eq!(default.back_color.alpha, 0x80);
+        assert_eq!(default.margin_v, 30);
+        let sign = script.style("Sign").unwrap();
+        assert_eq!(sign.primary_color, Color::rgb(255, 255, 0));
+        assert!(sign.italic);
+        assert_eq!(sign.alignment, 8);
+
+        assert_eq!(script.events.len(), 3);
+        let first = &script.events[0];
+        assert_eq!(first.kind, EventKind::Dialogue);
+        assert_eq!(first.name, "Alice");
+        assert_eq!(
+            first.time_span,
+            TimeSpan::new(TimePoint::from_msecs(1000), TimePoint::from_msecs(3500))
+        );
+        assert_eq!(first.text, "{\\i1}Hello,\\Nworld{\\i0}");
+        assert_eq!(first.plain_text(), "Hello,\nworld");
+        assert_eq!(script.events[1].kind, EventKind::Comment);
+        let last = &script.events[2];
+        assert_eq!(last.layer, 1);
+        assert_eq!(
+            last.time_span,
+            TimeSpan::new(
+                TimePoint::from_msecs(62_050),
+                TimePoint::from_msecs(3_600_990)
+            )
+        );
+        let tokens = last.tokens();
+        assert_matches!(tokens.first(), Some(TextToken::Override(tags)));
+        assert_eq!(tags[0], OverrideTag::Alignment(8));
+
+        assert_eq!(
+            script.extra_sections,
+            [
+                ExtraSection {
+                    name: "Fonts".into(),
+                    lines: vec!["fontname: font.ttf".into(), ";/[BC".into()],
+                },
+                ExtraSection {
+                    name: "Aegisub Project Garbage".into(),
+                    lines: vec!["Video File: video.mkv".into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn write_script() {
+        let script = Script::parse(SCRIPT).unwrap();
+        let mut output = Vec::new();
+        script.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("[Script Info]\nTitle: Example\nScriptType: v4.00+\n"));
+        assert!(output.contains(
+            "Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,-1,0,0,0,100,100,0,0,1,2.5,1,2,20,20,30,1\n"
+        ));
+        assert!(output.contains(
+            "Dialogue: 1,0:01:02.05,1:00:00.99,Sign,,0,0,0,,{\\an8\\pos(960,40)}Sign text\n"
+        ));
+        assert!(output.ends_with(
+            "\n[Fonts]\nfontname: font.ttf\n;/[BC\n\n[Aegisub Project Garbage]\nVideo File: video.mkv\n"
+        ));
+        assert_eq!(Script::parse(&output).unwrap(), script);
+    }
+
+    #[test]
+    fn write_subtitles_at_area() {
+        let time_span = TimeSpan::new(TimePoint::from_msecs(1234), TimePoint::from_msecs(5678));
+        let area = Area::try_from(AreaValues {
+            x1: 100,
+            y1: 400,
+            x2: 299,
+            y2: 449,
+        })
+        .unwrap();
+        let mut script =
+            Script::from_subtitles(Size { w: 720, h: 480 }, &[(time_span, "Two\nlines".into())]);
+        script.events[0] = script.events[0].clone().at_area(&area);
+
+        let mut output = Vec::new();
+        script.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("PlayResX: 720\nPlayResY: 480\n"));
+        assert!(output.ends_with(
+            "Dialogue: 0,0:00:01.23,0:00:05.68,Default,,0,0,0,,{\\an2\\pos(200,450)}Two\\Nlines\n"
+        ));
+    }
+
+    #[test]
+    fn parse_ssa_script() {
+        let script = Script::parse(
+            "[Script Info]
+ScriptType: v4.00
+
+[V4 Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, TertiaryColour, BackColour, Bold, Italic, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, AlphaLevel, Encoding
+Style: Default,Tahoma,24,16777215,65535,255,0,-1,0,1,2,3,6,30,30,10,0,0
+
+[Events]
+Format: Marked, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: Marked=0,0:00:00.50,0:00:01.00,*Default,,0000,0000,0000,,Text, with comma
+",
+        )
+        .unwrap();
+        let style = &script.styles[0];
+        assert_eq!(style.primary_color, Color::rgb(255, 255, 255));
+        assert_eq!(style.outline_color, Color::rgb(255, 0, 0));
+        // Top center in `SSA` alignment.
+        assert_eq!(style.alignment, 8);
+        assert_eq!(script.events[0].style, "Default");
+        assert_eq!(script.events[0].text, "Text, with comma");
+
+        let mut output = Vec::new();
+        script.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("[Script Info]\nScriptType: v4.00+\n\n[V4+ Styles]\n"));
+    }
+
+    #[test]
+    fn keep_text_spaces() {
+        let content = "[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0 , 0:00:00.50 ,0:00:01.00, Default,,0,0,0,,  Indented, and trailing  
+";
+        let script = Script::parse(content).unwrap();
+        assert_eq!(script.events[0].style, "Default");
+        assert_eq!(script.events[0].text, "  Indented, and trailing  ");
+
+        let mut output = Vec::new();
+        script.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with(",,  Indented, and trailing  \n"));
+    }
+
+    #[test]
+    fn parse_invalid_script() {
+        assert_matches!(
+            Script::parse("[Events]\nDialogue: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,Text"),
+            Err(AssError::MissingFormat { line: 2 })
+        );
+        assert_matches!(
+            Script::parse("[Events]\nFormat: Start, End, Text\nDialogue: 0:00:00.00,Text"),
+            Err(AssError::MissingFields {
+                line: 3,
+                expected: 3
+            })
+        );
+        assert_matches!(
+            Script::parse("[Events]\nFormat: Start, End, Text\nDialogue: 0:00:00.00,x,Text"),
+            Err(AssError::InvalidField {
+                line: 3,
+                field,
+                value
+            })
+        );
+        assert_eq!(field, "end");
+        assert_eq!(value, "x");
+    }
+
+    #[test]
+    fn time_format() {
+        let format = |msecs| TimePointAss(TimePoint::from_msecs(msecs)).to_string();
+        assert_eq!(format(0), "0:00:00.00");
+        assert_eq!(format(3_723_456), "1:02:03.46");
+        assert_eq!(format(-10), "0:00:00.00");
+        assert_eq!(
+            parse_time("1:02:03.45"),
+            Some(TimePoint::from_msecs(3_723_450))
+        );
+        assert_eq!(parse_time("0:00:01.5"), Some(TimePoint::from_msecs(1500)));
+        assert_eq!(parse_time("0:00:01.234"), Some(TimePoint::from_msecs(1234)));
+        assert_eq!(parse_time("0:00:-1.00"), None);
+        assert_eq!(parse_time("0:00:00.999999999999999999"), None);
+        assert_eq!(parse_time("9999999999999999:00:00.00"), None);
+    }
+
+    #[test]
+    fn color_format() {
+        let color: Color = "&H80FF0010".parse().unwrap();
+        assert_eq!(
+            color,
+            Color {
+                red: 0x10,
+                green: 0,
+                blue: 0xff,
+                alpha: 0x80
+            }
+        );
+        assert_eq!(color.to_string(), "&H80FF0010");
+        assert_eq!("&HFF&".parse::<Color>().unwrap(), Color::rgb(255, 0, 0));
+        assert!("red".parse::<Color>().is_err());
+    }
+}
@@ -0,0 +1,351 @@
+//! Tokenisation of the text of `ASS` events, with the override tags.
+
+use super::Color;
+use crate::text::{CueAlign, CueRow, CueStyle};
+use image::Rgb;
+
+/// A part of the text of an event.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum TextToken<'a> {
+    /// Text to display.
+    Text(&'a str),
+    /// A line break. `\N` is a hard line break, `\n` a soft one (only a line break in
+    /// wrapping style 2).
+    LineBreak {
+        /// If the line break is a hard one.
+        hard: bool,
+    },
+    /// A non-breaking space (`\h`).
+    HardSpace,
+    /// An override block (`{...}`), with its tags.
+    Override(Vec<OverrideTag<'a>>),
+}
+
+/// A tag of an override block.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum OverrideTag<'a> {
+    /// Enable or disable italic (`\i1`, `\i0`).
+    Italic(bool),
+    /// Enable or disable bold (`\b1`, `\b0`). A font weight (like `\b700`) enables bold.
+    Bold(bool),
+    /// Enable or disable underline (`\u1`, `\u0`).
+    Underline(bool),
+    /// Enable or disable strike out (`\s1`, `\s0`).
+    StrikeOut(bool),
+    /// Alignment of the line, in numpad layout (`\an1` to `\an9`).
+    Alignment(u8),
+    /// Position of the line, relative to its alignment point (`\pos(x,y)`).
+    Position {
+        /// Horizontal position.
+        x: f64,
+        /// Vertical position.
+        y: f64,
+    },
+    /// Font name (`\fnArial`).
+    FontName(&'a str),
+    /// Font size (`\fs20`).
+    FontSize(f64),
+    /// Reset the style to the line style, or to the named style (`\r`, `\rStyle`).
+    Reset(Option<&'a str>),
+    /// Any other tag, without the leading backslash (like `fad(200,200)`).
+    Other(&'a str),
+    /// Text of the block which is not a tag, often used as comment.
+    Comment(&'a str),
+}
+
+/// Split the text of an event in tokens.
+///
+/// The text is not validated: unclosed override blocks are considered as text.
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<TextToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(block) = rest.strip_prefix('{') {
+            if let Some(end) = block.find('}') {
+                tokens.push(TextToken::Override(parse_block(&block[..end])));
+                rest = &block[end + 1..];
+                continue;
+            }
+        }
+        let special = rest
+            .char_indices()
+            .skip(1)
+            .find(|&(idx, c)| c == '{' || (c == '\\' && is_escape(&rest[idx..])))
+            .map_or(rest.len(), |(idx, _)| idx);
+        let (part, remaining) = if is_escape(rest) {
+            rest.split_at(2)
+        } else {
+            rest.split_at(special)
+        };
+        tokens.push(match part {
+            "\\N" => TextToken::LineBreak { hard: true },
+            "\\n" => TextToken::LineBreak { hard: false },
+            "\\h" => TextToken::HardSpace,
+            text => TextToken::Text(text),
+        });
+        rest = remaining;
+    }
+    tokens
+}
+
+/// Check if `text` starts with an escape sequence of the text (`\N`, `\n` or `\h`).
+fn is_escape(text: &str) -> bool {
+    ["\\N", "\\n", "\\h"]
+        .iter()
+        .any(|escape| text.starts_with(escape))
+}
+
+/// Parse the content of an override block.
+fn parse_block(block: &str) -> Vec<OverrideTag<'_>> {
+    let mut tags = Vec::new();
+    let comment_end = block.find('\\').unwrap_or(block.len());
+    if comment_end > 0 {
+        tags.push(OverrideTag::Comment(&block[..comment_end]));
+    }
+
+    let mut rest = &block[comment_end..];
+    while let Some(tag) = rest.strip_prefix('\\') {
+        // A tag ends at the next backslash outside of parentheses, like in `\t(\i1)`.
+        let mut depth = 0_u32;
+        let end = tag
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    '\\' => return depth == 0,
+                    _ => {}
+                }
+                false
+            })
+            .map_or(tag.len(), |(idx, _)| idx);
+        if end > 0 {
+            tags.push(parse_tag(tag[..end].trim_end()));
+        }
+        rest = &tag[end..];
+    }
+    tags
+}
+
+/// Parse a tag, without its leading backslash.
+fn parse_tag(tag: &str) -> OverrideTag<'_> {
+    known_tag(tag).unwrap_or(OverrideTag::Other(tag))
+}
+
+/// Parse a tag with a known name and valid arguments.
+fn known_tag(tag: &str) -> Option<OverrideTag<'_>> {
+    let flag = |value: &str| match value {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    };
+    if let Some(args) = tag.strip_prefix("pos(") {
+        return position(args);
+    }
+    if let Some(value) = tag.strip_prefix("an") {
+        return value
+            .parse()
+            .ok()
+            .filter(|alignment| (1..=9).contains(alignment))
+            .map(OverrideTag::Alignment);
+    }
+    if let Some(name) = tag.strip_prefix("fn") {
+        return Some(OverrideTag::FontName(name));
+    }
+    if let Some(size) = tag.strip_prefix("fs") {
+        return size.parse().ok().map(OverrideTag::FontSize);
+    }
+    if let Some(value) = tag.strip_prefix('b') {
+        return value
+            .parse::<u32>()
+            .ok()
+            .map(|weight| OverrideTag::Bold(weight > 0));
+    }
+    if let Some(value) = tag.strip_prefix('i') {
+        return flag(value).map(OverrideTag::Italic);
+    }
+    if let Some(value) = tag.strip_prefix('u') {
+        return flag(value).map(OverrideTag::Underline);
+    }
+    if let Some(value) = tag.strip_prefix('s') {
+        return flag(value).map(OverrideTag::StrikeOut);
+    }
+    tag.strip_prefix('r')
+        .map(|style| OverrideTag::Reset((!style.is_empty()).then_some(style)))
+}
+
+/// Parse the arguments of a `\pos` tag.
+fn position(args: &str) -> Option<OverrideTag<'_>> {
+    let (x, y) = args.strip_suffix(')')?.split_once(',')?;
+    Some(OverrideTag::Position {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+    })
+}
+
+/// Get the text of an event without the override blocks, with line breaks as `\n`.
+#[must_use]
+pub fn plain_text(text: &str) -> String {
+    tokenize(text)
+        .into_iter()
+        .fold(String::with_capacity(text.len()), |mut plain, token| {
+            match token {
+                TextToken::Text(text) => plain.push_str(text),
+                TextToken::LineBreak { hard: true } => plain.push('\n'),
+                TextToken::LineBreak { hard: false } | TextToken::HardSpace => plain.push(' '),
+                TextToken::Override(_) => {}
+            }
+            plain
+        })
+}
+
+/// Convert the text of an event to styled rows, with the horizontal alignment of the `\an` tag.
+///
+/// The bold, italic, underline, strike out and primary color (`\c`, `\1c`) tags are applied,
+/// a reset (`\r`) goes back to the default style.
+pub(crate) fn cue_rows(text: &str) -> (Vec<CueRow>, Option<CueAlign>) {
+    let mut rows = Vec::new();
+    let mut row = CueRow::default();
+    let mut style = CueStyle::default();
+    let mut align = None;
+    for token in tokenize(text) {
+        match token {
+            TextToken::Text(text) => row.push(text, style),
+            TextToken::LineBreak { hard: true } => rows.push(std::mem::take(&mut row)),
+            TextToken::LineBreak { hard: false } | TextToken::HardSpace => row.push(" ", style),
+            TextToken::Override(tags) => {
+                for tag in tags {
+                    match tag {
+                        OverrideTag::Italic(italic) => style.italic = italic,
+                        OverrideTag::Bold(bold) => style.bold = bold,
+                        OverrideTag::Underline(underline) => style.underline = underline,
+                        OverrideTag::StrikeOut(strike_out) => style.strike_out = strike_out,
+                        OverrideTag::Alignment(alignment) => {
+                            align = match alignment % 3 {
+                                1 => Some(CueAlign::Left),
+                                2 => Some(CueAlign::Center),
+                                _ => Some(CueAlign::Right),
+                            };
+                        }
+                        OverrideTag::Reset(_) => style = CueStyle::default(),
+                        OverrideTag::Other(tag) => {
+                            if let Some(color) = primary_color(tag) {
+                                style.color = Some(color);
+                            }
+                        }
+                        OverrideTag::Position { .. }
+                        | OverrideTag::FontName(_)
+                        | OverrideTag::FontSize(_)
+                        | OverrideTag::Comment(_) => {}
+                    }
+                }
+            }
+        }
+    }
+    rows.push(row);
+    (rows, align)
+}
+
+/// Parse the color of a primary color tag (`c&HBBGGRR&` or `1c&HBBGGRR&`).
+fn primary_color(tag: &str) -> Option<Rgb<u8>> {
+    let value = tag.strip_prefix("1c").or_else(|| tag.strip_prefix('c'))?;
+    let color = value.parse::<Color>().ok()?;
+    Some(Rgb([color.red, color.green, color.blue]))
+}
+
+/// Escape a plain text to be used as text of an event.
+#[must_use]
+pub fn escape_text(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace('\n', "\\N")
+        .replace('{', "(")
+        .replace('}', ")")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_to_cue_rows() {
+        let (rows, align) = cue_rows(r"{\an8\b1}Bold {\c&H00FFFF&\i1}yellow\N{\r}plain\hend");
+        assert_eq!(align, Some(CueAlign::Center));
+        assert_eq!(rows.len(), 2);
+        let spans = &rows[0].spans;
+        assert_eq!(spans[0].text, "Bold ");
+        assert!(spans[0].style.bold && spans[0].style.color.is_none());
+        assert_eq!(spans[1].text, "yellow");
+        assert!(spans[1].style.bold && spans[1].style.italic);
+        assert_eq!(spans[1].style.color, Some(Rgb([255, 255, 0])));
+        assert_eq!(rows[1].spans.len(), 1);
+        assert_eq!(rows[1].text(), "plain end");
+        assert_eq!(rows[1].spans[0].style, CueStyle::default());
+        // Other tags starting with `c` are not colors.
+        assert_eq!(primary_color("clip(0,0,10,10)"), None);
+    }
+
+    #[test]
+    fn tokenize_text() {
+        assert_eq!(
+            tokenize(r"{\i1}Hello\Nworld{\i0}!"),
+            vec![
+                TextToken::Override(vec![OverrideTag::Italic(true)]),
+                TextToken::Text("Hello"),
+                TextToken::LineBreak { hard: true },
+                TextToken::Text("world"),
+                TextToken::Override(vec![OverrideTag::Italic(false)]),
+                TextToken::Text("!"),
+            ]
+        );
+        assert_eq!(
+            tokenize(r"a\hb\nc\d{unclosed"),
+            vec![
+                TextToken::Text("a"),
+                TextToken::HardSpace,
+                TextToken::Text("b"),
+                TextToken::LineBreak { hard: false },
+                TextToken::Text(r"c\d"),
+                TextToken::Text("{unclosed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_override_tags() {
+        assert_eq!(
+            tokenize(
+                r"{note\an8\pos(320.5, 40)\fnArial\fs24\b700\u1\s0\rAlt\t(0,500,\i1)\fad(200,200)}"
+            ),
+            vec![TextToken::Override(vec![
+                OverrideTag::Comment("note"),
+                OverrideTag::Alignment(8),
+                OverrideTag::Position { x: 320.5, y: 40. },
+                OverrideTag::FontName("Arial"),
+                OverrideTag::FontSize(24.),
+                OverrideTag::Bold(true),
+                OverrideTag::Underline(true),
+                OverrideTag::StrikeOut(false),
+                OverrideTag::Reset(Some("Alt")),
+                OverrideTag::Other(r"t(0,500,\i1)"),
+                OverrideTag::Other("fad(200,200)"),
+            ])]
+        );
+        assert_eq!(
+            tokenize(r"{\r\shad2\an0}"),
+            vec![TextToken::Override(vec![
+                OverrideTag::Reset(None),
+                OverrideTag::Other("shad2"),
+                OverrideTag::Other("an0"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn plain_and_escaped_text() {
+        assert_eq!(plain_text(r"{\i1}Hello\Nworld{\i0}\h!"), "Hello\nworld !");
+        assert_eq!(escape_text("Hello\nworld {x}"), r"Hello\Nworld (x)");
+    }
+}
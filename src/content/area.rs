@@ -50,6 +50,118 @@ impl Area {
             h: usize::from(self.height()),
         }
     }
+
+    /// Create an area with the same size, with the top left corner at `x`, `y`.
+    /// The position is clamped to keep the area inside the `u16` coordinates.
+    fn moved_to(self, x: i64, y: i64) -> Self {
+        let max_x = i64::from(u16::MAX - (self.width() - 1));
+        let max_y = i64::from(u16::MAX - (self.height() - 1));
+        let x1 = u16::try_from(x.clamp(0, max_x)).unwrap();
+        let y1 = u16::try_from(y.clamp(0, max_y)).unwrap();
+        Self(AreaValues {
+            x1,
+            y1,
+            x2: x1 + (self.width() - 1),
+            y2: y1 + (self.height() - 1),
+        })
+    }
+
+    /// Create the area of an image of size `width` x `height` with its top left corner at
+    /// `position`. An image thinner than 2 pixels get an area 2 pixels wide or high, and
+    /// the position is clamped to keep the area inside the `u16` coordinates.
+    #[must_use]
+    pub fn at_position(position: (u16, u16), width: u16, height: u16) -> Self {
+        Self(AreaValues {
+            x1: 0,
+            y1: 0,
+            x2: width.max(2) - 1,
+            y2: height.max(2) - 1,
+        })
+        .moved_to(i64::from(position.0), i64::from(position.1))
+    }
+
+    /// Move the area by `dx`, `dy` pixels. The area is kept in the positive coordinates.
+    #[must_use]
+    pub fn translate(self, dx: i32, dy: i32) -> Self {
+        self.moved_to(
+            i64::from(self.left()) + i64::from(dx),
+            i64::from(self.top()) + i64::from(dy),
+        )
+    }
+
+    /// Move the area to be inside the rectangle at `x`, `y` of size `bounds`.
+    /// If the area is bigger than the rectangle, it's aligned on the rectangle top left.
+    fn moved_inside(self, x: i64, y: i64, bounds: Size) -> Self {
+        let clamp = |pos: u16, len: u16, min: i64, max_len: usize| {
+            let max = min + i64::try_from(max_len).unwrap_or(i64::MAX / 2) - i64::from(len);
+            i64::from(pos).min(max).max(min)
+        };
+        self.moved_to(
+            clamp(self.left(), self.width(), x, bounds.w),
+            clamp(self.top(), self.height(), y, bounds.h),
+        )
+    }
+
+    /// Move the area the minimum to be inside a video frame of size `frame`.
+    #[must_use]
+    pub fn clamp_to_frame(self, frame: Size) -> Self {
+        self.moved_inside(0, 0, frame)
+    }
+
+    /// Move the area the minimum to be inside the safe area of a video frame of size `frame`.
+    ///
+    /// The safe area is the frame without a margin of `margin_ratio` of the frame size on
+    /// each side (`0.1` for the usual title safe area).
+    #[must_use]
+    pub fn move_into_safe_area(self, frame: Size, margin_ratio: f64) -> Self {
+        let margin = |len: usize| (cast::f64(len) * margin_ratio).round();
+        let (margin_x, margin_y) = (margin(frame.w), margin(frame.h));
+        let safe = Size {
+            w: cast::usize(cast::f64(frame.w) - 2. * margin_x).unwrap_or(0),
+            h: cast::usize(cast::f64(frame.h) - 2. * margin_y).unwrap_or(0),
+        };
+        self.moved_inside(
+            cast::i64(margin_x).unwrap_or(0),
+            cast::i64(margin_y).unwrap_or(0),
+            safe,
+        )
+    }
+
+    /// Center horizontally the area in a video frame of size `frame`.
+    #[must_use]
+    pub fn center_horizontally(self, frame: Size) -> Self {
+        let frame_width = i64::try_from(frame.w).unwrap_or(i64::MAX / 2);
+        let x = (frame_width - i64::from(self.width())) / 2;
+        self.moved_to(x, i64::from(self.top()))
+    }
+
+    /// Scale the position and the size of the area by `scale_x` and `scale_y`.
+    ///
+    /// # Errors
+    /// Will return [`ContentError::AreaOutOfRange`] if the scaled area doesn't fit in
+    /// `u16` coordinates.
+    pub fn scale(self, scale_x: f64, scale_y: f64) -> Result<Self, ContentError> {
+        let scale = |value: u16, factor: f64| {
+            cast::u16((f64::from(value) * factor).round())
+                .ok()
+                .ok_or(ContentError::AreaOutOfRange)
+        };
+        let x1 = scale(self.left(), scale_x)?;
+        let y1 = scale(self.top(), scale_y)?;
+        // An area must be at least 2 pixels wide and high.
+        let width = scale(self.width(), scale_x)?.max(2);
+        let height = scale(self.height(), scale_y)?.max(2);
+        Self::try_from(AreaValues {
+            x1,
+            y1,
+            x2: x1
+                .checked_add(width - 1)
+                .ok_or(ContentError::AreaOutOfRange)?,
+            y2: y1
+                .checked_add(height - 1)
+                .ok_or(ContentError::AreaOutOfRange)?,
+        })
+    }
 }
 
 impl TryFrom<AreaValues> for Area {
@@ -69,3 +181,36 @@ impl TryFrom<AreaValues> for Area {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x1: u16, y1: u16, x2: u16, y2: u16) -> Area {
+        Area::try_from(AreaValues { x1, y1, x2, y2 }).unwrap()
+    }
+
+    #[test]
+    fn move_area() {
+        let frame = Size { w: 720, h: 576 };
+        let sub = area(100, 500, 299, 599);
+        assert_eq!(sub.translate(-150, 10), area(0, 510, 199, 609));
+        assert_eq!(sub.clamp_to_frame(frame), area(100, 476, 299, 575));
+        assert_eq!(
+            sub.move_into_safe_area(frame, 0.1),
+            area(100, 418, 299, 517)
+        );
+        assert_eq!(sub.center_horizontally(frame), area(260, 500, 459, 599));
+    }
+
+    #[test]
+    fn scale_area() {
+        let sub = area(300, 900, 1499, 999);
+        let scaled = sub.scale(2. / 3., 2. / 3.).unwrap();
+        assert_eq!(scaled, area(200, 600, 999, 666));
+        assert!(matches!(
+            sub.scale(100., 1.),
+            Err(ContentError::AreaOutOfRange)
+        ));
+    }
+}
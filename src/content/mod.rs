@@ -15,4 +15,8 @@ pub enum ContentError {
     /// Example: If at least one coordinate value of second point are inferior of first point.
     #[error("invalid bounding box for Area")]
     InvalidAreaBounding,
+
+    /// Indicate the coordinates of an Area are out of the `u16` range.
+    #[error("area coordinates are out of range")]
+    AreaOutOfRange,
 }
@@ -0,0 +1,138 @@
+//! Color Look-Up Tables (`CLUT`) of `DVB` subtitles, with the default tables.
+
+use super::segment::{ClutDefinition, Depth};
+use crate::image::ycrcb_to_rgb;
+use image::Rgba;
+
+/// Flag of the entries of the 2-bit table, in a `CLUT` Definition Segment.
+const FLAG_2_BITS: u8 = 0x80;
+/// Flag of the entries of the 4-bit table.
+const FLAG_4_BITS: u8 = 0x40;
+/// Flag of the entries of the 8-bit table.
+const FLAG_8_BITS: u8 = 0x20;
+
+/// Fully transparent color.
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+/// The three tables of a `CLUT`, for the regions of each depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[expect(clippy::struct_field_names)]
+pub(super) struct Clut {
+    two_bit: [Rgba<u8>; 4],
+    four_bit: [Rgba<u8>; 16],
+    eight_bit: [Rgba<u8>; 256],
+}
+
+impl Default for Clut {
+    /// The default `CLUT` of the specification, used until a `CLUT` is defined.
+    fn default() -> Self {
+        let gray = |value| Rgba([value, value, value, 255]);
+        let two_bit = [TRANSPARENT, gray(255), gray(0), gray(127)];
+
+        let four_bit = std::array::from_fn(|idx| {
+            let level = if idx < 8 { 255 } else { 127 };
+            let channel = |bit: usize| if idx & bit == 0 { 0 } else { level };
+            if idx == 0 {
+                TRANSPARENT
+            } else {
+                Rgba([channel(1), channel(2), channel(4), 255])
+            }
+        });
+
+        let eight_bit = std::array::from_fn(|idx| {
+            let bit = |bit: usize, value: u8| if idx & bit == 0 { 0 } else { value };
+            let channel = |low: usize, high: usize, offset: u8, low_value, high_value| {
+                offset + bit(low, low_value) + bit(high, high_value)
+            };
+            if idx == 0 {
+                return TRANSPARENT;
+            }
+            if idx < 8 {
+                return Rgba([bit(1, 255), bit(2, 255), bit(4, 255), 63]);
+            }
+            let (offset, low, high, alpha) = match idx & 0x88 {
+                0x00 => (0, 85, 170, 255),
+                0x08 => (0, 85, 170, 127),
+                0x80 => (127, 43, 85, 255),
+                _ => (0, 43, 85, 255),
+            };
+            Rgba([
+                channel(0x01, 0x10, offset, low, high),
+                channel(0x02, 0x20, offset, low, high),
+                channel(0x04, 0x40, offset, low, high),
+                alpha,
+            ])
+        });
+
+        Self {
+            two_bit,
+            four_bit,
+            eight_bit,
+        }
+    }
+}
+
+impl Clut {
+    /// Apply the entries of a `CLUT` Definition Segment.
+    pub fn update(&mut self, definition: &ClutDefinition) {
+        for entry in &definition.entries {
+            let idx = usize::from(entry.id);
+            if entry.depths & FLAG_2_BITS != 0 {
+                if let Some(color) = self.two_bit.get_mut(idx) {
+                    *color = entry.color;
+                }
+            }
+            if entry.depths & FLAG_4_BITS != 0 {
+                if let Some(color) = self.four_bit.get_mut(idx) {
+                    *color = entry.color;
+                }
+            }
+            if entry.depths & FLAG_8_BITS != 0 {
+                self.eight_bit[idx] = entry.color;
+            }
+        }
+    }
+
+    /// Color of a pixel code of a region of `depth`.
+    pub fn color(&self, depth: Depth, code: u8) -> Rgba<u8> {
+        let table: &[Rgba<u8>] = match depth {
+            Depth::Bits2 => &self.two_bit,
+            Depth::Bits4 => &self.four_bit,
+            Depth::Bits8 => &self.eight_bit,
+        };
+        table.get(usize::from(code)).copied().unwrap_or(TRANSPARENT)
+    }
+}
+
+/// Convert a `CLUT` entry to `RGBA`. The `transparency` is `0` for opaque colors,
+/// and an entry with a `luma` of `0` is fully transparent.
+pub(super) fn ycrcbt_to_rgba(luma: u8, cr: u8, cb: u8, transparency: u8) -> Rgba<u8> {
+    if luma == 0 {
+        return TRANSPARENT;
+    }
+    let [red, green, blue] = ycrcb_to_rgb(luma, cr, cb).0;
+    Rgba([red, green, blue, 255 - transparency])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_tables() {
+        let clut = Clut::default();
+        assert_eq!(clut.color(Depth::Bits2, 0), TRANSPARENT);
+        assert_eq!(clut.color(Depth::Bits2, 1), Rgba([255, 255, 255, 255]));
+        assert_eq!(clut.color(Depth::Bits4, 9), Rgba([127, 0, 0, 255]));
+        assert_eq!(clut.color(Depth::Bits8, 7), Rgba([255, 255, 255, 63]));
+        assert_eq!(clut.color(Depth::Bits8, 0xff), Rgba([128, 128, 128, 255]));
+        assert_eq!(clut.color(Depth::Bits8, 0x77), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn convert_entries() {
+        assert_eq!(ycrcbt_to_rgba(0, 128, 128, 0), TRANSPARENT);
+        assert_eq!(ycrcbt_to_rgba(235, 128, 128, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(ycrcbt_to_rgba(16, 128, 128, 128), Rgba([0, 0, 0, 127]));
+    }
+}
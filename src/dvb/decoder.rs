@@ -0,0 +1,52 @@
+use super::{DvbError, DvbImage, Page};
+use crate::time::TimeSpan;
+
+/// Trait of `DVB` subtitles decoding.
+///
+/// The content of a subtitle is extracted from the [`Page`] when it's displayed, and the
+/// output is produced when the display ends.
+pub trait DvbDecoder {
+    /// Type of the content kept for a displayed subtitle.
+    type Content;
+    /// Type of the Output data for the subtitle.
+    type Output;
+
+    /// Extract the content of the displayed page.
+    ///
+    /// # Errors
+    /// Return the error happened during decoding.
+    fn decode(page: &Page) -> Result<Self::Content, DvbError>;
+
+    /// Produce the output of a subtitle displayed during `time_span`.
+    fn output(time_span: TimeSpan, content: Self::Content) -> Self::Output;
+}
+
+/// Decoder for `DVB` subtitles who provide only the times of subtitles.
+pub struct DecodeTimeOnly;
+impl DvbDecoder for DecodeTimeOnly {
+    type Content = ();
+    type Output = TimeSpan;
+
+    fn decode(_page: &Page) -> Result<Self::Content, DvbError> {
+        Ok(())
+    }
+
+    fn output(time_span: TimeSpan, (): Self::Content) -> Self::Output {
+        time_span
+    }
+}
+
+/// Decoder for `DVB` subtitles who provide the times and images of the subtitles.
+pub struct DecodeTimeImage;
+impl DvbDecoder for DecodeTimeImage {
+    type Content = DvbImage;
+    type Output = (TimeSpan, DvbImage);
+
+    fn decode(page: &Page) -> Result<Self::Content, DvbError> {
+        page.render().ok_or(DvbError::MissingImage)
+    }
+
+    fn output(time_span: TimeSpan, content: Self::Content) -> Self::Output {
+        (time_span, content)
+    }
+}
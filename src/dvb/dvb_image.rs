@@ -0,0 +1,121 @@
+use crate::{
+    content::Size,
+    image::{
+        ImageSize, IndexedImage, IndexedToImage, PalettedImage, ToImage, ToOcrImage, ToOcrImageOpt,
+    },
+};
+use image::{GrayImage, ImageBuffer, LumaA, Pixel as _, Rgba};
+
+/// Image of a `DVB` subtitle: the palette index of each pixel and the palette built
+/// from the color lookup tables of the regions.
+///
+/// The index `0` is always fully transparent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DvbImage {
+    width: u16,
+    height: u16,
+    position: (u16, u16),
+    frame_size: Size,
+    palette: Vec<Rgba<u8>>,
+    indexes: Vec<u8>,
+}
+
+impl DvbImage {
+    /// Create an image from the palette index of each pixel in row-major order.
+    ///
+    /// # Panics
+    /// Will panic if the number of indexes doesn't match `width` x `height`.
+    #[must_use]
+    pub fn new(
+        width: u16,
+        height: u16,
+        position: (u16, u16),
+        frame_size: Size,
+        palette: Vec<Rgba<u8>>,
+        indexes: Vec<u8>,
+    ) -> Self {
+        assert_eq!(indexes.len(), usize::from(width) * usize::from(height));
+        Self {
+            width,
+            height,
+            position,
+            frame_size,
+            palette,
+            indexes,
+        }
+    }
+
+    /// Position (`x`, `y`) of the top left pixel of the image on the screen.
+    #[must_use]
+    pub const fn position(&self) -> (u16, u16) {
+        self.position
+    }
+
+    /// Size of the display on which the image is shown.
+    #[must_use]
+    pub const fn frame_size(&self) -> Size {
+        self.frame_size
+    }
+
+    /// Access to the palette of the image.
+    #[must_use]
+    pub fn palette(&self) -> &[Rgba<u8>] {
+        &self.palette
+    }
+
+    /// Color of a palette index. Indexes missing from the palette are transparent.
+    #[must_use]
+    pub fn rgba(&self, index: u8) -> Rgba<u8> {
+        self.palette
+            .get(usize::from(index))
+            .copied()
+            .unwrap_or(Rgba([0, 0, 0, 0]))
+    }
+
+    /// Luminance and alpha of a palette index.
+    #[must_use]
+    pub fn luma_a(&self, index: u8) -> LumaA<u8> {
+        self.rgba(index).to_luma_alpha()
+    }
+}
+
+impl ImageSize for DvbImage {
+    fn width(&self) -> u32 {
+        u32::from(self.width)
+    }
+    fn height(&self) -> u32 {
+        u32::from(self.height)
+    }
+}
+
+impl IndexedImage for DvbImage {
+    fn indexes(&self) -> &[u8] {
+        &self.indexes
+    }
+
+    fn index_alpha(&self, index: u8) -> u8 {
+        self.rgba(index)[3]
+    }
+}
+
+impl PalettedImage for DvbImage {
+    fn palette_rgba(&self, index: u8) -> Rgba<u8> {
+        self.rgba(index)
+    }
+}
+
+impl ToImage for DvbImage {
+    type Pixel = Rgba<u8>;
+
+    fn to_image(&self) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        IndexedToImage::rgba(self).to_image()
+    }
+}
+
+/// Any visible and non black color is considered as text, unless the role of the palette
+/// indexes is used with [`ToOcrImageOpt::palette_roles`].
+impl ToOcrImage for DvbImage {
+    fn image(&self, opt: &ToOcrImageOpt) -> GrayImage {
+        IndexedToImage::new(self, |index| self.luma_a(index)).image(opt)
+    }
+}
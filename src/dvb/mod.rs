@@ -0,0 +1,217 @@
+//! Read functionalities for `DVB` bitmap subtitles (`ETSI EN 300 743`).
+//!
+//! `DVB` subtitles are carried in `PES` packets of broadcast transport streams. Each packet
+//! contains the segments of a display set: page composition, region composition, color
+//! look-up tables, object data with 2, 4 or 8-bit pixel codes, and display definition.
+//!
+//! The [`DvbParser`] decodes the subtitles from the `PES` packets of a stream, which can be
+//! extracted from a transport stream with [`ts::read_dvb_tracks`].
+//!
+//! Specification: <https://www.etsi.org/deliver/etsi_en/300700_300799/300743/01.06.01_60/en_300743v010601p.pdf>
+//!
+//! [`ts::read_dvb_tracks`]: crate::ts::read_dvb_tracks
+mod clut;
+mod decoder;
+mod dvb_image;
+mod object;
+mod page;
+mod parser;
+mod segment;
+
+pub use decoder::{DecodeTimeImage, DecodeTimeOnly, DvbDecoder};
+pub use dvb_image::DvbImage;
+pub use page::Page;
+pub use parser::DvbParser;
+
+use crate::time::TimePoint;
+use thiserror::Error;
+
+/// Error for `DVB` subtitles handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DvbError {
+    /// The payload of a `PES` packet doesn't start with the `DVB` subtitle identifiers.
+    #[error("the PES payload is not a DVB subtitle payload")]
+    InvalidPayload,
+
+    /// A segment doesn't start with the sync byte.
+    #[error("missing segment sync byte at offset {offset}")]
+    MissingSyncByte {
+        /// Offset of the segment in the payload.
+        offset: usize,
+    },
+
+    /// A segment is incomplete.
+    #[error("segment data is truncated")]
+    TruncatedSegment,
+
+    /// No region can be rendered for a displayed subtitle.
+    #[error("missing image of a displayed subtitle")]
+    MissingImage,
+}
+
+/// The payload of a `PES` packet of a `DVB` subtitle stream, with its timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DvbPacket {
+    /// Presentation timestamp, in 90 kHz units.
+    pub pts: u64,
+    /// Payload of the packet: the subtitling segments.
+    pub payload: Vec<u8>,
+}
+
+impl DvbPacket {
+    /// Create a packet from a complete `PES` packet, with its header.
+    ///
+    /// Return `None` if the `PES` header is invalid.
+    #[must_use]
+    pub fn from_pes(pes: &[u8]) -> Option<Self> {
+        let pes = crate::ts::pes_payload(pes)?;
+        Some(Self {
+            pts: pes.pts,
+            payload: pes.payload.to_vec(),
+        })
+    }
+
+    /// Presentation time of the packet.
+    #[must_use]
+    pub fn time(&self) -> TimePoint {
+        TimePoint::from_msecs(i64::try_from(self.pts / 90).unwrap_or(i64::MAX))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        content::Size,
+        image::{ImageSize as _, IndexedImage as _, ToImage as _},
+        time::TimeSpan,
+    };
+    use image::Rgba;
+
+    /// Add a segment to a payload.
+    fn push_segment(payload: &mut Vec<u8>, segment_type: u8, data: &[u8]) {
+        payload.extend([0x0f, segment_type, 0x00, 0x01]);
+        payload.extend(u16::try_from(data.len()).unwrap().to_be_bytes());
+        payload.extend(data);
+    }
+
+    /// Packets of a subtitle displayed from 1 to 3 seconds, then of an empty page.
+    pub(crate) fn packets() -> Vec<DvbPacket> {
+        let mut shown = vec![0x20, 0x00];
+        // Page with a time-out of 10 s, mode change, with the region `0` at 100x500.
+        push_segment(&mut shown, 0x10, &[10, 0x08, 0, 0xff, 0, 100, 0x01, 0xf4]);
+        // Region of 8x2, 4-bit, filled with `0`, with the object `0` at 1x0.
+        push_segment(
+            &mut shown,
+            0x11,
+            &[0, 0x08, 0, 8, 0, 2, 0x48, 0, 0, 0, 0, 0, 0x00, 0x01, 0, 0],
+        );
+        // `CLUT` with a white entry `1`.
+        push_segment(&mut shown, 0x12, &[0, 0x00, 1, 0x41, 235, 128, 128, 0]);
+        // Object with a line of `1` and 5 pixels of `3`, repeated on the bottom field.
+        push_segment(
+            &mut shown,
+            0x13,
+            &[0, 0, 0x00, 0, 5, 0, 0, 0x11, 0x10, 0x93, 0x00, 0xf0],
+        );
+        push_segment(&mut shown, 0x80, &[]);
+        shown.push(0xff);
+
+        let mut cleared = vec![0x20, 0x00];
+        push_segment(&mut cleared, 0x10, &[10, 0x10]);
+        push_segment(&mut cleared, 0x80, &[]);
+        cleared.push(0xff);
+
+        vec![
+            DvbPacket {
+                pts: 90_000,
+                payload: shown,
+            },
+            DvbPacket {
+                pts: 270_000,
+                payload: cleared,
+            },
+        ]
+    }
+
+    #[test]
+    fn parse_packets() {
+        let times = DvbParser::<_, DecodeTimeOnly>::new(packets())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = TimeSpan::new(TimePoint::from_msecs(1000), TimePoint::from_msecs(3000));
+        assert_eq!(times, [expected]);
+
+        let mut parser = DvbParser::<_, DecodeTimeImage>::new(packets());
+        let (time_span, image) = parser.next().unwrap().unwrap();
+        assert!(parser.next().is_none());
+        assert_eq!(time_span, expected);
+        assert_eq!(image.position(), (100, 500));
+        assert_eq!(image.frame_size(), Size { w: 720, h: 576 });
+        assert_eq!((image.width(), image.height()), (8, 2));
+        assert_eq!(image.indexes()[..8], [0, 1, 2, 2, 2, 2, 2, 0]);
+        assert_eq!(image.indexes()[8..], image.indexes()[..8]);
+        let pixels = image.to_image();
+        assert_eq!(pixels.get_pixel(1, 1), &Rgba([255, 255, 255, 255]));
+        assert_eq!(pixels.get_pixel(2, 0), &Rgba([255, 255, 0, 255]));
+        assert_eq!(pixels.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn render_oversized_regions() {
+        let mut payload = vec![0x20, 0x00];
+        // Page with the region `0` at 0x0, and the region `1` at 65534x0.
+        push_segment(
+            &mut payload,
+            0x10,
+            &[10, 0x08, 0, 0xff, 0, 0, 0, 0, 1, 0xff, 0xff, 0xfe, 0, 0],
+        );
+        // Regions of 8x2 and 65535x65535, 4-bit, filled with `1`, without objects.
+        push_segment(&mut payload, 0x11, &[0, 0x08, 0, 8, 0, 2, 0x48, 0, 0, 0x10]);
+        push_segment(
+            &mut payload,
+            0x11,
+            &[1, 0x08, 0xff, 0xff, 0xff, 0xff, 0x48, 0, 0, 0x10],
+        );
+        push_segment(&mut payload, 0x80, &[]);
+        payload.push(0xff);
+
+        let mut page = page::Page::default();
+        assert_eq!(page.update(&payload, None).unwrap(), Some(true));
+        let image = page.render().unwrap();
+        assert_eq!((image.width(), image.height()), (4096, 4096));
+        // Only the region `0` is in the page.
+        assert_eq!(
+            image.indexes().iter().filter(|&&index| index != 0).count(),
+            16
+        );
+    }
+
+    #[test]
+    fn ignore_other_pages_and_time_out() {
+        let times = DvbParser::<_, DecodeTimeOnly>::new(packets())
+            .with_pages(2, 2)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(times.is_empty());
+
+        // Without the empty page, the subtitle ends at its time-out.
+        let times = DvbParser::<_, DecodeTimeOnly>::new(packets().into_iter().take(1))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(times[0].end, TimePoint::from_msecs(11_000));
+    }
+
+    #[test]
+    fn read_pes_packet() {
+        let mut pes = vec![
+            0, 0, 1, 0xbd, 0, 11, 0x80, 0x80, 5, 0x21, 0x00, 0x05, 0xbf, 0x21,
+        ];
+        pes.extend([0x20, 0x00, 0xff]);
+        let packet = DvbPacket::from_pes(&pes).unwrap();
+        assert_eq!(packet.pts, 90_000);
+        assert_eq!(packet.time(), TimePoint::from_msecs(1000));
+        assert_eq!(packet.payload, [0x20, 0x00, 0xff]);
+    }
+}
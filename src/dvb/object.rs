@@ -0,0 +1,279 @@
+//! Decoding of the pixel data of objects in the region pixel buffers.
+
+use super::segment::{Depth, ObjectData};
+
+/// Data type of a 2-bit/pixel code string.
+const STRING_2_BITS: u8 = 0x10;
+/// Data type of a 4-bit/pixel code string.
+const STRING_4_BITS: u8 = 0x11;
+/// Data type of a 8-bit/pixel code string.
+const STRING_8_BITS: u8 = 0x12;
+/// Data type of a 2 to 4-bit map table.
+const MAP_TABLE_2_TO_4: u8 = 0x20;
+/// Data type of a 2 to 8-bit map table.
+const MAP_TABLE_2_TO_8: u8 = 0x21;
+/// Data type of a 4 to 8-bit map table.
+const MAP_TABLE_4_TO_8: u8 = 0x22;
+/// Data type of the end of an object line.
+const END_OF_LINE: u8 = 0xf0;
+
+/// Reader of the bits of a pixel code string, most significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Read up to 8 bits, return `None` at the end of the data.
+    fn read(&mut self, count: usize) -> Option<u8> {
+        let mut value = 0;
+        for _ in 0..count {
+            let byte = self.data.get(self.position / 8)?;
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | bit;
+            self.position += 1;
+        }
+        Some(value)
+    }
+
+    /// Number of bytes read, the last one being partially read.
+    const fn consumed(&self) -> usize {
+        self.position.div_ceil(8)
+    }
+}
+
+/// Read the next run of pixels of a code string, as a number of pixels and a pixel code.
+/// Return `None` at the end of the string.
+fn next_run(reader: &mut BitReader<'_>, bits: usize) -> Option<(usize, u8)> {
+    let code = reader.read(bits)?;
+    if code != 0 {
+        return Some((1, code));
+    }
+    let run = |reader: &mut BitReader<'_>, len, offset| {
+        reader.read(len).map(|run| usize::from(run) + offset)
+    };
+    match bits {
+        2 => {
+            if reader.read(1)? == 1 {
+                return Some((run(reader, 3, 3)?, reader.read(2)?));
+            }
+            if reader.read(1)? == 1 {
+                return Some((1, 0));
+            }
+            match reader.read(2)? {
+                0 => None,
+                1 => Some((2, 0)),
+                2 => Some((run(reader, 4, 12)?, reader.read(2)?)),
+                _ => Some((run(reader, 8, 29)?, reader.read(2)?)),
+            }
+        }
+        4 => {
+            if reader.read(1)? == 0 {
+                let run = run(reader, 3, 0)?;
+                return (run != 0).then_some((run + 2, 0));
+            }
+            if reader.read(1)? == 0 {
+                return Some((run(reader, 2, 4)?, reader.read(4)?));
+            }
+            match reader.read(2)? {
+                0 => Some((1, 0)),
+                1 => Some((2, 0)),
+                2 => Some((run(reader, 4, 9)?, reader.read(4)?)),
+                _ => Some((run(reader, 8, 25)?, reader.read(4)?)),
+            }
+        }
+        _ => {
+            if reader.read(1)? == 0 {
+                let run = run(reader, 7, 0)?;
+                return (run != 0).then_some((run, 0));
+            }
+            Some((run(reader, 7, 0)?, reader.read(8)?))
+        }
+    }
+}
+
+/// Map tables of the pixel codes of strings with less bits than the region.
+struct MapTables {
+    two_to_four: [u8; 4],
+    two_to_eight: [u8; 4],
+    four_to_eight: [u8; 16],
+}
+
+impl Default for MapTables {
+    fn default() -> Self {
+        Self {
+            two_to_four: [0x0, 0x7, 0x8, 0xf],
+            two_to_eight: [0x00, 0x77, 0x88, 0xff],
+            four_to_eight: std::array::from_fn(|idx| u8::try_from(idx * 0x11).unwrap_or(u8::MAX)),
+        }
+    }
+}
+
+impl MapTables {
+    /// Convert a pixel code of a string of `bits` bits to the `depth` of the region.
+    /// Strings with more bits than the region keep the most significant bits.
+    fn convert(&self, code: u8, bits: usize, depth: Depth) -> u8 {
+        let idx = usize::from(code);
+        match (bits, depth) {
+            (2, Depth::Bits4) => self.two_to_four[idx & 0x03],
+            (2, Depth::Bits8) => self.two_to_eight[idx & 0x03],
+            (4, Depth::Bits8) => self.four_to_eight[idx & 0x0f],
+            (4, Depth::Bits2) => code >> 2,
+            (8, Depth::Bits4) => code >> 4,
+            (8, Depth::Bits2) => code >> 6,
+            _ => code,
+        }
+    }
+}
+
+/// Pixel codes of a region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Pixmap {
+    pub width: usize,
+    pub height: usize,
+    pub depth: Depth,
+    pub codes: Vec<u8>,
+}
+
+impl Pixmap {
+    /// Create a pixmap filled with the `background` code.
+    pub fn new(width: usize, height: usize, depth: Depth, background: u8) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+            codes: vec![background; width * height],
+        }
+    }
+
+    /// Draw an object with its top left corner at `x`, `y`.
+    pub fn draw(&mut self, object: &ObjectData, x: usize, y: usize) {
+        self.draw_field(&object.top, x, y, object.non_modifying_color);
+        self.draw_field(&object.bottom, x, y + 1, object.non_modifying_color);
+    }
+
+    /// Draw the lines of a field, starting at line `y`.
+    fn draw_field(&mut self, mut data: &[u8], x: usize, mut y: usize, non_modifying: bool) {
+        let mut maps = MapTables::default();
+        let mut column = x;
+        while let Some((&data_type, rest)) = data.split_first() {
+            data = rest;
+            match data_type {
+                STRING_2_BITS | STRING_4_BITS | STRING_8_BITS => {
+                    let bits = match data_type {
+                        STRING_2_BITS => 2,
+                        STRING_4_BITS => 4,
+                        _ => 8,
+                    };
+                    let mut reader = BitReader::new(data);
+                    while let Some((run, code)) = next_run(&mut reader, bits) {
+                        if !(non_modifying && code == 1) {
+                            self.fill(column, y, run, maps.convert(code, bits, self.depth));
+                        }
+                        column += run;
+                    }
+                    data = &data[reader.consumed()..];
+                }
+                MAP_TABLE_2_TO_4 => {
+                    let Some(table) = data.get(..2) else { break };
+                    maps.two_to_four = std::array::from_fn(|idx| nibble(table, idx));
+                    data = &data[2..];
+                }
+                MAP_TABLE_2_TO_8 => {
+                    let Some(table) = data.get(..4) else { break };
+                    maps.two_to_eight.copy_from_slice(table);
+                    data = &data[4..];
+                }
+                MAP_TABLE_4_TO_8 => {
+                    let Some(table) = data.get(..16) else { break };
+                    maps.four_to_eight.copy_from_slice(table);
+                    data = &data[16..];
+                }
+                END_OF_LINE => {
+                    column = x;
+                    y += 2;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Set the code of `run` pixels of the line `y` from the column `x`.
+    fn fill(&mut self, x: usize, y: usize, run: usize, code: u8) {
+        if y >= self.height || x >= self.width {
+            return;
+        }
+        let start = y * self.width + x;
+        let end = start + run.min(self.width - x);
+        self.codes[start..end].fill(code);
+    }
+}
+
+/// Get a nibble of a table, the high nibble first.
+fn nibble(table: &[u8], idx: usize) -> u8 {
+    let byte = table[idx / 2];
+    if idx % 2 == 0 {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(top: Vec<u8>, bottom: Vec<u8>) -> ObjectData {
+        ObjectData {
+            id: 0,
+            version: 0,
+            non_modifying_color: false,
+            top,
+            bottom,
+        }
+    }
+
+    #[test]
+    fn decode_4_bit_strings() {
+        let mut pixmap = Pixmap::new(8, 4, Depth::Bits4, 0);
+        // `1`, then 5 pixels of `3` (`0000 1001 0011`), then end of string.
+        let line = vec![STRING_4_BITS, 0x10, 0x93, 0x00, END_OF_LINE];
+        pixmap.draw(&object(line.clone(), line), 1, 1);
+        let rows = pixmap.codes.chunks_exact(8).collect::<Vec<_>>();
+        assert_eq!(rows[0], [0; 8]);
+        assert_eq!(rows[1], [0, 1, 3, 3, 3, 3, 3, 0]);
+        assert_eq!(rows[2], [0, 1, 3, 3, 3, 3, 3, 0]);
+        assert_eq!(rows[3], [0; 8]);
+    }
+
+    #[test]
+    fn decode_2_bit_strings_with_map() {
+        let mut pixmap = Pixmap::new(4, 2, Depth::Bits8, 0);
+        // `3`, `1`, `2 pixels of 0` (`00 0 0 01`), end of string (`00 0 0 00`).
+        let line = vec![STRING_2_BITS, 0b1101_0000, 0b0100_0000, END_OF_LINE];
+        pixmap.draw(&object(line, Vec::new()), 0, 0);
+        assert_eq!(&pixmap.codes[..4], [0xff, 0x77, 0, 0]);
+    }
+
+    #[test]
+    fn decode_8_bit_strings() {
+        let mut pixmap = Pixmap::new(6, 1, Depth::Bits8, 0);
+        // `0x20`, then 4 pixels of `0x40`, end of string.
+        let line = vec![
+            STRING_8_BITS,
+            0x20,
+            0x00,
+            0x84,
+            0x40,
+            0x00,
+            0x00,
+            END_OF_LINE,
+        ];
+        pixmap.draw(&object(line, Vec::new()), 1, 0);
+        assert_eq!(pixmap.codes, [0, 0x20, 0x40, 0x40, 0x40, 0x40]);
+    }
+}
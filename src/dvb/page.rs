@@ -0,0 +1,254 @@
+//! State of the page of a subtitle stream, updated by the segments of each display set.
+
+use super::{
+    clut::Clut,
+    object::Pixmap,
+    segment::{
+        segments, ClutDefinition, DisplayDefinition, ObjectData, PageComposition, PageRegion,
+        PageState, RegionComposition, SegmentType,
+    },
+    DvbError, DvbImage,
+};
+use crate::content::Size;
+use image::Rgba;
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+
+/// Size of the display when no Display Definition Segment is sent.
+const DEFAULT_DISPLAY_SIZE: Size = Size { w: 720, h: 576 };
+/// Maximum width and height of a region, and of a rendered page, in pixels.
+/// This is the maximum size of the display of `DVB` subtitles.
+const MAX_SIZE: usize = 4096;
+
+/// Content of the page of a `DVB` subtitle stream, as defined by the segments of the
+/// current epoch.
+#[derive(Debug, Clone)]
+pub struct Page {
+    display_size: Size,
+    time_out: u8,
+    version: Option<u8>,
+    visible_regions: Vec<PageRegion>,
+    regions: BTreeMap<u8, RegionComposition>,
+    cluts: BTreeMap<u8, (u8, Clut)>,
+    objects: BTreeMap<u16, ObjectData>,
+    changed: bool,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self {
+            display_size: DEFAULT_DISPLAY_SIZE,
+            time_out: 0,
+            version: None,
+            visible_regions: Vec::new(),
+            regions: BTreeMap::new(),
+            cluts: BTreeMap::new(),
+            objects: BTreeMap::new(),
+            changed: false,
+        }
+    }
+}
+
+impl Page {
+    /// Size of the display on which the subtitles are shown.
+    #[must_use]
+    pub const fn display_size(&self) -> Size {
+        self.display_size
+    }
+
+    /// Time in seconds after which the page must be erased, `0` if not defined.
+    #[must_use]
+    pub const fn time_out(&self) -> u8 {
+        self.time_out
+    }
+
+    /// If no object is displayed in the visible regions of the page.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.visible_regions.iter().any(|visible| {
+            self.regions.get(&visible.id).is_some_and(|region| {
+                region
+                    .objects
+                    .iter()
+                    .any(|object| self.objects.contains_key(&object.id))
+            })
+        })
+    }
+
+    /// Render the visible regions of the page in an image.
+    ///
+    /// The image covers the bounding box of the regions, and its palette is built from
+    /// the colors used by the regions. Return `None` if no region is visible.
+    #[must_use]
+    pub fn render(&self) -> Option<DvbImage> {
+        let regions = self
+            .visible_regions
+            .iter()
+            .filter_map(|visible| Some((visible, self.regions.get(&visible.id)?)))
+            .collect::<Vec<_>>();
+        let left = regions.iter().map(|(visible, _)| visible.x).min()?;
+        let top = regions.iter().map(|(visible, _)| visible.y).min()?;
+        let right = regions
+            .iter()
+            .map(|(visible, region)| usize::from(visible.x) + region_size(region).0)
+            .max()?;
+        let bottom = regions
+            .iter()
+            .map(|(visible, region)| usize::from(visible.y) + region_size(region).1)
+            .max()?;
+        // The regions out of the maximum size of the page are clipped.
+        let width = (right - usize::from(left)).min(MAX_SIZE);
+        let height = (bottom - usize::from(top)).min(MAX_SIZE);
+
+        let mut palette = vec![Rgba([0, 0, 0, 0])];
+        let mut palette_indexes = HashMap::new();
+        let mut indexes = vec![0; width * height];
+        for (visible, region) in regions {
+            let default_clut = Clut::default();
+            let clut = self
+                .cluts
+                .get(&region.clut_id)
+                .map_or(&default_clut, |(_, clut)| clut);
+            let pixmap = self.region_pixmap(region);
+            let (x, y) = (usize::from(visible.x - left), usize::from(visible.y - top));
+            if x >= width {
+                continue;
+            }
+            let rows = pixmap.codes.chunks_exact(pixmap.width.max(1));
+            for (row, codes) in rows.take(height.saturating_sub(y)).enumerate() {
+                let start = (y + row) * width + x;
+                let len = codes.len().min(width - x);
+                for (index, code) in indexes[start..start + len].iter_mut().zip(codes) {
+                    let color = clut.color(region.depth, *code);
+                    if color[3] == 0 {
+                        continue;
+                    }
+                    *index = *palette_indexes.entry(color).or_insert_with(|| {
+                        u8::try_from(palette.len()).map_or_else(
+                            |_| {
+                                warn!(
+                                    "More than 256 colors in DVB subtitle, color {color:?} dropped"
+                                );
+                                0
+                            },
+                            |index| {
+                                palette.push(color);
+                                index
+                            },
+                        )
+                    });
+                }
+            }
+        }
+        Some(DvbImage::new(
+            u16::try_from(width).ok()?,
+            u16::try_from(height).ok()?,
+            (left, top),
+            self.display_size,
+            palette,
+            indexes,
+        ))
+    }
+
+    /// Draw the objects of a region.
+    fn region_pixmap(&self, region: &RegionComposition) -> Pixmap {
+        let background = if region.fill { region.background } else { 0 };
+        let (width, height) = region_size(region);
+        if (width, height) != (usize::from(region.width), usize::from(region.height)) {
+            warn!(
+                "Region {} of {}x{} pixels clipped to {width}x{height}",
+                region.id, region.width, region.height
+            );
+        }
+        let mut pixmap = Pixmap::new(width, height, region.depth, background);
+        for object in &region.objects {
+            if let Some(data) = self.objects.get(&object.id) {
+                pixmap.draw(data, usize::from(object.x), usize::from(object.y));
+            } else {
+                warn!("Missing object {} of region {}", object.id, region.id);
+            }
+        }
+        pixmap
+    }
+
+    /// Apply the segments of the payload of a `PES` packet. The segments of other pages than
+    /// `pages` (composition page and ancillary page) are ignored, if defined.
+    ///
+    /// Return `None` if the payload contains no page composition, else if the content
+    /// of the page changed since the previous page composition.
+    pub(super) fn update(
+        &mut self,
+        payload: &[u8],
+        pages: Option<(u16, u16)>,
+    ) -> Result<Option<bool>, DvbError> {
+        let mut composed = false;
+        for segment in segments(payload)? {
+            if let Some((composition, ancillary)) = pages {
+                if segment.page_id != composition && segment.page_id != ancillary {
+                    continue;
+                }
+            }
+            match segment.kind {
+                SegmentType::PageComposition => {
+                    let page = PageComposition::parse(segment.data)?;
+                    if page.state == PageState::ModeChange {
+                        // A new epoch starts, the content of the previous epoch is discarded.
+                        self.regions.clear();
+                        self.cluts.clear();
+                        self.objects.clear();
+                        self.changed = true;
+                    }
+                    if self.version != Some(page.version) || self.visible_regions != page.regions {
+                        self.changed = true;
+                    }
+                    self.version = Some(page.version);
+                    self.time_out = page.time_out;
+                    self.visible_regions = page.regions;
+                    composed = true;
+                }
+                SegmentType::RegionComposition => {
+                    let region = RegionComposition::parse(segment.data)?;
+                    if self.regions.get(&region.id) != Some(&region) {
+                        self.changed = true;
+                        self.regions.insert(region.id, region);
+                    }
+                }
+                SegmentType::ClutDefinition => {
+                    let definition = ClutDefinition::parse(segment.data)?;
+                    let (version, clut) = self
+                        .cluts
+                        .entry(definition.id)
+                        .or_insert_with(|| (definition.version.wrapping_add(1), Clut::default()));
+                    if *version != definition.version {
+                        clut.update(&definition);
+                        *version = definition.version;
+                        self.changed = true;
+                    }
+                }
+                SegmentType::ObjectData => {
+                    if let Some(object) = ObjectData::parse(segment.data)? {
+                        if self.objects.get(&object.id) != Some(&object) {
+                            self.changed = true;
+                            self.objects.insert(object.id, object);
+                        }
+                    } else {
+                        warn!("Objects coded as strings of characters are not supported");
+                    }
+                }
+                SegmentType::DisplayDefinition => {
+                    self.display_size = DisplayDefinition::parse(segment.data)?.size;
+                }
+                SegmentType::EndOfDisplaySet | SegmentType::Other(_) => {}
+            }
+        }
+        Ok(composed.then(|| std::mem::take(&mut self.changed)))
+    }
+}
+
+/// Size of a region, limited to the maximum size.
+fn region_size(region: &RegionComposition) -> (usize, usize) {
+    (
+        usize::from(region.width).min(MAX_SIZE),
+        usize::from(region.height).min(MAX_SIZE),
+    )
+}
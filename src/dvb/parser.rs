@@ -0,0 +1,138 @@
+use super::{DvbDecoder, DvbError, DvbPacket, Page};
+use crate::time::{TimePoint, TimeSpan};
+use std::{iter::FusedIterator, marker::PhantomData};
+
+/// A subtitle displayed, waiting for the end of its display.
+struct Pending<Content> {
+    start: TimePoint,
+    /// Time at which the page times out, if defined.
+    deadline: Option<TimePoint>,
+    content: Content,
+}
+
+/// To parse the subtitles of a `DVB` subtitle stream, from the `PES` packets of the stream.
+pub struct DvbParser<Packets, Decoder>
+where
+    Packets: Iterator<Item = DvbPacket>,
+    Decoder: DvbDecoder,
+{
+    packets: Packets,
+    pages: Option<(u16, u16)>,
+    page: Page,
+    pending: Option<Pending<Decoder::Content>>,
+    last_time: TimePoint,
+    /// Error to return after the subtitle ended by the failing display set.
+    error: Option<DvbError>,
+    phantom_data: PhantomData<Decoder>,
+}
+
+impl<Packets, Decoder> DvbParser<Packets, Decoder>
+where
+    Packets: Iterator<Item = DvbPacket>,
+    Decoder: DvbDecoder,
+{
+    /// Create a parser from the `PES` packets of a stream, in presentation order.
+    pub fn new<P>(packets: P) -> Self
+    where
+        P: IntoIterator<IntoIter = Packets>,
+    {
+        Self {
+            packets: packets.into_iter(),
+            pages: None,
+            page: Page::default(),
+            pending: None,
+            last_time: TimePoint::default(),
+            error: None,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Only decode the segments of a composition page and of its ancillary page,
+    /// as declared in the subtitling descriptor of the stream.
+    ///
+    /// By default, the segments of all pages are decoded.
+    #[must_use]
+    pub const fn with_pages(mut self, composition_page: u16, ancillary_page: u16) -> Self {
+        self.pages = Some((composition_page, ancillary_page));
+        self
+    }
+
+    /// End the display of the pending subtitle at `time`, or at its deadline if earlier.
+    fn end_pending(&mut self, time: TimePoint) -> Option<Decoder::Output> {
+        self.pending.take().map(|pending| {
+            let end = pending.deadline.map_or(time, |deadline| deadline.min(time));
+            Decoder::output(TimeSpan::new(pending.start, end), pending.content)
+        })
+    }
+}
+
+/// Compute the time at which a page times out.
+fn deadline(time: TimePoint, time_out: u8) -> Option<TimePoint> {
+    (time_out != 0).then(|| TimePoint::from_msecs(time.msecs() + i64::from(time_out) * 1000))
+}
+
+impl<Packets, Decoder> Iterator for DvbParser<Packets, Decoder>
+where
+    Packets: Iterator<Item = DvbPacket>,
+    Decoder: DvbDecoder,
+{
+    type Item = Result<Decoder::Output, DvbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        loop {
+            let Some(packet) = self.packets.next() else {
+                // At the end of the stream, the last subtitle ends at its deadline.
+                let end = self.pending.as_ref().map(|pending| {
+                    pending
+                        .deadline
+                        .unwrap_or_else(|| self.last_time.max(pending.start))
+                })?;
+                return self.end_pending(end).map(Ok);
+            };
+            let time = packet.time();
+            self.last_time = time;
+            let changed = match self.page.update(&packet.payload, self.pages) {
+                Ok(Some(changed)) => changed,
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            };
+            if !changed {
+                // The same page is sent again, its display is extended.
+                if let Some(pending) = &mut self.pending {
+                    pending.deadline = deadline(time, self.page.time_out());
+                }
+                continue;
+            }
+
+            let ended = self.end_pending(time);
+            if !self.page.is_empty() {
+                match Decoder::decode(&self.page) {
+                    Ok(content) => {
+                        self.pending = Some(Pending {
+                            start: time,
+                            deadline: deadline(time, self.page.time_out()),
+                            content,
+                        });
+                    }
+                    Err(err) => self.error = Some(err),
+                }
+            }
+            if let Some(output) = ended {
+                return Some(Ok(output));
+            }
+            if let Some(err) = self.error.take() {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+impl<Packets, Decoder> FusedIterator for DvbParser<Packets, Decoder>
+where
+    Packets: FusedIterator<Item = DvbPacket>,
+    Decoder: DvbDecoder,
+{
+}
@@ -0,0 +1,367 @@
+//! Parsing of the subtitling segments carried in the `PES` packets.
+
+use super::DvbError;
+use crate::content::Size;
+use image::Rgba;
+
+/// `data_identifier` of `DVB` subtitle `PES` payloads.
+const DATA_IDENTIFIER: u8 = 0x20;
+/// `subtitle_stream_id` of `DVB` subtitle `PES` payloads.
+const SUBTITLE_STREAM_ID: u8 = 0x00;
+/// Sync byte at the start of each segment.
+const SYNC_BYTE: u8 = 0x0f;
+/// Marker at the end of the segments of a `PES` payload.
+const END_OF_PES_MARKER: u8 = 0xff;
+/// Length of the segment header.
+const HEADER_LEN: usize = 6;
+
+/// Type of a subtitling segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SegmentType {
+    PageComposition,
+    RegionComposition,
+    ClutDefinition,
+    ObjectData,
+    DisplayDefinition,
+    EndOfDisplaySet,
+    Other(u8),
+}
+
+impl From<u8> for SegmentType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x10 => Self::PageComposition,
+            0x11 => Self::RegionComposition,
+            0x12 => Self::ClutDefinition,
+            0x13 => Self::ObjectData,
+            0x14 => Self::DisplayDefinition,
+            0x80 => Self::EndOfDisplaySet,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+/// A subtitling segment, with its content not parsed.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Segment<'a> {
+    pub kind: SegmentType,
+    pub page_id: u16,
+    pub data: &'a [u8],
+}
+
+/// Split the payload of a `PES` packet in segments.
+pub(super) fn segments(payload: &[u8]) -> Result<Vec<Segment<'_>>, DvbError> {
+    let [DATA_IDENTIFIER, SUBTITLE_STREAM_ID, segments_data @ ..] = payload else {
+        return Err(DvbError::InvalidPayload);
+    };
+    let mut data = segments_data;
+    let mut segments = Vec::new();
+    while let Some((&byte, rest)) = data.split_first() {
+        match byte {
+            SYNC_BYTE => {}
+            END_OF_PES_MARKER => break,
+            _ => {
+                return Err(DvbError::MissingSyncByte {
+                    offset: payload.len() - data.len(),
+                })
+            }
+        }
+        let header = rest
+            .get(..HEADER_LEN - 1)
+            .ok_or(DvbError::TruncatedSegment)?;
+        let length = usize::from(u16::from_be_bytes([header[3], header[4]]));
+        segments.push(Segment {
+            kind: SegmentType::from(header[0]),
+            page_id: u16::from_be_bytes([header[1], header[2]]),
+            data: rest
+                .get(HEADER_LEN - 1..HEADER_LEN - 1 + length)
+                .ok_or(DvbError::TruncatedSegment)?,
+        });
+        data = &rest[HEADER_LEN - 1 + length..];
+    }
+    Ok(segments)
+}
+
+/// Read a big endian `u16` at `offset`.
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, DvbError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or(DvbError::TruncatedSegment)
+}
+
+/// Read a byte at `offset`.
+fn read_u8(data: &[u8], offset: usize) -> Result<u8, DvbError> {
+    data.get(offset).copied().ok_or(DvbError::TruncatedSegment)
+}
+
+/// State of a page composition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PageState {
+    /// Update of the content of the current epoch.
+    NormalCase,
+    /// Refresh of the whole content, for decoders starting in the stream.
+    AcquisitionPoint,
+    /// Start of a new epoch: all the previous content is discarded.
+    ModeChange,
+}
+
+/// A region displayed on the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PageRegion {
+    pub id: u8,
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Content of a Page Composition Segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct PageComposition {
+    /// Time in seconds after which the page must be erased.
+    pub time_out: u8,
+    pub version: u8,
+    pub state: PageState,
+    pub regions: Vec<PageRegion>,
+}
+
+impl PageComposition {
+    pub fn parse(data: &[u8]) -> Result<Self, DvbError> {
+        let flags = read_u8(data, 1)?;
+        let state = match (flags >> 2) & 0x03 {
+            0 => PageState::NormalCase,
+            1 => PageState::AcquisitionPoint,
+            _ => PageState::ModeChange,
+        };
+        let regions = data[2..]
+            .chunks_exact(6)
+            .map(|region| PageRegion {
+                id: region[0],
+                x: u16::from_be_bytes([region[2], region[3]]),
+                y: u16::from_be_bytes([region[4], region[5]]),
+            })
+            .collect();
+        Ok(Self {
+            time_out: read_u8(data, 0)?,
+            version: flags >> 4,
+            state,
+            regions,
+        })
+    }
+}
+
+/// Number of bits per pixel of a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Depth {
+    Bits2,
+    Bits4,
+    Bits8,
+}
+
+/// An object displayed in a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RegionObject {
+    pub id: u16,
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Content of a Region Composition Segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RegionComposition {
+    pub id: u8,
+    pub version: u8,
+    /// If the region is filled with the background color.
+    pub fill: bool,
+    pub width: u16,
+    pub height: u16,
+    pub depth: Depth,
+    pub clut_id: u8,
+    /// Background pixel code, for the depth of the region.
+    pub background: u8,
+    pub objects: Vec<RegionObject>,
+}
+
+impl RegionComposition {
+    pub fn parse(data: &[u8]) -> Result<Self, DvbError> {
+        let flags = read_u8(data, 1)?;
+        let depth = match (read_u8(data, 6)? >> 2) & 0x07 {
+            1 => Depth::Bits2,
+            2 => Depth::Bits4,
+            _ => Depth::Bits8,
+        };
+        let background = match depth {
+            Depth::Bits8 => read_u8(data, 8)?,
+            Depth::Bits4 => read_u8(data, 9)? >> 4,
+            Depth::Bits2 => (read_u8(data, 9)? >> 2) & 0x03,
+        };
+
+        let mut objects = Vec::new();
+        let mut offset = 10;
+        while offset + 6 <= data.len() {
+            let object_type = data[offset + 2] >> 6;
+            objects.push(RegionObject {
+                id: read_u16(data, offset)?,
+                x: read_u16(data, offset + 2)? & 0x0fff,
+                y: read_u16(data, offset + 4)? & 0x0fff,
+            });
+            // Character objects have foreground and background pixel codes.
+            offset += if object_type == 1 || object_type == 2 {
+                8
+            } else {
+                6
+            };
+        }
+        Ok(Self {
+            id: read_u8(data, 0)?,
+            version: flags >> 4,
+            fill: flags & 0x08 != 0,
+            width: read_u16(data, 2)?,
+            height: read_u16(data, 4)?,
+            depth,
+            clut_id: read_u8(data, 7)?,
+            background,
+            objects,
+        })
+    }
+}
+
+/// An entry of a `CLUT` Definition Segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ClutEntry {
+    pub id: u8,
+    /// Flags of the `CLUT`s defined by the entry: `0x80` for 2-bit, `0x40` for 4-bit,
+    /// `0x20` for 8-bit.
+    pub depths: u8,
+    pub color: Rgba<u8>,
+}
+
+/// Content of a `CLUT` Definition Segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ClutDefinition {
+    pub id: u8,
+    pub version: u8,
+    pub entries: Vec<ClutEntry>,
+}
+
+impl ClutDefinition {
+    pub fn parse(data: &[u8]) -> Result<Self, DvbError> {
+        let mut entries = Vec::new();
+        let mut offset = 2;
+        while offset + 4 <= data.len() {
+            let (id, flags) = (data[offset], data[offset + 1]);
+            let full_range = flags & 0x01 != 0;
+            let (y, cr, cb, t) = if full_range {
+                let color = data
+                    .get(offset + 2..offset + 6)
+                    .ok_or(DvbError::TruncatedSegment)?;
+                (color[0], color[1], color[2], color[3])
+            } else {
+                // 6 bits of `Y`, 4 bits of `Cr` and `Cb`, 2 bits of `T`.
+                let value = read_u16(data, offset + 2)?;
+                let bits = |shift: u16, mask: u16, scale: u16| {
+                    u8::try_from(((value >> shift) & mask) << scale).unwrap_or_default()
+                };
+                (
+                    bits(10, 0x3f, 2),
+                    bits(6, 0x0f, 4),
+                    bits(2, 0x0f, 4),
+                    bits(0, 0x03, 6),
+                )
+            };
+            offset += if full_range { 6 } else { 4 };
+            entries.push(ClutEntry {
+                id,
+                depths: flags & 0xe0,
+                color: super::clut::ycrcbt_to_rgba(y, cr, cb, t),
+            });
+        }
+        Ok(Self {
+            id: read_u8(data, 0)?,
+            version: read_u8(data, 1)? >> 4,
+            entries,
+        })
+    }
+}
+
+/// Content of an Object Data Segment coded as pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ObjectData {
+    pub id: u16,
+    pub version: u8,
+    /// If the pixels of code `1` are not drawn.
+    pub non_modifying_color: bool,
+    /// Pixel data of the top field (even lines).
+    pub top: Vec<u8>,
+    /// Pixel data of the bottom field (odd lines).
+    pub bottom: Vec<u8>,
+}
+
+impl ObjectData {
+    /// Parse an object, return `None` for objects coded as characters, which are not supported.
+    pub fn parse(data: &[u8]) -> Result<Option<Self>, DvbError> {
+        let flags = read_u8(data, 2)?;
+        if (flags >> 2) & 0x03 != 0 {
+            return Ok(None);
+        }
+        let top_len = usize::from(read_u16(data, 3)?);
+        let bottom_len = usize::from(read_u16(data, 5)?);
+        let top = data.get(7..7 + top_len).ok_or(DvbError::TruncatedSegment)?;
+        let bottom = data
+            .get(7 + top_len..7 + top_len + bottom_len)
+            .ok_or(DvbError::TruncatedSegment)?;
+        Ok(Some(Self {
+            id: read_u16(data, 0)?,
+            version: flags >> 4,
+            non_modifying_color: flags & 0x02 != 0,
+            top: top.to_vec(),
+            // Without bottom field data, the top field is repeated.
+            bottom: if bottom.is_empty() { top } else { bottom }.to_vec(),
+        }))
+    }
+}
+
+/// Content of a Display Definition Segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct DisplayDefinition {
+    pub version: u8,
+    pub size: Size,
+}
+
+impl DisplayDefinition {
+    pub fn parse(data: &[u8]) -> Result<Self, DvbError> {
+        Ok(Self {
+            version: read_u8(data, 0)? >> 4,
+            size: Size {
+                w: usize::from(read_u16(data, 1)?) + 1,
+                h: usize::from(read_u16(data, 3)?) + 1,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches2::assert_matches;
+
+    #[test]
+    fn split_segments() {
+        let payload = [
+            0x20, 0x00, 0x0f, 0x10, 0x00, 0x01, 0x00, 0x02, 0x05, 0x14, 0x0f, 0x80, 0x00, 0x01,
+            0x00, 0x00, 0xff,
+        ];
+        let parsed = segments(&payload).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].kind, SegmentType::PageComposition);
+        assert_eq!(parsed[0].page_id, 1);
+        assert_eq!(parsed[0].data, [0x05, 0x14]);
+        assert_eq!(parsed[1].kind, SegmentType::EndOfDisplaySet);
+
+        let page = PageComposition::parse(parsed[0].data).unwrap();
+        assert_eq!(page.time_out, 5);
+        assert_eq!(page.version, 1);
+        assert_eq!(page.state, PageState::AcquisitionPoint);
+
+        assert_matches!(segments(&payload[1..]), Err(DvbError::InvalidPayload));
+        assert_matches!(segments(&payload[..8]), Err(DvbError::TruncatedSegment));
+    }
+}
@@ -15,4 +15,44 @@ pub enum SubtileError {
     /// Error during image dump
     #[error("dump images failed")]
     ImageDump(#[from] crate::image::DumpError),
+
+    /// Error with `DVB` subtitles
+    #[error("error with DVB subtitles")]
+    Dvb(#[from] crate::dvb::DvbError),
+
+    /// Error with `ASS`
+    #[error("error with ASS")]
+    Ass(#[from] crate::ass::AssError),
+
+    /// Error with `Matroska`
+    #[error("error with Matroska")]
+    Mkv(#[from] crate::mkv::MkvError),
+
+    /// Error with `SCC`
+    #[error("error with SCC")]
+    Scc(#[from] crate::scc::SccError),
+
+    /// Error with `EBU STL`
+    #[error("error with EBU STL")]
+    Stl(#[from] crate::stl::StlError),
+
+    /// Error with Teletext
+    #[error("error with Teletext")]
+    Teletext(#[from] crate::teletext::TeletextError),
+
+    /// Error with simple text formats
+    #[error("error with text subtitles")]
+    Text(#[from] crate::text::TextError),
+
+    /// Error with transport streams
+    #[error("error with transport stream")]
+    Ts(#[from] crate::ts::TsError),
+
+    /// Error with `TTML`
+    #[error("error with TTML")]
+    Ttml(#[from] crate::ttml::TtmlError),
+
+    /// Error with glyph `OCR`
+    #[error("error with glyph OCR")]
+    Ocr(#[from] crate::ocr::OcrError),
 }
@@ -0,0 +1,194 @@
+use crate::time::TimeSpan;
+
+/// Define how two subtitle images are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageComparison {
+    /// Images must have exactly the same pixels.
+    Exact,
+    /// Images must have a perceptual hash with a distance lower or equal to `max_distance`.
+    PerceptualHash {
+        /// Maximum number of different bits between the two hashes.
+        max_distance: u32,
+    },
+}
+
+/// A 64 bits perceptual hash of an image (`aHash`).
+///
+/// The image is reduced to a grid of 8x8 cells, and each bit is set if the mean
+/// intensity of the cell is greater than the mean intensity of the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PerceptualHash(u64);
+
+impl PerceptualHash {
+    const GRID_SIZE: u32 = 8;
+
+    /// Compute the hash of an image of size `width` x `height`, with
+    /// the intensity of each pixel provided in row-major order by `pixels`.
+    pub fn new<I>(width: u32, height: u32, pixels: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let grid = Self::GRID_SIZE;
+        let mut sums = [0u64; 64];
+        let mut counts = [0u64; 64];
+
+        let coordinates = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        coordinates.zip(pixels).for_each(|((x, y), intensity)| {
+            let cell = ((y * grid / height) * grid + (x * grid / width)) as usize;
+            sums[cell] += u64::from(intensity);
+            counts[cell] += 1;
+        });
+
+        let means = sums
+            .iter()
+            .zip(counts.iter())
+            .map(|(&sum, &count)| (sum * 256).checked_div(count).unwrap_or(0))
+            .collect::<Vec<_>>();
+        let global_mean = means.iter().sum::<u64>() / means.len() as u64;
+
+        let hash = means
+            .iter()
+            .enumerate()
+            .filter(|(_, &mean)| mean > global_mean)
+            .fold(0, |hash, (bit, _)| hash | (1 << bit));
+        Self(hash)
+    }
+
+    /// Number of different bits between two hashes.
+    #[must_use]
+    pub const fn distance(&self, other: &Self) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+}
+
+/// Define the comparison of subtitle images.
+pub trait CompareImage {
+    /// Check if the pixels of the two images are exactly the same.
+    fn same_pixels(&self, other: &Self) -> bool;
+
+    /// Compute a perceptual hash of the image.
+    fn perceptual_hash(&self) -> PerceptualHash;
+
+    /// Check if the two images are the same, regarding the `comparison` method.
+    fn is_same_image(&self, other: &Self, comparison: ImageComparison) -> bool {
+        match comparison {
+            ImageComparison::Exact => self.same_pixels(other),
+            ImageComparison::PerceptualHash { max_distance } => {
+                self.perceptual_hash().distance(&other.perceptual_hash()) <= max_distance
+            }
+        }
+    }
+}
+
+/// Merge consecutive subtitles with identical images into one subtitle.
+///
+/// Two subtitles are merged if the gap between the end of the first one and the start
+/// of the second one is lower or equal to `max_gap` (in milliseconds), and if their
+/// images are the same regarding `comparison`. The merged subtitle keeps the image of
+/// the first subtitle, and a [`TimeSpan`] covering both.
+#[profiling::function]
+pub fn merge_identical<T>(
+    subtitles: impl IntoIterator<Item = (TimeSpan, T)>,
+    comparison: ImageComparison,
+    max_gap: i64,
+) -> Vec<(TimeSpan, T)>
+where
+    T: CompareImage,
+{
+    let compute_hash = |image: &T| match comparison {
+        ImageComparison::Exact => None,
+        ImageComparison::PerceptualHash { .. } => Some(image.perceptual_hash()),
+    };
+
+    let mut merged: Vec<(TimeSpan, T)> = Vec::new();
+    let mut last_hash: Option<PerceptualHash> = None;
+    for (time, image) in subtitles {
+        let hash = compute_hash(&image);
+        let identical = merged.last().is_some_and(|(last_time, last_image)| {
+            let gap = time.start.msecs() - last_time.end.msecs();
+            gap <= max_gap
+                && match (comparison, last_hash, hash) {
+                    (ImageComparison::PerceptualHash { max_distance }, Some(last), Some(hash)) => {
+                        last.distance(&hash) <= max_distance
+                    }
+                    _ => last_image.same_pixels(&image),
+                }
+        });
+
+        if identical {
+            let (last_time, _) = merged.last_mut().unwrap();
+            last_time.end = last_time.end.max(time.end);
+        } else {
+            merged.push((time, image));
+            last_hash = hash;
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        content::{Area, AreaValues},
+        time::TimePoint,
+        vobsub::VobSubIndexedImage,
+    };
+
+    fn span(start: i64, end: i64) -> TimeSpan {
+        TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end))
+    }
+
+    fn image(raw_image: Vec<u8>) -> VobSubIndexedImage {
+        let area = Area::try_from(AreaValues {
+            x1: 10,
+            y1: 20,
+            x2: 13,
+            y2: 23,
+        })
+        .unwrap();
+        VobSubIndexedImage::new(area, [0, 1, 2, 3], [0, 15, 15, 15], raw_image)
+    }
+
+    #[test]
+    fn perceptual_hash_distance() {
+        let black = PerceptualHash::new(16, 16, [0; 256]);
+        let half = PerceptualHash::new(16, 16, (0..256).map(|i| if i < 128 { 0 } else { 255 }));
+        assert_eq!(black.distance(&black), 0);
+        assert_eq!(black.distance(&half), 32);
+    }
+
+    #[test]
+    fn merge_identical_images() {
+        let img_a = (0..16).map(|i| u8::from(i % 4 == 0)).collect::<Vec<_>>();
+        let img_b = (0..16).map(|i| u8::from(i < 8) * 2).collect::<Vec<_>>();
+        let subtitles = vec![
+            (span(0, 1000), image(img_a.clone())),
+            (span(1000, 2000), image(img_a.clone())),
+            (span(2040, 3000), image(img_a.clone())),
+            (span(3000, 4000), image(img_b)),
+            (span(5000, 6000), image(img_a.clone())),
+        ];
+
+        let merged = merge_identical(subtitles, ImageComparison::Exact, 40);
+        let times = merged.iter().map(|(time, _)| *time).collect::<Vec<_>>();
+        assert_eq!(times, [span(0, 3000), span(3000, 4000), span(5000, 6000)]);
+        assert_eq!(merged[0].1, image(img_a));
+    }
+
+    #[test]
+    fn merge_with_perceptual_hash() {
+        let img_a = (0..16).map(|i| u8::from(i % 4 == 0)).collect::<Vec<_>>();
+        let img_b = (0..16).map(|i| u8::from(i < 8) * 2).collect::<Vec<_>>();
+        let subtitles = vec![
+            (span(0, 1000), image(img_a.clone())),
+            (span(1000, 2000), image(img_a)),
+            (span(2000, 3000), image(img_b)),
+        ];
+
+        let comparison = ImageComparison::PerceptualHash { max_distance: 0 };
+        let merged = merge_identical(subtitles, comparison, 0);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].0, span(0, 2000));
+    }
+}
@@ -0,0 +1,122 @@
+use super::ToImage;
+use crate::content::Size;
+use image::{imageops, ImageBuffer, Pixel, Rgba, RgbaImage};
+
+/// A full-frame canvas, on which subtitle images are drawn at their screen position.
+///
+/// The canvas can be initialized with a background color (transparent or not),
+/// or with a video frame on which subtitles are alpha-blended.
+#[derive(Debug, Clone)]
+pub struct Compositor {
+    canvas: RgbaImage,
+}
+
+impl Compositor {
+    /// Create a canvas of the video frame size filled with the `background` color.
+    ///
+    /// # Panics
+    /// Will panic if the frame size doesn't fit in `u32`.
+    #[must_use]
+    pub fn new(frame_size: Size, background: Rgba<u8>) -> Self {
+        let width = u32::try_from(frame_size.w).unwrap();
+        let height = u32::try_from(frame_size.h).unwrap();
+        Self {
+            canvas: RgbaImage::from_pixel(width, height, background),
+        }
+    }
+
+    /// Create a canvas from a video frame, on which the subtitles will be blended.
+    #[must_use]
+    pub const fn with_frame(frame: RgbaImage) -> Self {
+        Self { canvas: frame }
+    }
+
+    /// Alpha-blend `image` on the canvas, with its top left pixel at `x`, `y`.
+    /// The parts of the image outside of the canvas are ignored.
+    #[profiling::function]
+    pub fn overlay<P>(&mut self, image: &ImageBuffer<P, Vec<u8>>, x: i64, y: i64)
+    where
+        P: Pixel<Subpixel = u8>,
+    {
+        let image = RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+            image.get_pixel(x, y).to_rgba()
+        });
+        imageops::overlay(&mut self.canvas, &image, x, y);
+    }
+
+    /// Generate the image of a subtitle and alpha-blend it on the canvas at `x`, `y`.
+    ///
+    /// The position is usually the [`Area`] of a `VobSub` image,
+    /// or the [`RleEncodedImage::position`] of a `PGS` image.
+    ///
+    /// [`Area`]: crate::content::Area
+    /// [`RleEncodedImage::position`]: crate::pgs::RleEncodedImage::position
+    pub fn draw<I>(&mut self, image: &I, x: u16, y: u16)
+    where
+        I: ToImage,
+    {
+        self.overlay(&image.to_image(), i64::from(x), i64::from(y));
+    }
+
+    /// Access the image of the canvas.
+    #[must_use]
+    pub const fn image(&self) -> &RgbaImage {
+        &self.canvas
+    }
+
+    /// Get the image of the canvas.
+    #[must_use]
+    pub fn into_image(self) -> RgbaImage {
+        self.canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        content::{Area, AreaValues},
+        image::ImageArea as _,
+        vobsub::{conv_to_rgba, VobSubIndexedImage, VobSubToImage},
+    };
+    use image::{LumaA, Rgb};
+
+    #[test]
+    fn draw_at_area_position() {
+        let area = Area::try_from(AreaValues {
+            x1: 2,
+            y1: 1,
+            x2: 3,
+            y2: 2,
+        })
+        .unwrap();
+        let indexed = VobSubIndexedImage::new(area, [0, 1, 0, 0], [0, 255, 0, 0], vec![1, 0, 1, 1]);
+        let palette = [Rgb([255, 0, 0]); 16];
+        let image = VobSubToImage::new(&indexed, &palette, conv_to_rgba);
+
+        let transparent = Rgba([0, 0, 0, 0]);
+        let mut compositor = Compositor::new(Size { w: 6, h: 4 }, transparent);
+        compositor.draw(&image, indexed.area().left(), indexed.area().top());
+
+        let canvas = compositor.into_image();
+        assert_eq!(canvas.dimensions(), (6, 4));
+        assert_eq!(*canvas.get_pixel(2, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(*canvas.get_pixel(3, 1), transparent);
+        assert_eq!(*canvas.get_pixel(3, 2), Rgba([255, 0, 0, 255]));
+        assert_eq!(*canvas.get_pixel(0, 0), transparent);
+    }
+
+    #[test]
+    fn blend_over_frame() {
+        let frame = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 255, 255]));
+        let mut compositor = Compositor::with_frame(frame);
+        let subtitle = ImageBuffer::from_pixel(2, 2, LumaA([255u8, 128]));
+        compositor.overlay(&subtitle, 3, -1);
+
+        let canvas = compositor.image();
+        let Rgba([r, g, b, _]) = *canvas.get_pixel(3, 0);
+        assert_eq!((r, g), (128, 128));
+        assert!(b > r);
+        assert_eq!(*canvas.get_pixel(2, 0), Rgba([0, 0, 255, 255]));
+    }
+}
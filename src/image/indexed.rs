@@ -0,0 +1,165 @@
+use super::{
+    content_bounds, detect_roles, prepare_ocr_image, remove_outline_colors, role_ocr_color,
+    roles_are_conclusive, BoundingBox, ImageSize, PaletteRole, ToImage, ToOcrImage, ToOcrImageOpt,
+};
+use image::{GrayAlphaImage, GrayImage, ImageBuffer, LumaA, Pixel, Rgba};
+
+/// Define access to a subtitle bitmap made of palette indexes,
+/// like [`VobSubIndexedImage`] and [`PgsIndexedImage`].
+///
+/// [`VobSubIndexedImage`]: crate::vobsub::VobSubIndexedImage
+/// [`PgsIndexedImage`]: crate::pgs::PgsIndexedImage
+pub trait IndexedImage: ImageSize {
+    /// Palette index of each pixel, in row-major order.
+    fn indexes(&self) -> &[u8];
+
+    /// Alpha (from 0 to 255) of a palette index.
+    fn index_alpha(&self, index: u8) -> u8;
+
+    /// Palette index of the pixel at `x`, `y`, or `None` if outside of the image.
+    fn pixel_index(&self, x: u32, y: u32) -> Option<u8> {
+        if x < self.width() && y < self.height() {
+            self.indexes().get((y * self.width() + x) as usize).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Bounding box of the visible pixels. See [`content_bounds`].
+    fn content_bounds(&self) -> Option<BoundingBox> {
+        content_bounds(self.indexes(), self.width(), |index| {
+            self.index_alpha(index)
+        })
+    }
+
+    /// Detect the role of the palette indexes. See [`detect_roles`].
+    fn palette_roles(&self) -> Vec<PaletteRole> {
+        detect_roles(
+            self.indexes(),
+            self.width(),
+            usize::from(u8::MAX) + 1,
+            |index| self.index_alpha(index),
+        )
+    }
+}
+
+/// Define access to the colors of the palette of an [`IndexedImage`],
+/// like [`PgsIndexedImage`] and a [`VobSubIndexedImage`] with its `*.idx` palette.
+///
+/// [`VobSubIndexedImage`]: crate::vobsub::VobSubIndexedImage
+/// [`PgsIndexedImage`]: crate::pgs::PgsIndexedImage
+pub trait PalettedImage: IndexedImage {
+    /// Color of a palette index, with an alpha from 0 to 255.
+    /// Indexes missing from the palette are transparent.
+    fn palette_rgba(&self, index: u8) -> Rgba<u8>;
+
+    /// Check if the palette indexes used by the pixels of both images have the same colors.
+    fn same_used_colors<O>(&self, other: &O) -> bool
+    where
+        O: PalettedImage,
+    {
+        let mut used = [false; 256];
+        for &index in self.indexes().iter().chain(other.indexes()) {
+            used[usize::from(index)] = true;
+        }
+        (0..=u8::MAX)
+            .filter(|&index| used[usize::from(index)])
+            .all(|index| self.palette_rgba(index) == other.palette_rgba(index))
+    }
+}
+
+/// This struct implement [`ToImage`] to generate an `ImageBuffer` from an [`IndexedImage`],
+/// with a lookup table build from a conversion function of palette indexes.
+///
+/// It also implement [`ToOcrImage`] when the pixels are [`LumaA`], any visible and non black
+/// color being considered as text. With [`ToOcrImageOpt::palette_roles`], the text is separated
+/// from the background with the [`PaletteRole`] of the indexes if they are conclusive.
+pub struct IndexedToImage<'a, I, P> {
+    image: &'a I,
+    lut: [P; 256],
+}
+
+impl<'a, I, P> IndexedToImage<'a, I, P>
+where
+    I: IndexedImage,
+    P: Pixel<Subpixel = u8>,
+{
+    /// Create an image converter, `convert` give the pixel value of a palette index.
+    pub fn new<C>(image: &'a I, convert: C) -> Self
+    where
+        C: Fn(u8) -> P,
+    {
+        let lut = std::array::from_fn(|index| convert(u8::try_from(index).unwrap_or(u8::MAX)));
+        Self { image, lut }
+    }
+
+    fn raw_pixels(&self) -> Vec<u8> {
+        let indexes = self.image.indexes();
+        let mut buf = Vec::with_capacity(indexes.len() * usize::from(P::CHANNEL_COUNT));
+        for &index in indexes {
+            buf.extend_from_slice(self.lut[usize::from(index)].channels());
+        }
+        buf
+    }
+}
+
+impl<'a, I> IndexedToImage<'a, I, Rgba<u8>>
+where
+    I: PalettedImage,
+{
+    /// Create an image converter using the colors of the palette of `image`.
+    pub fn rgba(image: &'a I) -> Self {
+        Self::new(image, |index| image.palette_rgba(index))
+    }
+}
+
+impl<I, P> ToImage for IndexedToImage<'_, I, P>
+where
+    I: IndexedImage,
+    P: Pixel<Subpixel = u8>,
+{
+    type Pixel = P;
+
+    #[profiling::function]
+    fn to_image(&self) -> ImageBuffer<P, Vec<u8>> {
+        ImageBuffer::from_vec(self.image.width(), self.image.height(), self.raw_pixels())
+            .expect("Failed to create image buffer")
+    }
+}
+
+impl<I> ToOcrImage for IndexedToImage<'_, I, LumaA<u8>>
+where
+    I: IndexedImage,
+{
+    #[profiling::function]
+    fn image(&self, opt: &ToOcrImageOpt) -> GrayImage {
+        let mut colors = self.lut;
+        if opt.palette_roles || opt.remove_outline {
+            let roles = self.image.palette_roles();
+            if opt.palette_roles && roles_are_conclusive(&roles) {
+                colors = std::array::from_fn(|index| {
+                    role_ocr_color(roles.get(index), opt.remove_outline)
+                });
+            } else if opt.remove_outline {
+                remove_outline_colors(&mut colors, &roles);
+            }
+        }
+        let raw_pixels = Self {
+            image: self.image,
+            lut: colors,
+        }
+        .raw_pixels();
+        let source = GrayAlphaImage::from_vec(self.image.width(), self.image.height(), raw_pixels)
+            .expect("Failed to create image buffer");
+
+        // Any visible and non black color is considered as text.
+        let convert = |LumaA([luminance, alpha]): LumaA<u8>| {
+            if alpha > 0 && luminance > 0 {
+                opt.text_color
+            } else {
+                opt.background_color
+            }
+        };
+        prepare_ocr_image(source, convert, opt)
+    }
+}
@@ -1,10 +1,34 @@
 //! Module for `Image` manipulation.
+mod compare;
+mod compose;
+mod indexed;
+mod ocr;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod pixels;
+mod roles;
+mod segmentation;
+mod transform;
 mod utils;
 
 // Re-export some useful image types.
-pub use image::{GrayImage, Luma};
+pub use compare::{merge_identical, CompareImage, ImageComparison, PerceptualHash};
+pub use compose::Compositor;
+pub use image::{GrayAlphaImage, GrayImage, Luma, LumaA, Rgba, RgbaImage};
+pub use indexed::{IndexedImage, IndexedToImage, PalettedImage};
+pub use ocr::{
+    otsu_level, prepare_ocr_image, remove_outline_colors, role_ocr_color, OcrThreshold, OcrUpscale,
+    UpscaleFilter,
+};
+#[cfg(feature = "rayon")]
+pub use parallel::{par_to_images, par_to_ocr_images};
+pub(crate) use pixels::ycrcb_to_rgb;
 pub use pixels::{luma_a_to_luma, luma_a_to_luma_convertor};
+pub use roles::{detect_roles, roles_are_conclusive, PaletteRole, PaletteRoleStats};
+pub use segmentation::{segment_lines, BoundingBox, Glyph, InkMask, TextLine, DEFAULT_INK_ALPHA};
+pub use transform::{
+    content_bounds, crop_indexed, scale_indexed, IndexedScaleFilter, IndexedTransform,
+};
 pub use utils::{dump_images, DumpError};
 
 use crate::content::Area;
@@ -47,6 +71,10 @@ pub trait ToImage {
 }
 
 /// Options for image generation.
+///
+/// Start from [`Default`] and use the `with_*` methods to change the options.
+#[expect(clippy::struct_excessive_bools)]
+#[non_exhaustive]
 #[derive(Debug, Clone, Copy)]
 pub struct ToOcrImageOpt {
     /// Number of border pixels to add on the input image
@@ -55,20 +83,108 @@ pub struct ToOcrImageOpt {
     pub text_color: Luma<u8>,
     /// Color of the background
     pub background_color: Luma<u8>,
+    /// Method used to separate text from background
+    pub threshold: OcrThreshold,
+    /// Upscale the image before thresholding
+    pub upscale: Option<OcrUpscale>,
+    /// Remove the outline (and shadow) around the text, from the role of the palette indexes
+    pub remove_outline: bool,
+    /// Separate the text from the background with the role of the palette indexes, when the
+    /// detected roles are conclusive, instead of the conversion of the colors
+    pub palette_roles: bool,
+    /// Invert text and background if the text seems to be dark on a bright background
+    pub auto_invert: bool,
+    /// Crop the image to the bounding box of the text
+    pub crop: bool,
+    /// Detect and compensate a small rotation of the text
+    pub deskew: bool,
 }
 
-// Implement [`Default`] for [`ToOcrImageOpt`] with a border of 5 pixel
-// and colors black for text and white for background.
+// Implement [`Default`] for [`ToOcrImageOpt`] with a border of 5 pixel,
+// colors black for text and white for background, and only the palette conversion.
 impl Default for ToOcrImageOpt {
     fn default() -> Self {
         Self {
             border: 5,
             text_color: Luma([0]),
             background_color: Luma([255]),
+            threshold: OcrThreshold::Palette,
+            upscale: None,
+            remove_outline: false,
+            palette_roles: false,
+            auto_invert: false,
+            crop: false,
+            deskew: false,
         }
     }
 }
 
+impl ToOcrImageOpt {
+    /// Set the number of border pixels to add on the input image.
+    #[must_use]
+    pub const fn with_border(mut self, border: u32) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Set the colors of the text and of the background.
+    #[must_use]
+    pub const fn with_colors(mut self, text_color: Luma<u8>, background_color: Luma<u8>) -> Self {
+        self.text_color = text_color;
+        self.background_color = background_color;
+        self
+    }
+
+    /// Set the method used to separate text from background.
+    #[must_use]
+    pub const fn with_threshold(mut self, threshold: OcrThreshold) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the upscaling done before thresholding, `None` to disable it.
+    #[must_use]
+    pub const fn with_upscale(mut self, upscale: Option<OcrUpscale>) -> Self {
+        self.upscale = upscale;
+        self
+    }
+
+    /// Enable or disable the removal of the outline around the text.
+    #[must_use]
+    pub const fn with_remove_outline(mut self, remove_outline: bool) -> Self {
+        self.remove_outline = remove_outline;
+        self
+    }
+
+    /// Enable or disable the use of the role of the palette indexes to find the text.
+    #[must_use]
+    pub const fn with_palette_roles(mut self, palette_roles: bool) -> Self {
+        self.palette_roles = palette_roles;
+        self
+    }
+
+    /// Enable or disable the inversion of dark text on a bright background.
+    #[must_use]
+    pub const fn with_auto_invert(mut self, auto_invert: bool) -> Self {
+        self.auto_invert = auto_invert;
+        self
+    }
+
+    /// Enable or disable the crop to the bounding box of the text.
+    #[must_use]
+    pub const fn with_crop(mut self, crop: bool) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    /// Enable or disable the rotation compensation.
+    #[must_use]
+    pub const fn with_deskew(mut self, deskew: bool) -> Self {
+        self.deskew = deskew;
+        self
+    }
+}
+
 /// Generate a `GrayImage` adapted for `OCR` from self.
 pub trait ToOcrImage {
     /// Generate the image for `OCR` in `GrayImage` format.
@@ -0,0 +1,495 @@
+//! Preparation of subtitle images for `OCR`.
+//!
+//! The images from bitmap subtitles are converted in [`GrayAlphaImage`] by the
+//! format specific code, which also remove the outline with [`remove_outline_colors`].
+//! Then [`prepare_ocr_image`] apply the steps configured in [`ToOcrImageOpt`] :
+//! upscaling, thresholding, inversion, cropping, deskew and border.
+use super::{PaletteRole, ToOcrImageOpt};
+use image::{
+    imageops::{self, FilterType},
+    GrayAlphaImage, GrayImage, ImageBuffer, Luma, LumaA,
+};
+
+/// Method used to separate text pixels from background pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcrThreshold {
+    /// Use the conversion provided by the format (palette based).
+    Palette,
+    /// Pixels with an intensity greater than or equal to the value are text.
+    Fixed(u8),
+    /// Threshold computed with the `Otsu` method on the intensity histogram.
+    Otsu,
+    /// Threshold computed for each pixel from the mean intensity of its neighborhood.
+    Adaptive {
+        /// Radius in pixels of the neighborhood.
+        radius: u32,
+        /// Offset added to the local mean to obtain the threshold.
+        offset: i16,
+    },
+}
+
+/// Filter used to upscale the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpscaleFilter {
+    /// Nearest neighbor.
+    Nearest,
+    /// Linear filter.
+    Bilinear,
+    /// Lanczos with window 3.
+    Lanczos,
+}
+
+impl From<UpscaleFilter> for FilterType {
+    fn from(value: UpscaleFilter) -> Self {
+        match value {
+            UpscaleFilter::Nearest => Self::Nearest,
+            UpscaleFilter::Bilinear => Self::Triangle,
+            UpscaleFilter::Lanczos => Self::Lanczos3,
+        }
+    }
+}
+
+/// Upscaling parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OcrUpscale {
+    /// Scale factor applied on width and height.
+    pub factor: u32,
+    /// Filter used for the resize.
+    pub filter: UpscaleFilter,
+}
+
+/// Maximum angle (in degrees) searched by deskew.
+const DESKEW_MAX_ANGLE: f64 = 5.;
+/// Step (in degrees) between two angles tested by deskew.
+const DESKEW_STEP: f64 = 0.5;
+
+/// Generate an image adapted for `OCR` from a [`GrayAlphaImage`] of a subtitle.
+///
+/// `convert` is the format specific conversion used with [`OcrThreshold::Palette`].
+/// Other threshold methods use the luminance weighted by the alpha of pixels.
+///
+/// The outline removal works on palette indexes, so it's applied by the format
+/// specific code before flattening the image, see [`remove_outline_colors`].
+#[profiling::function]
+pub fn prepare_ocr_image<C>(source: GrayAlphaImage, convert: C, opt: &ToOcrImageOpt) -> GrayImage
+where
+    C: Fn(LumaA<u8>) -> Luma<u8>,
+{
+    let mut source = source;
+    if let Some(upscale) = opt.upscale.filter(|upscale| upscale.factor > 1) {
+        let width = source.width().checked_mul(upscale.factor);
+        let height = source.height().checked_mul(upscale.factor);
+        if let Some((width, height)) = width.zip(height) {
+            source = imageops::resize(&source, width, height, upscale.filter.into());
+        } else {
+            log::warn!(
+                "Upscale factor {} is too big, upscale skipped.",
+                upscale.factor
+            );
+        }
+    }
+
+    let mut image = threshold(&source, convert, opt);
+    if opt.auto_invert {
+        invert_if_needed(&mut image, opt);
+    }
+    if opt.crop {
+        image = crop_to_text(&image, opt);
+    }
+    if opt.deskew {
+        image = deskew(&image, opt);
+    }
+    add_border(&image, opt)
+}
+
+// Intensity of a pixel, luminance weighted by alpha.
+fn intensity(pixel: LumaA<u8>) -> u8 {
+    let LumaA([luma, alpha]) = pixel;
+    u8::try_from(u16::from(luma) * u16::from(alpha) / 255).unwrap()
+}
+
+/// Remove the outline (and shadow) around the text from the colors of a palette,
+/// before an indexed image is flattened for `OCR`.
+///
+/// The colors of the [`PaletteRole::Outline`] and [`PaletteRole::AntiAlias`] indexes
+/// become transparent. `roles` can be detected with [`detect_roles`](super::detect_roles).
+pub fn remove_outline_colors(colors: &mut [LumaA<u8>], roles: &[PaletteRole]) {
+    colors
+        .iter_mut()
+        .zip(roles)
+        .filter(|(_, role)| matches!(role, PaletteRole::Outline | PaletteRole::AntiAlias))
+        .for_each(|(color, _)| *color = LumaA([0, 0]));
+}
+
+/// Color of a palette index from its role, to separate the text from the background:
+/// the [`PaletteRole::Fill`] indexes become opaque white, the others transparent.
+///
+/// If `remove_outline` is `false`, the [`PaletteRole::Outline`] and [`PaletteRole::AntiAlias`]
+/// indexes are kept with the text.
+#[must_use]
+pub const fn role_ocr_color(role: Option<&PaletteRole>, remove_outline: bool) -> LumaA<u8> {
+    let is_text = match role {
+        Some(PaletteRole::Fill) => true,
+        Some(PaletteRole::Outline | PaletteRole::AntiAlias) => !remove_outline,
+        Some(PaletteRole::Unused | PaletteRole::Background) | None => false,
+    };
+    if is_text {
+        LumaA([u8::MAX, u8::MAX])
+    } else {
+        LumaA([0, 0])
+    }
+}
+
+// Separate text from background with the method selected in options.
+fn threshold<C>(source: &GrayAlphaImage, convert: C, opt: &ToOcrImageOpt) -> GrayImage
+where
+    C: Fn(LumaA<u8>) -> Luma<u8>,
+{
+    let to_color = |is_text: bool| {
+        if is_text {
+            opt.text_color
+        } else {
+            opt.background_color
+        }
+    };
+
+    match opt.threshold {
+        OcrThreshold::Palette => ImageBuffer::from_fn(source.width(), source.height(), |x, y| {
+            convert(*source.get_pixel(x, y))
+        }),
+        OcrThreshold::Fixed(level) => {
+            ImageBuffer::from_fn(source.width(), source.height(), |x, y| {
+                to_color(intensity(*source.get_pixel(x, y)) >= level)
+            })
+        }
+        OcrThreshold::Otsu => {
+            let level = otsu_level(source.pixels().map(|pixel| intensity(*pixel)));
+            ImageBuffer::from_fn(source.width(), source.height(), |x, y| {
+                to_color(intensity(*source.get_pixel(x, y)) > level)
+            })
+        }
+        OcrThreshold::Adaptive { radius, offset } => {
+            adaptive_threshold(source, radius, offset, to_color)
+        }
+    }
+}
+
+/// Compute the `Otsu` threshold level of a list of intensity values.
+pub fn otsu_level(values: impl IntoIterator<Item = u8>) -> u8 {
+    let mut histogram = [0u64; 256];
+    values
+        .into_iter()
+        .for_each(|value| histogram[usize::from(value)] += 1);
+
+    let total = histogram.iter().sum::<u64>();
+    let sum_total = histogram
+        .iter()
+        .zip(0u64..)
+        .map(|(&count, value)| count * value)
+        .sum::<u64>();
+
+    let mut best = (0u8, 0.);
+    let mut back_weight = 0;
+    let mut back_sum = 0;
+    for (level, (&count, value)) in (0..=u8::MAX).zip(histogram.iter().zip(0u64..)) {
+        back_weight += count;
+        back_sum += count * value;
+        let fore_weight = total - back_weight;
+        if back_weight == 0 || fore_weight == 0 {
+            continue;
+        }
+        let back_mean = cast::f64(back_sum) / cast::f64(back_weight);
+        let fore_mean = cast::f64(sum_total - back_sum) / cast::f64(fore_weight);
+        let variance =
+            cast::f64(back_weight) * cast::f64(fore_weight) * (back_mean - fore_mean).powi(2);
+        if variance > best.1 {
+            best = (level, variance);
+        }
+    }
+    best.0
+}
+
+// Threshold each pixel with the mean of its neighborhood, computed with an integral image.
+fn adaptive_threshold<F>(
+    source: &GrayAlphaImage,
+    radius: u32,
+    offset: i16,
+    to_color: F,
+) -> GrayImage
+where
+    F: Fn(bool) -> Luma<u8>,
+{
+    let (width, height) = source.dimensions();
+    let stride = width as usize + 1;
+    let mut integral = vec![0u64; stride * (height as usize + 1)];
+    for y in 0..height as usize {
+        let mut line_sum = 0;
+        for x in 0..width as usize {
+            line_sum += u64::from(intensity(
+                *source.get_pixel(u32::try_from(x).unwrap(), u32::try_from(y).unwrap()),
+            ));
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + line_sum;
+        }
+    }
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let x0 = x.saturating_sub(radius) as usize;
+        let y0 = y.saturating_sub(radius) as usize;
+        let x1 = (x + radius + 1).min(width) as usize;
+        let y1 = (y + radius + 1).min(height) as usize;
+        let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+            - integral[y0 * stride + x1]
+            - integral[y1 * stride + x0];
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+        let mean = i64::try_from(sum / count).unwrap();
+        let value = i64::from(intensity(*source.get_pixel(x, y)));
+        to_color(value > mean + i64::from(offset))
+    })
+}
+
+// Invert text and background if the text cover the most part of the image.
+fn invert_if_needed(image: &mut GrayImage, opt: &ToOcrImageOpt) {
+    let nb_text = image
+        .pixels()
+        .filter(|pixel| **pixel == opt.text_color)
+        .count();
+    if nb_text * 2 > image.pixels().len() {
+        image.pixels_mut().for_each(|pixel| {
+            *pixel = if *pixel == opt.text_color {
+                opt.background_color
+            } else {
+                opt.text_color
+            };
+        });
+    }
+}
+
+// Crop the image to the bounding box of text pixels.
+fn crop_to_text(image: &GrayImage, opt: &ToOcrImageOpt) -> GrayImage {
+    let bounds = image
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| **pixel == opt.text_color)
+        .fold(None, |bounds, (x, y, _)| {
+            let (x1, y1, x2, y2) = bounds.unwrap_or((x, y, x, y));
+            Some((x1.min(x), y1.min(y), x2.max(x), y2.max(y)))
+        });
+    bounds.map_or_else(
+        || image.clone(),
+        |(x1, y1, x2, y2)| imageops::crop_imm(image, x1, y1, x2 - x1 + 1, y2 - y1 + 1).to_image(),
+    )
+}
+
+// Find the rotation angle which maximize the variance of the horizontal projection
+// of text pixels, and rotate the image to compensate it.
+fn deskew(image: &GrayImage, opt: &ToOcrImageOpt) -> GrayImage {
+    let text_pixels = image
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| **pixel == opt.text_color)
+        .map(|(x, y, _)| (f64::from(x), f64::from(y)))
+        .collect::<Vec<_>>();
+    if text_pixels.is_empty() {
+        return image.clone();
+    }
+
+    let height = f64::from(image.height());
+    let projection_variance = |angle: f64| {
+        let tan = angle.to_radians().tan();
+        let mut histogram = std::collections::BTreeMap::<i64, f64>::new();
+        for (x, y) in &text_pixels {
+            let projected = cast::i64((y - x * tan).round()).unwrap_or_default();
+            *histogram.entry(projected).or_default() += 1.;
+        }
+        let mean = cast::f64(text_pixels.len()) / height;
+        histogram
+            .values()
+            .map(|count| (count - mean).powi(2))
+            .sum::<f64>()
+    };
+
+    let nb_steps = cast::i32(DESKEW_MAX_ANGLE / DESKEW_STEP).unwrap();
+    let angle = (-nb_steps..=nb_steps)
+        .map(|step| f64::from(step) * DESKEW_STEP)
+        .map(|angle| (angle, projection_variance(angle)))
+        .fold((0., projection_variance(0.)), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+        .0;
+    if angle == 0. {
+        return image.clone();
+    }
+    rotate(image, angle, opt.background_color)
+}
+
+// Rotate the image around its center by `angle` (in degrees), with nearest neighbor.
+fn rotate(image: &GrayImage, angle: f64, background: Luma<u8>) -> GrayImage {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let center_x = f64::from(image.width()) / 2.;
+    let center_y = f64::from(image.height()) / 2.;
+    ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let dx = f64::from(x) - center_x;
+        let dy = f64::from(y) - center_y;
+        let src_x = cos.mul_add(dx, -sin * dy) + center_x;
+        let src_y = sin.mul_add(dx, cos * dy) + center_y;
+        cast::u32(src_x.round())
+            .ok()
+            .zip(cast::u32(src_y.round()).ok())
+            .and_then(|(src_x, src_y)| image.get_pixel_checked(src_x, src_y))
+            .copied()
+            .unwrap_or(background)
+    })
+}
+
+// Add the border defined in options around the image.
+fn add_border(image: &GrayImage, opt: &ToOcrImageOpt) -> GrayImage {
+    let border = opt.border;
+    let (width, height) = image.dimensions();
+    ImageBuffer::from_fn(width + border * 2, height + border * 2, |x, y| {
+        if x < border || x >= width + border || y < border || y >= height + border {
+            opt.background_color
+        } else {
+            *image.get_pixel(x - border, y - border)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::luma_a_to_luma;
+
+    // Create an image with a bright rectangle of text on a transparent background,
+    // surrounded by a dark outline.
+    fn text_image() -> GrayAlphaImage {
+        ImageBuffer::from_fn(20, 10, |x, y| {
+            if (4..16).contains(&x) && (3..7).contains(&y) {
+                LumaA([230, 255])
+            } else if (3..17).contains(&x) && (2..8).contains(&y) {
+                LumaA([20, 255])
+            } else {
+                LumaA([0, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn otsu_level_split() {
+        let values = [10, 12, 11, 200, 210, 205];
+        let level = otsu_level(values);
+        assert!((12..200).contains(&level));
+    }
+
+    #[test]
+    fn default_keep_size_with_border() {
+        let opt = ToOcrImageOpt::default();
+        let image = prepare_ocr_image(text_image(), luma_a_to_luma::<_, _, 100, 100>, &opt);
+        assert_eq!(image.dimensions(), (30, 20));
+        assert_eq!(*image.get_pixel(10, 10), opt.text_color);
+        assert_eq!(*image.get_pixel(1, 1), opt.background_color);
+    }
+
+    #[test]
+    fn otsu_crop_and_upscale() {
+        let opt = ToOcrImageOpt {
+            border: 0,
+            threshold: OcrThreshold::Otsu,
+            crop: true,
+            upscale: Some(OcrUpscale {
+                factor: 2,
+                filter: UpscaleFilter::Nearest,
+            }),
+            ..ToOcrImageOpt::default()
+        };
+        let image = prepare_ocr_image(text_image(), luma_a_to_luma::<_, _, 100, 100>, &opt);
+        assert_eq!(image.dimensions(), (24, 8));
+        assert!(image.pixels().all(|pixel| *pixel == opt.text_color));
+    }
+
+    #[test]
+    fn upscale_overflow_skipped() {
+        let opt = ToOcrImageOpt::default()
+            .with_border(0)
+            .with_upscale(Some(OcrUpscale {
+                factor: u32::MAX,
+                filter: UpscaleFilter::Nearest,
+            }));
+        let image = prepare_ocr_image(text_image(), luma_a_to_luma::<_, _, 100, 100>, &opt);
+        assert_eq!(image.dimensions(), (20, 10));
+    }
+
+    #[test]
+    fn remove_outline_roles() {
+        let mut colors = [
+            LumaA([0, 0]),
+            LumaA([230, 255]),
+            LumaA([20, 255]),
+            LumaA([128, 255]),
+        ];
+        let roles = [
+            PaletteRole::Background,
+            PaletteRole::Fill,
+            PaletteRole::Outline,
+            PaletteRole::AntiAlias,
+        ];
+        remove_outline_colors(&mut colors, &roles);
+        assert_eq!(
+            colors,
+            [
+                LumaA([0, 0]),
+                LumaA([230, 255]),
+                LumaA([0, 0]),
+                LumaA([0, 0])
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_invert_dark_text() {
+        // Dark text on an opaque bright box.
+        let source = ImageBuffer::from_fn(20, 10, |x, _| {
+            if (8..12).contains(&x) {
+                LumaA([10, 255])
+            } else {
+                LumaA([240, 255])
+            }
+        });
+        let opt = ToOcrImageOpt {
+            border: 0,
+            threshold: OcrThreshold::Fixed(128),
+            auto_invert: true,
+            ..ToOcrImageOpt::default()
+        };
+        let image = prepare_ocr_image(source, luma_a_to_luma::<_, _, 100, 100>, &opt);
+        assert_eq!(*image.get_pixel(10, 5), opt.text_color);
+        assert_eq!(*image.get_pixel(2, 5), opt.background_color);
+    }
+
+    #[test]
+    fn deskew_rotated_line() {
+        // A thin line with a slope of 3 degrees.
+        let tan = 3f64.to_radians().tan();
+        let source = ImageBuffer::from_fn(100, 30, |x, y| {
+            let line_y = 15. + (f64::from(x) - 50.) * tan;
+            if (f64::from(y) - line_y).abs() < 1. {
+                LumaA([255, 255])
+            } else {
+                LumaA([0, 0])
+            }
+        });
+        let opt = ToOcrImageOpt {
+            border: 0,
+            threshold: OcrThreshold::Fixed(128),
+            deskew: true,
+            crop: false,
+            ..ToOcrImageOpt::default()
+        };
+        let image = prepare_ocr_image(source, luma_a_to_luma::<_, _, 100, 100>, &opt);
+        let rows = (0..image.height())
+            .filter(|&y| (0..image.width()).any(|x| *image.get_pixel(x, y) == opt.text_color))
+            .count();
+        assert!(rows <= 3, "line spread on {rows} rows after deskew");
+    }
+}
@@ -0,0 +1,65 @@
+use super::{GrayImage, ToImage, ToOcrImage, ToOcrImageOpt};
+use image::ImageBuffer;
+use rayon::prelude::*;
+
+/// Generate the images of all `sources` in parallel.
+///
+/// The images are returned in the same order as `sources`.
+pub fn par_to_images<T>(sources: &[T]) -> Vec<ImageBuffer<T::Pixel, Vec<u8>>>
+where
+    T: ToImage + Sync,
+    T::Pixel: Send,
+{
+    sources.par_iter().map(ToImage::to_image).collect()
+}
+
+/// Generate the `OCR` images of all `sources` in parallel.
+///
+/// The images are returned in the same order as `sources`.
+pub fn par_to_ocr_images<T>(sources: &[T], opt: &ToOcrImageOpt) -> Vec<GrayImage>
+where
+    T: ToOcrImage + Sync,
+{
+    sources.par_iter().map(|source| source.image(opt)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        time::TimeSpan,
+        vobsub::{
+            conv_to_rgba, palette_rgb_to_luminance, Index, Sub, VobSubIndexedImage, VobSubOcrImage,
+            VobSubToImage,
+        },
+    };
+
+    #[test]
+    fn par_images_keep_order() {
+        let idx = Index::open("./fixtures/example.idx").unwrap();
+        let images = Sub::open("./fixtures/example.sub")
+            .unwrap()
+            .subtitles::<(TimeSpan, VobSubIndexedImage)>()
+            .map(|sub| sub.unwrap().1)
+            .collect::<Vec<_>>();
+
+        let converters = images
+            .iter()
+            .map(|image| VobSubToImage::new(image, idx.palette(), conv_to_rgba))
+            .collect::<Vec<_>>();
+        let expected = converters.iter().map(ToImage::to_image).collect::<Vec<_>>();
+        assert_eq!(par_to_images(&converters), expected);
+
+        let palette = palette_rgb_to_luminance(idx.palette());
+        let opt = ToOcrImageOpt::default();
+        let converters = images
+            .iter()
+            .map(|image| VobSubOcrImage::new(image, &palette))
+            .collect::<Vec<_>>();
+        let expected = converters
+            .iter()
+            .map(|conv| conv.image(&opt))
+            .collect::<Vec<_>>();
+        assert_eq!(par_to_ocr_images(&converters, &opt), expected);
+    }
+}
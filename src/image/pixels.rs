@@ -1,4 +1,4 @@
-use image::{Luma, LumaA, Primitive};
+use image::{Luma, LumaA, Primitive, Rgb};
 use std::borrow::Borrow;
 
 /// Convert Pixel from [`LumaA`] to [`Luma`] to remove alpha.
@@ -53,3 +53,17 @@ where
         }
     }
 }
+
+/// Convert a `YCrCb` color (`ITU-R BT.601` studio range), as used by `DVD` and `DVB`
+/// color lookup tables, to `RGB`.
+pub(crate) fn ycrcb_to_rgb(y: u8, cr: u8, cb: u8) -> Rgb<u8> {
+    let y = 1.164 * (f64::from(y) - 16.0);
+    let cr = f64::from(cr) - 128.0;
+    let cb = f64::from(cb) - 128.0;
+    let to_u8 = |value: f64| cast::u8(value.round().clamp(0.0, 255.0)).unwrap_or_default();
+    Rgb([
+        to_u8(1.596f64.mul_add(cr, y)),
+        to_u8(0.391f64.mul_add(-cb, 0.813f64.mul_add(-cr, y))),
+        to_u8(2.018f64.mul_add(cb, y)),
+    ])
+}
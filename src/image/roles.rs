@@ -0,0 +1,212 @@
+/// Role of a palette index in a subtitle image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaletteRole {
+    /// The index is not used by the pixels.
+    Unused,
+    /// Transparent background around the text.
+    Background,
+    /// Inside of the text glyphs.
+    Fill,
+    /// Outline around the text glyphs.
+    Outline,
+    /// Intermediate color used to smooth the edges.
+    AntiAlias,
+}
+
+/// Pixel statistics of palette indexes, used to detect their [`PaletteRole`].
+///
+/// Statistics can be accumulated over several images, to classify the palette of a whole track.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaletteRoleStats {
+    /// Number of pixels of each index.
+    counts: Vec<u64>,
+    /// Number of pixels of each index next to the background.
+    edges: Vec<u64>,
+    /// Is each index transparent.
+    transparent: Vec<bool>,
+}
+
+impl PaletteRoleStats {
+    /// Create empty statistics for a palette of `nb_indexes` entries.
+    #[must_use]
+    pub fn new(nb_indexes: usize) -> Self {
+        Self {
+            counts: vec![0; nb_indexes],
+            edges: vec![0; nb_indexes],
+            transparent: vec![true; nb_indexes],
+        }
+    }
+
+    /// Number of indexes of the palette.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Check if the palette has no index.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Add the pixels of an indexed image of width `width`.
+    ///
+    /// `alpha` give the alpha of an index, pixels with an alpha of zero are background.
+    /// Pixels with an index out of the statistics palette are ignored.
+    #[profiling::function]
+    pub fn add_image<A>(&mut self, pixels: &[u8], width: u32, alpha: A)
+    where
+        A: Fn(u8) -> u8,
+    {
+        if width == 0 {
+            return;
+        }
+        let width = width as usize;
+        let height = pixels.len() / width;
+        let is_background = |x: usize, y: usize| alpha(pixels[y * width + x]) == 0;
+
+        for (offset, &index) in pixels.iter().enumerate() {
+            let slot = usize::from(index);
+            if slot >= self.len() {
+                continue;
+            }
+            self.counts[slot] += 1;
+            if alpha(index) != 0 {
+                self.transparent[slot] = false;
+                let (x, y) = (offset % width, offset / width);
+                let edge = x == 0
+                    || y == 0
+                    || x + 1 == width
+                    || y + 1 == height
+                    || is_background(x - 1, y)
+                    || is_background(x + 1, y)
+                    || is_background(x, y - 1)
+                    || is_background(x, y + 1);
+                self.edges[slot] += u64::from(edge);
+            }
+        }
+    }
+
+    /// Classify the indexes of the palette.
+    ///
+    /// Transparent indexes are [`PaletteRole::Background`]. Among the visible indexes, the index
+    /// with the lowest ratio of pixels next to the background is the [`PaletteRole::Fill`],
+    /// the highest is the [`PaletteRole::Outline`] if it's used by at least half as many pixels
+    /// as the fill. Other visible indexes are [`PaletteRole::AntiAlias`].
+    #[must_use]
+    pub fn roles(&self) -> Vec<PaletteRole> {
+        let mut roles = self
+            .counts
+            .iter()
+            .zip(&self.transparent)
+            .map(|(&count, &transparent)| match (count, transparent) {
+                (0, _) => PaletteRole::Unused,
+                (_, true) => PaletteRole::Background,
+                _ => PaletteRole::AntiAlias,
+            })
+            .collect::<Vec<_>>();
+
+        let mut visible = (0..self.len())
+            .filter(|&slot| roles[slot] == PaletteRole::AntiAlias)
+            .map(|slot| {
+                let edge_ratio = cast::f64(self.edges[slot]) / cast::f64(self.counts[slot]);
+                (slot, edge_ratio)
+            })
+            .collect::<Vec<_>>();
+        // On equality, the most used index is considered as the fill.
+        visible.sort_by(|&(slot_a, a), &(slot_b, b)| {
+            a.total_cmp(&b)
+                .then(self.counts[slot_b].cmp(&self.counts[slot_a]))
+        });
+
+        if let Some(&(fill, _)) = visible.first() {
+            roles[fill] = PaletteRole::Fill;
+            if let Some(&(outline, _)) = visible.last().filter(|(slot, _)| *slot != fill) {
+                if self.counts[outline] * 2 >= self.counts[fill] {
+                    roles[outline] = PaletteRole::Outline;
+                }
+            }
+        }
+        roles
+    }
+}
+
+/// Check if the roles are conclusive enough to separate the text from the background:
+/// an index is the [`PaletteRole::Fill`], and if other indexes are visible,
+/// one of them is the [`PaletteRole::Outline`].
+#[must_use]
+pub fn roles_are_conclusive(roles: &[PaletteRole]) -> bool {
+    let has = |role| roles.contains(&role);
+    has(PaletteRole::Fill) && (has(PaletteRole::Outline) || !has(PaletteRole::AntiAlias))
+}
+
+/// Detect the role of the `nb_indexes` palette indexes of an indexed image of width `width`.
+///
+/// See [`PaletteRoleStats::roles`].
+#[must_use]
+pub fn detect_roles<A>(pixels: &[u8], width: u32, nb_indexes: usize, alpha: A) -> Vec<PaletteRole>
+where
+    A: Fn(u8) -> u8,
+{
+    let mut stats = PaletteRoleStats::new(nb_indexes);
+    stats.add_image(pixels, width, alpha);
+    stats.roles()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    const OUTLINED: [u8; 42] = [
+        0, 0, 0, 0, 0, 0, 0,
+        0, 2, 2, 2, 2, 2, 0,
+        0, 2, 1, 1, 1, 2, 0,
+        0, 2, 1, 3, 1, 2, 0,
+        0, 2, 2, 2, 2, 2, 0,
+        0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn detect_outlined_text() {
+        let alpha = |index: u8| [0, 15, 15, 15][usize::from(index)];
+        let roles = detect_roles(&OUTLINED, 7, 4, alpha);
+        assert_eq!(
+            roles,
+            [
+                PaletteRole::Background,
+                PaletteRole::Fill,
+                PaletteRole::Outline,
+                PaletteRole::AntiAlias,
+            ]
+        );
+    }
+
+    #[test]
+    fn conclusive_roles() {
+        use PaletteRole::{AntiAlias, Background, Fill, Outline};
+        assert!(roles_are_conclusive(&[
+            Background, Fill, Outline, AntiAlias
+        ]));
+        assert!(roles_are_conclusive(&[Background, Fill]));
+        assert!(!roles_are_conclusive(&[Background, Fill, AntiAlias]));
+        assert!(!roles_are_conclusive(&[Background, Background]));
+    }
+
+    #[test]
+    fn accumulate_track_stats() {
+        let alpha = |index: u8| [0, 15, 15, 0][usize::from(index)];
+        let mut stats = PaletteRoleStats::new(4);
+        stats.add_image(&OUTLINED, 7, alpha);
+        stats.add_image(&[0, 1, 1, 0], 4, alpha);
+        assert_eq!(
+            stats.roles(),
+            [
+                PaletteRole::Background,
+                PaletteRole::Fill,
+                PaletteRole::Outline,
+                PaletteRole::Background,
+            ]
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Segmentation of subtitle images in text lines and glyphs.
+//!
+//! The image is first converted in an [`InkMask`], then [`segment_lines`] split
+//! it in [`TextLine`]s, each one containing the [`Glyph`]s found in the line.
+//! Glyphs are connected components of ink pixels. Components placed above or
+//! below an other component (like the dot of an `i` or an accent) are merged
+//! in the same glyph, while slanted glyphs of italic text are kept separated.
+use image::{GrayImage, ImageBuffer, Luma, Pixel};
+use std::collections::VecDeque;
+
+/// Minimum alpha value of a pixel to be considered as ink by [`InkMask::from_alpha`].
+pub const DEFAULT_INK_ALPHA: u8 = 128;
+
+/// Ratio of the median line height under which a band of pixels is considered as
+/// part of an adjacent line (like a row of accents).
+const THIN_BAND_RATIO: f32 = 0.4;
+
+/// Bounding box of a region of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    /// Leftmost column of the region.
+    pub x: u32,
+    /// Topmost row of the region.
+    pub y: u32,
+    /// Width of the region.
+    pub width: u32,
+    /// Height of the region.
+    pub height: u32,
+}
+
+impl BoundingBox {
+    // Create a box from its inclusive min and max coordinates.
+    const fn from_coords(x1: u32, y1: u32, x2: u32, y2: u32) -> Self {
+        Self {
+            x: x1,
+            y: y1,
+            width: x2 + 1 - x1,
+            height: y2 + 1 - y1,
+        }
+    }
+
+    /// Column after the rightmost column of the region.
+    #[must_use]
+    pub const fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    /// Row after the bottom row of the region.
+    #[must_use]
+    pub const fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    /// Smallest box containing both boxes.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_coords(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.right().max(other.right()) - 1,
+            self.bottom().max(other.bottom()) - 1,
+        )
+    }
+
+    // Number of columns shared by the two boxes.
+    fn horizontal_overlap(&self, other: &Self) -> u32 {
+        self.right()
+            .min(other.right())
+            .saturating_sub(self.x.max(other.x))
+    }
+
+    // Check if the two boxes share at least one row.
+    const fn vertical_overlap(&self, other: &Self) -> bool {
+        self.y < other.bottom() && other.y < self.bottom()
+    }
+}
+
+/// Mask of the ink (text) pixels of an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InkMask {
+    width: u32,
+    height: u32,
+    ink: Vec<bool>,
+}
+
+impl InkMask {
+    /// Create a mask from an image, with a function to decide if a pixel is ink.
+    pub fn from_fn<P, F>(image: &ImageBuffer<P, Vec<P::Subpixel>>, is_ink: F) -> Self
+    where
+        P: Pixel,
+        F: Fn(&P) -> bool,
+    {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            ink: image.pixels().map(is_ink).collect(),
+        }
+    }
+
+    /// Create a mask from an image with alpha (like [`ToImage`] output),
+    /// pixels with an alpha greater than or equal to `alpha_threshold` are ink.
+    ///
+    /// [`ToImage`]: super::ToImage
+    #[must_use]
+    pub fn from_alpha<P>(image: &ImageBuffer<P, Vec<u8>>, alpha_threshold: u8) -> Self
+    where
+        P: Pixel<Subpixel = u8>,
+    {
+        Self::from_fn(image, |pixel| pixel.to_luma_alpha()[1] >= alpha_threshold)
+    }
+
+    /// Create a mask from an image generated for `OCR`, pixels of `text_color` are ink.
+    #[must_use]
+    pub fn from_ocr_image(image: &GrayImage, text_color: Luma<u8>) -> Self {
+        Self::from_fn(image, |pixel| *pixel == text_color)
+    }
+
+    /// Create a mask from raw values in row-major order.
+    ///
+    /// # Panics
+    /// Will panic if the number of values doesn't match `width` x `height`.
+    #[must_use]
+    pub fn from_raw(width: u32, height: u32, ink: Vec<bool>) -> Self {
+        assert_eq!(ink.len(), width as usize * height as usize);
+        Self { width, height, ink }
+    }
+
+    /// Width of the mask.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the mask.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Check if the pixel at `x`, `y` is ink. Pixels outside of the mask are not ink.
+    #[must_use]
+    pub fn is_ink(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.ink[(y * self.width + x) as usize]
+    }
+
+    /// Number of ink pixels in the mask.
+    #[must_use]
+    pub fn ink_count(&self) -> usize {
+        self.ink.iter().filter(|ink| **ink).count()
+    }
+
+    /// Extract the part of the mask inside `region`.
+    #[must_use]
+    pub fn crop(&self, region: BoundingBox) -> Self {
+        let ink = (region.y..region.bottom())
+            .flat_map(|y| (region.x..region.right()).map(move |x| (x, y)))
+            .map(|(x, y)| self.is_ink(x, y))
+            .collect();
+        Self {
+            width: region.width,
+            height: region.height,
+            ink,
+        }
+    }
+}
+
+/// A glyph found in a subtitle image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Position of the glyph in the segmented image.
+    pub bounds: BoundingBox,
+    /// Ink pixels of the glyph only (not of the neighboring glyphs), relative to `bounds`.
+    pub mask: InkMask,
+}
+
+/// A line of text found in a subtitle image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextLine {
+    /// Position of the line in the segmented image.
+    pub bounds: BoundingBox,
+    /// Glyphs of the line, sorted from left to right.
+    pub glyphs: Vec<Glyph>,
+}
+
+// A connected component of ink pixels.
+struct Component {
+    bounds: BoundingBox,
+    pixels: Vec<(u32, u32)>,
+}
+
+impl Component {
+    fn merge(&mut self, other: Self) {
+        self.bounds = self.bounds.union(&other.bounds);
+        self.pixels.extend(other.pixels);
+    }
+
+    fn into_glyph(self) -> Glyph {
+        let bounds = self.bounds;
+        let mut ink = vec![false; bounds.width as usize * bounds.height as usize];
+        self.pixels.iter().for_each(|(x, y)| {
+            ink[((y - bounds.y) * bounds.width + (x - bounds.x)) as usize] = true;
+        });
+        Glyph {
+            bounds,
+            mask: InkMask::from_raw(bounds.width, bounds.height, ink),
+        }
+    }
+}
+
+// Find the 8-connected components of ink pixels.
+fn connected_components(mask: &InkMask) -> Vec<Component> {
+    let mut visited = vec![false; mask.ink.len()];
+    let mut components = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for start in 0..mask.ink.len() {
+        if !mask.ink[start] || visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let start = u32::try_from(start).unwrap();
+        queue.push_back((start % mask.width, start / mask.width));
+
+        let mut pixels = Vec::new();
+        let (mut x1, mut y1, mut x2, mut y2) = (u32::MAX, u32::MAX, 0, 0);
+        while let Some((x, y)) = queue.pop_front() {
+            pixels.push((x, y));
+            (x1, y1, x2, y2) = (x1.min(x), y1.min(y), x2.max(x), y2.max(y));
+
+            for (nx, ny) in neighbors(x, y) {
+                let idx = (ny * mask.width + nx) as usize;
+                if mask.is_ink(nx, ny) && !visited[idx] {
+                    visited[idx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        components.push(Component {
+            bounds: BoundingBox::from_coords(x1, y1, x2, y2),
+            pixels,
+        });
+    }
+    components
+}
+
+// The 8 neighbors of a pixel, without negative coordinates.
+fn neighbors(x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+    (-1i64..=1)
+        .flat_map(|dy| (-1i64..=1).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| dx != 0 || dy != 0)
+        .filter_map(move |(dx, dy)| {
+            let nx = u32::try_from(i64::from(x) + dx).ok()?;
+            let ny = u32::try_from(i64::from(y) + dy).ok()?;
+            Some((nx, ny))
+        })
+}
+
+// Find the bands of rows containing ink, as `(first row, end row)`.
+fn ink_bands(mask: &InkMask) -> Vec<(u32, u32)> {
+    let mut bands = Vec::new();
+    let mut band_start = None;
+    for y in 0..mask.height {
+        let has_ink = (0..mask.width).any(|x| mask.is_ink(x, y));
+        match (has_ink, band_start) {
+            (true, None) => band_start = Some(y),
+            (false, Some(start)) => {
+                bands.push((start, y));
+                band_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = band_start {
+        bands.push((start, mask.height));
+    }
+    bands
+}
+
+// Merge the thin bands (like a row of accents) in the nearest adjacent band.
+fn merge_thin_bands(mut bands: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    let mut heights = bands
+        .iter()
+        .map(|(start, end)| end - start)
+        .collect::<Vec<_>>();
+    heights.sort_unstable();
+    let Some(&median) = heights.get(heights.len() / 2) else {
+        return bands;
+    };
+    let min_height = cast::u32((cast::f32(median) * THIN_BAND_RATIO).ceil()).unwrap_or(0);
+
+    let mut idx = 0;
+    while bands.len() > 1 && idx < bands.len() {
+        let (start, end) = bands[idx];
+        if end - start >= min_height {
+            idx += 1;
+            continue;
+        }
+        let gap_prev = idx.checked_sub(1).map(|prev| start - bands[prev].1);
+        let gap_next = bands.get(idx + 1).map(|next| next.0 - end);
+        let merge_with = match (gap_prev, gap_next) {
+            (Some(prev), Some(next)) if prev < next => idx - 1,
+            (Some(_), None) => idx - 1,
+            _ => idx + 1,
+        };
+        let merged = (
+            bands[idx].0.min(bands[merge_with].0),
+            bands[idx].1.max(bands[merge_with].1),
+        );
+        bands[merge_with] = merged;
+        bands.remove(idx);
+        idx = 0;
+    }
+    bands
+}
+
+// Merge components placed above or below each other in the same glyph.
+fn merge_stacked(mut components: Vec<Component>) -> Vec<Component> {
+    components.sort_by_key(|component| component.bounds.x);
+    let mut merged: Vec<Component> = Vec::with_capacity(components.len());
+    for component in components {
+        let stacked = merged.iter().rposition(|glyph| {
+            let overlap = glyph.bounds.horizontal_overlap(&component.bounds);
+            let min_width = glyph.bounds.width.min(component.bounds.width);
+            !glyph.bounds.vertical_overlap(&component.bounds) && overlap * 2 >= min_width
+        });
+        if let Some(idx) = stacked {
+            merged[idx].merge(component);
+        } else {
+            merged.push(component);
+        }
+    }
+    merged.sort_by_key(|component| component.bounds.x);
+    merged
+}
+
+/// Split the ink of a mask in text lines, and each line in glyphs.
+///
+/// Lines are returned from top to bottom.
+#[profiling::function]
+#[must_use]
+pub fn segment_lines(mask: &InkMask) -> Vec<TextLine> {
+    let bands = merge_thin_bands(ink_bands(mask));
+    let mut line_components = bands.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+
+    for component in connected_components(mask) {
+        let center = component.bounds.y + component.bounds.height / 2;
+        let line = bands
+            .iter()
+            .position(|(start, end)| (*start..*end).contains(&center))
+            .unwrap_or_else(|| {
+                bands
+                    .iter()
+                    .position(|(start, end)| {
+                        component.bounds.y < *end && *start < component.bounds.bottom()
+                    })
+                    .unwrap()
+            });
+        line_components[line].push(component);
+    }
+
+    line_components
+        .into_iter()
+        .filter(|components| !components.is_empty())
+        .map(|components| {
+            let glyphs = merge_stacked(components)
+                .into_iter()
+                .map(Component::into_glyph)
+                .collect::<Vec<_>>();
+            let bounds = glyphs
+                .iter()
+                .skip(1)
+                .fold(glyphs[0].bounds, |bounds, glyph| {
+                    bounds.union(&glyph.bounds)
+                });
+            TextLine { bounds, glyphs }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parse a mask from a list of text lines, `#` is ink.
+    fn mask(lines: &[&str]) -> InkMask {
+        let width = u32::try_from(lines[0].len()).unwrap();
+        let height = u32::try_from(lines.len()).unwrap();
+        let ink = lines
+            .iter()
+            .flat_map(|line| line.chars().map(|c| c == '#'))
+            .collect();
+        InkMask::from_raw(width, height, ink)
+    }
+
+    #[test]
+    fn two_lines_with_diacritics() {
+        let mask = mask(&[
+            "..........#......",
+            ".................",
+            "..#....####..##..",
+            "..#....#.....##..",
+            "..#....###.......",
+            "..#....#.....##..",
+            "..#....####..##..",
+            ".................",
+            ".................",
+            "..###...#...#....",
+            "..#.#...#...#....",
+            "..###...#...#....",
+            "..#.....#...#....",
+        ]);
+        let lines = segment_lines(&mask);
+        assert_eq!(lines.len(), 2);
+
+        // 'l', 'é' with its accent, ':' with its two dots.
+        assert_eq!(lines[0].glyphs.len(), 3);
+        assert_eq!(
+            lines[0].glyphs[1].bounds,
+            BoundingBox {
+                x: 7,
+                y: 0,
+                width: 4,
+                height: 7
+            }
+        );
+        assert_eq!(lines[0].glyphs[2].mask.ink_count(), 8);
+        assert_eq!(lines[1].glyphs.len(), 3);
+        assert_eq!(lines[1].bounds.y, 9);
+    }
+
+    #[test]
+    fn italic_glyphs_kept_separated() {
+        let mask = mask(&[
+            "....##...##",
+            "...##...##.",
+            "..##...##..",
+            ".##...##...",
+            "##...##....",
+        ]);
+        let lines = segment_lines(&mask);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].glyphs.len(), 2);
+        assert_eq!(lines[0].glyphs[0].mask.ink_count(), 10);
+        assert!(lines[0].glyphs[0].mask.is_ink(5, 0));
+        assert!(!lines[0].glyphs[0].mask.is_ink(5, 4));
+    }
+
+    #[test]
+    fn ink_mask_from_alpha() {
+        use image::{LumaA, RgbaImage};
+        let image = RgbaImage::from_fn(4, 2, |x, _| {
+            image::Rgba([255, 255, 255, u8::try_from(80 * x).unwrap()])
+        });
+        let mask = InkMask::from_alpha(&image, DEFAULT_INK_ALPHA);
+        assert_eq!(mask.ink_count(), 4);
+        let gray = ImageBuffer::from_pixel(2, 2, LumaA([0u8, 255]));
+        assert_eq!(InkMask::from_alpha(&gray, DEFAULT_INK_ALPHA).ink_count(), 4);
+    }
+}
@@ -0,0 +1,265 @@
+use super::{BoundingBox, IndexedImage};
+use crate::content::{Area, ContentError, Size};
+
+/// Define how the pixels of an indexed image are resampled during scaling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexedScaleFilter {
+    /// Use the source pixel nearest to the center of the target pixel.
+    Nearest,
+    /// Use the most frequent index of the source pixels covered by the target pixel.
+    /// In case of equality, the most opaque index is used, to preserve thin strokes.
+    #[default]
+    Majority,
+}
+
+/// Scale an indexed image of size `width` x `height` to `new_width` x `new_height`.
+///
+/// Pixels are palette indexes in row-major order. No new index is created, so the result
+/// can use the palette of the source image. `alpha` give the alpha of an index, used by
+/// [`IndexedScaleFilter::Majority`] to break ties.
+///
+/// # Panics
+/// Will panic if the number of `pixels` doesn't match `width` x `height`.
+#[profiling::function]
+pub fn scale_indexed<A>(
+    pixels: &[u8],
+    (width, height): (u32, u32),
+    (new_width, new_height): (u32, u32),
+    filter: IndexedScaleFilter,
+    alpha: A,
+) -> Vec<u8>
+where
+    A: Fn(u8) -> u8,
+{
+    assert_eq!(pixels.len(), width as usize * height as usize);
+    if width == 0 || height == 0 {
+        return vec![0; new_width as usize * new_height as usize];
+    }
+
+    // Range of source coordinates covered by a target coordinate (at least one pixel).
+    let footprint = |target: u32, target_len: u32, source_len: u32| {
+        let start = u64::from(target) * u64::from(source_len) / u64::from(target_len);
+        let end = (u64::from(target + 1) * u64::from(source_len)).div_ceil(u64::from(target_len));
+        let start = u32::try_from(start).unwrap();
+        start..u32::try_from(end).unwrap().max(start + 1)
+    };
+    let pixel = |x: u32, y: u32| pixels[(y * width + x) as usize];
+
+    let mut votes: Vec<(u8, u32)> = Vec::with_capacity(4);
+    (0..new_height)
+        .flat_map(|y| (0..new_width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let xs = footprint(x, new_width, width);
+            let ys = footprint(y, new_height, height);
+            match filter {
+                IndexedScaleFilter::Nearest => {
+                    pixel((xs.start + xs.end - 1) / 2, (ys.start + ys.end - 1) / 2)
+                }
+                IndexedScaleFilter::Majority => {
+                    votes.clear();
+                    for y in ys {
+                        for x in xs.clone() {
+                            let index = pixel(x, y);
+                            match votes.iter_mut().find(|(idx, _)| *idx == index) {
+                                Some((_, count)) => *count += 1,
+                                None => votes.push((index, 1)),
+                            }
+                        }
+                    }
+                    votes
+                        .iter()
+                        .max_by_key(|&&(index, count)| (count, alpha(index)))
+                        .map_or(0, |&(index, _)| index)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Find the bounding box of the visible pixels of an indexed image of width `width`.
+///
+/// A pixel is visible if the `alpha` of its index is not zero.
+/// Return `None` if the image has no visible pixel.
+#[must_use]
+pub fn content_bounds<A>(pixels: &[u8], width: u32, alpha: A) -> Option<BoundingBox>
+where
+    A: Fn(u8) -> u8,
+{
+    if width == 0 {
+        return None;
+    }
+    pixels
+        .chunks_exact(width as usize)
+        .zip(0..)
+        .filter_map(|(row, y)| {
+            let first = row.iter().position(|&index| alpha(index) != 0)?;
+            let last = row.iter().rposition(|&index| alpha(index) != 0)?;
+            let (first, last) = (u32::try_from(first).ok()?, u32::try_from(last).ok()?);
+            Some(BoundingBox {
+                x: first,
+                y,
+                width: last + 1 - first,
+                height: 1,
+            })
+        })
+        .reduce(|bounds, row| bounds.union(&row))
+}
+
+/// Extract the pixels of `region` from an indexed image of width `width`.
+///
+/// # Panics
+/// Will panic if `region` is not inside the image.
+#[must_use]
+pub fn crop_indexed(pixels: &[u8], width: u32, region: BoundingBox) -> Vec<u8> {
+    (region.y..region.bottom())
+        .flat_map(|y| {
+            let start = (y * width + region.x) as usize;
+            &pixels[start..start + region.width as usize]
+        })
+        .copied()
+        .collect()
+}
+
+/// Re-positioning and scaling of an [`IndexedImage`] displayed at a position of a video frame,
+/// like [`VobSubIndexedImage`] and [`PgsIndexedImage`].
+///
+/// [`VobSubIndexedImage`]: crate::vobsub::VobSubIndexedImage
+/// [`PgsIndexedImage`]: crate::pgs::PgsIndexedImage
+pub trait IndexedTransform: IndexedImage + Sized {
+    /// Area of the image on the video frame.
+    fn screen_area(&self) -> Area;
+
+    /// Move the image to `area`, which has the size of [`IndexedTransform::screen_area`].
+    #[must_use]
+    fn with_screen_area(self, area: Area) -> Self;
+
+    /// Create an image with the palette of `self`, displayed on `area` with the
+    /// palette indexes `indexes` in row-major order.
+    #[must_use]
+    fn with_pixels(&self, area: Area, indexes: Vec<u8>) -> Self;
+
+    /// Set the size of the video frame on which the image is displayed,
+    /// for the formats which store it. Used by [`IndexedTransform::scale_to_frame`].
+    #[must_use]
+    fn with_frame_size(self, _frame: Size) -> Self {
+        self
+    }
+
+    /// Scale the image and its area by `scale_x` and `scale_y`.
+    ///
+    /// Pixels are resampled with `filter`, keeping the palette indexes of the image.
+    ///
+    /// # Errors
+    /// Will return [`ContentError::AreaOutOfRange`] if the scaled area doesn't fit in
+    /// `u16` coordinates.
+    fn scale(
+        &self,
+        scale_x: f64,
+        scale_y: f64,
+        filter: IndexedScaleFilter,
+    ) -> Result<Self, ContentError> {
+        let area = self.screen_area().scale(scale_x, scale_y)?;
+        let indexes = scale_indexed(
+            self.indexes(),
+            (self.width(), self.height()),
+            (u32::from(area.width()), u32::from(area.height())),
+            filter,
+            |index| self.index_alpha(index),
+        );
+        Ok(self.with_pixels(area, indexes))
+    }
+
+    /// Scale the image and its area from a video frame of size `from` to a frame of size `to`.
+    ///
+    /// # Errors
+    /// Will return [`ContentError::AreaOutOfRange`] if the scaled area doesn't fit in
+    /// `u16` coordinates.
+    fn scale_to_frame(
+        &self,
+        from: Size,
+        to: Size,
+        filter: IndexedScaleFilter,
+    ) -> Result<Self, ContentError> {
+        let ratio = |to: usize, from: usize| cast::f64(to) / cast::f64(from);
+        self.scale(ratio(to.w, from.w), ratio(to.h, from.h), filter)
+            .map(|image| image.with_frame_size(to))
+    }
+
+    /// Move the image by `dx`, `dy` pixels. See [`Area::translate`].
+    #[must_use]
+    fn translate(self, dx: i32, dy: i32) -> Self {
+        let area = self.screen_area().translate(dx, dy);
+        self.with_screen_area(area)
+    }
+
+    /// Move the image to be inside a video frame of size `frame`. See [`Area::clamp_to_frame`].
+    #[must_use]
+    fn clamp_to_frame(self, frame: Size) -> Self {
+        let area = self.screen_area().clamp_to_frame(frame);
+        self.with_screen_area(area)
+    }
+
+    /// Move the image inside the safe area of a video frame. See [`Area::move_into_safe_area`].
+    #[must_use]
+    fn move_into_safe_area(self, frame: Size, margin_ratio: f64) -> Self {
+        let area = self.screen_area().move_into_safe_area(frame, margin_ratio);
+        self.with_screen_area(area)
+    }
+
+    /// Center horizontally the image in a video frame. See [`Area::center_horizontally`].
+    #[must_use]
+    fn center_horizontally(self, frame: Size) -> Self {
+        let area = self.screen_area().center_horizontally(frame);
+        self.with_screen_area(area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_down_and_up() {
+        #[rustfmt::skip]
+        let pixels = [
+            0, 1, 2, 2,
+            1, 0, 2, 2,
+            3, 3, 0, 0,
+            3, 0, 0, 0,
+        ];
+        let alpha = |index: u8| [0, 15, 15, 15][usize::from(index)];
+
+        let majority = scale_indexed(&pixels, (4, 4), (2, 2), IndexedScaleFilter::Majority, alpha);
+        assert_eq!(majority, [1, 2, 3, 0]);
+
+        let nearest = scale_indexed(&pixels, (4, 4), (2, 2), IndexedScaleFilter::Nearest, alpha);
+        assert_eq!(nearest, [0, 2, 3, 0]);
+
+        let up = scale_indexed(&[1, 2], (2, 1), (4, 2), IndexedScaleFilter::Majority, alpha);
+        assert_eq!(up, [1, 1, 2, 2, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn bounds_and_crop() {
+        #[rustfmt::skip]
+        let pixels = [
+            0, 0, 0, 0,
+            0, 0, 2, 0,
+            0, 1, 0, 0,
+            0, 0, 0, 0,
+        ];
+        let alpha = |index: u8| [0, 15, 15, 15][usize::from(index)];
+        let bounds = content_bounds(&pixels, 4, alpha).unwrap();
+        assert_eq!(
+            bounds,
+            BoundingBox {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 2
+            }
+        );
+        assert_eq!(crop_indexed(&pixels, 4, bounds), [0, 2, 1, 0]);
+        assert_eq!(content_bounds(&[0; 16], 4, alpha), None);
+    }
+}
@@ -20,12 +20,22 @@
 // For error-chain.
 #![recursion_limit = "1024"]
 
+pub mod ass;
 pub mod content;
+pub mod dvb;
 mod errors;
 pub mod image;
+pub mod mkv;
+pub mod ocr;
 pub mod pgs;
+pub mod scc;
 pub mod srt;
+pub mod stl;
+pub mod teletext;
+pub mod text;
 pub mod time;
+pub mod ts;
+pub mod ttml;
 mod util;
 pub mod vobsub;
 pub mod webvtt;
@@ -0,0 +1,166 @@
+//! Reading of `EBML` elements, the binary format of `Matroska` files.
+//!
+//! Specification : <https://www.rfc-editor.org/rfc/rfc8794.html>
+
+use super::MkvError;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+/// Maximum size of an element read in memory.
+const MAX_ELEMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Header of an `EBML` element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElementHeader {
+    /// Id of the element, with the length marker.
+    pub id: u32,
+    /// Size of the element data, `None` if unknown.
+    pub size: Option<u64>,
+    /// Position of the element header in the stream.
+    pub position: u64,
+    /// Position of the element data in the stream.
+    pub data_position: u64,
+}
+
+impl ElementHeader {
+    /// Position after the element data, `None` if the size is unknown.
+    pub fn end(&self) -> Option<u64> {
+        self.size.map(|size| self.data_position + size)
+    }
+}
+
+/// Read a variable size integer. Return the value (without the length marker)
+/// and the length in bytes, or `None` if the end of the stream is reached.
+fn read_vint<R: Read>(reader: &mut R) -> Result<Option<(u64, u32)>, MkvError> {
+    let mut first = [0];
+    match reader.read_exact(&mut first) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(MkvError::Read(err)),
+    }
+    let len = first[0].leading_zeros() + 1;
+    if len > 8 {
+        return Err(MkvError::InvalidVint);
+    }
+    let mut value = u64::from(first[0]) & (0xff >> len);
+    for _ in 1..len {
+        let mut byte = [0];
+        reader.read_exact(&mut byte).map_err(MkvError::Read)?;
+        value = (value << 8) | u64::from(byte[0]);
+    }
+    Ok(Some((value, len)))
+}
+
+/// Read a variable size integer, as used for track numbers in blocks.
+pub fn read_vint_value<R: Read>(reader: &mut R) -> Result<(u64, u32), MkvError> {
+    read_vint(reader)?.ok_or_else(|| MkvError::Read(ErrorKind::UnexpectedEof.into()))
+}
+
+/// Read the header of the next element, or `None` if the end of the stream is reached.
+pub fn read_header<R: Read + Seek>(reader: &mut R) -> Result<Option<ElementHeader>, MkvError> {
+    let position = reader.stream_position().map_err(MkvError::Read)?;
+    let Some((id, id_len)) = read_vint(reader)? else {
+        return Ok(None);
+    };
+    if id_len > 4 {
+        return Err(MkvError::InvalidVint);
+    }
+    // The id is kept with its length marker, as in the specification.
+    let id = u32::try_from(id | (1 << (7 * id_len)))
+        .ok()
+        .ok_or(MkvError::InvalidVint)?;
+    let (size, size_len) = read_vint_value(reader)?;
+    // A size with all bits set is an unknown size.
+    let size = (size != (1 << (7 * size_len)) - 1).then_some(size);
+    let data_position = reader.stream_position().map_err(MkvError::Read)?;
+    Ok(Some(ElementHeader {
+        id,
+        size,
+        position,
+        data_position,
+    }))
+}
+
+/// Move the reader after the element.
+pub fn skip<R: Seek>(reader: &mut R, header: &ElementHeader) -> Result<(), MkvError> {
+    let end = header
+        .end()
+        .ok_or(MkvError::UnknownSize { id: header.id })?;
+    reader.seek(SeekFrom::Start(end)).map_err(MkvError::Read)?;
+    Ok(())
+}
+
+/// Read the data of the element in memory.
+pub fn read_data<R: Read>(reader: &mut R, header: &ElementHeader) -> Result<Vec<u8>, MkvError> {
+    let size = header.size.ok_or(MkvError::UnknownSize { id: header.id })?;
+    if size > MAX_ELEMENT_SIZE {
+        return Err(MkvError::ElementTooLarge {
+            id: header.id,
+            size,
+        });
+    }
+    let mut data = Vec::new();
+    reader
+        .take(size)
+        .read_to_end(&mut data)
+        .map_err(MkvError::Read)?;
+    if data.len() as u64 != size {
+        return Err(MkvError::Read(ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(data)
+}
+
+/// Read an unsigned integer element.
+pub fn read_uint<R: Read>(reader: &mut R, header: &ElementHeader) -> Result<u64, MkvError> {
+    let data = read_data(reader, header)?;
+    if data.len() > 8 {
+        return Err(MkvError::InvalidElement { id: header.id });
+    }
+    Ok(data
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+/// Read a string element. The string can be padded with zeros.
+pub fn read_string<R: Read>(reader: &mut R, header: &ElementHeader) -> Result<String, MkvError> {
+    let mut data = read_data(reader, header)?;
+    if let Some(end) = data.iter().position(|byte| *byte == 0) {
+        data.truncate(end);
+    }
+    String::from_utf8(data)
+        .ok()
+        .ok_or(MkvError::InvalidElement { id: header.id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn parse_header() {
+        // `EBML` header id, with a size of 0x23 on 1 byte.
+        let mut reader = Cursor::new([0x1a, 0x45, 0xdf, 0xa3, 0xa3]);
+        let header = read_header(&mut reader).unwrap().unwrap();
+        assert_eq!(header.id, 0x1a45_dfa3);
+        assert_eq!(header.size, Some(0x23));
+        assert_eq!(header.data_position, 5);
+        assert_eq!(read_header(&mut reader).unwrap(), None);
+
+        // `Cluster` with unknown size on 8 bytes.
+        let mut reader = Cursor::new([
+            0x1f, 0x43, 0xb6, 0x75, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ]);
+        let header = read_header(&mut reader).unwrap().unwrap();
+        assert_eq!(header.id, 0x1f43_b675);
+        assert_eq!(header.size, None);
+    }
+
+    #[test]
+    fn parse_values() {
+        let mut reader = Cursor::new([0x86, 0x82, 0x01, 0x02, 0x86, 0x83, b'a', b'b', 0x00]);
+        let header = read_header(&mut reader).unwrap().unwrap();
+        assert_eq!(read_uint(&mut reader, &header).unwrap(), 0x0102);
+        let header = read_header(&mut reader).unwrap().unwrap();
+        assert_eq!(read_string(&mut reader, &header).unwrap(), "ab");
+    }
+}
@@ -0,0 +1,1060 @@
+//! Read subtitle tracks from `Matroska` (`.mkv`, `.mka`, `.mks`) and `WebM` files.
+//!
+//! This is a minimal demuxer: it lists the subtitle tracks of a file and
+//! extracts the blocks of a track, which can then be decoded with the
+//! [`vobsub`](crate::vobsub) or [`pgs`](crate::pgs) decoders, or as text cues.
+//!
+//! Specification : <https://www.matroska.org/technical/elements.html>
+//!
+//! ## Example code
+//!
+//! ```no_run
+//! use subtile::mkv::{Matroska, SubtitleCodec};
+//!
+//! let mut mkv = Matroska::open("movie.mkv").unwrap();
+//! let tracks = mkv.tracks().to_vec();
+//! for track in tracks {
+//!     println!("Track {} ({}): {:?}", track.number(), track.language(), track.codec());
+//!     if track.codec() == &SubtitleCodec::Text {
+//!         for cue in mkv.text_cues(track.number()).unwrap() {
+//!             println!("{:?}: {}", cue.time_span, cue.text);
+//!         }
+//!     }
+//! }
+//! ```
+
+mod ebml;
+
+use crate::{
+    ass,
+    pgs::{PgsDecoder, PgsError, SupParser, SupWriter},
+    text::{self, CuePosition},
+    time::{TimePoint, TimeSpan},
+    vobsub::{decode_spu, Index, VobSubError, VobSubIndexedImage},
+};
+use ebml::{read_data, read_header, read_string, read_uint, read_vint_value, skip, ElementHeader};
+use std::{
+    fs::File,
+    io::{self, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Ids of the `Matroska` elements used.
+mod id {
+    pub const EBML: u32 = 0x1a45_dfa3;
+    pub const DOC_TYPE: u32 = 0x4282;
+    pub const SEGMENT: u32 = 0x1853_8067;
+    pub const INFO: u32 = 0x1549_a966;
+    pub const TIMESTAMP_SCALE: u32 = 0x2a_d7b1;
+    pub const TRACKS: u32 = 0x1654_ae6b;
+    pub const TRACK_ENTRY: u32 = 0xae;
+    pub const TRACK_NUMBER: u32 = 0xd7;
+    pub const TRACK_TYPE: u32 = 0x83;
+    pub const CODEC_ID: u32 = 0x86;
+    pub const CODEC_PRIVATE: u32 = 0x63a2;
+    pub const LANGUAGE: u32 = 0x22_b59c;
+    pub const LANGUAGE_BCP47: u32 = 0x22_b59d;
+    pub const NAME: u32 = 0x536e;
+    pub const FLAG_DEFAULT: u32 = 0x88;
+    pub const FLAG_FORCED: u32 = 0x55aa;
+    pub const CONTENT_ENCODINGS: u32 = 0x6d80;
+    pub const CONTENT_ENCODING: u32 = 0x6240;
+    pub const CONTENT_COMPRESSION: u32 = 0x5034;
+    pub const CONTENT_COMP_ALGO: u32 = 0x4254;
+    pub const CONTENT_COMP_SETTINGS: u32 = 0x4255;
+    pub const CONTENT_ENCRYPTION: u32 = 0x5035;
+    pub const CLUSTER: u32 = 0x1f43_b675;
+    pub const TIMESTAMP: u32 = 0xe7;
+    pub const SIMPLE_BLOCK: u32 = 0xa3;
+    pub const BLOCK_GROUP: u32 = 0xa0;
+    pub const BLOCK: u32 = 0xa1;
+    pub const BLOCK_DURATION: u32 = 0x9b;
+    pub const CUES: u32 = 0x1c53_bb6b;
+    pub const TAGS: u32 = 0x1254_c367;
+    pub const CHAPTERS: u32 = 0x1043_a770;
+    pub const ATTACHMENTS: u32 = 0x1941_a469;
+    pub const SEEK_HEAD: u32 = 0x114d_9b74;
+}
+
+/// `TrackType` value of subtitle tracks.
+const TRACK_TYPE_SUBTITLE: u64 = 0x11;
+/// Default `TimestampScale`: timestamps are in milliseconds.
+const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+/// Mask of the lacing bits in block flags.
+const LACING_MASK: u8 = 0b0000_0110;
+
+/// Error for `Matroska` handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MkvError {
+    /// Io error on a path.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("Io error while reading stream")]
+    Read(#[source] io::Error),
+
+    /// The stream doesn't start with an `EBML` header of a `Matroska` or `WebM` document.
+    #[error("not a Matroska file")]
+    NotMatroska,
+
+    /// A variable size integer is invalid.
+    #[error("invalid EBML variable size integer")]
+    InvalidVint,
+
+    /// The value of an element is invalid.
+    #[error("invalid value for element {id:#x}")]
+    InvalidElement {
+        /// Id of the element
+        id: u32,
+    },
+
+    /// An element with an unknown size can't be read or skipped.
+    #[error("element {id:#x} has an unknown size")]
+    UnknownSize {
+        /// Id of the element
+        id: u32,
+    },
+
+    /// An element is too large to be read in memory.
+    #[error("element {id:#x} is too large ({size} bytes)")]
+    ElementTooLarge {
+        /// Id of the element
+        id: u32,
+        /// Size of the element
+        size: u64,
+    },
+
+    /// No subtitle track with this number in the file.
+    #[error("no subtitle track with number {0}")]
+    UnknownTrack(u64),
+
+    /// The codec of the track is not the one needed by the decoding.
+    #[error("track {track} use codec '{codec_id}', which can't be decoded as {expected}")]
+    CodecMismatch {
+        /// Number of the track
+        track: u64,
+        /// Codec id of the track
+        codec_id: String,
+        /// Name of the expected codec
+        expected: &'static str,
+    },
+
+    /// Blocks with lacing are not supported.
+    #[error("laced blocks are not supported, in track {0}")]
+    UnsupportedLacing(u64),
+
+    /// The content encoding of the track (encryption or compression) is not supported.
+    #[error("content encoding of track {0} is not supported")]
+    UnsupportedEncoding(u64),
+
+    /// Decompression of a block failed.
+    #[error("decompression of a block of track {0} failed")]
+    Decompression(u64),
+
+    /// The text of a block is not valid `UTF-8`.
+    #[error("text of a block of track {0} is not valid UTF-8")]
+    InvalidText(u64),
+
+    /// Error during `VobSub` decoding.
+    #[error("error with VobSub track")]
+    VobSub(#[from] VobSubError),
+
+    /// Error during `PGS` decoding.
+    #[error("error with PGS track")]
+    Pgs(#[from] PgsError),
+}
+
+/// Codec of a subtitle track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubtitleCodec {
+    /// `VobSub` images (`S_VOBSUB`), decoded with [`Matroska::vobsub`].
+    VobSub,
+    /// `PGS` images (`S_HDMV/PGS`), decoded with [`Matroska::pgs`].
+    Pgs,
+    /// Plain `UTF-8` text (`S_TEXT/UTF8`).
+    Text,
+    /// `SubStation Alpha` or `Advanced SubStation Alpha` text (`S_TEXT/SSA`, `S_TEXT/ASS`).
+    Ass,
+    /// `WebVTT` text (`S_TEXT/WEBVTT`).
+    WebVtt,
+    /// Another codec, not supported by this crate.
+    Other,
+}
+
+impl SubtitleCodec {
+    fn from_codec_id(codec_id: &str) -> Self {
+        match codec_id {
+            "S_VOBSUB" => Self::VobSub,
+            "S_HDMV/PGS" => Self::Pgs,
+            "S_TEXT/UTF8" => Self::Text,
+            "S_TEXT/SSA" | "S_TEXT/ASS" | "S_SSA" | "S_ASS" => Self::Ass,
+            "S_TEXT/WEBVTT" => Self::WebVtt,
+            _ => Self::Other,
+        }
+    }
+
+    const fn is_text(&self) -> bool {
+        matches!(self, Self::Text | Self::Ass | Self::WebVtt)
+    }
+}
+
+/// Compression of the blocks of a track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Compression {
+    /// Blocks are compressed with `zlib`.
+    Zlib,
+    /// Common first bytes of the blocks are removed.
+    HeaderStripping(Vec<u8>),
+    /// An encoding not supported.
+    Unsupported,
+}
+
+/// A subtitle track of a `Matroska` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtitleTrack {
+    number: u64,
+    codec_id: String,
+    codec: SubtitleCodec,
+    codec_private: Vec<u8>,
+    language: String,
+    name: Option<String>,
+    default: bool,
+    forced: bool,
+    compression: Option<Compression>,
+}
+
+impl Default for SubtitleTrack {
+    fn default() -> Self {
+        Self {
+            number: 0,
+            codec_id: String::new(),
+            codec: SubtitleCodec::Other,
+            codec_private: Vec::new(),
+            language: "eng".into(),
+            name: None,
+            default: true,
+            forced: false,
+            compression: None,
+        }
+    }
+}
+
+impl SubtitleTrack {
+    /// Number of the track, used to select the track to decode.
+    #[must_use]
+    pub const fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Codec id of the track, as in the file.
+    #[must_use]
+    pub fn codec_id(&self) -> &str {
+        &self.codec_id
+    }
+
+    /// Codec of the track.
+    #[must_use]
+    pub const fn codec(&self) -> &SubtitleCodec {
+        &self.codec
+    }
+
+    /// Private data of the codec: the `*.idx` content for `VobSub`, the header for `ASS`.
+    #[must_use]
+    pub fn codec_private(&self) -> &[u8] {
+        &self.codec_private
+    }
+
+    /// Language of the track, `BCP 47` if available, else `ISO 639-2`.
+    #[must_use]
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Name of the track, if any.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// If the track should be selected by default.
+    #[must_use]
+    pub const fn is_default(&self) -> bool {
+        self.default
+    }
+
+    /// If the track contains only forced subtitles.
+    #[must_use]
+    pub const fn is_forced(&self) -> bool {
+        self.forced
+    }
+}
+
+/// A block of data of a track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// Presentation time of the block.
+    pub timestamp: TimePoint,
+    /// Duration of the block, if provided.
+    pub duration: Option<TimePoint>,
+    /// Data of the block, decompressed.
+    pub data: Vec<u8>,
+}
+
+/// A text subtitle of a text track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextCue {
+    /// Display time of the subtitle.
+    pub time_span: TimeSpan,
+    /// Text of the subtitle. For `ASS` tracks, only the `Text` field of the event is kept.
+    pub text: String,
+}
+
+/// A decoded subtitle of a `VobSub` track.
+pub type VobSubSubtitle = Result<(TimeSpan, VobSubIndexedImage), VobSubError>;
+
+/// Number of fields before the text in `ASS` blocks:
+/// `ReadOrder, Layer, Style, Name, MarginL, MarginR, MarginV, Effect`.
+const ASS_FIELDS_BEFORE_TEXT: usize = 8;
+
+/// Reader of the subtitle tracks of a `Matroska` file.
+pub struct Matroska<R> {
+    reader: R,
+    timestamp_scale: u64,
+    tracks: Vec<SubtitleTrack>,
+    /// Position of the first cluster.
+    clusters_position: Option<u64>,
+    /// Position of the end of the segment, if known.
+    segment_end: Option<u64>,
+}
+
+impl Matroska<BufReader<File>> {
+    /// Open a `Matroska` file and read the list of its subtitle tracks.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::Io` if not able to open the file.
+    /// Will return an error if the file is not a valid `Matroska` file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MkvError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| MkvError::Io {
+            source,
+            path: path.into(),
+        })?;
+        Self::from_reader(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> Matroska<R> {
+    /// Read the list of the subtitle tracks of a `Matroska` stream.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the stream is not a valid `Matroska` stream.
+    pub fn from_reader(mut reader: R) -> Result<Self, MkvError> {
+        let header = read_header(&mut reader)
+            .ok()
+            .flatten()
+            .filter(|header| header.id == id::EBML)
+            .ok_or(MkvError::NotMatroska)?;
+        let mut doc_type = None;
+        for_each_child(&mut reader, &header, |reader, child| {
+            if child.id == id::DOC_TYPE {
+                doc_type = Some(read_string(reader, child)?);
+            } else {
+                skip(reader, child)?;
+            }
+            Ok(())
+        })?;
+        if !matches!(doc_type.as_deref(), Some("matroska" | "webm")) {
+            return Err(MkvError::NotMatroska);
+        }
+
+        let segment = loop {
+            let header = read_header(&mut reader)?.ok_or(MkvError::NotMatroska)?;
+            if header.id == id::SEGMENT {
+                break header;
+            }
+            skip(&mut reader, &header)?;
+        };
+
+        let mut mkv = Self {
+            reader,
+            timestamp_scale: DEFAULT_TIMESTAMP_SCALE,
+            tracks: Vec::new(),
+            clusters_position: None,
+            segment_end: segment.end(),
+        };
+        mkv.read_metadata()?;
+        Ok(mkv)
+    }
+
+    /// Read the segment information and tracks, until the first cluster.
+    fn read_metadata(&mut self) -> Result<(), MkvError> {
+        let mut tracks_found = false;
+        while let Some(header) = self.next_top_level()? {
+            match header.id {
+                id::INFO => {
+                    for_each_child(&mut self.reader, &header, |reader, child| {
+                        if child.id == id::TIMESTAMP_SCALE {
+                            self.timestamp_scale = read_uint(reader, child)?;
+                        } else {
+                            skip(reader, child)?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                id::TRACKS => {
+                    let mut tracks = Vec::new();
+                    for_each_child(&mut self.reader, &header, |reader, child| {
+                        if child.id == id::TRACK_ENTRY {
+                            if let Some(track) = read_track(reader, child)? {
+                                tracks.push(track);
+                            }
+                        } else {
+                            skip(reader, child)?;
+                        }
+                        Ok(())
+                    })?;
+                    self.tracks = tracks;
+                    tracks_found = true;
+                }
+                id::CLUSTER => {
+                    self.clusters_position.get_or_insert(header.position);
+                    if tracks_found || header.size.is_none() {
+                        break;
+                    }
+                    skip(&mut self.reader, &header)?;
+                }
+                _ => skip(&mut self.reader, &header)?,
+            }
+            if tracks_found && self.clusters_position.is_some() {
+                break;
+            }
+        }
+        if self.timestamp_scale == 0 {
+            return Err(MkvError::InvalidElement {
+                id: id::TIMESTAMP_SCALE,
+            });
+        }
+        Ok(())
+    }
+
+    /// Read the header of the next element of the segment.
+    fn next_top_level(&mut self) -> Result<Option<ElementHeader>, MkvError> {
+        if let Some(end) = self.segment_end {
+            let position = self.reader.stream_position().map_err(MkvError::Read)?;
+            if position >= end {
+                return Ok(None);
+            }
+        }
+        read_header(&mut self.reader)
+    }
+
+    /// Subtitle tracks of the file.
+    #[must_use]
+    pub fn tracks(&self) -> &[SubtitleTrack] {
+        &self.tracks
+    }
+
+    /// Get a subtitle track from its number.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::UnknownTrack` if no subtitle track have this number.
+    pub fn track(&self, number: u64) -> Result<&SubtitleTrack, MkvError> {
+        self.tracks
+            .iter()
+            .find(|track| track.number == number)
+            .ok_or(MkvError::UnknownTrack(number))
+    }
+
+    /// Read all the blocks of a subtitle track, in the file order.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::UnknownTrack` if no subtitle track have this number.
+    /// Will return an error if reading or decompression of a block failed.
+    pub fn read_blocks(&mut self, track: u64) -> Result<Vec<Block>, MkvError> {
+        let compression = self.track(track)?.compression.clone();
+        let mut blocks = Vec::new();
+        let Some(position) = self.clusters_position else {
+            return Ok(blocks);
+        };
+        self.reader
+            .seek(SeekFrom::Start(position))
+            .map_err(MkvError::Read)?;
+
+        while let Some(header) = self.next_top_level()? {
+            if header.id == id::CLUSTER {
+                self.read_cluster(&header, track, &mut blocks)?;
+            } else {
+                skip(&mut self.reader, &header)?;
+            }
+        }
+
+        blocks
+            .into_iter()
+            .map(|block| decode_block(block, compression.as_ref(), track))
+            .collect()
+    }
+
+    /// Read the blocks of `track` in a cluster.
+    fn read_cluster(
+        &mut self,
+        cluster: &ElementHeader,
+        track: u64,
+        blocks: &mut Vec<Block>,
+    ) -> Result<(), MkvError> {
+        let mut cluster_timestamp = 0;
+        loop {
+            let position = self.reader.stream_position().map_err(MkvError::Read)?;
+            if cluster.end().is_some_and(|end| position >= end)
+                || self.segment_end.is_some_and(|end| position >= end)
+            {
+                break;
+            }
+            let Some(header) = read_header(&mut self.reader)? else {
+                break;
+            };
+            match header.id {
+                id::TIMESTAMP => cluster_timestamp = read_uint(&mut self.reader, &header)?,
+                id::SIMPLE_BLOCK => {
+                    if let Some(block) = self.read_block(&header, track, cluster_timestamp, None)? {
+                        blocks.push(block);
+                    }
+                }
+                id::BLOCK_GROUP => {
+                    self.read_block_group(&header, track, cluster_timestamp, blocks)?;
+                }
+                // The end of a cluster of unknown size is the start of the next top level element.
+                id::CLUSTER
+                | id::CUES
+                | id::TAGS
+                | id::CHAPTERS
+                | id::ATTACHMENTS
+                | id::SEEK_HEAD
+                | id::INFO
+                | id::TRACKS
+                    if cluster.size.is_none() =>
+                {
+                    self.reader
+                        .seek(SeekFrom::Start(header.position))
+                        .map_err(MkvError::Read)?;
+                    break;
+                }
+                _ => skip(&mut self.reader, &header)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the block of a `BlockGroup`, with its duration.
+    fn read_block_group(
+        &mut self,
+        group: &ElementHeader,
+        track: u64,
+        cluster_timestamp: u64,
+        blocks: &mut Vec<Block>,
+    ) -> Result<(), MkvError> {
+        let mut block = None;
+        let mut duration = None;
+        for_each_child(&mut self.reader, group, |reader, child| {
+            match child.id {
+                id::BLOCK => block = Some(*child),
+                id::BLOCK_DURATION => duration = Some(read_uint(reader, child)?),
+                _ => {}
+            }
+            skip(reader, child)
+        })?;
+        if let Some(header) = block {
+            let end = self.reader.stream_position().map_err(MkvError::Read)?;
+            self.reader
+                .seek(SeekFrom::Start(header.data_position))
+                .map_err(MkvError::Read)?;
+            let block = self.read_block(&header, track, cluster_timestamp, duration)?;
+            self.reader
+                .seek(SeekFrom::Start(end))
+                .map_err(MkvError::Read)?;
+            blocks.extend(block);
+        }
+        Ok(())
+    }
+
+    /// Read a `Block` or `SimpleBlock`, if it is a block of `track`.
+    fn read_block(
+        &mut self,
+        header: &ElementHeader,
+        track: u64,
+        cluster_timestamp: u64,
+        duration: Option<u64>,
+    ) -> Result<Option<Block>, MkvError> {
+        let (block_track, len) = read_vint_value(&mut self.reader)?;
+        if block_track != track {
+            skip(&mut self.reader, header)?;
+            return Ok(None);
+        }
+        let mut block_header = [0; 3];
+        self.reader
+            .read_exact(&mut block_header)
+            .map_err(MkvError::Read)?;
+        if block_header[2] & LACING_MASK != 0 {
+            return Err(MkvError::UnsupportedLacing(track));
+        }
+        let relative = i16::from_be_bytes([block_header[0], block_header[1]]);
+        let data_header = ElementHeader {
+            size: header
+                .size
+                .and_then(|size| size.checked_sub(u64::from(len) + 3)),
+            ..*header
+        };
+        let data = read_data(&mut self.reader, &data_header)?;
+
+        let timestamp = i128::from(cluster_timestamp) + i128::from(relative);
+        Ok(Some(Block {
+            timestamp: self.to_time(timestamp),
+            duration: duration.map(|duration| self.to_time(i128::from(duration))),
+            data,
+        }))
+    }
+
+    /// Convert a time in `TimestampScale` units to a `TimePoint`.
+    fn to_time(&self, time: i128) -> TimePoint {
+        let msecs = time.saturating_mul(i128::from(self.timestamp_scale)) / 1_000_000;
+        let msecs = msecs.clamp(i128::from(i64::MIN), i128::from(i64::MAX));
+        TimePoint::from_msecs(i64::try_from(msecs).unwrap_or_default())
+    }
+
+    /// Check than `track` use the `expected` codec.
+    fn check_codec(
+        &self,
+        track: u64,
+        is_expected: impl Fn(&SubtitleCodec) -> bool,
+        expected: &'static str,
+    ) -> Result<&SubtitleTrack, MkvError> {
+        let track = self.track(track)?;
+        if is_expected(&track.codec) {
+            Ok(track)
+        } else {
+            Err(MkvError::CodecMismatch {
+                track: track.number,
+                codec_id: track.codec_id.clone(),
+                expected,
+            })
+        }
+    }
+
+    /// Decode a `VobSub` track. Return the [`Index`] from the codec private data, and the subtitles.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::CodecMismatch` if the track is not a `VobSub` track.
+    /// Will return an error if the reading of the blocks or the index failed.
+    pub fn vobsub(&mut self, track: u64) -> Result<(Index, Vec<VobSubSubtitle>), MkvError> {
+        let index = Index::from_bytes(
+            self.check_codec(track, |codec| codec == &SubtitleCodec::VobSub, "VobSub")?
+                .codec_private(),
+        )?;
+        let subtitles = self
+            .read_blocks(track)?
+            .into_iter()
+            .map(|block| {
+                let (mut time_span, image) = decode_spu(&block.data, block.timestamp.to_secs())?;
+                if let Some(duration) = block.duration {
+                    time_span.end = TimePoint::from_msecs(
+                        block.timestamp.msecs().saturating_add(duration.msecs()),
+                    );
+                }
+                Ok((time_span, image))
+            })
+            .collect();
+        Ok((index, subtitles))
+    }
+
+    /// Extract a `PGS` track as the content of a `.sup` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::CodecMismatch` if the track is not a `PGS` track.
+    /// Will return an error if the reading of the blocks failed.
+    pub fn pgs_stream(&mut self, track: u64) -> Result<Vec<u8>, MkvError> {
+        self.check_codec(track, |codec| codec == &SubtitleCodec::Pgs, "PGS")?;
+        let mut writer = SupWriter::new(Vec::new());
+        for block in self.read_blocks(track)? {
+            writer.write_segments(pgs_pts(block.timestamp), &block.data)?;
+        }
+        Ok(writer.into_inner())
+    }
+
+    /// Decode a `PGS` track with the `Decoder`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::CodecMismatch` if the track is not a `PGS` track.
+    /// Will return an error if the reading of the blocks failed.
+    pub fn pgs<Decoder: PgsDecoder>(
+        &mut self,
+        track: u64,
+    ) -> Result<SupParser<Cursor<Vec<u8>>, Decoder>, MkvError> {
+        Ok(SupParser::new(Cursor::new(self.pgs_stream(track)?)))
+    }
+
+    /// Read the cues of a text track (`UTF-8`, `ASS` or `WebVTT`).
+    ///
+    /// Blocks without duration have an empty time span.
+    ///
+    /// # Errors
+    ///
+    /// Will return `MkvError::CodecMismatch` if the track is not a text track.
+    /// Will return `MkvError::InvalidText` if the text of a block is not valid `UTF-8`.
+    pub fn text_cues(&mut self, track: u64) -> Result<Vec<TextCue>, MkvError> {
+        let codec = self
+            .check_codec(track, SubtitleCodec::is_text, "text")?
+            .codec
+            .clone();
+        self.read_blocks(track)?
+            .into_iter()
+            .map(|block| {
+                let text = String::from_utf8(block.data)
+                    .ok()
+                    .ok_or(MkvError::InvalidText(track))?;
+                let text = if codec == SubtitleCodec::Ass {
+                    text.splitn(ASS_FIELDS_BEFORE_TEXT + 1, ',')
+                        .nth(ASS_FIELDS_BEFORE_TEXT)
+                        .unwrap_or_default()
+                        .to_owned()
+                } else {
+                    text
+                };
+                let end = block.duration.map_or(block.timestamp, |duration| {
+                    TimePoint::from_msecs(block.timestamp.msecs().saturating_add(duration.msecs()))
+                });
+                Ok(TextCue {
+                    time_span: TimeSpan::new(block.timestamp, end),
+                    text,
+                })
+            })
+            .collect()
+    }
+
+    /// Read the cues of a text track in the shared text model.
+    ///
+    /// The styles and alignment of the override tags of `ASS` tracks are kept,
+    /// the other tracks are read as plain text.
+    ///
+    /// # Errors
+    ///
+    /// Will return the errors of [`Matroska::text_cues`].
+    pub fn cues(&mut self, track: u64) -> Result<Vec<text::Cue>, MkvError> {
+        let is_ass = self.track(track)?.codec == SubtitleCodec::Ass;
+        Ok(self
+            .text_cues(track)?
+            .into_iter()
+            .map(|cue| {
+                if is_ass {
+                    let (rows, align) = ass::cue_rows(&cue.text);
+                    text::Cue {
+                        time_span: cue.time_span,
+                        position: CuePosition { line: None, align },
+                        rows,
+                    }
+                } else {
+                    text::Cue::plain(cue.time_span, &cue.text)
+                }
+            })
+            .collect())
+    }
+}
+
+/// Call `f` for each child of the `parent` element.
+/// `f` must read or skip the child.
+fn for_each_child<R, F>(reader: &mut R, parent: &ElementHeader, mut f: F) -> Result<(), MkvError>
+where
+    R: Read + Seek,
+    F: FnMut(&mut R, &ElementHeader) -> Result<(), MkvError>,
+{
+    let end = parent
+        .end()
+        .ok_or(MkvError::UnknownSize { id: parent.id })?;
+    while reader.stream_position().map_err(MkvError::Read)? < end {
+        let child = read_header(reader)?.ok_or(MkvError::InvalidElement { id: parent.id })?;
+        f(reader, &child)?;
+    }
+    Ok(())
+}
+
+/// Read a `TrackEntry`, return the track if it is a subtitle track.
+fn read_track<R: Read + Seek>(
+    reader: &mut R,
+    entry: &ElementHeader,
+) -> Result<Option<SubtitleTrack>, MkvError> {
+    let mut track = SubtitleTrack::default();
+    let mut track_type = 0;
+    let mut bcp47 = None;
+    for_each_child(reader, entry, |reader, child| {
+        match child.id {
+            id::TRACK_NUMBER => track.number = read_uint(reader, child)?,
+            id::TRACK_TYPE => track_type = read_uint(reader, child)?,
+            id::CODEC_ID => track.codec_id = read_string(reader, child)?,
+            id::CODEC_PRIVATE => track.codec_private = read_data(reader, child)?,
+            id::LANGUAGE => track.language = read_string(reader, child)?,
+            id::LANGUAGE_BCP47 => bcp47 = Some(read_string(reader, child)?),
+            id::NAME => track.name = Some(read_string(reader, child)?),
+            id::FLAG_DEFAULT => track.default = read_uint(reader, child)? != 0,
+            id::FLAG_FORCED => track.forced = read_uint(reader, child)? != 0,
+            id::CONTENT_ENCODINGS => track.compression = Some(read_encodings(reader, child)?),
+            _ => skip(reader, child)?,
+        }
+        Ok(())
+    })?;
+    if track_type != TRACK_TYPE_SUBTITLE {
+        return Ok(None);
+    }
+    if let Some(language) = bcp47 {
+        track.language = language;
+    }
+    track.codec = SubtitleCodec::from_codec_id(&track.codec_id);
+    Ok(Some(track))
+}
+
+/// Read the `ContentEncodings` of a track.
+fn read_encodings<R: Read + Seek>(
+    reader: &mut R,
+    encodings: &ElementHeader,
+) -> Result<Compression, MkvError> {
+    let mut compression = None;
+    let mut count = 0;
+    for_each_child(reader, encodings, |reader, encoding| {
+        if encoding.id != id::CONTENT_ENCODING {
+            return skip(reader, encoding);
+        }
+        count += 1;
+        for_each_child(reader, encoding, |reader, child| match child.id {
+            id::CONTENT_COMPRESSION => {
+                let mut algo = 0;
+                let mut settings = Vec::new();
+                for_each_child(reader, child, |reader, child| {
+                    match child.id {
+                        id::CONTENT_COMP_ALGO => algo = read_uint(reader, child)?,
+                        id::CONTENT_COMP_SETTINGS => settings = read_data(reader, child)?,
+                        _ => skip(reader, child)?,
+                    }
+                    Ok(())
+                })?;
+                compression = Some(match algo {
+                    0 => Compression::Zlib,
+                    3 => Compression::HeaderStripping(settings),
+                    _ => Compression::Unsupported,
+                });
+                Ok(())
+            }
+            id::CONTENT_ENCRYPTION => {
+                compression = Some(Compression::Unsupported);
+                skip(reader, child)
+            }
+            _ => skip(reader, child),
+        })
+    })?;
+    // Chained encodings are not supported.
+    if count > 1 {
+        return Ok(Compression::Unsupported);
+    }
+    Ok(compression.unwrap_or(Compression::HeaderStripping(Vec::new())))
+}
+
+/// Convert a block timestamp to a `PGS` timestamp, in 90 kHz units wrapped on 32 bits
+/// as in `.sup` files. Negative timestamps are clamped to 0.
+fn pgs_pts(timestamp: TimePoint) -> u32 {
+    let ticks = timestamp.msecs().max(0).wrapping_mul(90);
+    u32::try_from(ticks & i64::from(u32::MAX)).unwrap_or_default()
+}
+
+/// Decompress the data of a block.
+fn decode_block(
+    mut block: Block,
+    compression: Option<&Compression>,
+    track: u64,
+) -> Result<Block, MkvError> {
+    match compression {
+        None => {}
+        Some(Compression::Zlib) => {
+            block.data = miniz_oxide::inflate::decompress_to_vec_zlib(&block.data)
+                .ok()
+                .ok_or(MkvError::Decompression(track))?;
+        }
+        Some(Compression::HeaderStripping(header)) => {
+            block.data.splice(0..0, header.iter().copied());
+        }
+        Some(Compression::Unsupported) => return Err(MkvError::UnsupportedEncoding(track)),
+    }
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        image::ToImage as _,
+        pgs::{DecodeTimeImage, RleToImage},
+        vobsub::Sub,
+    };
+    use assert_matches2::assert_matches;
+    use image::{Luma, LumaA};
+
+    const FIXTURE: &str = "./fixtures/example.mkv";
+
+    fn time_span(start: i64, end: i64) -> TimeSpan {
+        TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end))
+    }
+
+    #[test]
+    fn list_subtitle_tracks() {
+        let mkv = Matroska::open(FIXTURE).unwrap();
+        let tracks = mkv.tracks();
+        assert_eq!(
+            tracks.iter().map(SubtitleTrack::number).collect::<Vec<_>>(),
+            [2, 3, 4, 5]
+        );
+
+        let pgs = mkv.track(2).unwrap();
+        assert_eq!(pgs.codec(), &SubtitleCodec::Pgs);
+        assert_eq!(pgs.language(), "eng");
+        assert_eq!(pgs.name(), Some("PGS"));
+        assert!(pgs.is_default());
+        assert!(!pgs.is_forced());
+
+        let text = mkv.track(3).unwrap();
+        assert_eq!(text.codec(), &SubtitleCodec::Text);
+        assert_eq!(text.language(), "fre");
+        assert!(!text.is_default());
+        assert!(text.is_forced());
+
+        let ass = mkv.track(4).unwrap();
+        assert_eq!(ass.codec(), &SubtitleCodec::Ass);
+        assert_eq!(ass.language(), "de-DE");
+        assert!(ass.codec_private().starts_with(b"[Script Info]"));
+
+        assert_eq!(mkv.track(5).unwrap().codec(), &SubtitleCodec::VobSub);
+        assert_matches!(mkv.track(1), Err(MkvError::UnknownTrack(1)));
+    }
+
+    #[test]
+    fn decode_text_tracks() {
+        let mut mkv = Matroska::open(FIXTURE).unwrap();
+        assert_eq!(
+            mkv.text_cues(3).unwrap(),
+            [
+                TextCue {
+                    time_span: time_span(100, 1000),
+                    text: "Bonjour\ntout le monde".into(),
+                },
+                TextCue {
+                    time_span: time_span(1400, 1400),
+                    text: "Second".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            mkv.text_cues(4).unwrap(),
+            [TextCue {
+                time_span: time_span(200, 1200),
+                text: "Hello, {\\i1}world{\\i0}".into(),
+            }]
+        );
+        assert_matches!(
+            mkv.text_cues(2),
+            Err(MkvError::CodecMismatch { track: 2, .. })
+        );
+    }
+
+    #[test]
+    fn decode_shared_cues() {
+        let mut mkv = Matroska::open(FIXTURE).unwrap();
+        let cues = mkv.cues(3).unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].rows.len(), 2);
+        assert_eq!(cues[0].plain_text(), "Bonjour\ntout le monde");
+
+        let cues = mkv.cues(4).unwrap();
+        assert_eq!(cues[0].time_span, time_span(200, 1200));
+        let spans = &cues[0].rows[0].spans;
+        assert_eq!(spans[1].text, "world");
+        assert!(!spans[0].style.italic && spans[1].style.italic);
+        assert_matches!(mkv.cues(2), Err(MkvError::CodecMismatch { track: 2, .. }));
+    }
+
+    #[test]
+    fn decode_pgs_track() {
+        let mut mkv = Matroska::open(FIXTURE).unwrap();
+        let subtitles = mkv
+            .pgs::<DecodeTimeImage>(2)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        assert_eq!(subtitles.len(), expected.len());
+        for ((time, image), (expected_time, expected_image)) in subtitles.iter().zip(&expected) {
+            // `Matroska` timestamps are in milliseconds, the `.sup` ones are more precise.
+            assert_eq!(time.start, expected_time.start);
+            assert!((time.end.msecs() - expected_time.end.msecs()).abs() <= 1);
+            let to_luma = |pixel: LumaA<u8>| Luma([pixel.0[0]]);
+            assert_eq!(
+                RleToImage::new(image, to_luma).to_image(),
+                RleToImage::new(expected_image, to_luma).to_image()
+            );
+        }
+    }
+
+    #[test]
+    fn pgs_timestamps_wrap() {
+        assert_eq!(pgs_pts(TimePoint::from_msecs(1000)), 90_000);
+        assert_eq!(pgs_pts(TimePoint::from_msecs(-40)), 0);
+        // 2^32 ticks of 90 kHz are about 13.25 hours.
+        let wrap_msecs = (1i64 << 32) / 90;
+        assert_eq!(pgs_pts(TimePoint::from_msecs(wrap_msecs + 1000)), 89_924);
+        assert_ne!(
+            pgs_pts(TimePoint::from_msecs(wrap_msecs + 1000)),
+            pgs_pts(TimePoint::from_msecs(wrap_msecs + 2000))
+        );
+    }
+
+    #[test]
+    fn decode_vobsub_track() {
+        let mut mkv = Matroska::open(FIXTURE).unwrap();
+        let (index, subtitles) = mkv.vobsub(5).unwrap();
+        assert_eq!(index.size(), Some(crate::content::Size { w: 718, h: 480 }));
+
+        let expected = Sub::open("./fixtures/tiny.sub")
+            .unwrap()
+            .subtitles::<(TimeSpan, VobSubIndexedImage)>()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        let subtitles = subtitles
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(subtitles.len(), expected.len());
+        assert_eq!(subtitles[0].0, time_span(1000, 3000));
+        assert_eq!(subtitles[0].1, expected[0].1);
+    }
+
+    #[test]
+    fn reject_other_files() {
+        assert_matches!(
+            Matroska::open("./fixtures/only_one.sup").err(),
+            Some(MkvError::NotMatroska)
+        );
+    }
+}
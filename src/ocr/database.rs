@@ -0,0 +1,198 @@
+use super::OcrError;
+use crate::image::InkMask;
+use std::io::{BufRead, Write};
+
+/// Header line of the text format of the database.
+const DATABASE_HEADER: &str = "# subtile glyph database v1";
+/// Prefix of the line describing a glyph.
+const GLYPH_PREFIX: &str = "glyph ";
+
+/// Information about a known glyph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphInfo {
+    /// Text represented by the glyph (can be more than one character).
+    pub text: String,
+    /// Is the glyph in italic.
+    pub italic: bool,
+}
+
+/// A glyph stored in a [`GlyphDatabase`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphEntry {
+    /// Ink of the glyph.
+    pub mask: InkMask,
+    /// Vertical offset of the glyph top from the line top.
+    pub line_offset: u32,
+    /// Information of the glyph.
+    pub info: GlyphInfo,
+}
+
+/// A database of known glyphs, used by [`GlyphDatabase::recognize`] to match glyphs.
+///
+/// The database can be saved and loaded in a simple text format with [`GlyphDatabase::write`]
+/// and [`GlyphDatabase::read`].
+///
+/// [`GlyphDatabase::recognize`]: super::GlyphDatabase::recognize
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlyphDatabase {
+    pub(super) entries: Vec<GlyphEntry>,
+}
+
+impl GlyphDatabase {
+    /// Create an empty database.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add a glyph in the database.
+    pub fn add(&mut self, entry: GlyphEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Number of glyphs in the database.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the database is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the glyphs of the database.
+    pub fn iter(&self) -> impl Iterator<Item = &GlyphEntry> {
+        self.entries.iter()
+    }
+
+    /// Write the database in text format.
+    ///
+    /// Backslashes, line feeds and carriage returns of the glyph text are escaped.
+    ///
+    /// # Errors
+    /// Will return [`OcrError::Io`] if writing in `writer` failed.
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), OcrError> {
+        writeln!(writer, "{DATABASE_HEADER}")?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "{GLYPH_PREFIX}{} {} {} {} {}",
+                entry.mask.width(),
+                entry.mask.height(),
+                entry.line_offset,
+                u8::from(entry.info.italic),
+                escape_text(&entry.info.text)
+            )?;
+            for y in 0..entry.mask.height() {
+                let row = (0..entry.mask.width())
+                    .map(|x| if entry.mask.is_ink(x, y) { '#' } else { '.' })
+                    .collect::<String>();
+                writeln!(writer, "{row}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a database written with [`GlyphDatabase::write`].
+    ///
+    /// # Errors
+    /// Will return [`OcrError::Io`] if reading from `reader` failed.
+    /// Will return [`OcrError::InvalidDatabase`] if the content is not a valid database.
+    pub fn read(reader: impl BufRead) -> Result<Self, OcrError> {
+        let mut lines = reader.lines().enumerate();
+        let invalid = |line: usize, reason: &'static str| OcrError::InvalidDatabase {
+            line: line + 1,
+            reason,
+        };
+
+        let header = lines.next().map(|(_, header)| header).transpose()?;
+        if header.as_deref() != Some(DATABASE_HEADER) {
+            return Err(invalid(0, "missing header"));
+        }
+
+        let mut database = Self::new();
+        while let Some((line_num, line)) = lines.next() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let fields = line
+                .strip_prefix(GLYPH_PREFIX)
+                .ok_or_else(|| invalid(line_num, "expected glyph description"))?;
+            let mut fields = fields.splitn(5, ' ');
+            let mut number = || {
+                fields
+                    .next()
+                    .and_then(|field| field.parse::<u32>().ok())
+                    .ok_or_else(|| invalid(line_num, "invalid glyph number field"))
+            };
+            let (width, height, line_offset, italic) = (number()?, number()?, number()?, number()?);
+            let text = fields
+                .next()
+                .ok_or_else(|| invalid(line_num, "missing glyph text"))
+                .and_then(|text| {
+                    unescape_text(text).ok_or_else(|| invalid(line_num, "invalid glyph text"))
+                })?;
+
+            // Sizes come from the file, so the ink grows with the rows actually read.
+            let mut ink = Vec::new();
+            for _ in 0..height {
+                let (row_num, row) = lines
+                    .next()
+                    .ok_or_else(|| invalid(line_num, "missing glyph rows"))?;
+                let row = row?;
+                if row.len() != width as usize {
+                    return Err(invalid(row_num, "invalid glyph row width"));
+                }
+                ink.extend(row.bytes().map(|c| c == b'#'));
+            }
+
+            database.add(GlyphEntry {
+                mask: InkMask::from_raw(width, height, ink),
+                line_offset,
+                info: GlyphInfo {
+                    text,
+                    italic: italic != 0,
+                },
+            });
+        }
+        Ok(database)
+    }
+}
+
+/// Escape the characters of a glyph text which would break the line based format.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverse [`escape_text`], return `None` on an invalid escape sequence.
+fn unescape_text(text: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.push(match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            });
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Some(unescaped)
+}
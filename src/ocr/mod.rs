@@ -0,0 +1,306 @@
+//! Glyph matching `OCR` of subtitle images.
+//!
+//! Glyphs are segmented from the image with [`segment_lines`], and compared pixel by pixel
+//! with the glyphs of a [`GlyphDatabase`]. Unknown glyphs are given to a callback, whose answer
+//! is added in the database, so a database can be trained while recognizing subtitles.
+
+mod database;
+
+pub use database::{GlyphDatabase, GlyphEntry, GlyphInfo};
+
+use crate::image::{segment_lines, Glyph, InkMask, TextLine, ToOcrImage, ToOcrImageOpt};
+use thiserror::Error;
+
+/// Error of the glyph `OCR`.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum OcrError {
+    /// Io error on the glyph database.
+    #[error("io error on glyph database")]
+    Io(#[from] std::io::Error),
+
+    /// The content of the glyph database is invalid.
+    #[error("invalid glyph database at line {line}: {reason}")]
+    InvalidDatabase {
+        /// Line number of the error.
+        line: usize,
+        /// Description of the error.
+        reason: &'static str,
+    },
+}
+
+/// Options of the glyph `OCR`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphOcrOpt {
+    /// Maximum difference of width and height (in pixels) between a glyph and a known glyph.
+    pub size_tolerance: u32,
+    /// Maximum difference of vertical position in the line (in pixels)
+    /// between a glyph and a known glyph.
+    pub offset_tolerance: u32,
+    /// Maximum ratio of different pixels, relative to the ink of the glyphs, to match a known glyph.
+    pub max_diff_ratio: f32,
+    /// Minimum gap between two glyphs, relative to the line height, to insert a space.
+    pub space_ratio: f32,
+    /// Character used for glyphs not recognized.
+    pub unknown_char: char,
+}
+
+impl Default for GlyphOcrOpt {
+    fn default() -> Self {
+        Self {
+            size_tolerance: 1,
+            offset_tolerance: 2,
+            max_diff_ratio: 0.1,
+            space_ratio: 0.25,
+            unknown_char: '\u{FFFD}',
+        }
+    }
+}
+
+/// A glyph not found in the database, given to the training callback.
+#[derive(Debug)]
+pub struct UnknownGlyph<'a> {
+    /// The glyph to recognize.
+    pub glyph: &'a Glyph,
+    /// The line containing the glyph.
+    pub line: &'a TextLine,
+    /// Index of the line in the image.
+    pub line_index: usize,
+}
+
+impl UnknownGlyph<'_> {
+    /// Vertical offset of the glyph top from the line top.
+    #[must_use]
+    pub const fn line_offset(&self) -> u32 {
+        self.glyph.bounds.y - self.line.bounds.y
+    }
+}
+
+impl GlyphDatabase {
+    /// Find the best known glyph matching `mask`, positioned at `line_offset` in its line.
+    #[must_use]
+    pub fn find(&self, mask: &InkMask, line_offset: u32, opt: &GlyphOcrOpt) -> Option<&GlyphInfo> {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry.mask.width().abs_diff(mask.width()) <= opt.size_tolerance
+                    && entry.mask.height().abs_diff(mask.height()) <= opt.size_tolerance
+                    && entry.line_offset.abs_diff(line_offset) <= opt.offset_tolerance
+            })
+            .map(|entry| (entry, diff_ratio(&entry.mask, mask)))
+            .filter(|(_, ratio)| *ratio <= opt.max_diff_ratio)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entry, _)| &entry.info)
+    }
+
+    /// Recognize the text of `mask`.
+    ///
+    /// Glyphs not found in the database are given to `unknown`. If it returns a [`GlyphInfo`],
+    /// the glyph is added in the database, else [`GlyphOcrOpt::unknown_char`] is used.
+    /// Lines are separated by `\n`, and italic glyphs are surrounded by `<i>` and `</i>` tags.
+    #[profiling::function]
+    pub fn recognize<F>(&mut self, mask: &InkMask, opt: &GlyphOcrOpt, mut unknown: F) -> String
+    where
+        F: FnMut(&UnknownGlyph) -> Option<GlyphInfo>,
+    {
+        let lines = segment_lines(mask);
+        let mut text = String::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            if line_index > 0 {
+                text.push('\n');
+            }
+
+            let mut italic = false;
+            let mut previous_right = None;
+            for glyph in &line.glyphs {
+                let line_offset = glyph.bounds.y - line.bounds.y;
+                let info = self
+                    .find(&glyph.mask, line_offset, opt)
+                    .cloned()
+                    .or_else(|| {
+                        let info = unknown(&UnknownGlyph {
+                            glyph,
+                            line,
+                            line_index,
+                        })?;
+                        self.add(GlyphEntry {
+                            mask: glyph.mask.clone(),
+                            line_offset,
+                            info: info.clone(),
+                        });
+                        Some(info)
+                    });
+                let glyph_italic = info.as_ref().is_some_and(|info| info.italic);
+
+                if italic && !glyph_italic {
+                    text.push_str("</i>");
+                }
+                let is_space = previous_right.is_some_and(|right: u32| {
+                    let gap = glyph.bounds.x.saturating_sub(right);
+                    cast::f32(gap) >= opt.space_ratio * cast::f32(line.bounds.height)
+                });
+                if is_space {
+                    text.push(' ');
+                }
+                if !italic && glyph_italic {
+                    text.push_str("<i>");
+                }
+                italic = glyph_italic;
+                previous_right = Some(glyph.bounds.right());
+
+                match info {
+                    Some(info) => text.push_str(&info.text),
+                    None => text.push(opt.unknown_char),
+                }
+            }
+            if italic {
+                text.push_str("</i>");
+            }
+        }
+        text
+    }
+
+    /// Recognize the text of a subtitle image.
+    ///
+    /// The image is generated with [`ToOcrImage`], and pixels of `ocr_opt.text_color` are the ink
+    /// of the glyphs. See [`GlyphDatabase::recognize`].
+    pub fn recognize_image<I, F>(
+        &mut self,
+        image: &I,
+        ocr_opt: &ToOcrImageOpt,
+        opt: &GlyphOcrOpt,
+        unknown: F,
+    ) -> String
+    where
+        I: ToOcrImage,
+        F: FnMut(&UnknownGlyph) -> Option<GlyphInfo>,
+    {
+        let image = image.image(ocr_opt);
+        let mask = InkMask::from_ocr_image(&image, ocr_opt.text_color);
+        self.recognize(&mask, opt, unknown)
+    }
+}
+
+/// Ratio of different pixels between two masks aligned on top-left, relative to their ink.
+fn diff_ratio(a: &InkMask, b: &InkMask) -> f32 {
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+    let diff = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| a.is_ink(x, y) != b.is_ink(x, y))
+        .count();
+    let ink = a.ink_count().max(b.ink_count()).max(1);
+    cast::f32(diff) / cast::f32(ink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a mask from rows of `#` (ink) and `.`.
+    fn mask(rows: &[&str]) -> InkMask {
+        let width = u32::try_from(rows[0].len()).unwrap();
+        let height = u32::try_from(rows.len()).unwrap();
+        let ink = rows.iter().flat_map(|row| row.chars().map(|c| c == '#'));
+        InkMask::from_raw(width, height, ink.collect())
+    }
+
+    const TEXT: [&str; 5] = [
+        "#...#.###........#...#",
+        "#...#..#.........#...#",
+        "#####..#.........#####",
+        "#...#..#.........#...#",
+        "#...#.###........#...#",
+    ];
+
+    #[test]
+    fn train_and_recognize() {
+        let mut database = GlyphDatabase::new();
+        let opt = GlyphOcrOpt::default();
+        let mut asked = Vec::new();
+        let text = database.recognize(&mask(&TEXT), &opt, |unknown| {
+            let text = if unknown.glyph.bounds.width == 5 {
+                "H"
+            } else {
+                "i"
+            };
+            asked.push(text);
+            Some(GlyphInfo {
+                text: text.into(),
+                italic: false,
+            })
+        });
+        assert_eq!(text, "Hi H");
+        assert_eq!(asked, ["H", "i"]);
+        assert_eq!(database.len(), 2);
+
+        let text = database.recognize(&mask(&TEXT), &opt, |_| None);
+        assert_eq!(text, "Hi H");
+    }
+
+    #[test]
+    fn unknown_and_italic_glyphs() {
+        let mut database = GlyphDatabase::new();
+        let opt = GlyphOcrOpt::default();
+        let text = database.recognize(&mask(&TEXT), &opt, |unknown| {
+            (unknown.glyph.bounds.width == 5).then(|| GlyphInfo {
+                text: "H".into(),
+                italic: true,
+            })
+        });
+        assert_eq!(text, "<i>H</i>\u{FFFD} <i>H</i>");
+    }
+
+    #[test]
+    fn database_round_trip() {
+        let mut database = GlyphDatabase::new();
+        database.add(GlyphEntry {
+            mask: mask(&["#.#", ".#.", "#.#"]),
+            line_offset: 2,
+            info: GlyphInfo {
+                text: "x y".into(),
+                italic: true,
+            },
+        });
+
+        let mut saved = Vec::new();
+        database.write(&mut saved).unwrap();
+        let loaded = GlyphDatabase::read(saved.as_slice()).unwrap();
+        assert_eq!(loaded, database);
+
+        let invalid = GlyphDatabase::read(&b"glyph 1 1 0 0 a\n#\n"[..]);
+        assert!(matches!(
+            invalid,
+            Err(OcrError::InvalidDatabase { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn database_escaped_text() {
+        let mut database = GlyphDatabase::new();
+        database.add(GlyphEntry {
+            mask: mask(&["#"]),
+            line_offset: 0,
+            info: GlyphInfo {
+                text: "a\nb\\".into(),
+                italic: false,
+            },
+        });
+
+        let mut saved = Vec::new();
+        database.write(&mut saved).unwrap();
+        let loaded = GlyphDatabase::read(saved.as_slice()).unwrap();
+        assert_eq!(loaded, database);
+    }
+
+    #[test]
+    fn database_huge_size() {
+        let header = "# subtile glyph database v1\n";
+        let content = format!("{header}glyph 4000000000 4000000000 0 0 a\n#\n");
+        let invalid = GlyphDatabase::read(content.as_bytes());
+        assert!(matches!(
+            invalid,
+            Err(OcrError::InvalidDatabase { line: 3, .. })
+        ));
+    }
+}
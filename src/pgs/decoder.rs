@@ -3,7 +3,7 @@ use std::io::{BufRead, Seek};
 
 use super::{
     ods::{self, ObjectDefinitionSegment},
-    pds,
+    pcs, pds,
     pgs_image::RleEncodedImage,
     segment::{read_header, skip_segment, SegmentTypeCode},
     PgsError,
@@ -81,6 +81,7 @@ impl PgsDecoder for DecodeTimeImage {
         let mut palette = None;
         let mut image = None;
         let mut prev_ods = None;
+        let mut composition = None;
 
         while let Some(seg_header) = {
             if subtitle.is_some() {
@@ -90,6 +91,14 @@ impl PgsDecoder for DecodeTimeImage {
             }
         } {
             match seg_header.type_code() {
+                SegmentTypeCode::Pcs => {
+                    let seg_size = seg_header.size() as usize;
+                    let pcs = pcs::read(reader, seg_size)?;
+                    // The composition ending the subtitle display have no object.
+                    if let Some(object) = pcs.objects.first() {
+                        composition = Some((object.x, object.y, pcs.frame_size));
+                    }
+                }
                 SegmentTypeCode::Pds => {
                     let seg_size = seg_header.size() as usize;
                     let pds = pds::read(reader, seg_size)?;
@@ -103,12 +112,12 @@ impl PgsDecoder for DecodeTimeImage {
                     // otherwise, keep read data to complete it with data from following segment.
                     if let ObjectDefinitionSegment::Complete(ods) = ods {
                         let palette = palette.take().ok_or(PgsError::MissingPalette)?;
-                        image = Some(RleEncodedImage::new(
-                            ods.width,
-                            ods.height,
-                            palette,
-                            ods.object_data,
-                        ));
+                        let rle_image =
+                            RleEncodedImage::new(ods.width, ods.height, palette, ods.object_data);
+                        image = Some(match composition {
+                            Some((x, y, frame_size)) => rle_image.with_placement(x, y, frame_size),
+                            None => rle_image,
+                        });
                     } else {
                         prev_ods = Some(ods);
                     }
@@ -125,7 +134,7 @@ impl PgsDecoder for DecodeTimeImage {
                         start_time = Some(time);
                     }
                 }
-                SegmentTypeCode::Pcs | SegmentTypeCode::Wds => {
+                SegmentTypeCode::Wds => {
                     // Segment not taken into account are skipped
                     skip_segment(reader, &seg_header)?;
                 }
@@ -5,15 +5,23 @@
 //!
 mod decoder;
 mod ods;
+mod pcs;
 mod pds;
 mod pgs_image;
+mod pgs_indexed;
 mod segment;
 mod sup;
+mod sup_writer;
 mod u24;
 
 pub use decoder::{DecodeTimeImage, DecodeTimeOnly, PgsDecoder};
-pub use pgs_image::{RleEncodedImage, RleToImage};
+pub use pds::{Palette, PaletteEntry};
+pub use pgs_image::{track_palette_roles, RleEncodedImage, RleToImage};
+#[cfg(feature = "rayon")]
+pub use pgs_indexed::par_decode_indexed;
+pub use pgs_indexed::PgsIndexedImage;
 pub use sup::SupParser;
+pub use sup_writer::SupWriter;
 
 use self::segment::SegmentTypeCode;
 use std::{
@@ -36,10 +44,26 @@ pub enum PgsError {
         path: PathBuf,
     },
 
+    /// Io error while reading a stream.
+    #[error("Io error while reading stream")]
+    Read(#[source] io::Error),
+
+    /// Io error while writing a stream.
+    #[error("Io error while writing stream")]
+    Write(#[source] io::Error),
+
+    /// A segment is incomplete.
+    #[error("segment data is truncated")]
+    TruncatedSegment,
+
     /// Encapsulates errors from `Object Definition Segment` parsing.
     #[error("object Definition Segment parsing")]
     ODSParse(#[from] ods::Error),
 
+    /// Encapsulates errors from `Presentation Composition Segment` parsing.
+    #[error("presentation Composition Segment parsing")]
+    PCSParse(#[from] pcs::Error),
+
     /// Encapsulates errors from `Palette Definition Segment` parsing.
     #[error("palette Definition Segment parsing")]
     PDSParse(#[from] pds::Error),
@@ -0,0 +1,110 @@
+use crate::content::Size;
+use std::io::{self, Read};
+use thiserror::Error;
+
+/// Size of the fixed part of the `PCS` (before composition objects).
+const PCS_HEADER_SIZE: usize = 11;
+/// Size of a composition object without cropping information.
+const OBJECT_SIZE: usize = 8;
+/// Size of the cropping information of a composition object.
+const CROPPING_SIZE: usize = 8;
+/// Flag value to indicate the object is cropped.
+const OBJECT_CROPPED_FLAG: u8 = 0x40;
+
+/// Error `PCS` (Presentation Composition Segment) handling.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Read `PresentationCompositionSegment` in a buffer failed.
+    #[error("failed to read buffer with `PresentationCompositionSegment`")]
+    BufferParse(#[source] io::Error),
+
+    /// The segment is too small for the composition objects it declares.
+    #[error("`PresentationCompositionSegment` of size {size} is too small")]
+    TooSmall {
+        /// Size of the segment.
+        size: usize,
+    },
+}
+
+/// Position of an object on the screen.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CompositionObject {
+    _object_id: u16, // ID of the ODS segment that defines the image to be shown
+    _window_id: u8,  // Id of the WDS segment to which the image is allocated in the PCS
+    pub x: u16,      // Horizontal position of the top left pixel of the image on the screen
+    pub y: u16,      // Vertical position of the top left pixel of the image on the screen
+}
+
+/// This segment is used for composing a sub picture.
+#[derive(Debug)]
+pub(crate) struct PresentationCompositionSegment {
+    pub frame_size: Size, // Video width and height in pixels
+    pub objects: Vec<CompositionObject>,
+}
+
+pub(crate) fn read<R: Read>(
+    reader: &mut R,
+    segments_size: usize,
+) -> Result<PresentationCompositionSegment, Error> {
+    let mut pcs_buf = vec![0; segments_size];
+    reader
+        .read_exact(&mut pcs_buf)
+        .map_err(Error::BufferParse)?;
+
+    let too_small = || Error::TooSmall {
+        size: segments_size,
+    };
+    let read_u16 = |offset: usize| {
+        pcs_buf
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .ok_or_else(too_small)
+    };
+
+    let frame_size = Size {
+        w: usize::from(read_u16(0)?),
+        h: usize::from(read_u16(2)?),
+    };
+    // Skip frame rate, composition number, composition state, palette update flag and palette id.
+    let nb_objects = *pcs_buf.get(PCS_HEADER_SIZE - 1).ok_or_else(too_small)?;
+
+    let mut offset = PCS_HEADER_SIZE;
+    let objects = (0..nb_objects)
+        .map(|_| {
+            let object = CompositionObject {
+                _object_id: read_u16(offset)?,
+                _window_id: *pcs_buf.get(offset + 2).ok_or_else(too_small)?,
+                x: read_u16(offset + 4)?,
+                y: read_u16(offset + 6)?,
+            };
+            let cropped = pcs_buf[offset + 3] & OBJECT_CROPPED_FLAG != 0;
+            offset += OBJECT_SIZE + if cropped { CROPPING_SIZE } else { 0 };
+            Ok(object)
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(PresentationCompositionSegment {
+        frame_size,
+        objects,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_composition() {
+        let data = [
+            0x07, 0x80, 0x04, 0x38, 0x10, 0x00, 0x01, 0x80, 0x00, 0x00, 0x01, // header
+            0x00, 0x00, 0x00, 0x00, 0x02, 0x9a, 0x03, 0xd4, // object
+        ];
+        let pcs = read(&mut data.as_slice(), data.len()).unwrap();
+        assert_eq!(pcs.frame_size, Size { w: 1920, h: 1080 });
+        assert_eq!(pcs.objects.len(), 1);
+        assert_eq!((pcs.objects[0].x, pcs.objects[0].y), (666, 980));
+
+        let truncated = read(&mut &data[..15], 15);
+        assert!(matches!(truncated, Err(Error::TooSmall { size: 15 })));
+    }
+}
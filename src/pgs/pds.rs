@@ -9,18 +9,29 @@ pub enum Error {
     BufferParse(#[source] io::Error),
 }
 
-#[derive(Debug, Clone)]
+/// Palette of a `PGS` subtitle, defined by a `Palette Definition Segment`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Palette {
     entries: Vec<PaletteEntry>,
     offset: i16,
 }
 impl Palette {
-    fn new(entries: Vec<PaletteEntry>) -> Self {
+    /// Create a palette from its entries.
+    #[must_use]
+    pub fn new(entries: Vec<PaletteEntry>) -> Self {
         let offset = compute_offset(&entries);
         Self { entries, offset }
     }
 
+    /// Entries of the palette.
+    #[must_use]
+    pub fn entries(&self) -> &[PaletteEntry] {
+        &self.entries
+    }
+
+    /// Get the entry used by pixels with the index `id`.
     #[expect(clippy::cast_sign_loss)]
+    #[must_use]
     pub fn get(&self, id: u8) -> Option<&PaletteEntry> {
         let idx = i16::from(id) + self.offset;
         self.entries.get(idx as usize)
@@ -36,13 +47,19 @@ fn compute_offset(palette: &[PaletteEntry]) -> i16 {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Color of an entry of a [`Palette`].
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PaletteEntry {
-    entry_id: u8,               // Entry number of the palette
-    pub luminance: u8,          // Luminance (Y value)
-    _color_difference_red: u8,  // Color Difference Red (Cr value)
-    _color_difference_blue: u8, // Color Difference Blue (Cb value)
-    pub transparency: u8,       // Transparency (Alpha value)
+    /// Entry number of the palette
+    pub entry_id: u8,
+    /// Luminance (Y value)
+    pub luminance: u8,
+    /// Color Difference Red (Cr value)
+    pub color_difference_red: u8,
+    /// Color Difference Blue (Cb value)
+    pub color_difference_blue: u8,
+    /// Transparency (Alpha value)
+    pub transparency: u8,
 }
 #[derive(Debug)]
 pub(crate) struct PaletteDefinitionSegment {
@@ -72,8 +89,8 @@ pub(crate) fn read<R: Read>(
             PaletteEntry {
                 entry_id: pds_buf[offset],
                 luminance: pds_buf[offset + 1],
-                _color_difference_red: pds_buf[offset + 2],
-                _color_difference_blue: pds_buf[offset + 3],
+                color_difference_red: pds_buf[offset + 2],
+                color_difference_blue: pds_buf[offset + 3],
                 transparency: pds_buf[offset + 4],
             }
         })
@@ -1,6 +1,16 @@
-use super::pds::{Palette, PaletteEntry};
-use crate::image::{ImageSize, ToImage, ToOcrImage, ToOcrImageOpt};
-use image::{ImageBuffer, Luma, LumaA, Pixel, Primitive};
+use super::{
+    pds::{Palette, PaletteEntry},
+    PgsIndexedImage,
+};
+use crate::{
+    content::Size,
+    image::{
+        content_bounds, crop_indexed, detect_roles, prepare_ocr_image, remove_outline_colors,
+        role_ocr_color, roles_are_conclusive, CompareImage, ImageSize, IndexedImage as _,
+        PaletteRole, PaletteRoleStats, PerceptualHash, ToImage, ToOcrImage, ToOcrImageOpt,
+    },
+};
+use image::{GrayAlphaImage, ImageBuffer, Luma, LumaA, Pixel, Primitive};
 use std::io::{ErrorKind, Read as _};
 
 /// Define a type of `fn` who covert pixel from `PaletteEntry` to a target color type.
@@ -11,6 +21,8 @@ type PixelConversion<TargetColor> = fn(&PaletteEntry) -> TargetColor;
 pub struct RleEncodedImage {
     width: u16,
     height: u16,
+    position: (u16, u16),
+    frame_size: Option<Size>,
     palette: Palette,
     raw: Vec<u8>,
 }
@@ -24,11 +36,140 @@ impl RleEncodedImage {
         Self {
             width,
             height,
+            position: (0, 0),
+            frame_size: None,
             palette,
             raw,
         }
     }
 
+    /// Set the position of the image on the screen, and the size of the video frame,
+    /// as defined by the `Presentation Composition Segment`.
+    #[must_use]
+    pub const fn with_placement(mut self, x: u16, y: u16, frame_size: Size) -> Self {
+        self.position = (x, y);
+        self.frame_size = Some(frame_size);
+        self
+    }
+
+    /// Access to the palette of the image.
+    #[must_use]
+    pub const fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Position (`x`, `y`) of the top left pixel of the image on the screen.
+    #[must_use]
+    pub const fn position(&self) -> (u16, u16) {
+        self.position
+    }
+
+    /// Size of the video frame on which the image is displayed, if known.
+    #[must_use]
+    pub const fn frame_size(&self) -> Option<Size> {
+        self.frame_size
+    }
+
+    /// Decode the palette index of each pixel, in row-major order.
+    ///
+    /// The runs are written directly in the output buffer. If the `RLE` data are truncated,
+    /// missing pixels are filled with the index `0`.
+    #[must_use]
+    #[profiling::function]
+    pub fn decode_indexed(&self) -> Vec<u8> {
+        let nb_pixels = self.width() as usize * self.height() as usize;
+        let mut indexes = Vec::with_capacity(nb_pixels);
+        let mut data = self.raw.as_slice();
+        while let Some((&byte, rest)) = data.split_first() {
+            data = rest;
+            if byte != 0 {
+                indexes.push(byte);
+                continue;
+            }
+            let Some((&flags, rest)) = data.split_first() else {
+                break;
+            };
+            data = rest;
+            if flags == 0 {
+                continue; // End of line
+            }
+
+            let count = match CountMarker::from(flags) {
+                CountMarker::Short => usize::from(flags & 0b0011_1111),
+                CountMarker::Long => {
+                    let Some((&low, rest)) = data.split_first() else {
+                        break;
+                    };
+                    data = rest;
+                    usize::from(u16::from_be_bytes([flags & 0b0011_1111, low]))
+                }
+            };
+            let color = match ColorMarker::from(flags) {
+                ColorMarker::Color0 => 0,
+                ColorMarker::ColorN => {
+                    let Some((&color, rest)) = data.split_first() else {
+                        break;
+                    };
+                    data = rest;
+                    color
+                }
+            };
+            let count = count.min(nb_pixels.saturating_sub(indexes.len()));
+            indexes.resize(indexes.len() + count, color);
+        }
+        indexes.resize(nb_pixels, 0);
+        indexes
+    }
+
+    /// Build a lookup table of the conversion of each palette index to a pixel.
+    ///
+    /// Indexes missing from the palette are converted from a transparent white.
+    pub fn palette_lut<P, C>(&self, convert: C) -> [P; 256]
+    where
+        C: Fn(LumaA<u8>) -> P,
+    {
+        let missing = LumaA([u8::MAX, u8::MIN]);
+        std::array::from_fn(|index| {
+            let entry = u8::try_from(index)
+                .ok()
+                .and_then(|index| self.palette.get(index));
+            convert(entry.map_or(missing, pe_to_luma_a))
+        })
+    }
+
+    /// Alpha of a palette index. Indexes missing from the palette are transparent.
+    fn index_alpha(&self, index: u8) -> u8 {
+        self.palette
+            .get(index)
+            .map_or(0, |entry| entry.transparency)
+    }
+
+    /// Crop the image to the bounding box of its visible pixels (with a non-zero alpha
+    /// in the palette), and adjust the position to match.
+    ///
+    /// The pixels are re-encoded in `RLE`. Return `None` if the image has no visible pixel.
+    ///
+    /// # Panics
+    /// Will panic if the cropped image size doesn't fit in `u16`, which should not happen.
+    #[must_use]
+    #[profiling::function]
+    pub fn crop_to_content(&self) -> Option<Self> {
+        let indexes = self.decode_indexed();
+        let bounds = content_bounds(&indexes, self.width(), |index| self.index_alpha(index))?;
+        let cropped = crop_indexed(&indexes, self.width(), bounds);
+
+        let to_u16 = |value: u32| u16::try_from(value).unwrap();
+        let (x, y) = self.position;
+        Some(Self {
+            width: to_u16(bounds.width),
+            height: to_u16(bounds.height),
+            position: (x + to_u16(bounds.x), y + to_u16(bounds.y)),
+            frame_size: self.frame_size,
+            palette: self.palette.clone(),
+            raw: encode_rle(&cropped, bounds.width),
+        })
+    }
+
     /// Iterate on image pixels converted with a specified function.
     pub fn pixels<D: Primitive>(
         &self,
@@ -85,8 +226,104 @@ impl<'a> IntoIterator for &'a RleEncodedImage {
     }
 }
 
+/// Compare the decoded pixels of the images.
+/// The perceptual hash use the luminance weighted by the alpha of pixels as intensity.
+impl CompareImage for RleEncodedImage {
+    fn same_pixels(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.iter().eq(other.iter())
+    }
+
+    fn perceptual_hash(&self) -> PerceptualHash {
+        let pixels = self.iter().map(|LumaA([luma, alpha])| {
+            u8::try_from(u16::from(luma) * u16::from(alpha) / 255).unwrap()
+        });
+        PerceptualHash::new(self.width(), self.height(), pixels)
+    }
+}
+
+/// Encode the pixels of a [`PgsIndexedImage`] in `RLE`, for example to write
+/// a transformed image with a [`SupWriter`](super::SupWriter).
+impl From<&PgsIndexedImage> for RleEncodedImage {
+    fn from(image: &PgsIndexedImage) -> Self {
+        Self {
+            width: u16::try_from(image.width()).unwrap(),
+            height: u16::try_from(image.height()).unwrap(),
+            position: image.position(),
+            frame_size: image.frame_size(),
+            palette: image.palette().clone(),
+            raw: encode_rle(image.indexes(), image.width()),
+        }
+    }
+}
+
+/// Detect the role of the palette indexes used by the subtitles of a `PGS` track.
+///
+/// The pixels of all subtitles are analysed together, the alpha of the indexes come from
+/// the palette of each subtitle. See [`PaletteRoleStats::roles`].
+#[profiling::function]
+pub fn track_palette_roles<'a, I>(images: I) -> Vec<PaletteRole>
+where
+    I: IntoIterator<Item = &'a RleEncodedImage>,
+{
+    let mut stats = PaletteRoleStats::new(usize::from(u8::MAX) + 1);
+    for image in images {
+        stats.add_image(&image.decode_indexed(), image.width(), |index| {
+            image.index_alpha(index)
+        });
+    }
+    stats.roles()
+}
+
+/// Convert palette indexes to the raw channels of pixels with a lookup table.
+fn apply_lut<P>(indexes: &[u8], lut: &[P; 256]) -> Vec<u8>
+where
+    P: Pixel<Subpixel = u8>,
+{
+    let mut buf = Vec::with_capacity(indexes.len() * usize::from(P::CHANNEL_COUNT));
+    for &index in indexes {
+        buf.extend_from_slice(lut[usize::from(index)].channels());
+    }
+    buf
+}
+
+/// Encode palette indexes of an image of width `width` in the `PGS` `RLE` format.
+fn encode_rle(indexes: &[u8], width: u32) -> Vec<u8> {
+    const MAX_RUN: usize = 0x3FFF;
+    let mut raw = Vec::with_capacity(indexes.len() / 2);
+    for line in indexes.chunks_exact(width as usize) {
+        let mut pixels = line;
+        while let Some(&color) = pixels.first() {
+            let run = pixels
+                .iter()
+                .take(MAX_RUN)
+                .take_while(|&&index| index == color)
+                .count();
+            pixels = &pixels[run..];
+
+            if color != 0 && run < 3 {
+                raw.resize(raw.len() + run, color);
+                continue;
+            }
+            let [high, low] = u16::try_from(run).unwrap().to_be_bytes();
+            let color_flag = if color == 0 { 0 } else { 0b1000_0000 };
+            raw.push(0);
+            if run < 64 {
+                raw.push(color_flag | low);
+            } else {
+                raw.extend([color_flag | 0b0100_0000 | high, low]);
+            }
+            if color != 0 {
+                raw.push(color);
+            }
+        }
+        // End of line
+        raw.extend([0, 0]);
+    }
+    raw
+}
+
 /// Convert a [`PaletteEntry`] to a `LumaA`<P>
-fn pe_to_luma_a<P: Primitive>(input: &PaletteEntry) -> LumaA<P> {
+pub(super) fn pe_to_luma_a<P: Primitive>(input: &PaletteEntry) -> LumaA<P> {
     let luminance = P::from(input.luminance).unwrap();
     let alpha = P::from(input.transparency).unwrap();
     LumaA([luminance, alpha])
@@ -101,6 +338,7 @@ where
 {
     rle_image: &'a RleEncodedImage,
     conv_fn: C,
+    roles: Option<&'a [PaletteRole]>,
 }
 
 impl<'a, P, C> RleToImage<'a, P, C>
@@ -110,7 +348,22 @@ where
 {
     /// Create a struct to generate an image from [`RleEncodedImage`]
     pub const fn new(rle_image: &'a RleEncodedImage, conv_fn: C) -> Self {
-        Self { rle_image, conv_fn }
+        Self {
+            rle_image,
+            conv_fn,
+            roles: None,
+        }
+    }
+
+    /// Use the given role of the palette indexes to separate the text from the background
+    /// for `OCR`: only the pixels of the [`PaletteRole::Fill`] indexes are text, with the
+    /// outline if it's not removed.
+    ///
+    /// The roles can be detected on the whole track with [`track_palette_roles`].
+    #[must_use]
+    pub const fn with_roles(mut self, roles: &'a [PaletteRole]) -> Self {
+        self.roles = Some(roles);
+        self
     }
 }
 
@@ -128,20 +381,19 @@ where
     {
         let width = self.rle_image.width();
         let height = self.rle_image.height();
-        let pixel_iter = self.rle_image.into_iter();
-
-        let buf_size = (width * height) as usize * P::CHANNEL_COUNT as usize;
-        let mut buf = Vec::with_capacity(buf_size);
-        pixel_iter
-            .map(|p| (self.conv_fn)(p))
-            .for_each(|p| buf.extend_from_slice(p.channels()));
+        let lut = self.rle_image.palette_lut(&self.conv_fn);
+        let buf = apply_lut(&self.rle_image.decode_indexed(), &lut);
 
         ImageBuffer::<P, Vec<u8>>::from_vec(width, height, buf)
             .expect("Failed to create image buffer")
     }
 }
 
-/// Implement [`ToOcrImage`] from [`RleEncodedImage`]
+/// Implement [`ToOcrImage`] from [`RleEncodedImage`].
+///
+/// The conversion function separates the text from the background, unless roles are given
+/// with [`RleToImage::with_roles`], or detected with [`ToOcrImageOpt::palette_roles`] and
+/// conclusive (see [`roles_are_conclusive`]). Then the [`PaletteRole`] of the indexes are used.
 impl<C> ToOcrImage for RleToImage<'_, Luma<u8>, C>
 where
     C: Fn(LumaA<u8>) -> Luma<u8>,
@@ -150,19 +402,45 @@ where
     fn image(&self, opt: &ToOcrImageOpt) -> image::GrayImage {
         let width = self.rle_image.width();
         let height = self.rle_image.height();
-        let border = opt.border;
 
-        let raw_pixels = self.rle_image.into_iter().collect::<Vec<_>>();
+        let indexes = self.rle_image.decode_indexed();
 
-        ImageBuffer::from_fn(width + border * 2, height + border * 2, |x, y| {
-            if x < border || x >= width + border || y < border || y >= height + border {
-                opt.background_color
-            } else {
-                let offset = (y - border) * width + (x - border);
-                let pixel = raw_pixels[offset as usize];
-                (self.conv_fn)(pixel)
+        // The roles of the palette indexes separate the text from the background if they
+        // are given, or detected when asked and conclusive.
+        let detected;
+        let roles = match self.roles {
+            Some(roles) => roles,
+            None if opt.palette_roles || opt.remove_outline => {
+                detected = detect_roles(&indexes, width, 256, |index| {
+                    self.rle_image.index_alpha(index)
+                });
+                detected.as_slice()
             }
-        })
+            None => &[],
+        };
+        if self.roles.is_some() || (opt.palette_roles && roles_are_conclusive(roles)) {
+            let lut =
+                std::array::from_fn(|index| role_ocr_color(roles.get(index), opt.remove_outline));
+            let source = GrayAlphaImage::from_vec(width, height, apply_lut(&indexes, &lut))
+                .expect("Failed to create image buffer");
+            let convert = |LumaA([_, alpha]): LumaA<u8>| {
+                if alpha > 0 {
+                    opt.text_color
+                } else {
+                    opt.background_color
+                }
+            };
+            return prepare_ocr_image(source, convert, opt);
+        }
+
+        let mut lut = self.rle_image.palette_lut(|pixel| pixel);
+        if opt.remove_outline {
+            remove_outline_colors(&mut lut, roles);
+        }
+        let source = GrayAlphaImage::from_vec(width, height, apply_lut(&indexes, &lut))
+            .expect("Failed to create image buffer");
+
+        prepare_ocr_image(source, &self.conv_fn, opt)
     }
 }
 
@@ -305,3 +583,133 @@ impl From<u8> for CountMarker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgs::{DecodeTimeImage, SupParser};
+    use std::{fs::File, io::BufReader};
+
+    fn load_image() -> RleEncodedImage {
+        let mut parser =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap();
+        parser.next().unwrap().unwrap().1
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let image = load_image();
+        let indexes = image.decode_indexed();
+        let encoded = RleEncodedImage {
+            raw: encode_rle(&indexes, image.width()),
+            ..image.clone()
+        };
+        assert_eq!(encoded.decode_indexed(), indexes);
+    }
+
+    #[test]
+    fn fast_decode_match_iterator() {
+        let image = load_image();
+        let lut = image.palette_lut(|pixel| pixel);
+        let pixels = image
+            .decode_indexed()
+            .into_iter()
+            .map(|index| lut[usize::from(index)]);
+        assert!(pixels.eq(image.iter()));
+    }
+
+    fn entry(entry_id: u8, luminance: u8, transparency: u8) -> PaletteEntry {
+        PaletteEntry {
+            entry_id,
+            luminance,
+            color_difference_red: 128,
+            color_difference_blue: 128,
+            transparency,
+        }
+    }
+
+    // Encode the indexes in an image, and check the fast decoding match them and the iterator.
+    fn check_fast_decode(width: u16, palette: Palette, indexes: &[u8]) -> RleEncodedImage {
+        let height = u16::try_from(indexes.len() / usize::from(width)).unwrap();
+        let raw = encode_rle(indexes, u32::from(width));
+        let image = RleEncodedImage::new(width, height, palette, raw);
+        assert_eq!(image.decode_indexed(), indexes);
+
+        let lut = image.palette_lut(|pixel| pixel);
+        let pixels = image
+            .decode_indexed()
+            .into_iter()
+            .map(|index| lut[usize::from(index)]);
+        assert!(pixels.eq(image.iter()));
+        image
+    }
+
+    #[test]
+    fn fast_decode_long_runs() {
+        let palette = Palette::new((0..8).map(|id| entry(id, id * 30, 255)).collect());
+        // Runs longer than the `RLE` maximum of 16383 pixels, and short runs between.
+        let width = 20_000;
+        let mut indexes = vec![7; usize::from(width)];
+        indexes.extend((0..width).map(|x| [0, 3, 3, 5][usize::from(x / 5000)]));
+        indexes.extend((0..width).map(|x| u8::try_from(x % 3).unwrap()));
+        check_fast_decode(width, palette, &indexes);
+    }
+
+    #[test]
+    fn fast_decode_transparent_palette() {
+        let palette = Palette::new((0..4).map(|id| entry(id, 200, 0)).collect());
+        let indexes = [0, 1, 1, 2, 3, 3, 3, 0].repeat(8);
+        let image = check_fast_decode(8, palette, &indexes);
+
+        let lut = image.palette_lut(|pixel| pixel);
+        assert!(lut.iter().all(|LumaA([_, alpha])| *alpha == 0));
+        assert_eq!(image.crop_to_content().map(|image| image.raw), None);
+    }
+
+    #[test]
+    fn fast_decode_missing_palette_entries() {
+        // Only the entries 1 and 2 are defined, other indexes are transparent white.
+        let palette = Palette::new(vec![entry(1, 50, 255), entry(2, 100, 255)]);
+        let indexes = [1, 2, 3, 3, 200, 0, 2, 1].repeat(4);
+        let image = check_fast_decode(8, palette, &indexes);
+
+        let lut = image.palette_lut(|pixel| pixel);
+        assert_eq!(lut[1], LumaA([50, 255]));
+        assert_eq!(lut[3], LumaA([u8::MAX, 0]));
+        assert_eq!(lut[200], LumaA([u8::MAX, 0]));
+    }
+
+    #[test]
+    fn crop_transparent_margins() {
+        let image = load_image();
+        let cropped = image.crop_to_content().unwrap();
+        let (x, y) = (
+            cropped.position().0 - image.position().0,
+            cropped.position().1 - image.position().1,
+        );
+
+        let source = image.iter().collect::<Vec<_>>();
+        let expected = (u32::from(y)..u32::from(y) + cropped.height()).flat_map(|row| {
+            let start = (row * image.width() + u32::from(x)) as usize;
+            source[start..start + cropped.width() as usize]
+                .iter()
+                .copied()
+        });
+        assert!(cropped.iter().eq(expected));
+        assert!(cropped.crop_to_content().unwrap().raw == cropped.raw);
+    }
+
+    #[test]
+    fn ocr_with_track_roles() {
+        let image = load_image();
+        let roles = track_palette_roles([&image]);
+        assert!(roles.contains(&PaletteRole::Fill));
+
+        let opt = ToOcrImageOpt::default();
+        let ocr_image = RleToImage::new(&image, |_| opt.background_color)
+            .with_roles(&roles)
+            .image(&opt);
+        assert!(ocr_image.pixels().any(|pixel| *pixel == opt.text_color));
+    }
+}
@@ -0,0 +1,278 @@
+use super::{pds::Palette, pgs_image::pe_to_luma_a, RleEncodedImage};
+use crate::{
+    content::{Area, Size},
+    image::{ycrcb_to_rgb, ImageSize, IndexedImage, IndexedTransform, PalettedImage},
+};
+use image::{LumaA, Rgba};
+
+/// Decoded image of a `PGS` subtitle: the palette index of each pixel and the full palette.
+///
+/// Unlike [`RleEncodedImage`], the pixels are decoded only once, which allows
+/// cheap pixel access, comparisons and palette manipulation. It can be scaled or moved
+/// with [`IndexedTransform`], then encoded back in a [`RleEncodedImage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgsIndexedImage {
+    width: u16,
+    height: u16,
+    position: (u16, u16),
+    frame_size: Option<Size>,
+    palette: Palette,
+    indexes: Vec<u8>,
+}
+
+impl PgsIndexedImage {
+    /// Create an image from the palette index of each pixel in row-major order.
+    ///
+    /// # Panics
+    /// Will panic if the number of indexes doesn't match `width` x `height`.
+    #[must_use]
+    pub fn new(width: u16, height: u16, palette: Palette, indexes: Vec<u8>) -> Self {
+        assert_eq!(indexes.len(), usize::from(width) * usize::from(height));
+        Self {
+            width,
+            height,
+            position: (0, 0),
+            frame_size: None,
+            palette,
+            indexes,
+        }
+    }
+
+    /// Position (`x`, `y`) of the top left pixel of the image on the screen.
+    #[must_use]
+    pub const fn position(&self) -> (u16, u16) {
+        self.position
+    }
+
+    /// Size of the video frame on which the image is displayed, if known.
+    #[must_use]
+    pub const fn frame_size(&self) -> Option<Size> {
+        self.frame_size
+    }
+
+    /// Access to the palette of the image.
+    #[must_use]
+    pub const fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Replace the palette of the image.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Luminance and alpha of a palette index.
+    /// Indexes missing from the palette are a transparent white.
+    #[must_use]
+    pub fn luma_a(&self, index: u8) -> LumaA<u8> {
+        self.palette
+            .get(index)
+            .map_or(LumaA([u8::MAX, u8::MIN]), pe_to_luma_a)
+    }
+}
+
+/// Decode all the `RLE` images in parallel.
+///
+/// The decoded images are returned in the same order as `images`.
+#[cfg(feature = "rayon")]
+#[must_use]
+pub fn par_decode_indexed(images: &[RleEncodedImage]) -> Vec<PgsIndexedImage> {
+    use rayon::prelude::*;
+
+    images.par_iter().map(PgsIndexedImage::from).collect()
+}
+
+impl From<&RleEncodedImage> for PgsIndexedImage {
+    fn from(rle_image: &RleEncodedImage) -> Self {
+        Self {
+            width: u16::try_from(rle_image.width()).unwrap(),
+            height: u16::try_from(rle_image.height()).unwrap(),
+            position: rle_image.position(),
+            frame_size: rle_image.frame_size(),
+            palette: rle_image.palette().clone(),
+            indexes: rle_image.decode_indexed(),
+        }
+    }
+}
+
+impl ImageSize for PgsIndexedImage {
+    fn width(&self) -> u32 {
+        u32::from(self.width)
+    }
+    fn height(&self) -> u32 {
+        u32::from(self.height)
+    }
+}
+
+impl IndexedImage for PgsIndexedImage {
+    fn indexes(&self) -> &[u8] {
+        &self.indexes
+    }
+
+    fn index_alpha(&self, index: u8) -> u8 {
+        self.luma_a(index)[1]
+    }
+}
+
+impl PalettedImage for PgsIndexedImage {
+    fn palette_rgba(&self, index: u8) -> Rgba<u8> {
+        self.palette
+            .get(index)
+            .map_or(Rgba([u8::MAX, u8::MAX, u8::MAX, 0]), |entry| {
+                let [red, green, blue] = ycrcb_to_rgb(
+                    entry.luminance,
+                    entry.color_difference_red,
+                    entry.color_difference_blue,
+                )
+                .0;
+                Rgba([red, green, blue, entry.transparency])
+            })
+    }
+}
+
+/// An image thinner than 2 pixels is considered 2 pixels wide or high,
+/// see [`Area::at_position`].
+impl IndexedTransform for PgsIndexedImage {
+    fn screen_area(&self) -> Area {
+        Area::at_position(self.position, self.width, self.height)
+    }
+
+    fn with_screen_area(self, area: Area) -> Self {
+        Self {
+            position: (area.left(), area.top()),
+            ..self
+        }
+    }
+
+    fn with_pixels(&self, area: Area, indexes: Vec<u8>) -> Self {
+        Self {
+            width: area.width(),
+            height: area.height(),
+            position: (area.left(), area.top()),
+            frame_size: self.frame_size,
+            palette: self.palette.clone(),
+            indexes,
+        }
+    }
+
+    fn with_frame_size(self, frame: Size) -> Self {
+        Self {
+            frame_size: Some(frame),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        image::{IndexedScaleFilter, IndexedToImage, ToImage as _},
+        pgs::{DecodeTimeImage, PaletteEntry, RleToImage, SupParser},
+    };
+    use std::{fs::File, io::BufReader};
+
+    #[test]
+    fn indexed_from_rle() {
+        let mut parser =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap();
+        let (_, rle_image) = parser.next().unwrap().unwrap();
+
+        let image = PgsIndexedImage::from(&rle_image);
+        assert_eq!(image.position(), rle_image.position());
+        let pixels = IndexedToImage::new(&image, |index| image.luma_a(index)).to_image();
+        assert!(pixels.pixels().copied().eq(rle_image.iter()));
+
+        let bounds = image.content_bounds().unwrap();
+        let cropped = rle_image.crop_to_content().unwrap();
+        assert_eq!(
+            (bounds.width, bounds.height),
+            (cropped.width(), cropped.height())
+        );
+    }
+
+    #[test]
+    fn indexed_to_image_match_rle_to_image() {
+        let mut parser =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap();
+        let (_, rle_image) = parser.next().unwrap().unwrap();
+        let image = PgsIndexedImage::from(&rle_image);
+
+        let indexed = IndexedToImage::new(&image, |index| image.luma_a(index)).to_image();
+        let rle = RleToImage::new(&rle_image, |pixel| pixel).to_image();
+        assert_eq!(indexed, rle);
+
+        let rgba = IndexedToImage::rgba(&image).to_image();
+        assert!(rgba
+            .pixels()
+            .zip(rle.pixels())
+            .all(|(Rgba([.., alpha]), LumaA([_, expected]))| alpha == expected));
+    }
+
+    #[test]
+    fn palette_access() {
+        let mut parser =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap();
+        let (_, rle_image) = parser.next().unwrap().unwrap();
+        let image = PgsIndexedImage::from(&rle_image);
+        assert!(image.same_used_colors(&image));
+
+        let mut recolored = image.clone();
+        let entries = image
+            .palette()
+            .entries()
+            .iter()
+            .map(|entry| PaletteEntry {
+                luminance: entry.luminance.wrapping_add(64),
+                ..entry.clone()
+            })
+            .collect();
+        recolored.set_palette(Palette::new(entries));
+        assert!(!image.same_used_colors(&recolored));
+        assert_eq!(image.palette_rgba(255), Rgba([255, 255, 255, 0]));
+    }
+
+    #[test]
+    fn scale_1080p_to_576p() {
+        let mut parser =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap();
+        let (_, rle_image) = parser.next().unwrap().unwrap();
+        let image = PgsIndexedImage::from(&rle_image);
+
+        let (from, to) = (Size { w: 1920, h: 1080 }, Size { w: 720, h: 576 });
+        let scaled = image
+            .scale_to_frame(from, to, IndexedScaleFilter::Majority)
+            .unwrap()
+            .move_into_safe_area(to, 0.1);
+        assert_eq!(scaled.frame_size(), Some(to));
+        assert_eq!(
+            scaled.indexes().len(),
+            scaled.width() as usize * scaled.height() as usize
+        );
+        assert!(scaled.width() < image.width() && scaled.height() < image.height());
+        let area = scaled.screen_area();
+        assert!(usize::from(area.left()) >= 72 && usize::from(area.top()) >= 58);
+        assert!(usize::from(area.left() + area.width()) <= 720 - 72);
+
+        // The scaled image can be encoded back to `RLE`.
+        let encoded = RleEncodedImage::from(&scaled);
+        assert_eq!(encoded.position(), scaled.position());
+        assert_eq!(encoded.decode_indexed(), scaled.indexes());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn decode_indexed_in_parallel() {
+        let images =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap()
+                .map(|sub| sub.unwrap().1)
+                .collect::<Vec<_>>();
+        let expected = images.iter().map(PgsIndexedImage::from).collect::<Vec<_>>();
+        assert_eq!(par_decode_indexed(&images), expected);
+    }
+}
@@ -5,7 +5,7 @@ use std::{
 };
 
 // Segment start Magic Number
-const MAGIC_NUMBER: [u8; 2] = [0x50, 0x47];
+pub const MAGIC_NUMBER: [u8; 2] = [0x50, 0x47];
 
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq, Eq)]
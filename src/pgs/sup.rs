@@ -1,7 +1,7 @@
 use super::{PgsDecoder, PgsError};
 use std::{
     fs::{self, File},
-    io::{BufRead, BufReader, Seek},
+    io::{BufRead, BufReader, Cursor, Read, Seek},
     iter::FusedIterator,
     marker::PhantomData,
     path::Path,
@@ -47,6 +47,49 @@ where
     }
 }
 
+impl<R, Decoder> SupParser<BufReader<R>, Decoder>
+where
+    R: Read + Seek,
+    Decoder: PgsDecoder,
+{
+    /// Create a parser for a `*.sup` content from a reader (impl [`std::io::Read`] and [`Seek`]).
+    pub fn from_reader(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl<B, Decoder> SupParser<Cursor<B>, Decoder>
+where
+    B: AsRef<[u8]>,
+    Decoder: PgsDecoder,
+{
+    /// Create a parser for a `*.sup` content from an in-memory buffer.
+    pub const fn from_bytes(data: B) -> Self {
+        Self::new(Cursor::new(data))
+    }
+}
+
+impl<Decoder> SupParser<Cursor<Vec<u8>>, Decoder>
+where
+    Decoder: PgsDecoder,
+{
+    /// Create a parser for a `*.sup` content by reading all the content of an async `reader`.
+    ///
+    /// The content is buffered in memory before parsing, see [`SupParser::from_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `PgsError::Read` if failed to read from `reader`.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R>(reader: R) -> Result<Self, PgsError>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let data = crate::util::read_all_async(reader, PgsError::Read).await?;
+        Ok(Self::from_bytes(data))
+    }
+}
+
 impl<Reader, Decoder> Iterator for SupParser<Reader, Decoder>
 where
     Reader: BufRead + Seek,
@@ -77,6 +120,7 @@ mod tests {
 
     use super::SupParser;
     use crate::{
+        content::Size,
         pgs::{DecodeTimeImage, DecodeTimeOnly, PgsError},
         time::{TimePoint, TimeSpan},
     };
@@ -98,6 +142,45 @@ mod tests {
         assert!(file_subtitles.len() == 1);
     }
 
+    #[test]
+    fn parse_from_bytes() {
+        let data = std::fs::read("./fixtures/only_one.sup").unwrap();
+        let from_bytes = SupParser::<_, DecodeTimeOnly>::from_bytes(data.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let from_reader = SupParser::<_, DecodeTimeOnly>::from_reader(std::io::Cursor::new(data))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let from_file =
+            SupParser::<BufReader<File>, DecodeTimeOnly>::from_file("./fixtures/only_one.sup")
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        assert_eq!(from_bytes, from_file);
+        assert_eq!(from_reader, from_file);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn parse_from_async_reader() {
+        let data = std::fs::read("./fixtures/only_one.sup").unwrap();
+        let parser = SupParser::<_, DecodeTimeOnly>::from_async_reader(data.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(parser.map(Result::unwrap).count(), 1);
+    }
+
+    #[test]
+    fn parse_image_placement() {
+        let parser =
+            SupParser::<BufReader<File>, DecodeTimeImage>::from_file("./fixtures/only_one.sup")
+                .unwrap();
+
+        let (_, image) = parser.map(|sub| sub.unwrap()).next().unwrap();
+        assert_eq!(image.frame_size(), Some(Size { w: 2048, h: 858 }));
+        assert_eq!(image.position(), (985, 779));
+    }
+
     #[test]
     fn parse_sequence_without_ods() {
         let controls = &[
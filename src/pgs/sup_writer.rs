@@ -0,0 +1,121 @@
+use super::{segment::MAGIC_NUMBER, PgsError};
+use std::io::Write;
+
+/// Size of the type code and size of a segment, without the `.sup` header.
+const RAW_HEADER_LEN: usize = 1 + 2;
+
+/// Write `Presentation Graphic Stream` segments in the `.sup` file format.
+///
+/// Containers like `Matroska` or `MPEG-TS` store the segments without the `.sup` header
+/// (magic number and timestamps). This writer adds it, to obtain a stream readable by [`SupParser`].
+///
+/// [`SupParser`]: super::SupParser
+pub struct SupWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> SupWriter<W> {
+    /// Create a writer of `.sup` content in `writer`.
+    pub const fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write the `raw` segments (type code, size and content) with the presentation timestamp `pts`
+    /// in 90 kHz units. The decoding timestamp is set to zero, as it's not used by decoders.
+    ///
+    /// # Errors
+    ///
+    /// Will return `PgsError::TruncatedSegment` if the last segment of `raw` is incomplete.
+    /// Will return `PgsError::Write` if writing failed.
+    pub fn write_segments(&mut self, pts: u32, raw: &[u8]) -> Result<(), PgsError> {
+        self.write_segments_with_dts(pts, 0, raw)
+    }
+
+    /// Write the `raw` segments with the presentation timestamp `pts` and the decoding
+    /// timestamp `dts` in 90 kHz units, when the source container provides it.
+    ///
+    /// # Errors
+    ///
+    /// Will return the errors of [`SupWriter::write_segments`].
+    pub fn write_segments_with_dts(
+        &mut self,
+        pts: u32,
+        dts: u32,
+        mut raw: &[u8],
+    ) -> Result<(), PgsError> {
+        while !raw.is_empty() {
+            if raw.len() < RAW_HEADER_LEN {
+                return Err(PgsError::TruncatedSegment);
+            }
+            let size = usize::from(u16::from_be_bytes([raw[1], raw[2]]));
+            let segment_len = RAW_HEADER_LEN + size;
+            if raw.len() < segment_len {
+                return Err(PgsError::TruncatedSegment);
+            }
+            let (segment, remaining) = raw.split_at(segment_len);
+
+            self.writer
+                .write_all(&MAGIC_NUMBER)
+                .and_then(|()| self.writer.write_all(&pts.to_be_bytes()))
+                .and_then(|()| self.writer.write_all(&dts.to_be_bytes()))
+                .and_then(|()| self.writer.write_all(segment))
+                .map_err(PgsError::Write)?;
+            raw = remaining;
+        }
+        Ok(())
+    }
+
+    /// Get back the inner writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgs::{DecodeTimeOnly, SupParser};
+    use assert_matches2::assert_matches;
+    use std::{
+        fs::{self, File},
+        io::BufReader,
+    };
+
+    #[test]
+    fn rewrite_sup() {
+        // Strip the `.sup` headers of the fixture, then write them back.
+        let data = fs::read("./fixtures/only_one.sup").unwrap();
+        let mut writer = SupWriter::new(Vec::new());
+        let mut remaining = data.as_slice();
+        while !remaining.is_empty() {
+            let pts = u32::from_be_bytes(remaining[2..6].try_into().unwrap());
+            let dts = u32::from_be_bytes(remaining[6..10].try_into().unwrap());
+            let size = usize::from(u16::from_be_bytes([remaining[11], remaining[12]]));
+            writer
+                .write_segments_with_dts(pts, dts, &remaining[10..13 + size])
+                .unwrap();
+            remaining = &remaining[13 + size..];
+        }
+        let rewritten = writer.into_inner();
+        assert_eq!(rewritten, data);
+
+        let times = SupParser::<_, DecodeTimeOnly>::from_bytes(rewritten.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected =
+            SupParser::<BufReader<File>, DecodeTimeOnly>::from_file("./fixtures/only_one.sup")
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        assert_eq!(times, expected);
+    }
+
+    #[test]
+    fn truncated_segment() {
+        let mut writer = SupWriter::new(Vec::new());
+        assert_matches!(
+            writer.write_segments(0, &[0x80, 0x00, 0x02, 0x00]),
+            Err(PgsError::TruncatedSegment)
+        );
+    }
+}
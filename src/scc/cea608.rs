@@ -0,0 +1,821 @@
+//! Decoding and encoding of `CEA-608` closed captions (line 21 data).
+//!
+//! Captions are sent as byte pairs, one pair per video frame. Each byte has an odd parity
+//! bit, and the pairs are characters or control codes (sent twice for reliability).
+
+use crate::{
+    text::{self, CuePosition, CueStyle},
+    time::TimeSpan,
+};
+use image::Rgb;
+
+/// Number of rows of the caption grid.
+pub const ROWS: usize = 15;
+/// Number of columns of the caption grid.
+pub const COLUMNS: usize = 32;
+
+/// Colors of the `PAC` and mid-row codes, in code order.
+const COLORS: [CaptionColor; 7] = [
+    CaptionColor::White,
+    CaptionColor::Green,
+    CaptionColor::Blue,
+    CaptionColor::Cyan,
+    CaptionColor::Red,
+    CaptionColor::Yellow,
+    CaptionColor::Magenta,
+];
+
+/// Special characters, for codes `0x11 0x30` to `0x11 0x3f`.
+const SPECIAL_CHARS: [char; 16] = [
+    '®', '°', '½', '¿', '™', '¢', '£', '♪', 'à', ' ', 'è', 'â', 'ê', 'î', 'ô', 'û',
+];
+
+/// Extended characters (Spanish, French and miscellaneous), for codes `0x12 0x20` to `0x12 0x3f`.
+const EXTENDED_CHARS_1: [char; 32] = [
+    'Á', 'É', 'Ó', 'Ú', 'Ü', 'ü', '‘', '¡', '*', '\'', '—', '©', '℠', '•', '“', '”', 'À', 'Â', 'Ç',
+    'È', 'Ê', 'Ë', 'ë', 'Î', 'Ï', 'ï', 'Ô', 'Ù', 'ù', 'Û', '«', '»',
+];
+
+/// Extended characters (Portuguese, German and Danish), for codes `0x13 0x20` to `0x13 0x3f`.
+const EXTENDED_CHARS_2: [char; 32] = [
+    'Ã', 'ã', 'Í', 'Ì', 'ì', 'Ò', 'ò', 'Õ', 'õ', '{', '}', '\\', '^', '_', '|', '~', 'Ä', 'ä', 'Ö',
+    'ö', 'ß', '¥', '¤', '¦', 'Å', 'å', 'Ø', 'ø', '┌', '┐', '└', '┘',
+];
+
+/// Standard characters sent before the extended characters of [`EXTENDED_CHARS_1`],
+/// displayed by decoders not supporting them.
+const EXTENDED_FALLBACKS_1: &[u8; 32] = b"AEOUUu'!  - . \"\"AACEEEeIIiOUuU\"\"";
+
+/// Standard characters sent before the extended characters of [`EXTENDED_CHARS_2`].
+const EXTENDED_FALLBACKS_2: &[u8; 32] = b"AaIIiOoOo  /    AaOos   AaOo++++";
+
+/// Row of each `PAC` first byte (without channel bit), for the two ranges of second byte.
+const PAC_ROWS: [(u8, [Option<u8>; 2]); 8] = [
+    (0x10, [Some(11), None]),
+    (0x11, [Some(1), Some(2)]),
+    (0x12, [Some(3), Some(4)]),
+    (0x13, [Some(12), Some(13)]),
+    (0x14, [Some(14), Some(15)]),
+    (0x15, [Some(5), Some(6)]),
+    (0x16, [Some(7), Some(8)]),
+    (0x17, [Some(9), Some(10)]),
+];
+
+/// Miscellaneous control codes, second byte after `0x14`.
+mod code {
+    pub const RESUME_CAPTION_LOADING: u8 = 0x20;
+    pub const BACKSPACE: u8 = 0x21;
+    pub const DELETE_TO_END_OF_ROW: u8 = 0x24;
+    pub const ROLL_UP_2: u8 = 0x25;
+    pub const ROLL_UP_4: u8 = 0x27;
+    pub const RESUME_DIRECT_CAPTIONING: u8 = 0x29;
+    pub const TEXT_RESTART: u8 = 0x2a;
+    pub const RESUME_TEXT_DISPLAY: u8 = 0x2b;
+    pub const ERASE_DISPLAYED_MEMORY: u8 = 0x2c;
+    pub const CARRIAGE_RETURN: u8 = 0x2d;
+    pub const ERASE_NON_DISPLAYED_MEMORY: u8 = 0x2e;
+    pub const END_OF_CAPTION: u8 = 0x2f;
+}
+
+/// Color of the caption text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaptionColor {
+    /// White, the default color.
+    #[default]
+    White,
+    /// Green.
+    Green,
+    /// Blue.
+    Blue,
+    /// Cyan.
+    Cyan,
+    /// Red.
+    Red,
+    /// Yellow.
+    Yellow,
+    /// Magenta.
+    Magenta,
+}
+
+impl CaptionColor {
+    /// Get the `RGB` value of the color.
+    #[must_use]
+    pub const fn rgb(self) -> Rgb<u8> {
+        match self {
+            Self::White => Rgb([255, 255, 255]),
+            Self::Green => Rgb([0, 255, 0]),
+            Self::Blue => Rgb([0, 0, 255]),
+            Self::Cyan => Rgb([0, 255, 255]),
+            Self::Red => Rgb([255, 0, 0]),
+            Self::Yellow => Rgb([255, 255, 0]),
+            Self::Magenta => Rgb([255, 0, 255]),
+        }
+    }
+}
+
+/// Style of the caption text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptionStyle {
+    /// Color of the text.
+    pub color: CaptionColor,
+    /// If the text is italic.
+    pub italic: bool,
+    /// If the text is underlined.
+    pub underline: bool,
+}
+
+/// Data channel of the captions. `CC1` and `CC2` are carried in the first field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Channel {
+    /// First channel, usually the main language.
+    #[default]
+    Cc1,
+    /// Second channel.
+    Cc2,
+}
+
+/// A text with its style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptionSpan {
+    /// Text of the span.
+    pub text: String,
+    /// Style of the text.
+    pub style: CaptionStyle,
+}
+
+/// A row of displayed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptionRow {
+    /// Row on the screen, from `1` (top) to `15` (bottom).
+    pub row: u8,
+    /// Column of the first character, from `0` to `31`.
+    pub column: u8,
+    /// Styled texts of the row.
+    pub spans: Vec<CaptionSpan>,
+}
+
+impl CaptionRow {
+    /// Get the text of the row, without style.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+/// A caption displayed during a time span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caption {
+    /// Time span of the caption.
+    pub time_span: TimeSpan,
+    /// Rows of the caption, from top to bottom.
+    pub rows: Vec<CaptionRow>,
+}
+
+impl Caption {
+    /// Get the text of the caption, with the rows separated by `\n`.
+    #[must_use]
+    pub fn plain_text(&self) -> String {
+        self.rows
+            .iter()
+            .map(CaptionRow::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Convert to the shared text model. The default white color is not kept, and the line is
+/// the position of the first row on the rows of the caption grid.
+impl From<&Caption> for text::Cue {
+    fn from(caption: &Caption) -> Self {
+        let line = caption.rows.first().map(|row| {
+            let line = usize::from(row.row.saturating_sub(1)) * 100 / ROWS;
+            u8::try_from(line).unwrap_or(100)
+        });
+        Self {
+            time_span: caption.time_span,
+            position: CuePosition { line, align: None },
+            rows: caption
+                .rows
+                .iter()
+                .map(|row| text::CueRow {
+                    double_height: false,
+                    spans: row
+                        .spans
+                        .iter()
+                        .map(|span| text::CueSpan {
+                            text: span.text.clone(),
+                            style: CueStyle {
+                                italic: span.style.italic,
+                                underline: span.style.underline,
+                                color: (span.style.color != CaptionColor::White)
+                                    .then(|| span.style.color.rgb()),
+                                ..CueStyle::default()
+                            },
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A character of the caption grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    character: char,
+    style: CaptionStyle,
+}
+
+/// Content of a caption memory.
+type Memory = [[Option<Cell>; COLUMNS]; ROWS];
+
+/// Caption mode, selected by the control codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Captions are loaded off screen, then displayed at once.
+    PopOn,
+    /// Captions scroll up in a window with this number of rows.
+    RollUp(usize),
+    /// Captions are directly written on screen.
+    PaintOn,
+}
+
+/// `CEA-608` decoder state machine, for one data channel.
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    channel: Channel,
+    displayed: Memory,
+    non_displayed: Memory,
+    mode: Mode,
+    row: usize,
+    column: usize,
+    style: CaptionStyle,
+    active_channel: Option<Channel>,
+    text_mode: bool,
+    last_control: Option<[u8; 2]>,
+}
+
+impl Decoder {
+    /// Create a decoder for a data `channel`.
+    #[must_use]
+    pub const fn new(channel: Channel) -> Self {
+        Self {
+            channel,
+            displayed: [[None; COLUMNS]; ROWS],
+            non_displayed: [[None; COLUMNS]; ROWS],
+            mode: Mode::PopOn,
+            row: ROWS - 1,
+            column: 0,
+            style: CaptionStyle {
+                color: CaptionColor::White,
+                italic: false,
+                underline: false,
+            },
+            active_channel: None,
+            text_mode: false,
+            last_control: None,
+        }
+    }
+
+    /// Process a byte pair. Return `true` if the displayed memory may have changed.
+    pub fn decode(&mut self, pair: [u8; 2]) -> bool {
+        let [b1, b2] = pair.map(|byte| byte & 0x7f);
+        if (0x10..=0x1f).contains(&b1) {
+            // Control codes are sent twice, the repetition is ignored.
+            if self.last_control == Some([b1, b2]) {
+                self.last_control = None;
+                return false;
+            }
+            self.last_control = Some([b1, b2]);
+            let channel = if b1 & 0x08 == 0 {
+                Channel::Cc1
+            } else {
+                Channel::Cc2
+            };
+            self.active_channel = Some(channel);
+            return channel == self.channel && self.control(b1 & !0x08, b2);
+        }
+        self.last_control = None;
+        if b1 < 0x10 {
+            // Padding, or start of extended data services packets.
+            if b1 != 0 {
+                self.active_channel = None;
+            }
+            return false;
+        }
+        if self.active_channel != Some(self.channel) || self.text_mode {
+            return false;
+        }
+        let mut changed = self.write_char(standard_char(b1));
+        if b2 >= 0x20 {
+            changed |= self.write_char(standard_char(b2));
+        }
+        changed
+    }
+
+    /// Get the rows of the displayed memory which contain text.
+    #[must_use]
+    pub fn displayed(&self) -> Vec<CaptionRow> {
+        self.displayed
+            .iter()
+            .zip(1..)
+            .filter_map(|(cells, row)| memory_row(cells, row))
+            .collect()
+    }
+
+    /// Process a control code, with the channel bit removed from `b1`.
+    fn control(&mut self, b1: u8, b2: u8) -> bool {
+        match (b1, b2) {
+            (0x14 | 0x15, 0x20..=0x2f) => self.misc_control(b2),
+            (0x17, 0x21..=0x23) => {
+                self.column = (self.column + usize::from(b2 - 0x20)).min(COLUMNS - 1);
+                false
+            }
+            (0x11, 0x20..=0x2f) => {
+                let code = usize::from((b2 - 0x20) >> 1);
+                if let Some(color) = COLORS.get(code) {
+                    self.style.color = *color;
+                    self.style.italic = false;
+                } else {
+                    self.style.italic = true;
+                }
+                self.style.underline = b2 & 1 != 0;
+                // A mid-row code is displayed as a space.
+                self.write_char(' ')
+            }
+            (0x11, 0x30..=0x3f) => self.write_char(SPECIAL_CHARS[usize::from(b2 - 0x30)]),
+            (0x12 | 0x13, 0x20..=0x3f) => {
+                let chars = if b1 == 0x12 {
+                    &EXTENDED_CHARS_1
+                } else {
+                    &EXTENDED_CHARS_2
+                };
+                // The extended character replaces the standard character sent before.
+                self.column = self.column.saturating_sub(1);
+                self.write_char(chars[usize::from(b2 - 0x20)])
+            }
+            (_, 0x40..=0x7f) => self.preamble_address(b1, b2),
+            _ => false,
+        }
+    }
+
+    /// Process a miscellaneous control code.
+    fn misc_control(&mut self, code: u8) -> bool {
+        match code {
+            code::RESUME_CAPTION_LOADING => {
+                self.mode = Mode::PopOn;
+                self.text_mode = false;
+                false
+            }
+            code::BACKSPACE => {
+                self.column = self.column.saturating_sub(1);
+                let (row, column) = (self.row, self.column);
+                self.target()[row][column] = None;
+                self.writes_displayed()
+            }
+            code::DELETE_TO_END_OF_ROW => {
+                let (row, column) = (self.row, self.column);
+                self.target()[row][column..].fill(None);
+                self.writes_displayed()
+            }
+            code::ROLL_UP_2..=code::ROLL_UP_4 => {
+                let rows = usize::from(code - code::ROLL_UP_2 + 2);
+                let was_roll_up = matches!(self.mode, Mode::RollUp(_));
+                self.mode = Mode::RollUp(rows);
+                self.text_mode = false;
+                if was_roll_up {
+                    self.clear_outside_window();
+                    return true;
+                }
+                // Entering roll-up mode erases the memories.
+                self.displayed = [[None; COLUMNS]; ROWS];
+                self.non_displayed = [[None; COLUMNS]; ROWS];
+                self.row = ROWS - 1;
+                self.column = 0;
+                true
+            }
+            code::RESUME_DIRECT_CAPTIONING => {
+                self.mode = Mode::PaintOn;
+                self.text_mode = false;
+                false
+            }
+            code::TEXT_RESTART | code::RESUME_TEXT_DISPLAY => {
+                self.text_mode = true;
+                false
+            }
+            code::ERASE_DISPLAYED_MEMORY => {
+                self.displayed = [[None; COLUMNS]; ROWS];
+                true
+            }
+            code::CARRIAGE_RETURN => {
+                let Mode::RollUp(rows) = self.mode else {
+                    return false;
+                };
+                let top = (self.row + 1).saturating_sub(rows);
+                self.displayed.copy_within(top + 1..=self.row, top);
+                self.displayed[self.row] = [None; COLUMNS];
+                self.clear_outside_window();
+                self.column = 0;
+                true
+            }
+            code::ERASE_NON_DISPLAYED_MEMORY => {
+                self.non_displayed = [[None; COLUMNS]; ROWS];
+                false
+            }
+            code::END_OF_CAPTION => {
+                std::mem::swap(&mut self.displayed, &mut self.non_displayed);
+                self.mode = Mode::PopOn;
+                true
+            }
+            // Alarm and flash codes are ignored.
+            _ => false,
+        }
+    }
+
+    /// Process a Preamble Address Code, which sets the row, the column and the style.
+    fn preamble_address(&mut self, b1: u8, b2: u8) -> bool {
+        let Some(row) = PAC_ROWS
+            .iter()
+            .find(|(code, _)| *code == b1)
+            .and_then(|(_, rows)| rows[usize::from(b2 & 0x20 != 0)])
+        else {
+            return false;
+        };
+        let row = usize::from(row - 1);
+
+        let attribute = b2 & 0x1f;
+        let code = usize::from(attribute >> 1);
+        self.style = CaptionStyle {
+            color: COLORS.get(code).copied().unwrap_or_default(),
+            italic: code == 7,
+            underline: attribute & 1 != 0,
+        };
+        self.column = code.checked_sub(8).map_or(0, |indent| indent * 4);
+
+        let mut changed = false;
+        if let Mode::RollUp(rows) = self.mode {
+            if row != self.row {
+                // The roll-up window moves with its content to the new base row.
+                let rows = rows.min(row + 1).min(self.row + 1);
+                let mut window = [[None; COLUMNS]; ROWS];
+                window[row + 1 - rows..=row]
+                    .copy_from_slice(&self.displayed[self.row + 1 - rows..=self.row]);
+                self.displayed = window;
+                changed = true;
+            }
+        }
+        self.row = row;
+        changed
+    }
+
+    /// Memory where the characters are written in the current mode.
+    fn target(&mut self) -> &mut Memory {
+        if self.writes_displayed() {
+            &mut self.displayed
+        } else {
+            &mut self.non_displayed
+        }
+    }
+
+    /// If the characters are written in the displayed memory in the current mode.
+    const fn writes_displayed(&self) -> bool {
+        !matches!(self.mode, Mode::PopOn)
+    }
+
+    /// Write a character at the cursor position, and move the cursor.
+    fn write_char(&mut self, character: char) -> bool {
+        let (row, column, style) = (self.row, self.column, self.style);
+        self.target()[row][column] = Some(Cell { character, style });
+        self.column = (column + 1).min(COLUMNS - 1);
+        self.writes_displayed()
+    }
+
+    /// Erase the displayed rows outside the roll-up window.
+    fn clear_outside_window(&mut self) {
+        if let Mode::RollUp(rows) = self.mode {
+            let top = (self.row + 1).saturating_sub(rows);
+            self.displayed[..top].fill([None; COLUMNS]);
+            self.displayed[self.row + 1..].fill([None; COLUMNS]);
+        }
+    }
+}
+
+/// Get the text of a memory row, without leading and trailing spaces.
+fn memory_row(cells: &[Option<Cell>; COLUMNS], row: u8) -> Option<CaptionRow> {
+    let is_text = |cell: &Option<Cell>| cell.is_some_and(|cell| cell.character != ' ');
+    let first = cells.iter().position(is_text)?;
+    let last = cells.iter().rposition(is_text)?;
+
+    let mut spans: Vec<CaptionSpan> = Vec::new();
+    for cell in &cells[first..=last] {
+        let (character, style) = cell.map_or_else(
+            || (' ', spans.last().map(|span| span.style).unwrap_or_default()),
+            |cell| (cell.character, cell.style),
+        );
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.text.push(character),
+            _ => spans.push(CaptionSpan {
+                text: character.into(),
+                style,
+            }),
+        }
+    }
+    Some(CaptionRow {
+        row,
+        column: u8::try_from(first).ok()?,
+        spans,
+    })
+}
+
+/// Get the character of a standard character code.
+const fn standard_char(code: u8) -> char {
+    match code {
+        0x2a => 'á',
+        0x5c => 'é',
+        0x5e => 'í',
+        0x5f => 'ó',
+        0x60 => 'ú',
+        0x7b => 'ç',
+        0x7c => '÷',
+        0x7d => 'Ñ',
+        0x7e => 'ñ',
+        0x7f => '█',
+        _ => code as char,
+    }
+}
+
+/// Encoder of pop-on captions, producing byte pairs without parity.
+#[derive(Debug, Default)]
+pub(super) struct Encoder {
+    pairs: Vec<[u8; 2]>,
+    pending: Option<u8>,
+}
+
+impl Encoder {
+    /// Encode a pop-on caption, with the `lines` centered at the bottom of the screen.
+    ///
+    /// Lines are truncated to the grid width, and only the last four lines are kept.
+    pub(super) fn pop_on(lines: &[&str]) -> Vec<[u8; 2]> {
+        let mut encoder = Self::default();
+        encoder.misc_control(code::RESUME_CAPTION_LOADING);
+        encoder.misc_control(code::ERASE_NON_DISPLAYED_MEMORY);
+        let lines = &lines[lines.len().saturating_sub(4)..];
+        for (line, row) in lines.iter().zip(ROWS + 1 - lines.len()..) {
+            let chars: Vec<_> = line
+                .chars()
+                .filter(|c| encode_char(*c).is_some())
+                .take(COLUMNS)
+                .collect();
+            let column = (COLUMNS - chars.len()) / 2;
+            encoder.preamble_address(row, column - column % 4);
+            if column % 4 != 0 {
+                encoder.control([0x17, 0x20 + u8::try_from(column % 4).unwrap_or_default()]);
+            }
+            for c in chars {
+                encoder.char(c);
+            }
+        }
+        encoder.misc_control(code::END_OF_CAPTION);
+        encoder.pairs
+    }
+
+    /// Encode the erasing of the displayed caption.
+    pub(super) fn erase() -> Vec<[u8; 2]> {
+        let mut encoder = Self::default();
+        encoder.misc_control(code::ERASE_DISPLAYED_MEMORY);
+        encoder.pairs
+    }
+
+    /// Add a control code, sent twice.
+    fn control(&mut self, pair: [u8; 2]) {
+        self.flush();
+        self.pairs.extend([pair, pair]);
+    }
+
+    /// Add a miscellaneous control code.
+    fn misc_control(&mut self, code: u8) {
+        self.control([0x14, code]);
+    }
+
+    /// Add a Preamble Address Code for a `row` (`1` to `15`) and an indent `column`.
+    fn preamble_address(&mut self, row: usize, column: usize) {
+        let (b1, high) = PAC_ROWS
+            .iter()
+            .find_map(|(b1, rows)| {
+                rows.iter()
+                    .position(|pac_row| pac_row.map(usize::from) == Some(row))
+                    .map(|high| (*b1, high))
+            })
+            .unwrap_or((0x14, 1));
+        let indent = u8::try_from(column / 4).unwrap_or_default();
+        let attribute = if indent == 0 { 0 } else { (8 + indent) << 1 };
+        self.control([b1, 0x40 | if high == 1 { 0x20 } else { 0 } | attribute]);
+    }
+
+    /// Add a character. Characters which can't be encoded are ignored.
+    fn char(&mut self, c: char) {
+        match encode_char(c) {
+            Some(EncodedChar::Standard(code)) => {
+                if let Some(pending) = self.pending.take() {
+                    self.pairs.push([pending, code]);
+                } else {
+                    self.pending = Some(code);
+                }
+            }
+            Some(EncodedChar::Special(code)) => self.control([0x11, code]),
+            Some(EncodedChar::Extended(b1, b2, fallback)) => {
+                self.char(char::from(fallback));
+                self.control([b1, b2]);
+            }
+            None => {}
+        }
+    }
+
+    /// Send the pending standard character.
+    fn flush(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            self.pairs.push([pending, 0]);
+        }
+    }
+}
+
+/// Encoding of a character.
+enum EncodedChar {
+    /// A standard character code.
+    Standard(u8),
+    /// A special character, second byte after `0x11`.
+    Special(u8),
+    /// An extended character code, with the standard fallback character to send before.
+    Extended(u8, u8, u8),
+}
+
+/// Find the encoding of a character.
+fn encode_char(c: char) -> Option<EncodedChar> {
+    if let Some(code) = (0x20..=0x7f).find(|code| standard_char(*code) == c) {
+        return Some(EncodedChar::Standard(code));
+    }
+    if let Some(idx) = SPECIAL_CHARS.iter().position(|special| *special == c) {
+        return Some(EncodedChar::Special(0x30 + u8::try_from(idx).ok()?));
+    }
+    [
+        (0x12, &EXTENDED_CHARS_1, EXTENDED_FALLBACKS_1),
+        (0x13, &EXTENDED_CHARS_2, EXTENDED_FALLBACKS_2),
+    ]
+    .into_iter()
+    .find_map(|(b1, chars, fallbacks)| {
+        let idx = chars.iter().position(|extended| *extended == c)?;
+        Some(EncodedChar::Extended(
+            b1,
+            0x20 + u8::try_from(idx).ok()?,
+            fallbacks[idx],
+        ))
+    })
+}
+
+/// Set the odd parity bit of a byte.
+pub(super) const fn with_parity(byte: u8) -> u8 {
+    if byte.count_ones() % 2 == 0 {
+        byte | 0x80
+    } else {
+        byte
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode the pairs, return the displayed text after each pair changing it.
+    fn decode(decoder: &mut Decoder, pairs: &[[u8; 2]]) -> Vec<String> {
+        let mut texts = Vec::new();
+        for pair in pairs {
+            if decoder.decode(*pair) {
+                let rows = decoder.displayed();
+                texts.push(
+                    rows.iter()
+                        .map(CaptionRow::text)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+            }
+        }
+        texts
+    }
+
+    #[test]
+    fn decode_pop_on() {
+        let mut decoder = Decoder::new(Channel::Cc1);
+        let pairs = [
+            [0x94, 0x20],
+            [0x94, 0x20],
+            [0x94, 0xae],
+            [0x94, 0xae],
+            // Row 14, indent 4.
+            [0x94, 0x52],
+            [0x94, 0x52],
+            [0xc8, 0xe9],
+            // Mid-row italics.
+            [0x91, 0xae],
+            [0x91, 0xae],
+            [0xf9, 0x6f],
+            [0x75, 0x80],
+            // Row 15, yellow.
+            [0x94, 0xea],
+            [0x94, 0xea],
+            [0xc5, 0xf4],
+            [0xe5, 0x80],
+            [0x13, 0x34],
+            [0x13, 0x34],
+            [0x94, 0x2f],
+            [0x94, 0x2f],
+        ];
+        assert_eq!(decode(&mut decoder, &pairs), ["Hi you\nEtß"]);
+
+        let rows = decoder.displayed();
+        assert_eq!(rows[0].row, 14);
+        assert_eq!(rows[0].column, 4);
+        assert_eq!(rows[0].spans.len(), 2);
+        assert!(rows[0].spans[1].style.italic);
+        assert_eq!(rows[1].row, 15);
+        assert_eq!(rows[1].column, 0);
+        assert_eq!(rows[1].spans[0].style.color, CaptionColor::Yellow);
+
+        let cue = text::Cue::from(&Caption {
+            time_span: TimeSpan::default(),
+            rows,
+        });
+        assert_eq!(cue.position.line, Some(86));
+        assert_eq!(cue.plain_text(), "Hi you\nEtß");
+        assert!(cue.rows[0].spans[1].style.italic);
+        assert_eq!(cue.rows[0].spans[0].style.color, None);
+        assert_eq!(cue.rows[1].spans[0].style.color, Some(Rgb([255, 255, 0])));
+
+        assert_eq!(decode(&mut decoder, &[[0x94, 0x2c]]), [""]);
+    }
+
+    #[test]
+    fn decode_roll_up() {
+        let mut decoder = Decoder::new(Channel::Cc1);
+        let pairs = [
+            // RU2, CR, then PAC row 15.
+            [0x94, 0x25],
+            [0x94, 0x25],
+            [0x94, 0xad],
+            [0x94, 0xad],
+            [0x94, 0x70],
+            [0x94, 0x70],
+            [0xc1, 0x80],
+            [0x94, 0xad],
+            [0x94, 0xad],
+            [0xc2, 0x80],
+            [0x94, 0xad],
+            [0x94, 0xad],
+            [0x43, 0x80],
+        ];
+        assert_eq!(
+            decode(&mut decoder, &pairs),
+            ["", "", "A", "A", "A\nB", "B", "B\nC"]
+        );
+        assert_eq!(decoder.displayed()[1].row, 15);
+    }
+
+    #[test]
+    fn decode_paint_on_other_channel() {
+        let mut decoder = Decoder::new(Channel::Cc2);
+        let pairs = [
+            // CC1 paint-on is ignored.
+            [0x94, 0x29],
+            [0xc1, 0x80],
+            // CC2 paint-on, with a backspace.
+            [0x1c, 0x29],
+            [0x1c, 0x29],
+            [0x1c, 0x70],
+            [0xc1, 0xc2],
+            [0x1c, 0x21],
+            [0x43, 0x80],
+        ];
+        assert_eq!(decode(&mut decoder, &pairs), ["AB", "A", "AC"]);
+    }
+
+    #[test]
+    fn encode_pop_on() {
+        let pairs = Encoder::pop_on(&["Hé", "Ab*"]);
+        let mut decoder = Decoder::new(Channel::Cc1);
+        let with_parity = pairs.iter().map(|pair| pair.map(with_parity));
+        assert_eq!(with_parity.filter(|pair| decoder.decode(*pair)).count(), 1);
+        let rows = decoder.displayed();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].row, rows[0].column), (14, 15));
+        assert_eq!(rows[0].text(), "Hé");
+        assert_eq!((rows[1].row, rows[1].column), (15, 14));
+        assert_eq!(rows[1].text(), "Ab*");
+    }
+
+    #[test]
+    fn parity() {
+        assert_eq!(with_parity(0x14), 0x94);
+        assert_eq!(with_parity(0x20), 0x20);
+        assert_eq!(with_parity(0x2f), 0x2f);
+        assert_eq!(with_parity(0x00), 0x80);
+    }
+}
@@ -0,0 +1,355 @@
+//! Scenarist Closed Captions (`SCC`) functionality, with a `CEA-608` decoder.
+//!
+//! `SCC` files contain the `CEA-608` byte pairs of the first field, as hexadecimal words,
+//! on lines starting with the `SMPTE` timecode of their first frame.
+//!
+//! The captions can be decoded as positioned text cues, and subtitles can be written
+//! as pop-on captions.
+mod cea608;
+mod timecode;
+
+pub use cea608::{
+    Caption, CaptionColor, CaptionRow, CaptionSpan, CaptionStyle, Channel, Decoder, COLUMNS, ROWS,
+};
+pub use timecode::Timecode;
+
+use crate::time::{TimePoint, TimeSpan};
+use cea608::{with_parity, Encoder};
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Header of `SCC` files.
+const HEADER: &str = "Scenarist_SCC V1.0";
+
+/// Error for `SCC` handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SccError {
+    /// If an error happen during the file opening.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("failed to read the SCC content")]
+    Read(#[source] io::Error),
+
+    /// The content doesn't start with the `Scenarist_SCC V1.0` header.
+    #[error("missing 'Scenarist_SCC V1.0' header")]
+    MissingHeader,
+
+    /// A line doesn't start with a valid timecode.
+    #[error("line {line}: invalid timecode '{value}'")]
+    InvalidTimecode {
+        /// Line number (starting at 1).
+        line: usize,
+        /// Invalid timecode.
+        value: String,
+    },
+
+    /// A line contains a word which is not four hexadecimal digits.
+    #[error("line {line}: invalid word '{value}'")]
+    InvalidWord {
+        /// Line number (starting at 1).
+        line: usize,
+        /// Invalid word.
+        value: String,
+    },
+}
+
+/// A line of a `SCC` file: byte pairs sent from a timecode, one pair per frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SccLine {
+    /// Timecode of the first byte pair.
+    pub timecode: Timecode,
+    /// Byte pairs, with their parity bit.
+    pub data: Vec<[u8; 2]>,
+}
+
+/// Content of a `SCC` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scc {
+    /// Lines of the file, in time order.
+    pub lines: Vec<SccLine>,
+}
+
+impl Scc {
+    /// Create a `SCC` content from subtitles, as pop-on captions centered at the bottom of
+    /// the screen.
+    ///
+    /// Each caption is loaded before its start time when possible, so it is displayed on time.
+    /// Characters which can't be encoded in `CEA-608` are dropped.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)]) -> Self {
+        let frame = |time| Timecode::from_time(time, true).frame_count();
+        let mut lines = Vec::new();
+        let mut next_free = 0;
+        let mut pending_erase: Option<u32> = None;
+        for (time_span, text) in subtitles {
+            let data = Encoder::pop_on(&text.lines().collect::<Vec<_>>());
+            let length = u32::try_from(data.len()).unwrap_or(u32::MAX);
+            let start = frame(time_span.start);
+            let load = start.saturating_sub(length - 1);
+
+            // The erase is not needed if the next caption replaces the displayed one.
+            if let Some(erase) = pending_erase.take() {
+                if erase < start {
+                    let erase = erase.max(next_free);
+                    lines.push(SccLine::new(erase, Encoder::erase()));
+                    next_free = erase + 2;
+                }
+            }
+            let load = load.max(next_free);
+            lines.push(SccLine::new(load, data));
+            next_free = load + length;
+            pending_erase = Some(frame(time_span.end));
+        }
+        if let Some(erase) = pending_erase {
+            lines.push(SccLine::new(erase.max(next_free), Encoder::erase()));
+        }
+        Self { lines }
+    }
+
+    /// Read a `SCC` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `SccError::Io` if not able to read the file.
+    /// Will return an other `SccError` if the content is not valid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SccError> {
+        let path = path.as_ref();
+        let content = fs::read(path).map_err(|source| SccError::Io {
+            source,
+            path: path.into(),
+        })?;
+        Self::parse(&String::from_utf8_lossy(&content))
+    }
+
+    /// Read a `SCC` content from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `SccError::Read` if not able to read from the `reader`.
+    /// Will return an other `SccError` if the content is not valid.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, SccError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(SccError::Read)?;
+        Self::parse(&String::from_utf8_lossy(&content))
+    }
+
+    /// Parse a `SCC` content.
+    ///
+    /// # Errors
+    ///
+    /// Will return `SccError::MissingHeader` if the content doesn't start with the header.
+    /// Will return `SccError::InvalidTimecode` or `SccError::InvalidWord` for an invalid line.
+    pub fn parse(content: &str) -> Result<Self, SccError> {
+        let mut lines = content
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        if !lines
+            .next()
+            .is_some_and(|(_, line)| line.trim_end() == HEADER)
+        {
+            return Err(SccError::MissingHeader);
+        }
+
+        let lines = lines
+            .map(|(idx, line)| {
+                let line_num = idx + 1;
+                let mut words = line.split_whitespace();
+                let value = words.next().unwrap_or_default();
+                let timecode = Timecode::parse(value).ok_or_else(|| SccError::InvalidTimecode {
+                    line: line_num,
+                    value: value.into(),
+                })?;
+                let data = words
+                    .map(|word| {
+                        parse_word(word).ok_or_else(|| SccError::InvalidWord {
+                            line: line_num,
+                            value: word.into(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(SccLine { timecode, data })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { lines })
+    }
+
+    /// Decode the captions of a data `channel`.
+    ///
+    /// A new caption starts each time the displayed text changes. A caption still displayed
+    /// at the end of the file ends after the last byte pair.
+    #[must_use]
+    pub fn captions(&self, channel: Channel) -> Vec<Caption> {
+        let mut decoder = Decoder::new(channel);
+        let mut captions = Vec::new();
+        let mut current: Option<(TimePoint, Vec<CaptionRow>)> = None;
+        let mut end = TimePoint::default();
+        for line in &self.lines {
+            let mut change = None;
+            for (offset, pair) in (0..).zip(&line.data) {
+                if decoder.decode(*pair) {
+                    change = Some(line.timecode.time(offset));
+                }
+            }
+            let length = u32::try_from(line.data.len()).unwrap_or(u32::MAX);
+            end = end.max(line.timecode.time(length));
+
+            let Some(time) = change else {
+                continue;
+            };
+            let rows = decoder.displayed();
+            if current
+                .as_ref()
+                .is_some_and(|(_, current)| *current == rows)
+            {
+                continue;
+            }
+            if let Some((start, rows)) = current.take() {
+                captions.push(Caption {
+                    time_span: TimeSpan::new(start, time),
+                    rows,
+                });
+            }
+            if !rows.is_empty() {
+                current = Some((time, rows));
+            }
+        }
+        if let Some((start, rows)) = current {
+            captions.push(Caption {
+                time_span: TimeSpan::new(start, end.max(start)),
+                rows,
+            });
+        }
+        captions
+    }
+
+    /// Write the content in the `SCC` format, with drop-frame timecodes.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `io::Error` if writing fails.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        writeln!(writer, "{HEADER}")?;
+        for line in &self.lines {
+            write!(writer, "\n{}\t", line.timecode)?;
+            for (idx, [b1, b2]) in line.data.iter().enumerate() {
+                let separator = if idx == 0 { "" } else { " " };
+                write!(writer, "{separator}{b1:02x}{b2:02x}")?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl SccLine {
+    /// Create a line at a drop-frame `frame`, from pairs without parity.
+    fn new(frame: u32, pairs: Vec<[u8; 2]>) -> Self {
+        Self {
+            timecode: Timecode::from_frame_count(frame, true),
+            data: pairs
+                .into_iter()
+                .map(|pair| pair.map(with_parity))
+                .collect(),
+        }
+    }
+}
+
+/// Parse a word of four hexadecimal digits.
+fn parse_word(word: &str) -> Option<[u8; 2]> {
+    if word.len() != 4 {
+        return None;
+    }
+    u16::from_str_radix(word, 16).ok().map(u16::to_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches2::assert_matches;
+
+    const EXAMPLE: &str = "Scenarist_SCC V1.0
+
+00:00:00;20\t9420 9420 94ae 94ae 9452 9452 97a2 97a2 c8e5 ecec ef80 942f 942f
+
+00:00:02;00\t942c 942c
+
+00:00:03;00\t9425 9425 94ad 94ad 9470 9470 d2ef ecec
+";
+
+    #[test]
+    fn parse_and_decode() {
+        let scc = Scc::parse(EXAMPLE).unwrap();
+        assert_eq!(scc.lines.len(), 3);
+        assert_eq!(scc.lines[1].data, [[0x94, 0x2c], [0x94, 0x2c]]);
+
+        let captions = scc.captions(Channel::Cc1);
+        assert_eq!(captions.len(), 2);
+        assert_eq!(captions[0].plain_text(), "Hello");
+        assert_eq!(captions[0].rows[0].row, 14);
+        assert_eq!(captions[0].rows[0].column, 6);
+        // End of caption is the twelfth pair.
+        let start = Timecode::parse("00:00:00;20").unwrap().time(11);
+        let end = Timecode::parse("00:00:02;00").unwrap().time(0);
+        assert_eq!(captions[0].time_span, TimeSpan::new(start, end));
+        assert_eq!(captions[1].plain_text(), "Roll");
+        assert!(scc.captions(Channel::Cc2).is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_matches!(
+            Scc::parse("00:00:00;00\t9420"),
+            Err(SccError::MissingHeader)
+        );
+        assert_matches!(
+            Scc::parse("Scenarist_SCC V1.0\n\n00:00:00;00\t942"),
+            Err(SccError::InvalidWord { line: 3, .. })
+        );
+        assert_matches!(
+            Scc::parse("Scenarist_SCC V1.0\n00:00:60;00\t9420"),
+            Err(SccError::InvalidTimecode { line: 2, .. })
+        );
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let subtitles = [
+            (
+                TimeSpan::new(TimePoint::from_msecs(2000), TimePoint::from_msecs(4000)),
+                "Première ligne\nSecond line".to_owned(),
+            ),
+            (
+                TimeSpan::new(TimePoint::from_msecs(4000), TimePoint::from_msecs(6000)),
+                "Next".to_owned(),
+            ),
+        ];
+        let mut content = Vec::new();
+        Scc::from_subtitles(&subtitles).write(&mut content).unwrap();
+        let content = String::from_utf8(content).unwrap();
+        assert!(content.starts_with("Scenarist_SCC V1.0\n\n"));
+
+        let captions = Scc::parse(&content).unwrap().captions(Channel::Cc1);
+        assert_eq!(captions.len(), 2);
+        for (caption, (time_span, text)) in captions.iter().zip(&subtitles) {
+            assert_eq!(caption.plain_text(), *text);
+            assert!((caption.time_span.start.msecs() - time_span.start.msecs()).abs() < 40);
+            assert!((caption.time_span.end.msecs() - time_span.end.msecs()).abs() < 40);
+        }
+        assert_eq!(captions[0].rows[0].row, 14);
+        assert_eq!(captions[1].rows[0].row, 15);
+    }
+}
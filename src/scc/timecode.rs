@@ -0,0 +1,175 @@
+//! `SMPTE` timecodes at 29.97 frames per second, with drop-frame support.
+
+use crate::time::TimePoint;
+use std::fmt;
+
+/// Number of frames per second of the timecode labels.
+const NOMINAL_FPS: u32 = 30;
+/// Number of frames in ten minutes, in drop-frame.
+const FRAMES_PER_10_MINUTES: u32 = 17_982;
+/// Number of frames in a minute, except the tenth minutes, in drop-frame.
+const FRAMES_PER_MINUTE: u32 = 1798;
+
+/// A timecode of a `SCC` file, like `01:00:00:00` or `01:00:00;00` for drop-frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    /// Hours.
+    pub hours: u32,
+    /// Minutes.
+    pub minutes: u32,
+    /// Seconds.
+    pub seconds: u32,
+    /// Frames.
+    pub frames: u32,
+    /// If the timecode is drop-frame: the frames `0` and `1` of each minute are skipped,
+    /// except for each tenth minute, to keep the labels in sync with the clock.
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    /// Parse a timecode, with `;` or `.` before the frames for drop-frame.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let drop_frame = value.contains([';', '.']);
+        let mut parts = value.split([':', ';', '.']).map(|part| {
+            (!part.is_empty() && part.bytes().all(|c| c.is_ascii_digit()))
+                .then(|| part.parse().ok())
+                .flatten()
+        });
+        let timecode = Self {
+            hours: parts.next()??,
+            minutes: parts.next()??,
+            seconds: parts.next()??,
+            frames: parts.next()??,
+            drop_frame,
+        };
+        (parts.next().is_none()
+            && timecode.hours < 100
+            && timecode.minutes < 60
+            && timecode.seconds < 60
+            && timecode.frames < NOMINAL_FPS)
+            .then_some(timecode)
+    }
+
+    /// Create a timecode from a number of frames since `00:00:00:00`.
+    #[must_use]
+    pub const fn from_frame_count(frame_count: u32, drop_frame: bool) -> Self {
+        let frames = if drop_frame {
+            let tens = frame_count / FRAMES_PER_10_MINUTES;
+            let remainder = frame_count % FRAMES_PER_10_MINUTES;
+            let dropped = if remainder < 2 {
+                18 * tens
+            } else {
+                18 * tens + 2 * ((remainder - 2) / FRAMES_PER_MINUTE)
+            };
+            frame_count.saturating_add(dropped)
+        } else {
+            frame_count
+        };
+        Self {
+            hours: frames / (NOMINAL_FPS * 3600),
+            minutes: frames / (NOMINAL_FPS * 60) % 60,
+            seconds: frames / NOMINAL_FPS % 60,
+            frames: frames % NOMINAL_FPS,
+            drop_frame,
+        }
+    }
+
+    /// Create the timecode of the frame displayed at `time`.
+    #[must_use]
+    pub fn from_time(time: TimePoint, drop_frame: bool) -> Self {
+        let msecs = u64::try_from(time.msecs()).unwrap_or_default();
+        let frames = msecs.saturating_mul(30).saturating_add(500) / 1001;
+        Self::from_frame_count(u32::try_from(frames).unwrap_or(u32::MAX), drop_frame)
+    }
+
+    /// Number of frames since `00:00:00:00`.
+    #[must_use]
+    pub const fn frame_count(&self) -> u32 {
+        let total_minutes = self.hours * 60 + self.minutes;
+        let frames =
+            ((self.hours * 3600 + self.minutes * 60 + self.seconds) * NOMINAL_FPS) + self.frames;
+        if self.drop_frame {
+            frames - 2 * (total_minutes - total_minutes / 10)
+        } else {
+            frames
+        }
+    }
+
+    /// Time of the frame at `offset` frames after the timecode.
+    #[must_use]
+    pub fn time(&self, offset: u32) -> TimePoint {
+        frame_time(self.frame_count().saturating_add(offset))
+    }
+}
+
+/// Time of a frame at 29.97 frames per second.
+fn frame_time(frame: u32) -> TimePoint {
+    TimePoint::from_msecs((i64::from(frame) * 1001 + 15) / 30)
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_timecodes() {
+        let timecode = Timecode::parse("01:02:03;04").unwrap();
+        assert!(timecode.drop_frame);
+        assert_eq!(timecode.to_string(), "01:02:03;04");
+        assert_eq!(
+            Timecode::parse("00:00:01:15").unwrap().time(0),
+            TimePoint::from_msecs(1502)
+        );
+        assert_eq!(Timecode::parse("00:00:00:30"), None);
+        assert_eq!(Timecode::parse("00:00:00"), None);
+        assert_eq!(Timecode::parse("00:00:00:00:00"), None);
+    }
+
+    #[test]
+    fn extreme_time_does_not_overflow() {
+        for drop_frame in [false, true] {
+            let timecode = Timecode::from_time(TimePoint::from_msecs(i64::MAX), drop_frame);
+            assert!(timecode.hours > 0);
+        }
+    }
+
+    #[test]
+    fn drop_frame_counts() {
+        let count = |value| Timecode::parse(value).unwrap().frame_count();
+        assert_eq!(count("00:00:59;29"), 1799);
+        assert_eq!(count("00:01:00;02"), 1800);
+        assert_eq!(count("00:10:00;00"), FRAMES_PER_10_MINUTES);
+        // Drop-frame timecodes are nearly in sync with the clock.
+        assert_eq!(count("01:00:00;00"), 107_892);
+        assert_eq!(
+            Timecode::parse("01:00:00;00").unwrap().time(0),
+            TimePoint::from_msecs(3_599_996)
+        );
+
+        for frame in [0, 1799, 1800, 17_981, 17_982, 17_984, 107_892] {
+            let timecode = Timecode::from_frame_count(frame, true);
+            assert_eq!(timecode.frame_count(), frame, "{timecode}");
+        }
+        assert_eq!(
+            Timecode::from_frame_count(1800, true).to_string(),
+            "00:01:00;02"
+        );
+        assert_eq!(
+            Timecode::from_time(TimePoint::from_msecs(3_599_996), true).to_string(),
+            "01:00:00;00"
+        );
+    }
+}
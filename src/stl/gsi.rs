@@ -0,0 +1,387 @@
+//! General Subtitle Information (`GSI`) block of `EBU STL` files.
+
+use super::{FrameRate, StlError, Timecode};
+
+/// Size of the `GSI` block.
+pub(super) const GSI_SIZE: usize = 1024;
+
+/// Characters of the codes `0x80` to `0xff` of the code page `437`.
+const CODE_PAGE_437: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»\
+    ░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+    αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+/// Characters of the codes `0x80` to `0xff` of the code page `850`.
+const CODE_PAGE_850: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜø£Ø×ƒáíóúñÑªº¿®¬½¼¡«»\
+    ░▒▓│┤ÁÂÀ©╣║╗╝¢¥┐└┴┬├─┼ãÃ╚╔╩╦╠═╬¤ðÐÊËÈıÍÎÏ┘┌█▄¦Ì▀\
+    ÓßÔÒõÕµþÞÚÛÙýÝ¯´\u{ad}±‗¾¶§÷¸°¨·¹³²■\u{a0}";
+
+/// Code page of the text fields of the `GSI` block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CodePage {
+    /// United States, `437`.
+    UnitedStates,
+    /// Multilingual, `850`.
+    #[default]
+    Multilingual,
+    /// Portugal, `860`.
+    Portugal,
+    /// Canada-French, `863`.
+    CanadaFrench,
+    /// Nordic, `865`.
+    Nordic,
+}
+
+impl CodePage {
+    /// Number of the code page.
+    #[must_use]
+    pub const fn number(self) -> u16 {
+        match self {
+            Self::UnitedStates => 437,
+            Self::Multilingual => 850,
+            Self::Portugal => 860,
+            Self::CanadaFrench => 863,
+            Self::Nordic => 865,
+        }
+    }
+
+    /// Characters of the upper half of the code page, if supported.
+    const fn upper_half(self) -> Option<&'static str> {
+        match self {
+            Self::UnitedStates => Some(CODE_PAGE_437),
+            Self::Multilingual => Some(CODE_PAGE_850),
+            Self::Portugal | Self::CanadaFrench | Self::Nordic => None,
+        }
+    }
+
+    /// Decode a text field, without its padding spaces.
+    ///
+    /// The characters outside `ASCII` are only decoded for the code pages `437` and `850`.
+    fn decode(self, field: &[u8]) -> String {
+        let upper_half = self.upper_half();
+        let text = field
+            .iter()
+            .map(|&code| {
+                code.checked_sub(0x80).map_or_else(
+                    || char::from(code),
+                    |idx| {
+                        upper_half
+                            .and_then(|upper_half| upper_half.chars().nth(usize::from(idx)))
+                            .unwrap_or('\u{fffd}')
+                    },
+                )
+            })
+            .collect::<String>();
+        text.trim_end().to_owned()
+    }
+
+    /// Encode a text. Characters which can't be encoded are replaced by `?`.
+    fn encode(self, text: &str) -> Vec<u8> {
+        let upper_half = self.upper_half();
+        text.chars()
+            .map(|character| {
+                u8::try_from(character)
+                    .ok()
+                    .filter(u8::is_ascii)
+                    .or_else(|| {
+                        let idx = upper_half?.chars().position(|other| other == character)?;
+                        u8::try_from(0x80 + idx).ok()
+                    })
+                    .unwrap_or(b'?')
+            })
+            .collect()
+    }
+}
+
+/// Display standard of the subtitles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayStandard {
+    /// Not defined.
+    Undefined,
+    /// Open subtitling, burnt in the video.
+    OpenSubtitling,
+    /// Level-1 Teletext.
+    #[default]
+    Level1Teletext,
+    /// Level-2 Teletext.
+    Level2Teletext,
+}
+
+impl DisplayStandard {
+    /// If the subtitles are displayed with Teletext.
+    #[must_use]
+    pub const fn is_teletext(self) -> bool {
+        matches!(self, Self::Level1Teletext | Self::Level2Teletext)
+    }
+}
+
+/// Character code table of the text fields of the `TTI` blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CharacterTable {
+    /// Latin alphabet, `ISO 6937`.
+    #[default]
+    Latin,
+    /// Latin and Cyrillic alphabets, `ISO 8859-5`.
+    LatinCyrillic,
+    /// Latin and Arabic alphabets, `ISO 8859-6`.
+    LatinArabic,
+    /// Latin and Greek alphabets, `ISO 8859-7`.
+    LatinGreek,
+    /// Latin and Hebrew alphabets, `ISO 8859-8`.
+    LatinHebrew,
+}
+
+/// Content of the `GSI` block.
+///
+/// The numbers of `TTI` blocks, of subtitles and of subtitle groups are computed
+/// when writing a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gsi {
+    /// Code page of the text fields of the `GSI` block.
+    pub code_page: CodePage,
+    /// Frame rate of the timecodes.
+    pub frame_rate: FrameRate,
+    /// Display standard of the subtitles.
+    pub display_standard: DisplayStandard,
+    /// Character code table of the subtitles.
+    pub character_table: CharacterTable,
+    /// Language code, as two hexadecimal digits: `09` for English, `0F` for French.
+    pub language_code: String,
+    /// Original programme title.
+    pub program_title: String,
+    /// Original episode title.
+    pub episode_title: String,
+    /// Translated programme title.
+    pub translated_program_title: String,
+    /// Translated episode title.
+    pub translated_episode_title: String,
+    /// Translator's name.
+    pub translator_name: String,
+    /// Translator's contact details.
+    pub translator_contact: String,
+    /// Subtitle list reference code.
+    pub list_reference: String,
+    /// Creation date, `YYMMDD`.
+    pub creation_date: String,
+    /// Revision date, `YYMMDD`.
+    pub revision_date: String,
+    /// Revision number.
+    pub revision_number: u8,
+    /// Maximum number of displayable characters in a row.
+    pub max_row_chars: u8,
+    /// Maximum number of displayable rows.
+    pub max_rows: u8,
+    /// If the timecodes are intended for use.
+    pub timecode_status: bool,
+    /// Timecode of the start of the programme.
+    pub start_of_programme: Timecode,
+    /// Timecode of the first subtitle.
+    pub first_in_cue: Timecode,
+    /// Total number of disks.
+    pub disk_count: u8,
+    /// Sequence number of the disk.
+    pub disk_number: u8,
+    /// Country of origin, as an `ISO 3166` alpha-3 code.
+    pub country_of_origin: String,
+    /// Publisher.
+    pub publisher: String,
+    /// Editor's name.
+    pub editor_name: String,
+    /// Editor's contact details.
+    pub editor_contact: String,
+    /// User-defined area, of 576 bytes.
+    pub user_data: Vec<u8>,
+}
+
+impl Default for Gsi {
+    fn default() -> Self {
+        Self {
+            code_page: CodePage::default(),
+            frame_rate: FrameRate::default(),
+            display_standard: DisplayStandard::default(),
+            character_table: CharacterTable::default(),
+            language_code: "00".to_owned(),
+            program_title: String::new(),
+            episode_title: String::new(),
+            translated_program_title: String::new(),
+            translated_episode_title: String::new(),
+            translator_name: String::new(),
+            translator_contact: String::new(),
+            list_reference: String::new(),
+            creation_date: String::new(),
+            revision_date: String::new(),
+            revision_number: 0,
+            max_row_chars: 40,
+            max_rows: 23,
+            timecode_status: true,
+            start_of_programme: Timecode::default(),
+            first_in_cue: Timecode::default(),
+            disk_count: 1,
+            disk_number: 1,
+            country_of_origin: String::new(),
+            publisher: String::new(),
+            editor_name: String::new(),
+            editor_contact: String::new(),
+            user_data: vec![b' '; 576],
+        }
+    }
+}
+
+/// Counts of the `TTI` blocks, subtitles and groups, written in the `GSI` block.
+pub(super) struct Counts {
+    pub blocks: usize,
+    pub subtitles: usize,
+    pub groups: usize,
+}
+
+impl Gsi {
+    /// Parse a `GSI` block.
+    pub(super) fn parse(block: &[u8; GSI_SIZE]) -> Result<Self, StlError> {
+        let field = |start: usize, len: usize| &block[start..start + len];
+        let ascii =
+            |start: usize, len: usize| String::from_utf8_lossy(field(start, len)).trim().to_owned();
+        let invalid = |name: &'static str, start: usize, len: usize| StlError::InvalidGsi {
+            field: name,
+            value: String::from_utf8_lossy(field(start, len)).into_owned(),
+        };
+        let number = |name: &'static str, start: usize, len: usize| {
+            let value = ascii(start, len);
+            if value.is_empty() {
+                return Ok(0);
+            }
+            value
+                .parse::<u8>()
+                .ok()
+                .ok_or_else(|| invalid(name, start, len))
+        };
+
+        let code_page = match field(0, 3) {
+            b"437" => CodePage::UnitedStates,
+            b"850" | b"   " => CodePage::Multilingual,
+            b"860" => CodePage::Portugal,
+            b"863" => CodePage::CanadaFrench,
+            b"865" => CodePage::Nordic,
+            _ => return Err(invalid("code page number", 0, 3)),
+        };
+        let frame_rate = FrameRate::from_disk_format_code(&ascii(3, 8))
+            .ok_or_else(|| invalid("disk format code", 3, 8))?;
+        let display_standard = match block[11] {
+            b' ' => DisplayStandard::Undefined,
+            b'0' => DisplayStandard::OpenSubtitling,
+            b'1' => DisplayStandard::Level1Teletext,
+            b'2' => DisplayStandard::Level2Teletext,
+            _ => return Err(invalid("display standard code", 11, 1)),
+        };
+        let character_table = match field(12, 2) {
+            b"00" | b"  " => CharacterTable::Latin,
+            b"01" => CharacterTable::LatinCyrillic,
+            b"02" => CharacterTable::LatinArabic,
+            b"03" => CharacterTable::LatinGreek,
+            b"04" => CharacterTable::LatinHebrew,
+            _ => return Err(invalid("character code table", 12, 2)),
+        };
+        let timecode = |name: &'static str, start: usize| {
+            Timecode::parse_gsi(&ascii(start, 8), frame_rate).ok_or_else(|| invalid(name, start, 8))
+        };
+        let text = |start: usize, len: usize| code_page.decode(field(start, len));
+
+        Ok(Self {
+            code_page,
+            frame_rate,
+            display_standard,
+            character_table,
+            language_code: ascii(14, 2),
+            program_title: text(16, 32),
+            episode_title: text(48, 32),
+            translated_program_title: text(80, 32),
+            translated_episode_title: text(112, 32),
+            translator_name: text(144, 32),
+            translator_contact: text(176, 32),
+            list_reference: text(208, 16),
+            creation_date: ascii(224, 6),
+            revision_date: ascii(230, 6),
+            revision_number: number("revision number", 236, 2)?,
+            max_row_chars: number("maximum number of characters in a row", 251, 2)?,
+            max_rows: number("maximum number of rows", 253, 2)?,
+            timecode_status: block[255] != b'0',
+            start_of_programme: timecode("time code: start-of-programme", 256)?,
+            first_in_cue: timecode("time code: first in-cue", 264)?,
+            disk_count: number("total number of disks", 272, 1)?,
+            disk_number: number("disk sequence number", 273, 1)?,
+            country_of_origin: ascii(274, 3),
+            publisher: text(277, 32),
+            editor_name: text(309, 32),
+            editor_contact: text(341, 32),
+            user_data: field(448, 576).to_vec(),
+        })
+    }
+
+    /// Get the content of the `GSI` block.
+    pub(super) fn to_block(&self, counts: &Counts) -> Vec<u8> {
+        let display_standard = match self.display_standard {
+            DisplayStandard::Undefined => " ",
+            DisplayStandard::OpenSubtitling => "0",
+            DisplayStandard::Level1Teletext => "1",
+            DisplayStandard::Level2Teletext => "2",
+        };
+        let character_table = match self.character_table {
+            CharacterTable::Latin => "00",
+            CharacterTable::LatinCyrillic => "01",
+            CharacterTable::LatinArabic => "02",
+            CharacterTable::LatinGreek => "03",
+            CharacterTable::LatinHebrew => "04",
+        };
+
+        let mut block = Vec::with_capacity(GSI_SIZE);
+        for (value, len) in [
+            (self.code_page.number().to_string(), 3),
+            (self.frame_rate.disk_format_code().to_owned(), 8),
+            (display_standard.to_owned(), 1),
+            (character_table.to_owned(), 2),
+            (self.language_code.clone(), 2),
+        ] {
+            push_field(&mut block, value.bytes(), len);
+        }
+        for (value, len) in [
+            (&self.program_title, 32),
+            (&self.episode_title, 32),
+            (&self.translated_program_title, 32),
+            (&self.translated_episode_title, 32),
+            (&self.translator_name, 32),
+            (&self.translator_contact, 32),
+            (&self.list_reference, 16),
+        ] {
+            push_field(&mut block, self.code_page.encode(value), len);
+        }
+        for (value, len) in [
+            (self.creation_date.clone(), 6),
+            (self.revision_date.clone(), 6),
+            (format!("{:02}", self.revision_number), 2),
+            (format!("{:05}", counts.blocks), 5),
+            (format!("{:05}", counts.subtitles), 5),
+            (format!("{:03}", counts.groups), 3),
+            (format!("{:02}", self.max_row_chars), 2),
+            (format!("{:02}", self.max_rows), 2),
+            (u8::from(self.timecode_status).to_string(), 1),
+            (self.start_of_programme.to_string(), 8),
+            (self.first_in_cue.to_string(), 8),
+            (self.disk_count.to_string(), 1),
+            (self.disk_number.to_string(), 1),
+            (self.country_of_origin.clone(), 3),
+        ] {
+            push_field(&mut block, value.bytes(), len);
+        }
+        for value in [&self.publisher, &self.editor_name, &self.editor_contact] {
+            push_field(&mut block, self.code_page.encode(value), 32);
+        }
+        // Spare bytes, then the user-defined area.
+        block.resize(448, b' ');
+        push_field(&mut block, self.user_data.iter().copied(), 576);
+        block
+    }
+}
+
+/// Add a field of `len` bytes, truncated or padded with spaces.
+fn push_field(block: &mut Vec<u8>, value: impl IntoIterator<Item = u8>, len: usize) {
+    let start = block.len();
+    block.extend(value.into_iter().take(len));
+    block.resize(start + len, b' ');
+}
@@ -0,0 +1,416 @@
+//! `EBU STL` (`Tech 3264`) subtitle files functionality.
+//!
+//! `STL` files are binary files, made of a General Subtitle Information (`GSI`) block,
+//! followed by Text and Timing Information (`TTI`) blocks. The text fields contain
+//! `ISO 6937` characters and control codes for the Teletext colors and boxes, and for
+//! italics and underline in open subtitling.
+//!
+//! The subtitles can be read as styled cues, and written from cues or from plain text
+//! subtitles.
+//!
+//! Specification: <https://tech.ebu.ch/docs/tech/tech3264.pdf>
+mod gsi;
+mod text;
+mod timecode;
+mod tti;
+
+pub use gsi::{CharacterTable, CodePage, DisplayStandard, Gsi};
+pub use text::Cue;
+pub use timecode::{FrameRate, Timecode};
+pub use tti::{CumulativeStatus, Justification, Tti};
+
+use crate::time::TimeSpan;
+use gsi::{Counts, GSI_SIZE};
+use log::warn;
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use text::UNUSED;
+use thiserror::Error;
+use tti::{LAST_EXTENSION, MAX_BLOCKS, TEXT_FIELD_SIZE, TTI_SIZE};
+
+/// Error for `EBU STL` handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum StlError {
+    /// If an error happen during the file opening.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("failed to read the STL content")]
+    Read(#[source] io::Error),
+
+    /// The content is not a `GSI` block followed by `TTI` blocks.
+    #[error("invalid STL size of {size} bytes")]
+    InvalidSize {
+        /// Size of the content.
+        size: usize,
+    },
+
+    /// A field of the `GSI` block is invalid.
+    #[error("invalid GSI field '{field}': '{value}'")]
+    InvalidGsi {
+        /// Name of the field.
+        field: &'static str,
+        /// Invalid value.
+        value: String,
+    },
+
+    /// A timecode of a `TTI` block is invalid.
+    #[error("invalid timecode in TTI block {block}")]
+    InvalidTimecode {
+        /// Index of the block (starting at 0).
+        block: usize,
+    },
+}
+
+/// Content of an `EBU STL` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stl {
+    /// General Subtitle Information.
+    pub gsi: Gsi,
+    /// Text and Timing Information blocks, in file order.
+    pub blocks: Vec<Tti>,
+}
+
+impl Stl {
+    /// Create a `STL` content from cues. The text of each cue is split in extension
+    /// blocks when it doesn't fit in a `TTI` block. The text which doesn't fit in the
+    /// `241` blocks allowed for a subtitle is dropped.
+    ///
+    /// For Teletext display standards, the rows are placed in boxes.
+    /// Characters which can't be encoded in `ISO 6937` are replaced by `?`.
+    #[must_use]
+    pub fn from_cues(gsi: Gsi, cues: &[Cue]) -> Self {
+        let teletext = gsi.display_standard.is_teletext();
+        let mut blocks = Vec::new();
+        for cue in cues {
+            let text = text::encode(&cue.rows, teletext);
+            let mut chunks = text.chunks(TEXT_FIELD_SIZE).collect::<Vec<_>>();
+            if chunks.len() > MAX_BLOCKS {
+                warn!(
+                    "Text of subtitle {} is too long, {} blocks dropped",
+                    cue.number,
+                    chunks.len() - MAX_BLOCKS
+                );
+                chunks.truncate(MAX_BLOCKS);
+            }
+            let chunk_count = chunks.len().max(1);
+            for idx in 0..chunk_count {
+                let mut field = [UNUSED; TEXT_FIELD_SIZE];
+                if let Some(chunk) = chunks.get(idx) {
+                    field[..chunk.len()].copy_from_slice(chunk);
+                }
+                blocks.push(Tti {
+                    group: 0,
+                    number: cue.number,
+                    extension: if idx + 1 == chunk_count {
+                        LAST_EXTENSION
+                    } else {
+                        u8::try_from(idx).unwrap_or_default()
+                    },
+                    cumulative: cue.cumulative,
+                    time_in: Timecode::from_time(cue.time_span.start, gsi.frame_rate),
+                    time_out: Timecode::from_time(cue.time_span.end, gsi.frame_rate),
+                    vertical_position: cue.vertical_position,
+                    justification: cue.justification,
+                    comment: false,
+                    text: field,
+                });
+            }
+        }
+        Self { gsi, blocks }
+    }
+
+    /// Create a `STL` content from subtitles, as centered Teletext subtitles at 25 frames
+    /// per second, at the bottom of the screen.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)]) -> Self {
+        let cues = (1..)
+            .zip(subtitles)
+            .map(|(number, (time_span, text))| Cue {
+                number,
+                ..Cue::from(&crate::text::Cue::plain(*time_span, text))
+            })
+            .collect::<Vec<_>>();
+        let mut gsi = Gsi::default();
+        if let Some((time_span, _)) = subtitles.first() {
+            gsi.first_in_cue = Timecode::from_time(time_span.start, gsi.frame_rate);
+        }
+        Self::from_cues(gsi, &cues)
+    }
+
+    /// Read a `STL` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `StlError::Io` if not able to read the file.
+    /// Will return an other `StlError` if the content is not valid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StlError> {
+        let path = path.as_ref();
+        let content = fs::read(path).map_err(|source| StlError::Io {
+            source,
+            path: path.into(),
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Read a `STL` content from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `StlError::Read` if not able to read from the `reader`.
+    /// Will return an other `StlError` if the content is not valid.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, StlError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(StlError::Read)?;
+        Self::parse(&content)
+    }
+
+    /// Parse a `STL` content.
+    ///
+    /// # Errors
+    ///
+    /// Will return `StlError::InvalidSize` if the content is not made of complete blocks.
+    /// Will return `StlError::InvalidGsi` or `StlError::InvalidTimecode` for an invalid block.
+    pub fn parse(content: &[u8]) -> Result<Self, StlError> {
+        let invalid_size = || StlError::InvalidSize {
+            size: content.len(),
+        };
+        let (gsi, tti) = content
+            .split_first_chunk::<GSI_SIZE>()
+            .ok_or_else(invalid_size)?;
+        if tti.len() % TTI_SIZE != 0 {
+            return Err(invalid_size());
+        }
+        let gsi = Gsi::parse(gsi)?;
+        let blocks = tti
+            .chunks_exact(TTI_SIZE)
+            .enumerate()
+            .map(|(index, block)| Tti::parse(index, block, gsi.frame_rate))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { gsi, blocks })
+    }
+
+    /// Get the subtitles, with the text of their extension blocks.
+    ///
+    /// The comments and the user data are skipped. The times are the times of the timecodes,
+    /// without subtracting the start of the programme.
+    #[must_use]
+    pub fn cues(&self) -> Vec<Cue> {
+        let frame_rate = self.gsi.frame_rate;
+        let latin = self.gsi.character_table == CharacterTable::Latin;
+        let mut cues = Vec::new();
+        let mut blocks = self
+            .blocks
+            .iter()
+            .filter(|block| !block.comment && !block.is_user_data())
+            .peekable();
+        while let Some(first) = blocks.next() {
+            let mut text = first.text.to_vec();
+            let mut last = first.extension;
+            while last != LAST_EXTENSION {
+                match blocks.next_if(|block| block.number == first.number) {
+                    Some(block) => {
+                        text.extend(block.text);
+                        last = block.extension;
+                    }
+                    None => break,
+                }
+            }
+            cues.push(Cue {
+                number: first.number,
+                time_span: TimeSpan::new(
+                    first.time_in.time(frame_rate),
+                    first.time_out.time(frame_rate),
+                ),
+                vertical_position: first.vertical_position,
+                justification: first.justification,
+                cumulative: first.cumulative,
+                rows: text::decode(&text, latin),
+            });
+        }
+        cues
+    }
+
+    /// Write the content in the `STL` format.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `io::Error` if writing fails.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        let text_blocks = self.blocks.iter().filter(|block| !block.is_user_data());
+        let counts = Counts {
+            blocks: self.blocks.len(),
+            subtitles: text_blocks
+                .clone()
+                .filter(|block| block.extension == LAST_EXTENSION)
+                .count(),
+            groups: text_blocks
+                .map(|block| block.group)
+                .collect::<BTreeSet<_>>()
+                .len()
+                .max(1),
+        };
+        writer.write_all(&self.gsi.to_block(&counts))?;
+        for block in &self.blocks {
+            writer.write_all(&block.to_block())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        teletext::Color,
+        text::{CueRow, CueSpan, CueStyle},
+        time::TimePoint,
+    };
+    use assert_matches2::assert_matches;
+
+    fn subtitles() -> Vec<(TimeSpan, String)> {
+        vec![
+            (
+                TimeSpan::new(TimePoint::from_msecs(1000), TimePoint::from_msecs(3000)),
+                "Première ligne\nSecond line".to_owned(),
+            ),
+            (
+                TimeSpan::new(TimePoint::from_msecs(4040), TimePoint::from_msecs(6000)),
+                "Čeština €".to_owned(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut stl = Stl::from_subtitles(&subtitles());
+        stl.gsi.program_title = "Programme télé".to_owned();
+        stl.gsi.language_code = "0F".to_owned();
+        let mut content = Vec::new();
+        stl.write(&mut content).unwrap();
+        assert_eq!(content.len(), GSI_SIZE + 2 * TTI_SIZE);
+        assert_eq!(&content[..11], b"850STL25.01");
+        assert_eq!(&content[238..248], b"0000200002");
+
+        let read = Stl::parse(&content).unwrap();
+        assert_eq!(read.gsi, stl.gsi);
+        assert_eq!(read.blocks, stl.blocks);
+        let cues = read.cues();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].plain_text(), "Première ligne\nSecond line");
+        assert_eq!(cues[0].time_span, subtitles()[0].0);
+        assert_eq!(cues[0].vertical_position, 20);
+        assert_eq!(cues[0].justification, Justification::Centered);
+        assert_eq!(cues[1].plain_text(), "Čeština ?");
+        assert_eq!(cues[1].time_span, subtitles()[1].0);
+    }
+
+    #[test]
+    fn extension_blocks() {
+        let long = "a".repeat(150);
+        let cue = Cue {
+            number: 7,
+            time_span: subtitles()[0].0,
+            vertical_position: 10,
+            justification: Justification::Left,
+            cumulative: CumulativeStatus::First,
+            rows: vec![CueRow {
+                double_height: true,
+                spans: vec![CueSpan {
+                    text: long.clone(),
+                    style: CueStyle {
+                        color: Some(Color::Cyan.rgb()),
+                        italic: true,
+                        ..CueStyle::default()
+                    },
+                }],
+            }],
+        };
+        let gsi = Gsi {
+            display_standard: DisplayStandard::OpenSubtitling,
+            frame_rate: FrameRate::Fps30,
+            ..Gsi::default()
+        };
+        let stl = Stl::from_cues(gsi, std::slice::from_ref(&cue));
+        assert_eq!(stl.blocks.len(), 2);
+        assert_eq!(stl.blocks[0].extension, 0);
+        assert_eq!(stl.blocks[1].extension, LAST_EXTENSION);
+
+        let mut content = Vec::new();
+        stl.write(&mut content).unwrap();
+        let cues = Stl::from_reader(content.as_slice()).unwrap().cues();
+        assert_eq!(cues, std::slice::from_ref(&cue));
+
+        // The text beyond the last allowed extension block is dropped.
+        let cue = Cue {
+            rows: vec![CueRow::plain(&"x".repeat(TEXT_FIELD_SIZE * 300))],
+            ..cue
+        };
+        let stl = Stl::from_cues(Gsi::default(), &[cue]);
+        assert_eq!(stl.blocks.len(), MAX_BLOCKS);
+        assert_eq!(stl.blocks[MAX_BLOCKS - 2].extension, 0xef);
+        assert_eq!(stl.blocks[MAX_BLOCKS - 1].extension, LAST_EXTENSION);
+    }
+
+    #[test]
+    fn convert_text_cues() {
+        let cues = Stl::from_subtitles(&subtitles()).cues();
+        let text_cue = crate::text::Cue::from(&cues[0]);
+        assert_eq!(text_cue.time_span, cues[0].time_span);
+        assert_eq!(text_cue.position.line, Some(80));
+        assert_eq!(text_cue.position.align, Some(crate::text::CueAlign::Center));
+        assert_eq!(text_cue.plain_text(), cues[0].plain_text());
+        assert_eq!(
+            Cue::from(&text_cue),
+            Cue {
+                number: 0,
+                ..cues[0].clone()
+            }
+        );
+
+        // Without position, the rows end at the bottom of the screen.
+        let plain = crate::text::Cue::plain(subtitles()[1].0, "One row");
+        let cue = Cue::from(&plain);
+        assert_eq!(cue.vertical_position, 22);
+        assert_eq!(cue.justification, Justification::Centered);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_matches!(
+            Stl::parse(&[0; 100]),
+            Err(StlError::InvalidSize { size: 100 })
+        );
+
+        let mut content = Vec::new();
+        Stl::from_subtitles(&subtitles())
+            .write(&mut content)
+            .unwrap();
+        let mut invalid = content.clone();
+        invalid[3..11].copy_from_slice(b"STL24.01");
+        assert_matches!(
+            Stl::parse(&invalid),
+            Err(StlError::InvalidGsi {
+                field: "disk format code",
+                ..
+            })
+        );
+        let mut invalid = content;
+        invalid[GSI_SIZE + TTI_SIZE + 7] = 60;
+        assert_matches!(
+            Stl::parse(&invalid),
+            Err(StlError::InvalidTimecode { block: 1 })
+        );
+    }
+}
@@ -0,0 +1,410 @@
+//! Text fields of the `TTI` blocks: `ISO 6937` characters and control codes.
+
+use super::{CumulativeStatus, Justification};
+use crate::{
+    teletext::Color,
+    text::{self, CueAlign, CuePosition, CueRow, CueSpan, CueStyle},
+    time::TimeSpan,
+};
+
+/// Italics on, in open subtitling.
+const ITALICS_ON: u8 = 0x80;
+/// Italics off, in open subtitling.
+const ITALICS_OFF: u8 = 0x81;
+/// Underline on, in open subtitling.
+const UNDERLINE_ON: u8 = 0x82;
+/// Underline off, in open subtitling.
+const UNDERLINE_OFF: u8 = 0x83;
+/// Start of a new row.
+const NEW_LINE: u8 = 0x8a;
+/// Unused space at the end of a text field.
+pub(super) const UNUSED: u8 = 0x8f;
+/// End box, in Teletext.
+const END_BOX: u8 = 0x0a;
+/// Start box, in Teletext.
+const START_BOX: u8 = 0x0b;
+/// Double height, in Teletext.
+const DOUBLE_HEIGHT: u8 = 0x0d;
+/// Height of a row, in percent of the 25 rows of a Teletext screen.
+const ROW_PERCENT: u8 = 4;
+/// Row of the last row of the subtitles at the bottom of a Teletext screen.
+const BOTTOM_ROW: u8 = 22;
+
+/// Characters of the codes `0xa0` to `0xff` of `ISO 6937`, without the diacritical marks.
+const UPPER_HALF: [char; 96] = [
+    '\u{a0}', '¡', '¢', '£', '$', '¥', '#', '§', '¤', '‘', '“', '«', '←', '↑', '→', '↓', //
+    '°', '±', '²', '³', '×', 'µ', '¶', '·', '÷', '’', '”', '»', '¼', '½', '¾', '¿', //
+    '\u{fffd}', '\u{300}', '\u{301}', '\u{302}', '\u{303}', '\u{304}', '\u{306}',
+    '\u{307}', //
+    '\u{308}', '\u{fffd}', '\u{30a}', '\u{327}', '\u{fffd}', '\u{30b}', '\u{328}',
+    '\u{30c}', //
+    '―', '¹', '®', '©', '™', '♪', '¬', '¦', '\u{fffd}', '\u{fffd}', '\u{fffd}', '\u{fffd}', //
+    '⅛', '⅜', '⅝', '⅞', //
+    'Ω', 'Æ', 'Đ', 'ª', 'Ħ', '\u{fffd}', 'Ĳ', 'Ŀ', 'Ł', 'Ø', 'Œ', 'º', 'Þ', 'Ŧ', 'Ŋ', 'ŉ', //
+    'ĸ', 'æ', 'đ', 'ð', 'ħ', 'ı', 'ĳ', 'ŀ', 'ł', 'ø', 'œ', 'ß', 'þ', 'ŧ', 'ŋ', '\u{ad}', //
+];
+
+/// Letters composed with each diacritical mark: the code of the mark, the base letters,
+/// and the composed letters.
+const COMPOSED: [(u8, &str, &str); 13] = [
+    (0xc1, "AEIOUaeiou", "ÀÈÌÒÙàèìòù"),
+    (
+        0xc2,
+        "ACEGILNORSUYZacegilnorsuyz",
+        "ÁĆÉǴÍĹŃÓŔŚÚÝŹáćéǵíĺńóŕśúýź",
+    ),
+    (0xc3, "ACEGHIJOSUWYaceghijosuwy", "ÂĈÊĜĤÎĴÔŜÛŴŶâĉêĝĥîĵôŝûŵŷ"),
+    (0xc4, "AINOUainou", "ÃĨÑÕŨãĩñõũ"),
+    (0xc5, "AEIOUaeiou", "ĀĒĪŌŪāēīōū"),
+    (0xc6, "AGUagu", "ĂĞŬăğŭ"),
+    (0xc7, "CEGIZcegz", "ĊĖĠİŻċėġż"),
+    (0xc8, "AEIOUYaeiouy", "ÄËÏÖÜŸäëïöüÿ"),
+    (0xca, "AUau", "ÅŮåů"),
+    (0xcb, "CGKLNRSTcgklnrst", "ÇĢĶĻŅŖŞŢçģķļņŗşţ"),
+    (0xcd, "OUou", "ŐŰőű"),
+    (0xce, "AEIUaeiu", "ĄĘĮŲąęįų"),
+    (0xcf, "CDELNRSTZcdelnrstz", "ČĎĚĽŇŘŠŤŽčďěľňřšťž"),
+];
+
+/// A subtitle of a `STL` file, from one or several `TTI` blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// Subtitle number.
+    pub number: u16,
+    /// Time span of the subtitle.
+    pub time_span: TimeSpan,
+    /// Vertical position: the Teletext row, or the line in open subtitling.
+    pub vertical_position: u8,
+    /// Justification of the rows.
+    pub justification: Justification,
+    /// Position of the subtitle in a cumulative set.
+    pub cumulative: CumulativeStatus,
+    /// Rows of the subtitle, from top to bottom. Only the color, italic and underline
+    /// styles are stored in `STL`.
+    pub rows: Vec<CueRow>,
+}
+
+impl Cue {
+    /// Get the text of the subtitle, with the rows separated by `\n`.
+    #[must_use]
+    pub fn plain_text(&self) -> String {
+        self.rows
+            .iter()
+            .map(CueRow::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Convert to the shared text model, with the vertical position read as a Teletext row.
+impl From<&Cue> for text::Cue {
+    fn from(cue: &Cue) -> Self {
+        Self {
+            time_span: cue.time_span,
+            position: CuePosition {
+                line: Some(cue.vertical_position.saturating_mul(ROW_PERCENT).min(100)),
+                align: match cue.justification {
+                    Justification::Unchanged => None,
+                    Justification::Left => Some(CueAlign::Left),
+                    Justification::Centered => Some(CueAlign::Center),
+                    Justification::Right => Some(CueAlign::Right),
+                },
+            },
+            rows: cue.rows.clone(),
+        }
+    }
+}
+
+/// Convert from the shared text model, for a Teletext display with number `0`.
+///
+/// Without position, the subtitle is centered at the bottom of the screen, with the rows
+/// separated by an empty row. The bold and strike out styles are not stored in `STL`.
+impl From<&text::Cue> for Cue {
+    fn from(cue: &text::Cue) -> Self {
+        let vertical_position = cue.position.line.map_or_else(
+            || {
+                let row_count = u8::try_from(cue.rows.len()).unwrap_or(u8::MAX);
+                BOTTOM_ROW.saturating_sub(2 * row_count.saturating_sub(1))
+            },
+            |line| line / ROW_PERCENT,
+        );
+        Self {
+            number: 0,
+            time_span: cue.time_span,
+            vertical_position: vertical_position.clamp(1, 23),
+            justification: match cue.position.align {
+                Some(CueAlign::Left) => Justification::Left,
+                Some(CueAlign::Center) | None => Justification::Centered,
+                Some(CueAlign::Right) => Justification::Right,
+            },
+            cumulative: CumulativeStatus::NotCumulative,
+            rows: cue.rows.clone(),
+        }
+    }
+}
+
+/// Decode a character of the upper half of `ISO 6937`, or a diacritical mark and its letter.
+fn decode_character(code: u8, next: Option<u8>) -> (String, bool) {
+    let idx = usize::from(code.saturating_sub(0xa0));
+    if !(0xc1..=0xcf).contains(&code) {
+        return (UPPER_HALF[idx].to_string(), false);
+    }
+    let Some(letter) = next.filter(|next| (0x20..0x7f).contains(next)) else {
+        return (String::new(), false);
+    };
+    let letter = char::from(letter);
+    let composed =
+        COMPOSED
+            .iter()
+            .find(|(mark, _, _)| *mark == code)
+            .and_then(|(_, bases, composed)| {
+                let position = bases.chars().position(|base| base == letter)?;
+                composed.chars().nth(position)
+            });
+    // Keep the combining mark after the letter if there is no composed character.
+    let text = composed.map_or_else(
+        || [letter, UPPER_HALF[idx]].into_iter().collect(),
+        |composed| composed.to_string(),
+    );
+    (text, true)
+}
+
+/// Encode a character in `ISO 6937`. Return `None` if it can't be encoded.
+fn encode_character(character: char) -> Option<Vec<u8>> {
+    if (' '..='~').contains(&character) {
+        return u8::try_from(character).ok().map(|code| vec![code]);
+    }
+    if let Some(idx) = UPPER_HALF
+        .iter()
+        .position(|upper| *upper == character && !('\u{300}'..='\u{36f}').contains(upper))
+    {
+        return u8::try_from(0xa0 + idx).ok().map(|code| vec![code]);
+    }
+    COMPOSED.iter().find_map(|(mark, bases, composed)| {
+        let position = composed.chars().position(|other| other == character)?;
+        let base = bases.chars().nth(position)?;
+        Some(vec![*mark, u8::try_from(base).ok()?])
+    })
+}
+
+/// Builder of the rows of a text field.
+#[derive(Default)]
+struct RowsBuilder {
+    rows: Vec<CueRow>,
+    row: CueRow,
+    style: CueStyle,
+    /// A control code, displayed as a space, was found since the last character.
+    pending_space: bool,
+}
+
+impl RowsBuilder {
+    fn push(&mut self, text: &str) {
+        let last_is_space = self
+            .row
+            .spans
+            .last()
+            .and_then(|span| span.text.chars().last())
+            .map_or(true, |last| last == ' ');
+        let space = std::mem::take(&mut self.pending_space) && !last_is_space;
+        if space && !text.starts_with(' ') {
+            self.push_styled(" ");
+        }
+        if !(text == " " && last_is_space) {
+            self.push_styled(text);
+        }
+    }
+
+    fn push_styled(&mut self, text: &str) {
+        match self.row.spans.last_mut() {
+            Some(span) if span.style == self.style => span.text.push_str(text),
+            _ => self.row.spans.push(CueSpan {
+                text: text.to_owned(),
+                style: self.style,
+            }),
+        }
+    }
+
+    fn new_row(&mut self) {
+        let mut row = std::mem::take(&mut self.row);
+        // The text of the row is trimmed.
+        if let Some(span) = row.spans.last_mut() {
+            span.text.truncate(span.text.trim_end().len());
+        }
+        row.spans.retain(|span| !span.text.is_empty());
+        if !row.spans.is_empty() {
+            self.rows.push(row);
+        }
+        // The Teletext attributes apply to a row.
+        self.style.color = None;
+        self.pending_space = false;
+    }
+}
+
+/// Decode the rows of a text field. The upper half of the codes is decoded in `ISO 6937`
+/// when `latin` is set.
+pub(super) fn decode(text: &[u8], latin: bool) -> Vec<CueRow> {
+    let mut builder = RowsBuilder::default();
+    let mut idx = 0;
+    while let Some(&code) = text.get(idx) {
+        idx += 1;
+        match code {
+            0x00..=0x07 => {
+                builder.style.color = color(code).to_cue_color();
+                builder.pending_space = true;
+            }
+            DOUBLE_HEIGHT => {
+                builder.row.double_height = true;
+                builder.pending_space = true;
+            }
+            0x08..=0x1f => builder.pending_space = true,
+            0x20..=0x7f => builder.push(&char::from(code).to_string()),
+            ITALICS_ON => builder.style.italic = true,
+            ITALICS_OFF => builder.style.italic = false,
+            UNDERLINE_ON => builder.style.underline = true,
+            UNDERLINE_OFF => builder.style.underline = false,
+            NEW_LINE => builder.new_row(),
+            0x84..=0x9f => {}
+            0xa0..=0xff if latin => {
+                let (character, used_next) = decode_character(code, text.get(idx).copied());
+                if used_next {
+                    idx += 1;
+                }
+                builder.push(&character);
+            }
+            0xa0..=0xff => builder.push("\u{fffd}"),
+        }
+    }
+    builder.new_row();
+    builder.rows
+}
+
+/// Encode rows in a text field. Characters which can't be encoded are replaced by `?`.
+///
+/// In `teletext`, the rows are placed in boxes and separated by an empty row.
+pub(super) fn encode(rows: &[CueRow], teletext: bool) -> Vec<u8> {
+    let mut text = Vec::new();
+    let mut italic = false;
+    let mut underline = false;
+    for (idx, row) in rows.iter().enumerate() {
+        if idx > 0 {
+            text.push(NEW_LINE);
+            if teletext {
+                text.push(NEW_LINE);
+            }
+        }
+        if row.double_height {
+            text.push(DOUBLE_HEIGHT);
+        }
+        let mut color = Color::White;
+        if teletext {
+            if let Some(first) = row.spans.first() {
+                color = Color::from_cue_color(first.style.color);
+                text.push(color_code(color));
+            }
+            text.extend([START_BOX, START_BOX]);
+        }
+        for span in &row.spans {
+            let span_color = Color::from_cue_color(span.style.color);
+            if span_color != color {
+                color = span_color;
+                text.push(color_code(color));
+            }
+            if span.style.italic != italic {
+                italic = span.style.italic;
+                text.push(if italic { ITALICS_ON } else { ITALICS_OFF });
+            }
+            if span.style.underline != underline {
+                underline = span.style.underline;
+                text.push(if underline {
+                    UNDERLINE_ON
+                } else {
+                    UNDERLINE_OFF
+                });
+            }
+            for character in span.text.chars() {
+                text.extend(encode_character(character).unwrap_or_else(|| vec![b'?']));
+            }
+        }
+        if teletext {
+            text.extend([END_BOX, END_BOX]);
+        }
+    }
+    text
+}
+
+/// Get the color of a Teletext alphanumeric color code.
+const fn color(code: u8) -> Color {
+    match code {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+/// Get the Teletext alphanumeric color code of a color.
+const fn color_code(color: Color) -> u8 {
+    match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::White => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_iso_6937() {
+        let rows = decode(b"\xc2Ecole d'\xc3ete \xfb \xc8u\xd5\x8a\x8aNext", true);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].text(), "École d'ête ß ü♪");
+        assert_eq!(rows[1].text(), "Next");
+        // Unknown combinations keep the combining mark.
+        assert_eq!(decode(b"\xc2x", true)[0].text(), "x\u{301}");
+        assert_eq!(decode(b"\xc2x", false)[0].text(), "\u{fffd}x");
+    }
+
+    #[test]
+    fn decode_control_codes() {
+        let rows = decode(
+            b"\x0d\x03\x0b\x0bYellow\x02green\x0a\x0a\x8a\x8a\x80italic\x81 \x82under\x83",
+            true,
+        );
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].double_height);
+        assert_eq!(rows[0].text(), "Yellow green");
+        assert_eq!(rows[0].spans[0].style.color, Some(Color::Yellow.rgb()));
+        assert_eq!(rows[0].spans[1].style.color, Some(Color::Green.rgb()));
+        assert_eq!(rows[1].text(), "italic under");
+        assert!(rows[1].spans[0].style.italic);
+        assert!(rows[1].spans[2].style.underline);
+        assert_eq!(rows[1].spans[2].text, "under");
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let rows = decode(b"\xc2Ecole \x03\x80jaune\x81\x8a$5 \xc8Uber", true);
+        let encoded = encode(&rows, false);
+        assert_eq!(decode(&encoded, true), rows);
+        let encoded = encode(&rows, true);
+        assert_eq!(
+            decode(&encoded, true)
+                .iter()
+                .map(CueRow::text)
+                .collect::<Vec<_>>(),
+            ["École jaune", "$5 Über"]
+        );
+        assert_eq!(encode_character('€'), None);
+    }
+}
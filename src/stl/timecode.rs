@@ -0,0 +1,174 @@
+//! Timecodes of `EBU STL` files, at 25 or 30 frames per second.
+
+use crate::time::TimePoint;
+use std::fmt;
+
+/// Frame rate of the timecodes, from the disk format code of the `GSI` block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrameRate {
+    /// 25 frames per second, `STL25.01`.
+    #[default]
+    Fps25,
+    /// 30 frames per second, `STL30.01`.
+    Fps30,
+}
+
+impl FrameRate {
+    /// Number of frames per second.
+    #[must_use]
+    pub const fn fps(self) -> u8 {
+        match self {
+            Self::Fps25 => 25,
+            Self::Fps30 => 30,
+        }
+    }
+
+    /// Parse a disk format code.
+    pub(super) fn from_disk_format_code(code: &str) -> Option<Self> {
+        match code {
+            "STL25.01" => Some(Self::Fps25),
+            "STL30.01" => Some(Self::Fps30),
+            _ => None,
+        }
+    }
+
+    /// Get the disk format code.
+    pub(super) const fn disk_format_code(self) -> &'static str {
+        match self {
+            Self::Fps25 => "STL25.01",
+            Self::Fps30 => "STL30.01",
+        }
+    }
+}
+
+/// A timecode of an `EBU STL` file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timecode {
+    /// Hours.
+    pub hours: u8,
+    /// Minutes.
+    pub minutes: u8,
+    /// Seconds.
+    pub seconds: u8,
+    /// Frames.
+    pub frames: u8,
+}
+
+impl Timecode {
+    /// Create the timecode of the frame displayed at `time`.
+    ///
+    /// Like the clock of a timecode, the hours wrap around after `23`.
+    #[must_use]
+    pub fn from_time(time: TimePoint, frame_rate: FrameRate) -> Self {
+        let fps = u64::from(frame_rate.fps());
+        let msecs = u64::try_from(time.msecs()).unwrap_or_default();
+        let frames = msecs.saturating_mul(fps).saturating_add(500) / 1000;
+        let to_u8 = |value: u64| u8::try_from(value).unwrap_or(u8::MAX);
+        Self {
+            hours: to_u8(frames / (fps * 3600) % 24),
+            minutes: to_u8(frames / (fps * 60) % 60),
+            seconds: to_u8(frames / fps % 60),
+            frames: to_u8(frames % fps),
+        }
+    }
+
+    /// Time of the first frame of the timecode.
+    #[must_use]
+    pub fn time(&self, frame_rate: FrameRate) -> TimePoint {
+        let fps = i64::from(frame_rate.fps());
+        let seconds =
+            i64::from(self.hours) * 3600 + i64::from(self.minutes) * 60 + i64::from(self.seconds);
+        let frames = seconds * fps + i64::from(self.frames);
+        TimePoint::from_msecs((frames * 1000 + fps / 2) / fps)
+    }
+
+    /// Check the values of the fields.
+    const fn is_valid(self, frame_rate: FrameRate) -> bool {
+        self.hours < 24 && self.minutes < 60 && self.seconds < 60 && self.frames < frame_rate.fps()
+    }
+
+    /// Parse a timecode of the `GSI` block, in the `HHMMSSFF` format.
+    pub(super) fn parse_gsi(value: &str, frame_rate: FrameRate) -> Option<Self> {
+        if value.len() != 8 || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let field = |idx: usize| value[idx..idx + 2].parse().ok();
+        let timecode = Self {
+            hours: field(0)?,
+            minutes: field(2)?,
+            seconds: field(4)?,
+            frames: field(6)?,
+        };
+        timecode.is_valid(frame_rate).then_some(timecode)
+    }
+
+    /// Parse a timecode of a `TTI` block, with a byte per field.
+    pub(super) const fn parse_tti(value: [u8; 4], frame_rate: FrameRate) -> Option<Self> {
+        let [hours, minutes, seconds, frames] = value;
+        let timecode = Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+        };
+        if timecode.is_valid(frame_rate) {
+            Some(timecode)
+        } else {
+            None
+        }
+    }
+
+    /// Get the timecode as written in a `TTI` block.
+    pub(super) const fn to_tti(self) -> [u8; 4] {
+        [self.hours, self.minutes, self.seconds, self.frames]
+    }
+}
+
+/// Display the timecode in the `HHMMSSFF` format of the `GSI` block.
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}{:02}{:02}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_times() {
+        let timecode = Timecode::parse_gsi("10000012", FrameRate::Fps25).unwrap();
+        assert_eq!(timecode.time(FrameRate::Fps25).msecs(), 36_000_480);
+        assert_eq!(
+            Timecode::from_time(TimePoint::from_msecs(36_000_480), FrameRate::Fps25),
+            timecode
+        );
+        assert_eq!(timecode.to_string(), "10000012");
+        assert_eq!(Timecode::parse_gsi("10000025", FrameRate::Fps25), None);
+        assert_eq!(
+            Timecode::parse_tti([0, 0, 1, 29], FrameRate::Fps30)
+                .unwrap()
+                .time(FrameRate::Fps30)
+                .msecs(),
+            1967
+        );
+    }
+
+    #[test]
+    fn wrap_after_24_hours() {
+        let time = TimePoint::from_msecs((25 * 3600 + 2) * 1000);
+        let timecode = Timecode::from_time(time, FrameRate::Fps25);
+        assert_eq!(
+            Timecode::parse_tti(timecode.to_tti(), FrameRate::Fps25),
+            Some(timecode)
+        );
+        assert_eq!(timecode.time(FrameRate::Fps25).msecs(), 3_602_000);
+
+        let timecode = Timecode::from_time(TimePoint::from_msecs(i64::MAX), FrameRate::Fps30);
+        assert!(timecode.is_valid(FrameRate::Fps30));
+    }
+}
@@ -0,0 +1,147 @@
+//! Text and Timing Information (`TTI`) blocks of `EBU STL` files.
+
+use super::{text::UNUSED, FrameRate, StlError, Timecode};
+
+/// Size of a `TTI` block.
+pub(super) const TTI_SIZE: usize = 128;
+/// Size of the text field of a `TTI` block.
+pub(super) const TEXT_FIELD_SIZE: usize = 112;
+/// Extension block number of the last block of a subtitle.
+pub(super) const LAST_EXTENSION: u8 = 0xff;
+/// Maximum number of blocks of a subtitle: the extension blocks `0x00` to `0xef`,
+/// then the last block.
+pub(super) const MAX_BLOCKS: usize = 0xf1;
+/// Extension block number of the blocks of user data.
+const USER_DATA_EXTENSION: u8 = 0xfe;
+
+/// Justification of the rows of a subtitle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Justification {
+    /// Unchanged presentation, as in the text field.
+    #[default]
+    Unchanged,
+    /// Left-justified.
+    Left,
+    /// Centered.
+    Centered,
+    /// Right-justified.
+    Right,
+}
+
+/// Position of a subtitle in a cumulative set, where subtitles are added to the
+/// displayed ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CumulativeStatus {
+    /// The subtitle is not part of a cumulative set.
+    #[default]
+    NotCumulative,
+    /// First subtitle of a cumulative set.
+    First,
+    /// Intermediate subtitle of a cumulative set.
+    Intermediate,
+    /// Last subtitle of a cumulative set.
+    Last,
+}
+
+/// A `TTI` block: a subtitle, or a part of it in extension blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tti {
+    /// Subtitle group number.
+    pub group: u8,
+    /// Subtitle number.
+    pub number: u16,
+    /// Extension block number: from `0` for the blocks of a subtitle, `0xff` for the last one.
+    pub extension: u8,
+    /// Position of the subtitle in a cumulative set.
+    pub cumulative: CumulativeStatus,
+    /// Timecode of the start of the display.
+    pub time_in: Timecode,
+    /// Timecode of the end of the display.
+    pub time_out: Timecode,
+    /// Vertical position: the Teletext row, or the line in open subtitling.
+    pub vertical_position: u8,
+    /// Justification of the rows.
+    pub justification: Justification,
+    /// If the block contains a comment instead of a subtitle.
+    pub comment: bool,
+    /// Text field, with its characters and control codes.
+    pub text: [u8; TEXT_FIELD_SIZE],
+}
+
+impl Tti {
+    /// Parse the `TTI` block at `index`, of `TTI_SIZE` bytes.
+    pub(super) fn parse(
+        index: usize,
+        block: &[u8],
+        frame_rate: FrameRate,
+    ) -> Result<Self, StlError> {
+        let timecode = |start: usize| {
+            let value = [
+                block[start],
+                block[start + 1],
+                block[start + 2],
+                block[start + 3],
+            ];
+            Timecode::parse_tti(value, frame_rate).ok_or(StlError::InvalidTimecode { block: index })
+        };
+        let mut text = [UNUSED; TEXT_FIELD_SIZE];
+        text.copy_from_slice(&block[16..]);
+        Ok(Self {
+            group: block[0],
+            number: u16::from_le_bytes([block[1], block[2]]),
+            extension: block[3],
+            cumulative: match block[4] {
+                1 => CumulativeStatus::First,
+                2 => CumulativeStatus::Intermediate,
+                3 => CumulativeStatus::Last,
+                _ => CumulativeStatus::NotCumulative,
+            },
+            time_in: timecode(5)?,
+            time_out: timecode(9)?,
+            vertical_position: block[13],
+            justification: match block[14] {
+                1 => Justification::Left,
+                2 => Justification::Centered,
+                3 => Justification::Right,
+                _ => Justification::Unchanged,
+            },
+            comment: block[15] == 1,
+            text,
+        })
+    }
+
+    /// If the block contains user data instead of text.
+    pub(super) const fn is_user_data(&self) -> bool {
+        self.extension == USER_DATA_EXTENSION
+    }
+
+    /// Get the content of the `TTI` block.
+    pub(super) fn to_block(&self) -> [u8; TTI_SIZE] {
+        let mut block = [0; TTI_SIZE];
+        let [number_low, number_high] = self.number.to_le_bytes();
+        block[..5].copy_from_slice(&[
+            self.group,
+            number_low,
+            number_high,
+            self.extension,
+            match self.cumulative {
+                CumulativeStatus::NotCumulative => 0,
+                CumulativeStatus::First => 1,
+                CumulativeStatus::Intermediate => 2,
+                CumulativeStatus::Last => 3,
+            },
+        ]);
+        block[5..9].copy_from_slice(&self.time_in.to_tti());
+        block[9..13].copy_from_slice(&self.time_out.to_tti());
+        block[13] = self.vertical_position;
+        block[14] = match self.justification {
+            Justification::Unchanged => 0,
+            Justification::Left => 1,
+            Justification::Centered => 2,
+            Justification::Right => 3,
+        };
+        block[15] = u8::from(self.comment);
+        block[16..].copy_from_slice(&self.text);
+        block
+    }
+}
@@ -0,0 +1,151 @@
+//! Latin `G0` character set of Teletext, with its national option sub-sets.
+
+/// Positions of the characters replaced by the national option sub-sets.
+const NATIONAL_POSITIONS: [u8; 13] = [
+    0x23, 0x24, 0x40, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f, 0x60, 0x7b, 0x7c, 0x7d, 0x7e,
+];
+
+/// National option sub-set of the Latin `G0` character set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NationalOption {
+    /// English, the default sub-set.
+    #[default]
+    English,
+    /// German.
+    German,
+    /// Swedish, Finnish and Hungarian.
+    SwedishFinnishHungarian,
+    /// Italian.
+    Italian,
+    /// French.
+    French,
+    /// Portuguese and Spanish.
+    PortugueseSpanish,
+    /// Czech and Slovak.
+    CzechSlovak,
+    /// Polish.
+    Polish,
+    /// Turkish.
+    Turkish,
+    /// Serbian, Croatian and Slovenian.
+    SerbianCroatianSlovenian,
+    /// Romanian.
+    Romanian,
+    /// Estonian.
+    Estonian,
+    /// Lettish and Lithuanian.
+    LettishLithuanian,
+}
+
+impl NationalOption {
+    /// Get the sub-set from a 7 bits character set designation, as sent in the packets
+    /// `X/28` and `M/29`, or from the `C12`-`C14` bits of a page header for the first group.
+    ///
+    /// Return `None` for the non-Latin sets.
+    #[must_use]
+    pub const fn from_designation(designation: u8) -> Option<Self> {
+        Some(match designation & 0x7f {
+            0x00 | 0x10 => Self::English,
+            0x01 | 0x09 | 0x11 | 0x21 => Self::German,
+            0x02 | 0x0a | 0x12 => Self::SwedishFinnishHungarian,
+            0x03 | 0x0b | 0x13 => Self::Italian,
+            0x04 | 0x0c | 0x14 => Self::French,
+            0x05 | 0x15 => Self::PortugueseSpanish,
+            0x06 | 0x0e | 0x26 => Self::CzechSlovak,
+            0x08 => Self::Polish,
+            0x16 | 0x36 => Self::Turkish,
+            0x1d => Self::SerbianCroatianSlovenian,
+            0x1f => Self::Romanian,
+            0x22 => Self::Estonian,
+            0x23 => Self::LettishLithuanian,
+            _ => return None,
+        })
+    }
+
+    /// Characters of the sub-set, at the national positions.
+    const fn characters(self) -> [char; 13] {
+        match self {
+            Self::English => [
+                '£', '$', '@', '←', '½', '→', '↑', '#', '―', '¼', '‖', '¾', '÷',
+            ],
+            Self::German => [
+                '#', '$', '§', 'Ä', 'Ö', 'Ü', '^', '_', '°', 'ä', 'ö', 'ü', 'ß',
+            ],
+            Self::SwedishFinnishHungarian => [
+                '#', '¤', 'É', 'Ä', 'Ö', 'Å', 'Ü', '_', 'é', 'ä', 'ö', 'å', 'ü',
+            ],
+            Self::Italian => [
+                '£', '$', 'é', '°', 'ç', '→', '↑', '#', 'ù', 'à', 'ò', 'è', 'ì',
+            ],
+            Self::French => [
+                'é', 'ï', 'à', 'ë', 'ê', 'ù', 'î', '#', 'è', 'â', 'ô', 'û', 'ç',
+            ],
+            Self::PortugueseSpanish => [
+                'ç', '$', '¡', 'á', 'é', 'í', 'ó', 'ú', '¿', 'ü', 'ñ', 'è', 'à',
+            ],
+            Self::CzechSlovak => [
+                '#', 'ů', 'č', 'ť', 'ž', 'ý', 'í', 'ř', 'é', 'á', 'ě', 'ú', 'š',
+            ],
+            Self::Polish => [
+                '#', 'ń', 'ą', 'Ƶ', 'Ś', 'Ł', 'ć', 'ó', 'ę', 'ż', 'ś', 'ł', 'ź',
+            ],
+            Self::Turkish => [
+                '₺', 'ğ', 'İ', 'Ş', 'Ö', 'Ç', 'Ü', 'Ğ', 'ı', 'ş', 'ö', 'ç', 'ü',
+            ],
+            Self::SerbianCroatianSlovenian => [
+                '#', 'Ë', 'Č', 'Ć', 'Ž', 'Đ', 'Š', 'ë', 'č', 'ć', 'ž', 'đ', 'š',
+            ],
+            Self::Romanian => [
+                '#', '¤', 'Ţ', 'Â', 'Ş', 'Ă', 'Î', 'ı', 'ţ', 'â', 'ş', 'ă', 'î',
+            ],
+            Self::Estonian => [
+                '#', 'õ', 'Š', 'Ä', 'Ö', 'Ž', 'Ü', 'Õ', 'š', 'ä', 'ö', 'ž', 'ü',
+            ],
+            Self::LettishLithuanian => [
+                '#', '$', 'Š', 'ė', 'ę', 'Ž', 'č', 'ū', 'š', 'ą', 'ų', 'ž', 'į',
+            ],
+        }
+    }
+
+    /// Get the character of a code from `0x20` to `0x7f`.
+    pub(super) fn character(self, code: u8) -> char {
+        if code == 0x7f {
+            return '■';
+        }
+        NATIONAL_POSITIONS
+            .iter()
+            .position(|position| *position == code)
+            .map_or_else(|| char::from(code), |idx| self.characters()[idx])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn national_characters() {
+        assert_eq!(NationalOption::English.character(b'A'), 'A');
+        assert_eq!(NationalOption::English.character(0x23), '£');
+        assert_eq!(NationalOption::German.character(0x7e), 'ß');
+        assert_eq!(NationalOption::French.character(0x40), 'à');
+        assert_eq!(NationalOption::French.character(0x7f), '■');
+    }
+
+    #[test]
+    fn designations() {
+        assert_eq!(
+            NationalOption::from_designation(0x04),
+            Some(NationalOption::French)
+        );
+        assert_eq!(
+            NationalOption::from_designation(0x08),
+            Some(NationalOption::Polish)
+        );
+        assert_eq!(
+            NationalOption::from_designation(0x1f),
+            Some(NationalOption::Romanian)
+        );
+        assert_eq!(NationalOption::from_designation(0x24), None);
+    }
+}
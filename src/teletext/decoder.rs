@@ -0,0 +1,206 @@
+use super::{
+    hamming::{hamming_24_18, hamming_8_4, odd_parity},
+    page::{render, Header, PageRows, COLUMNS, PAGE_ROWS},
+    Cue, CueRow, NationalOption, TeletextError,
+};
+use crate::{dvb::DvbPacket, time::TimePoint, time::TimeSpan};
+
+/// First `data_identifier` of `EBU` data in `PES` packets.
+const EBU_DATA_IDENTIFIERS: std::ops::RangeInclusive<u8> = 0x10..=0x1f;
+/// `data_unit_id` of Teletext non-subtitle data.
+const TELETEXT_DATA_UNIT: u8 = 0x02;
+/// `data_unit_id` of Teletext subtitle data.
+const SUBTITLE_DATA_UNIT: u8 = 0x03;
+/// Length of a Teletext data unit.
+const DATA_UNIT_LEN: usize = 44;
+/// Framing code of the Teletext packets, as transmitted in `PES` packets.
+const FRAMING_CODE: u8 = 0xe4;
+/// Size of a Teletext packet: the magazine and row address, and the data.
+const PACKET_LEN: usize = 2 + COLUMNS;
+
+/// Decoder of the pages of a Teletext service, producing the cues of one page.
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    magazine: u8,
+    page: u8,
+    national_option: Option<NationalOption>,
+    /// Header of the page being received, if any.
+    receiving: Option<Header>,
+    start: TimePoint,
+    rows: PageRows,
+    /// Character set designations of the page (`X/28`) and of the magazine (`M/29`).
+    designations: (Option<u8>, Option<u8>),
+    displayed: Option<(TimePoint, Vec<CueRow>)>,
+    last_time: TimePoint,
+    cues: Vec<Cue>,
+}
+
+impl Decoder {
+    /// Create a decoder for a page, with its number as written in hexadecimal:
+    /// `0x888` for the page `888` of the magazine `8`.
+    #[must_use]
+    pub const fn new(page: u16) -> Self {
+        let [magazine, page] = page.to_be_bytes();
+        Self {
+            // The magazine `8` is transmitted as `0`.
+            magazine: magazine & 0x07,
+            page,
+            national_option: None,
+            receiving: None,
+            start: TimePoint::from_msecs(0),
+            rows: [None; PAGE_ROWS],
+            designations: (None, None),
+            displayed: None,
+            last_time: TimePoint::from_msecs(0),
+            cues: Vec::new(),
+        }
+    }
+
+    /// Use a national option sub-set, instead of the one signaled in the stream.
+    #[must_use]
+    pub const fn with_national_option(mut self, national_option: NationalOption) -> Self {
+        self.national_option = Some(national_option);
+        self
+    }
+
+    /// Decode the Teletext packets of a `PES` packet.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TeletextError::InvalidPayload` if the payload doesn't contain `EBU` data.
+    /// Will return `TeletextError::TruncatedDataUnit` if a data unit is incomplete.
+    pub fn decode(&mut self, packet: &DvbPacket) -> Result<(), TeletextError> {
+        let time = packet.time();
+        let Some((identifier, mut data)) = packet.payload.split_first() else {
+            return Err(TeletextError::InvalidPayload);
+        };
+        if !EBU_DATA_IDENTIFIERS.contains(identifier) {
+            return Err(TeletextError::InvalidPayload);
+        }
+        while let [unit_id, len, rest @ ..] = data {
+            let len = usize::from(*len);
+            let unit = rest.get(..len).ok_or(TeletextError::TruncatedDataUnit)?;
+            data = &rest[len..];
+            if !matches!(*unit_id, TELETEXT_DATA_UNIT | SUBTITLE_DATA_UNIT)
+                || len != DATA_UNIT_LEN
+                || unit[1] != FRAMING_CODE
+            {
+                continue;
+            }
+            // The bits of each byte are transmitted in reverse order.
+            let mut line = [0; PACKET_LEN];
+            for (byte, transmitted) in line.iter_mut().zip(&unit[2..]) {
+                *byte = transmitted.reverse_bits();
+            }
+            self.decode_line(time, &line);
+        }
+        Ok(())
+    }
+
+    /// Decode a Teletext packet, received at `time`.
+    fn decode_line(&mut self, time: TimePoint, line: &[u8; PACKET_LEN]) {
+        self.last_time = time;
+        let (Some(address_low), Some(address_high)) = (hamming_8_4(line[0]), hamming_8_4(line[1]))
+        else {
+            return;
+        };
+        let magazine = address_low & 0x07;
+        let number = usize::from(address_low >> 3 | address_high << 1);
+        let mut data = [0; COLUMNS];
+        data.copy_from_slice(&line[2..]);
+
+        match number {
+            0 => self.decode_header(time, magazine, &data),
+            1..=25 if magazine == self.magazine && self.receiving.is_some() => {
+                // Characters with parity errors are displayed as spaces.
+                self.rows[number] = Some(data.map(|byte| odd_parity(byte).unwrap_or(b' ')));
+            }
+            28 if magazine == self.magazine && self.receiving.is_some() => {
+                if let Some(designation) = designation(&data, &[0]) {
+                    self.designations.0 = Some(designation);
+                }
+            }
+            29 if magazine == self.magazine => {
+                if let Some(designation) = designation(&data, &[0, 4]) {
+                    self.designations.1 = Some(designation);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle a page header, which ends the transmission of the previous page.
+    fn decode_header(&mut self, time: TimePoint, magazine: u8, data: &[u8; COLUMNS]) {
+        let Some(header) = Header::parse(data) else {
+            return;
+        };
+        if self.receiving.is_some() && (magazine == self.magazine || header.serial) {
+            self.complete();
+        }
+        if magazine == self.magazine && header.page == self.page {
+            if header.erase {
+                self.rows = [None; PAGE_ROWS];
+            }
+            self.designations.0 = None;
+            self.start = time;
+            self.receiving = Some(header);
+        }
+    }
+
+    /// Update the displayed content with the received page.
+    fn complete(&mut self) {
+        let Some(header) = self.receiving.take() else {
+            return;
+        };
+        // The national option bits of the header complete the designated character set.
+        let national_option = self.national_option.unwrap_or_else(|| {
+            let group = self.designations.0.or(self.designations.1).unwrap_or(0) & 0x78;
+            NationalOption::from_designation(group | header.national_option)
+                .or_else(|| NationalOption::from_designation(header.national_option))
+                .unwrap_or_default()
+        });
+        let rows = render(&self.rows, header.boxed(), national_option);
+        if self
+            .displayed
+            .as_ref()
+            .is_some_and(|(_, displayed)| *displayed == rows)
+        {
+            return;
+        }
+        if let Some((start, rows)) = self.displayed.take() {
+            self.cues.push(Cue {
+                time_span: TimeSpan::new(start, self.start.max(start)),
+                rows,
+            });
+        }
+        if !rows.is_empty() {
+            self.displayed = Some((self.start, rows));
+        }
+    }
+
+    /// Finish the decoding, and get the cues of the page.
+    ///
+    /// A cue still displayed at the end of the stream ends at the last packet.
+    #[must_use]
+    pub fn finish(mut self) -> Vec<Cue> {
+        self.complete();
+        if let Some((start, rows)) = self.displayed.take() {
+            self.cues.push(Cue {
+                time_span: TimeSpan::new(start, self.last_time.max(start)),
+                rows,
+            });
+        }
+        self.cues
+    }
+}
+
+/// Get the default character set designation of a packet `X/28` or `M/29`,
+/// if its designation code is one of `codes`.
+fn designation(data: &[u8; COLUMNS], codes: &[u8]) -> Option<u8> {
+    let code = hamming_8_4(data[0])?;
+    if !codes.contains(&code) {
+        return None;
+    }
+    let triplet = hamming_24_18([data[1], data[2], data[3]])?;
+    u8::try_from(triplet >> 7 & 0x7f).ok()
+}
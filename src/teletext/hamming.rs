@@ -0,0 +1,111 @@
+//! Error protection codes of the Teletext packets.
+
+/// Codewords of the `Hamming 8/4` code, indexed by their value.
+const HAMMING_8_4: [u8; 16] = [
+    0x15, 0x02, 0x49, 0x5e, 0x64, 0x73, 0x38, 0x2f, 0xd0, 0xc7, 0x8c, 0x9b, 0xa1, 0xb6, 0xfd, 0xea,
+];
+
+/// Decode a `Hamming 8/4` protected byte, correcting single bit errors.
+///
+/// Return `None` for an uncorrectable error.
+pub(super) fn hamming_8_4(byte: u8) -> Option<u8> {
+    // The codewords have a minimum distance of 4: at most one is at a distance of 1.
+    (0..)
+        .zip(HAMMING_8_4)
+        .find(|(_, codeword)| (codeword ^ byte).count_ones() <= 1)
+        .map(|(value, _)| value)
+}
+
+/// Decode a `Hamming 24/18` protected triplet, correcting single bit errors.
+///
+/// Return the 18 data bits, or `None` for an uncorrectable error.
+pub(super) fn hamming_24_18(triplet: [u8; 3]) -> Option<u32> {
+    let mut bits = u32::from_le_bytes([triplet[0], triplet[1], triplet[2], 0]);
+
+    // Each of the 5 first parity tests covers the bit positions (from 1) having the bit of
+    // the test set. The last test covers all bits. All tests give an odd parity.
+    let syndrome = (0..5)
+        .filter(|test| {
+            let covered = (1..=23)
+                .filter(|position| position & (1 << test) != 0)
+                .fold(0_u32, |mask, position| mask | 1 << (position - 1));
+            (bits & covered).count_ones() % 2 == 0
+        })
+        .fold(0, |syndrome, test| syndrome | 1 << test);
+    let even_parity = bits.count_ones() % 2 == 0;
+    match (syndrome, even_parity) {
+        (0, _) => {}
+        (position @ 1..=23, true) => bits ^= 1 << (position - 1),
+        _ => return None,
+    }
+
+    Some((bits >> 2 & 0x01) | (bits >> 3 & 0x0e) | (bits >> 4 & 0x07f0) | (bits >> 5 & 0x0003_f800))
+}
+
+/// Decode a character with an odd parity bit. Return `None` on parity error.
+pub(super) const fn odd_parity(byte: u8) -> Option<u8> {
+    if byte.count_ones() % 2 == 1 {
+        Some(byte & 0x7f)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode 18 data bits in a `Hamming 24/18` triplet.
+    fn encode_24_18(data: u32) -> [u8; 3] {
+        let mut bits = (data & 0x01) << 2
+            | (data & 0x0e) << 3
+            | (data & 0x07f0) << 4
+            | (data & 0x0003_f800) << 5;
+        for test in 0..5 {
+            let covered = (1..=23)
+                .filter(|position| position & (1 << test) != 0)
+                .fold(0_u32, |mask, position| mask | 1 << (position - 1));
+            if (bits & covered).count_ones() % 2 == 0 {
+                bits |= 1 << ((1 << test) - 1);
+            }
+        }
+        if bits.count_ones() % 2 == 0 {
+            bits |= 1 << 23;
+        }
+        let [b0, b1, b2, _] = bits.to_le_bytes();
+        [b0, b1, b2]
+    }
+
+    #[test]
+    fn decode_8_4() {
+        for (value, codeword) in (0..).zip(HAMMING_8_4) {
+            assert_eq!(hamming_8_4(codeword), Some(value));
+            for bit in 0..8 {
+                assert_eq!(hamming_8_4(codeword ^ 1 << bit), Some(value));
+            }
+        }
+        assert_eq!(hamming_8_4(0x15 ^ 0x03), None);
+    }
+
+    #[test]
+    fn decode_24_18() {
+        for data in [0, 1, 0x2a5a5, 0x3ffff, 0x12345] {
+            let triplet = encode_24_18(data);
+            assert_eq!(hamming_24_18(triplet), Some(data));
+            for bit in 0..24 {
+                let mut corrupted = triplet;
+                corrupted[bit / 8] ^= 1 << (bit % 8);
+                assert_eq!(hamming_24_18(corrupted), Some(data));
+            }
+            let mut corrupted = triplet;
+            corrupted[0] ^= 0x11;
+            assert_eq!(hamming_24_18(corrupted), None);
+        }
+    }
+
+    #[test]
+    fn check_parity() {
+        assert_eq!(odd_parity(0xc1), Some(b'A'));
+        assert_eq!(odd_parity(b'A'), None);
+    }
+}
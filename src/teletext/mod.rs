@@ -0,0 +1,194 @@
+//! Teletext subtitles functionality (`EBU ETS 300 706`), as carried in `DVB` streams.
+//!
+//! Teletext pages are sent row by row in packets `X/0` (page header) to `X/25`, with the
+//! addresses and page headers protected by `Hamming 8/4` codes, and the characters by a
+//! parity bit. Subtitle pages, often `888`, only display the text placed in boxes.
+//!
+//! The [`Decoder`] reassembles a page from the `PES` packets of the stream (see
+//! [`DvbPacket`]), and produces timed cues with the displayed rows, their colors and
+//! the characters of the national option sub-set of the page.
+//!
+//! [`DvbPacket`]: crate::dvb::DvbPacket
+mod charset;
+mod decoder;
+mod hamming;
+mod page;
+
+pub use charset::NationalOption;
+pub use decoder::Decoder;
+pub use page::{Color, Cue, CueRow, CueSpan};
+
+use crate::dvb::DvbPacket;
+use thiserror::Error;
+
+/// Error for Teletext handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TeletextError {
+    /// The payload of a `PES` packet doesn't start with an `EBU` data identifier.
+    #[error("the PES payload is not an EBU data payload")]
+    InvalidPayload,
+
+    /// A data unit is incomplete.
+    #[error("data unit is truncated")]
+    TruncatedDataUnit,
+}
+
+/// Decode the cues of a page from the `PES` packets of a Teletext stream.
+///
+/// The `page` number is written in hexadecimal, like `0x888`.
+///
+/// # Errors
+///
+/// Will return an error if the payload of a packet is invalid.
+pub fn decode_cues<'a, I>(packets: I, page: u16) -> Result<Vec<Cue>, TeletextError>
+where
+    I: IntoIterator<Item = &'a DvbPacket>,
+{
+    let mut decoder = Decoder::new(page);
+    for packet in packets {
+        decoder.decode(packet)?;
+    }
+    Ok(decoder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{TimePoint, TimeSpan};
+    use assert_matches2::assert_matches;
+
+    /// Encode a value with the `Hamming 8/4` code.
+    fn hamming(value: u8) -> u8 {
+        (0..=255)
+            .find(|byte| hamming::hamming_8_4(*byte) == Some(value))
+            .unwrap()
+    }
+
+    /// Add the odd parity bit to a character.
+    const fn parity(code: u8) -> u8 {
+        if code.count_ones() % 2 == 0 {
+            code | 0x80
+        } else {
+            code
+        }
+    }
+
+    /// Build a data unit containing a Teletext packet.
+    fn data_unit(magazine: u8, number: u8, data: [u8; 40]) -> Vec<u8> {
+        let mut unit = vec![0x03, 44, 0x00, 0xe4];
+        let address = [hamming(magazine | (number & 1) << 3), hamming(number >> 1)];
+        unit.extend(address.iter().chain(&data).map(|byte| byte.reverse_bits()));
+        unit
+    }
+
+    /// Build a page header packet.
+    fn header(magazine: u8, page: u8, erase: bool, national_option: u8) -> Vec<u8> {
+        let mut data = [parity(b' '); 40];
+        let values = [
+            page & 0x0f,
+            page >> 4,
+            0,
+            if erase { 0x08 } else { 0 },
+            0,
+            0x08,
+            0,
+            national_option << 1,
+        ];
+        for (byte, value) in data.iter_mut().zip(values) {
+            *byte = hamming(value);
+        }
+        data_unit(magazine, 0, data)
+    }
+
+    /// Build a row packet.
+    fn row(magazine: u8, number: u8, text: &[u8]) -> Vec<u8> {
+        let mut data = [parity(b' '); 40];
+        for (byte, code) in data.iter_mut().zip(text) {
+            *byte = parity(*code);
+        }
+        data_unit(magazine, number, data)
+    }
+
+    fn packet(secs: u64, units: &[Vec<u8>]) -> DvbPacket {
+        let mut payload = vec![0x10];
+        payload.extend(units.iter().flatten());
+        DvbPacket {
+            pts: secs * 90_000,
+            payload,
+        }
+    }
+
+    #[test]
+    fn decode_subtitle_page() {
+        let packets = [
+            packet(
+                1,
+                &[
+                    header(0, 0x88, true, 1),
+                    row(0, 22, b"\x0b\x0bGr\x7dn\x0a\x0a"),
+                    // A page of another magazine doesn't end the page.
+                    header(1, 0x00, true, 0),
+                ],
+            ),
+            packet(
+                2,
+                &[
+                    // The same content is sent again.
+                    header(0, 0x88, false, 1),
+                    header(0, 0x89, true, 1),
+                ],
+            ),
+            packet(3, &[header(0, 0x88, true, 1), header(0, 0x89, true, 1)]),
+            packet(
+                4,
+                &[
+                    header(0, 0x88, true, 4),
+                    row(0, 20, b"\x0b\x0b\x40 bient\x7ct\x0a"),
+                ],
+            ),
+            packet(6, &[header(0, 0x88, true, 4)]),
+        ];
+        let cues = decode_cues(&packets, 0x888).unwrap();
+        assert_eq!(cues.len(), 2);
+        let time_span =
+            |start, end| TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end));
+        assert_eq!(cues[0].time_span, time_span(1000, 3000));
+        assert_eq!(cues[0].plain_text(), "Grün");
+        assert_eq!(cues[0].rows[0].row, 22);
+        assert_eq!(cues[0].rows[0].column, 2);
+        assert_eq!(cues[1].time_span, time_span(4000, 6000));
+        assert_eq!(cues[1].plain_text(), "à bientôt");
+
+        assert!(decode_cues(&packets, 0x100).unwrap().is_empty());
+        let forced = packets
+            .iter()
+            .fold(
+                Decoder::new(0x888).with_national_option(NationalOption::English),
+                |mut decoder, packet| {
+                    decoder.decode(packet).unwrap();
+                    decoder
+                },
+            )
+            .finish();
+        assert_eq!(forced[0].plain_text(), "Gr¾n");
+    }
+
+    #[test]
+    fn invalid_payloads() {
+        let mut decoder = Decoder::new(0x888);
+        let packet = DvbPacket {
+            pts: 0,
+            payload: vec![0x20, 0x00],
+        };
+        assert_matches!(decoder.decode(&packet), Err(TeletextError::InvalidPayload));
+        let packet = DvbPacket {
+            pts: 0,
+            payload: vec![0x10, 0x03, 44, 0x00],
+        };
+        assert_matches!(
+            decoder.decode(&packet),
+            Err(TeletextError::TruncatedDataUnit)
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use super::{hamming::hamming_8_4, NationalOption};
+use crate::{
+    text::{self, CuePosition, CueStyle},
+    time::TimeSpan,
+};
+use image::Rgb;
+
+/// Number of characters in a row.
+pub(super) const COLUMNS: usize = 40;
+/// Number of packets carrying rows of a page, from `X/0` to `X/25`.
+pub(super) const PAGE_ROWS: usize = 26;
+
+/// Character codes of the rows of a page, by packet number.
+pub(super) type PageRows = [Option<[u8; COLUMNS]>; PAGE_ROWS];
+
+/// Color of the text, set by the spacing attributes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Color {
+    /// Black.
+    Black,
+    /// Red.
+    Red,
+    /// Green.
+    Green,
+    /// Yellow.
+    Yellow,
+    /// Blue.
+    Blue,
+    /// Magenta.
+    Magenta,
+    /// Cyan.
+    Cyan,
+    /// White, the default color.
+    #[default]
+    White,
+}
+
+impl Color {
+    /// Get the color of an alphanumeric or mosaic color attribute.
+    const fn from_attribute(code: u8) -> Self {
+        match code & 0x07 {
+            0 => Self::Black,
+            1 => Self::Red,
+            2 => Self::Green,
+            3 => Self::Yellow,
+            4 => Self::Blue,
+            5 => Self::Magenta,
+            6 => Self::Cyan,
+            _ => Self::White,
+        }
+    }
+
+    /// Get the `RGB` value of the color.
+    #[must_use]
+    pub const fn rgb(self) -> Rgb<u8> {
+        match self {
+            Self::Black => Rgb([0, 0, 0]),
+            Self::Red => Rgb([255, 0, 0]),
+            Self::Green => Rgb([0, 255, 0]),
+            Self::Yellow => Rgb([255, 255, 0]),
+            Self::Blue => Rgb([0, 0, 255]),
+            Self::Magenta => Rgb([255, 0, 255]),
+            Self::Cyan => Rgb([0, 255, 255]),
+            Self::White => Rgb([255, 255, 255]),
+        }
+    }
+
+    /// Get the nearest color of a `RGB` value, each component being on or off.
+    #[must_use]
+    pub fn from_rgb(Rgb([red, green, blue]): Rgb<u8>) -> Self {
+        let on = |component: u8| u8::from(component >= 0x80);
+        Self::from_attribute(on(red) | on(green) << 1 | on(blue) << 2)
+    }
+
+    /// Get the color of a [`text::CueStyle`], the default white being `None`.
+    pub(crate) fn to_cue_color(self) -> Option<Rgb<u8>> {
+        (self != Self::White).then(|| self.rgb())
+    }
+
+    /// Get the nearest color of a [`text::CueStyle`] color, white if it's not set.
+    pub(crate) fn from_cue_color(color: Option<Rgb<u8>>) -> Self {
+        color.map_or(Self::White, Self::from_rgb)
+    }
+}
+
+/// A text with its color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueSpan {
+    /// Text of the span.
+    pub text: String,
+    /// Color of the text.
+    pub color: Color,
+}
+
+/// A row of displayed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueRow {
+    /// Row on the screen, from `1` (below the header) to `24` (bottom).
+    pub row: u8,
+    /// Column of the first character, from `0` to `39`.
+    pub column: u8,
+    /// If the text is displayed in double height, over this row and the next one.
+    pub double_height: bool,
+    /// Colored texts of the row.
+    pub spans: Vec<CueSpan>,
+}
+
+impl CueRow {
+    /// Get the text of the row, without color.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+/// A Teletext page displayed during a time span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// Time span of the cue.
+    pub time_span: TimeSpan,
+    /// Rows of the cue, from top to bottom.
+    pub rows: Vec<CueRow>,
+}
+
+impl Cue {
+    /// Get the text of the cue, with the rows separated by `\n`.
+    #[must_use]
+    pub fn plain_text(&self) -> String {
+        self.rows
+            .iter()
+            .map(CueRow::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Convert to the shared text model. The colors are kept, and the line is the position of the
+/// first row on the 25 rows of the screen.
+impl From<&Cue> for text::Cue {
+    fn from(cue: &Cue) -> Self {
+        Self {
+            time_span: cue.time_span,
+            position: CuePosition {
+                line: cue
+                    .rows
+                    .first()
+                    .map(|row| row.row.saturating_mul(4).min(100)),
+                align: None,
+            },
+            rows: cue
+                .rows
+                .iter()
+                .map(|row| text::CueRow {
+                    double_height: row.double_height,
+                    spans: row
+                        .spans
+                        .iter()
+                        .map(|span| text::CueSpan {
+                            text: span.text.clone(),
+                            style: CueStyle {
+                                color: span.color.to_cue_color(),
+                                ..CueStyle::default()
+                            },
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Content of a page header, packet `X/0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools)]
+pub(super) struct Header {
+    /// Page number in the magazine, `0xff` for time filling headers.
+    pub page: u8,
+    /// `C4`: the previous content of the page must be erased.
+    pub erase: bool,
+    /// `C5`: the page is a newsflash, displayed in boxes.
+    pub newsflash: bool,
+    /// `C6`: the page is a subtitle, displayed in boxes.
+    pub subtitle: bool,
+    /// `C11`: the pages of all magazines are transmitted one after the other.
+    pub serial: bool,
+    /// `C12`-`C14`: national option sub-set.
+    pub national_option: u8,
+}
+
+impl Header {
+    /// Parse the data of a header packet.
+    pub fn parse(data: &[u8; COLUMNS]) -> Option<Self> {
+        let mut values = [0; 8];
+        for (value, byte) in values.iter_mut().zip(data) {
+            *value = hamming_8_4(*byte)?;
+        }
+        Some(Self {
+            page: values[0] | values[1] << 4,
+            erase: values[3] & 0x08 != 0,
+            newsflash: values[5] & 0x04 != 0,
+            subtitle: values[5] & 0x08 != 0,
+            serial: values[7] & 0x01 != 0,
+            national_option: values[7] >> 1 & 0x07,
+        })
+    }
+
+    /// If only the text in boxes is displayed.
+    pub const fn boxed(self) -> bool {
+        self.newsflash || self.subtitle
+    }
+}
+
+/// Get the displayed rows of a page, from row `1` to row `24`.
+///
+/// When `boxed` is set, only the text between the start box and end box attributes is
+/// displayed. The row following a row using double height is hidden.
+pub(super) fn render(rows: &PageRows, boxed: bool, option: NationalOption) -> Vec<CueRow> {
+    let mut displayed = Vec::new();
+    let mut hidden = false;
+    for (number, codes) in (0..).zip(rows).take(25).skip(1) {
+        if hidden {
+            hidden = false;
+            continue;
+        }
+        let Some(codes) = codes else {
+            continue;
+        };
+        let (row, uses_double_height) = render_row(number, codes, boxed, option);
+        hidden = uses_double_height;
+        displayed.extend(row);
+    }
+    displayed
+}
+
+/// Get the displayed text of a row, and if it uses double height.
+fn render_row(
+    number: u8,
+    codes: &[u8; COLUMNS],
+    boxed: bool,
+    option: NationalOption,
+) -> (Option<CueRow>, bool) {
+    let mut color = Color::White;
+    let mut mosaic = false;
+    let mut in_box = false;
+    let mut double_height = false;
+    let mut uses_double_height = false;
+    let mut displayed_double_height = false;
+
+    // Attributes are displayed as spaces, and take effect on the following characters.
+    let mut cells = Vec::with_capacity(COLUMNS);
+    for &code in codes {
+        let visible = !boxed || in_box;
+        let character = if code < 0x20 || !visible || (mosaic && code & 0x20 != 0) {
+            ' '
+        } else {
+            displayed_double_height |= double_height;
+            option.character(code)
+        };
+        cells.push((character, color));
+
+        match code {
+            0x00..=0x07 => {
+                color = Color::from_attribute(code);
+                mosaic = false;
+            }
+            0x10..=0x17 => {
+                color = Color::from_attribute(code);
+                mosaic = true;
+            }
+            0x0a => in_box = false,
+            0x0b => in_box = true,
+            0x0c | 0x0e => double_height = false,
+            0x0d | 0x0f => {
+                double_height = true;
+                uses_double_height = true;
+            }
+            _ => {}
+        }
+    }
+
+    let first = cells.iter().position(|(character, _)| *character != ' ');
+    let last = cells.iter().rposition(|(character, _)| *character != ' ');
+    let (Some(first), Some(last)) = (first, last) else {
+        return (None, uses_double_height);
+    };
+    let mut spans = Vec::<CueSpan>::new();
+    for &(character, color) in &cells[first..=last] {
+        match spans.last_mut() {
+            // Spaces don't start a new span.
+            Some(span) if span.color == color || character == ' ' => span.text.push(character),
+            _ => spans.push(CueSpan {
+                text: character.to_string(),
+                color,
+            }),
+        }
+    }
+    let row = CueRow {
+        row: number,
+        column: u8::try_from(first).unwrap_or_default(),
+        double_height: displayed_double_height,
+        spans,
+    };
+    (Some(row), uses_double_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(content: &[u8]) -> [u8; COLUMNS] {
+        let mut row = [b' '; COLUMNS];
+        row[..content.len()].copy_from_slice(content);
+        row
+    }
+
+    #[test]
+    fn render_boxed_rows() {
+        let mut rows: PageRows = [None; PAGE_ROWS];
+        rows[20] = Some(row(b"hidden \x03\x0d\x0b\x0bBonjour \x06@ tous\x0a\x0a"));
+        rows[21] = Some(row(b"\x0b\x0bhidden by double height"));
+        rows[22] = Some(row(b"  \x0b\x0b Second\x0a"));
+
+        let displayed = render(&rows, true, NationalOption::French);
+        assert_eq!(displayed.len(), 2);
+        assert_eq!(displayed[0].row, 20);
+        assert_eq!(displayed[0].column, 11);
+        assert!(displayed[0].double_height);
+        assert_eq!(displayed[0].text(), "Bonjour  à tous");
+        assert_eq!(displayed[0].spans[0].color, Color::Yellow);
+        assert_eq!(displayed[0].spans[1].color, Color::Cyan);
+        assert_eq!(displayed[1].row, 22);
+        assert_eq!(displayed[1].text(), "Second");
+
+        let displayed = render(&rows, false, NationalOption::English);
+        assert_eq!(displayed[0].text(), "hidden     Bonjour  @ tous");
+    }
+
+    #[test]
+    fn convert_to_text_cue() {
+        let mut rows: PageRows = [None; PAGE_ROWS];
+        rows[20] = Some(row(b"White cyan
+
+"));
+        let cue = Cue {
+            time_span: TimeSpan::default(),
+            rows: render(&rows, true, NationalOption::English),
+        };
+        let text_cue = text::Cue::from(&cue);
+        assert_eq!(text_cue.position.line, Some(80));
+        assert_eq!(text_cue.plain_text(), cue.plain_text());
+        let spans = &text_cue.rows[0].spans;
+        assert_eq!(spans[0].style.color, None);
+        assert_eq!(spans[1].style.color, Some(Rgb([0, 255, 255])));
+
+        assert_eq!(Color::from_rgb(Rgb([250, 200, 10])), Color::Yellow);
+        assert_eq!(Color::from_cue_color(None), Color::White);
+    }
+}
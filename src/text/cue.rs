@@ -0,0 +1,170 @@
+//! Text cues shared by the text formats.
+//!
+//! The simple text formats read and write them directly, the other formats
+//! (`STL`, Teletext, `SCC`, `ASS`, `TTML`, `Matroska`) convert their cues from and to them.
+
+use crate::time::TimeSpan;
+use image::Rgb;
+
+/// Style of a subtitle text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools)]
+pub struct CueStyle {
+    /// If the text is bold.
+    pub bold: bool,
+    /// If the text is italic.
+    pub italic: bool,
+    /// If the text is underlined.
+    pub underline: bool,
+    /// If the text is struck out.
+    pub strike_out: bool,
+    /// Color of the text, `None` for the default color of the player.
+    pub color: Option<Rgb<u8>>,
+}
+
+impl CueStyle {
+    /// Get the style with only the attributes set in both styles.
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        Self {
+            bold: self.bold && other.bold,
+            italic: self.italic && other.italic,
+            underline: self.underline && other.underline,
+            strike_out: self.strike_out && other.strike_out,
+            color: self.color.filter(|_| self.color == other.color),
+        }
+    }
+
+    /// Get the style with the attributes set in one of the styles.
+    /// The color of `other` is used if both colors are set.
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            bold: self.bold || other.bold,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+            strike_out: self.strike_out || other.strike_out,
+            color: other.color.or(self.color),
+        }
+    }
+}
+
+/// A text with its style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueSpan {
+    /// Text of the span.
+    pub text: String,
+    /// Style of the text.
+    pub style: CueStyle,
+}
+
+/// A row of a subtitle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CueRow {
+    /// If the row is displayed in double height, in Teletext.
+    pub double_height: bool,
+    /// Styled texts of the row.
+    pub spans: Vec<CueSpan>,
+}
+
+impl CueRow {
+    /// Create a row of text without style.
+    #[must_use]
+    pub fn plain(text: &str) -> Self {
+        let mut row = Self::default();
+        row.push(text, CueStyle::default());
+        row
+    }
+
+    /// Add a text at the end of the row, merged with the last span if it has the same style.
+    pub fn push(&mut self, text: &str, style: CueStyle) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(CueSpan {
+                text: text.to_owned(),
+                style,
+            }),
+        }
+    }
+
+    /// Get the text of the row, without style.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Get the style common to all the spans of the row.
+    #[must_use]
+    pub fn common_style(&self) -> CueStyle {
+        self.spans
+            .iter()
+            .map(|span| span.style)
+            .reduce(CueStyle::intersection)
+            .unwrap_or_default()
+    }
+}
+
+/// Horizontal alignment of the rows of a subtitle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CueAlign {
+    /// Aligned on the left.
+    Left,
+    /// Centered.
+    Center,
+    /// Aligned on the right.
+    Right,
+}
+
+/// Position of a subtitle on the screen. Unknown values are left to the player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CuePosition {
+    /// Vertical position of the top of the first row, in percent of the screen height
+    /// from the top.
+    pub line: Option<u8>,
+    /// Horizontal alignment of the rows.
+    pub align: Option<CueAlign>,
+}
+
+/// A subtitle of a text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// Time span of the subtitle.
+    pub time_span: TimeSpan,
+    /// Position of the subtitle.
+    pub position: CuePosition,
+    /// Rows of the subtitle, from top to bottom.
+    pub rows: Vec<CueRow>,
+}
+
+impl Cue {
+    /// Create a subtitle without style nor position, with a row per line of `text`.
+    #[must_use]
+    pub fn plain(time_span: TimeSpan, text: &str) -> Self {
+        Self {
+            time_span,
+            position: CuePosition::default(),
+            rows: text.lines().map(CueRow::plain).collect(),
+        }
+    }
+
+    /// Get the text of the subtitle, with the rows separated by `\n`.
+    #[must_use]
+    pub fn plain_text(&self) -> String {
+        self.rows
+            .iter()
+            .map(CueRow::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Create subtitles without style from texts.
+pub(super) fn plain_cues(subtitles: &[(TimeSpan, String)]) -> Vec<Cue> {
+    subtitles
+        .iter()
+        .map(|(time_span, text)| Cue::plain(*time_span, text))
+        .collect()
+}
@@ -0,0 +1,265 @@
+//! `LRC` lyrics: `[mm:ss.xx]text` lines, with only the start time of each line.
+//!
+//! A line ends at the start of the next one, and a line without text marks the end of
+//! the previous one. The `[key:value]` tags (like `[ar:Artist]`) are kept, except the
+//! `[offset:ms]` tag which is applied to the times.
+
+use super::{
+    cue::plain_cues,
+    numbered_lines, read_file, read_stream,
+    timestamp::{parse_time, Timestamp},
+    Cue, CuePosition, CueRow, TextError,
+};
+use crate::time::{TimePoint, TimeSpan};
+use std::{
+    io::{self, Read},
+    path::Path,
+};
+
+/// Duration of the last line, when no line marks its end, in milliseconds.
+const LAST_LINE_DURATION: i64 = 5000;
+
+/// Content of a `LRC` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lrc {
+    /// Tags with their values, like `("ar", "Artist")`.
+    pub tags: Vec<(String, String)>,
+    /// Lines of the lyrics, in time order.
+    pub cues: Vec<Cue>,
+}
+
+impl Lrc {
+    /// Create a `LRC` content from subtitles, without tags.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)]) -> Self {
+        Self {
+            tags: Vec::new(),
+            cues: plain_cues(subtitles),
+        }
+    }
+
+    /// Read a `LRC` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Io` if not able to read the file.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TextError> {
+        Self::parse(&read_file(path.as_ref())?)
+    }
+
+    /// Read a `LRC` content from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Read` if not able to read from the `reader`.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, TextError> {
+        Self::parse(&read_stream(reader)?)
+    }
+
+    /// Parse a `LRC` content.
+    ///
+    /// A line can start with several timestamps, for repeated lyrics. The word timestamps
+    /// of the enhanced format (`<mm:ss.xx>`) are removed. Lines without timestamp nor tag
+    /// are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::InvalidTiming` if a timestamp is not valid.
+    pub fn parse(content: &str) -> Result<Self, TextError> {
+        let mut tags = Vec::new();
+        let mut lines = Vec::new();
+        for (line_num, line) in numbered_lines(content) {
+            let mut rest = line.trim();
+            let mut times = Vec::new();
+            while let Some((group, text)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+                if group.starts_with(|c: char| c.is_ascii_digit()) {
+                    let time = parse_time(group).ok_or_else(|| TextError::InvalidTiming {
+                        line: line_num,
+                        value: line.into(),
+                    })?;
+                    times.push(time);
+                } else if let Some((key, value)) = group.split_once(':') {
+                    tags.push((key.trim().to_owned(), value.trim().to_owned()));
+                }
+                rest = text;
+            }
+            let text = strip_word_times(rest);
+            lines.extend(times.into_iter().map(|time| (time, text.clone())));
+        }
+
+        // A positive offset shows the lyrics sooner.
+        let mut offset = 0;
+        tags.retain(|(key, value)| {
+            let is_offset = key.eq_ignore_ascii_case("offset");
+            if is_offset {
+                offset = value.parse::<i64>().unwrap_or_default();
+            }
+            !is_offset
+        });
+        lines.sort_by_key(|(time, _)| *time);
+
+        let cues = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, text))| !text.is_empty())
+            .map(|(idx, (start, text))| {
+                let end = lines[idx + 1..]
+                    .iter()
+                    .map(|(time, _)| *time)
+                    .find(|time| time > start)
+                    .unwrap_or_else(|| {
+                        TimePoint::from_msecs(start.msecs().saturating_add(LAST_LINE_DURATION))
+                    });
+                let time =
+                    |time: TimePoint| TimePoint::from_msecs(time.msecs().saturating_sub(offset));
+                Cue {
+                    time_span: TimeSpan::new(time(*start), time(end)),
+                    position: CuePosition::default(),
+                    rows: vec![CueRow::plain(text)],
+                }
+            })
+            .collect();
+        Ok(Self { tags, cues })
+    }
+
+    /// Write the content in the `LRC` format.
+    ///
+    /// The rows of each line are joined with spaces. A line without text is added at the
+    /// end of a line, if the next line doesn't start at this time.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `io::Error` if writing fails.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        for (key, value) in &self.tags {
+            writeln!(writer, "[{key}:{value}]")?;
+        }
+        let timestamp = |time| Timestamp {
+            time,
+            hour_digits: None,
+            fraction_digits: 2,
+        };
+        for (idx, cue) in self.cues.iter().enumerate() {
+            let rows = cue.rows.iter().map(CueRow::text).collect::<Vec<_>>();
+            writeln!(
+                writer,
+                "[{}]{}",
+                timestamp(cue.time_span.start),
+                rows.join(" ")
+            )?;
+            let end = cue.time_span.end;
+            if !self
+                .cues
+                .get(idx + 1)
+                .is_some_and(|next| next.time_span.start == end)
+            {
+                writeln!(writer, "[{}]", timestamp(end))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Remove the word timestamps (`<mm:ss.xx>`) of a text.
+fn strip_word_times(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let tag = &rest[start..=start + len];
+        if parse_time(&tag[1..len]).is_none() {
+            result.push_str(tag);
+        }
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    result.trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches2::assert_matches;
+
+    const EXAMPLE: &str = "[ar:Artist]
+[ti:Title]
+[offset:+500]
+[00:12.00]<00:12.00>Line <00:12.50>one
+[00:17.20][01:10.00]Chorus
+[00:21.10]Line three
+[00:25.00]
+";
+
+    #[test]
+    fn parse_lyrics() {
+        let lrc = Lrc::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            lrc.tags,
+            [
+                ("ar".to_owned(), "Artist".to_owned()),
+                ("ti".to_owned(), "Title".to_owned())
+            ]
+        );
+        let cues = lrc
+            .cues
+            .iter()
+            .map(|cue| {
+                (
+                    cue.time_span.start.msecs(),
+                    cue.time_span.end.msecs(),
+                    cue.plain_text(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cues,
+            [
+                (11_500, 16_700, "Line one".to_owned()),
+                (16_700, 20_600, "Chorus".to_owned()),
+                (20_600, 24_500, "Line three".to_owned()),
+                (69_500, 74_500, "Chorus".to_owned()),
+            ]
+        );
+        assert_matches!(
+            Lrc::parse("[00:12.00]Text\n[00:1a.00]Text"),
+            Err(TextError::InvalidTiming { line: 2, .. })
+        );
+    }
+
+    #[test]
+    fn parse_extreme_values() {
+        let lrc = Lrc::parse("[153722867280912:55.00]Text").unwrap();
+        let time_span = lrc.cues[0].time_span;
+        assert_eq!(time_span.start, TimePoint::from_msecs(i64::MAX - 807));
+        assert_eq!(time_span.end, TimePoint::from_msecs(i64::MAX));
+
+        let lrc = Lrc::parse("[offset:-9223372036854775808]\n[00:01.00]Text").unwrap();
+        assert_eq!(lrc.cues[0].time_span.start, TimePoint::from_msecs(i64::MAX));
+
+        for line in [
+            "[00:00.999999999999999999]Text",
+            "[153722867280913:00.00]Text",
+        ] {
+            assert_matches!(
+                Lrc::parse(line),
+                Err(TextError::InvalidTiming { line: 1, .. })
+            );
+        }
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let lrc = Lrc::parse(EXAMPLE).unwrap();
+        let mut content = Vec::new();
+        lrc.write(&mut content).unwrap();
+        let content = String::from_utf8(content).unwrap();
+        assert!(content.starts_with("[ar:Artist]\n[ti:Title]\n[00:11.50]Line one\n"));
+        assert!(content.contains("[00:20.60]Line three\n[00:24.50]\n"));
+        assert_eq!(Lrc::parse(&content).unwrap(), lrc);
+    }
+}
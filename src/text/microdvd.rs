@@ -0,0 +1,363 @@
+//! `MicroDVD` subtitles: `{start}{end}text` lines, with times in frames.
+//!
+//! The rows of a subtitle are separated by `|`. The style tags `{y:i}` (for a row)
+//! and `{Y:i}` (for the subtitle) set italic (`i`), bold (`b`), underline (`u`) and
+//! strike out (`s`). A row starting with `/` is in italic. Other control codes,
+//! like the colors and fonts, are ignored.
+
+use super::{
+    cue::plain_cues, numbered_lines, read_file, read_stream, Cue, CuePosition, CueRow, CueStyle,
+    TextError,
+};
+use crate::time::{TimePoint, TimeSpan};
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+/// Number of bytes read to probe a file.
+const PROBE_SIZE: u64 = 64;
+
+/// Content of a `MicroDVD` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicroDvd {
+    /// Frame rate of the frame numbers.
+    pub frame_rate: f64,
+    /// Subtitles, in file order.
+    pub cues: Vec<Cue>,
+}
+
+impl MicroDvd {
+    /// Create a `MicroDVD` content from subtitles, with frame numbers at `frame_rate`.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)], frame_rate: f64) -> Self {
+        Self {
+            frame_rate,
+            cues: plain_cues(subtitles),
+        }
+    }
+
+    /// Read a `MicroDVD` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Io` if not able to read the file.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn open<P: AsRef<Path>>(path: P, frame_rate: Option<f64>) -> Result<Self, TextError> {
+        Self::parse(&read_file(path.as_ref())?, frame_rate)
+    }
+
+    /// Read a `MicroDVD` content from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Read` if not able to read from the `reader`.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn from_reader<R: Read>(reader: R, frame_rate: Option<f64>) -> Result<Self, TextError> {
+        Self::parse(&read_stream(reader)?, frame_rate)
+    }
+
+    /// Parse a `MicroDVD` content.
+    ///
+    /// The frame rate of a `{1}{1}23.976` header line is used if present, the positive
+    /// `frame_rate` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::InvalidTiming` if a line doesn't start with frame numbers.
+    /// Will return `TextError::MissingFrameRate` if there is no header line nor `frame_rate`.
+    pub fn parse(content: &str, frame_rate: Option<f64>) -> Result<Self, TextError> {
+        let mut lines = numbered_lines(content)
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(line_num, line)| {
+                split_frames(line.trim()).ok_or_else(|| TextError::InvalidTiming {
+                    line: line_num,
+                    value: line.into(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let header = lines
+            .first()
+            .and_then(|&(start, end, text)| (start == end && start <= 1).then_some(text))
+            .and_then(|text| text.trim().replace(',', ".").parse::<f64>().ok())
+            .filter(|frame_rate| is_valid(*frame_rate));
+        if header.is_some() {
+            lines.remove(0);
+        }
+        let frame_rate = header
+            .or_else(|| frame_rate.filter(|frame_rate| is_valid(*frame_rate)))
+            .ok_or(TextError::MissingFrameRate)?;
+
+        let time = |frame: u32| TimePoint::from_frames(i64::from(frame), frame_rate);
+        let cues = lines
+            .into_iter()
+            .map(|(start, end, text)| Cue {
+                time_span: TimeSpan::new(time(start), time(end)),
+                position: CuePosition::default(),
+                rows: parse_text(text),
+            })
+            .collect();
+        Ok(Self { frame_rate, cues })
+    }
+
+    /// Write the content in the `MicroDVD` format, with a frame rate header line.
+    ///
+    /// The style of each row is the style common to all its spans.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `io::Error` if writing fails.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        writeln!(writer, "{{1}}{{1}}{}", self.frame_rate)?;
+        for cue in &self.cues {
+            let start = frame(cue.time_span.start, self.frame_rate);
+            let end = frame(cue.time_span.end, self.frame_rate);
+            let rows = cue
+                .rows
+                .iter()
+                .map(|row| {
+                    let style = style_code(row.common_style());
+                    if style.is_empty() {
+                        row.text()
+                    } else {
+                        format!("{{y:{style}}}{}", row.text())
+                    }
+                })
+                .collect::<Vec<_>>();
+            writeln!(writer, "{{{start}}}{{{end}}}{}", rows.join("|"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Does the specified path appear to point to a `MicroDVD` file?
+///
+/// # Errors
+///
+/// Will return `Err` if the file can't be read.
+pub fn is_microdvd_file<P: AsRef<Path>>(path: P) -> Result<bool, TextError> {
+    let path = path.as_ref();
+    let mkerr = |source| TextError::Io {
+        source,
+        path: path.into(),
+    };
+    let mut content = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(PROBE_SIZE).read_to_end(&mut content))
+        .map_err(mkerr)?;
+    let content = super::decode(&content);
+    Ok(content
+        .trim_start()
+        .lines()
+        .next()
+        .and_then(split_frames)
+        .is_some())
+}
+
+/// Check if a frame rate can be used to convert frame numbers.
+fn is_valid(frame_rate: f64) -> bool {
+    frame_rate.is_finite() && frame_rate > 0.
+}
+
+/// Get the number of the frame displayed at `time`.
+#[expect(clippy::cast_precision_loss)]
+fn frame(time: TimePoint, frame_rate: f64) -> u64 {
+    cast::u64((time.msecs() as f64 * frame_rate / 1000.).round()).unwrap_or_default()
+}
+
+/// Split a line in its start frame, end frame and text.
+fn split_frames(line: &str) -> Option<(u32, u32, &str)> {
+    let (start, rest) = split_frame(line)?;
+    let (end, text) = split_frame(rest)?;
+    Some((start, end, text))
+}
+
+/// Split a `{frame}` number at the start of a text, from the rest of the text.
+fn split_frame(text: &str) -> Option<(u32, &str)> {
+    let (number, rest) = text.strip_prefix('{')?.split_once('}')?;
+    let all_digits = !number.is_empty() && number.bytes().all(|c| c.is_ascii_digit());
+    all_digits
+        .then(|| number.parse().ok())
+        .flatten()
+        .map(|number| (number, rest))
+}
+
+/// Part of the text of a row.
+enum Token<'a> {
+    /// Text to display.
+    Text(&'a str),
+    /// Style of the rest of the row.
+    RowStyle(CueStyle),
+    /// Style of the whole subtitle.
+    SubtitleStyle(CueStyle),
+}
+
+/// Parse the text of a subtitle, with its rows separated by `|`.
+fn parse_text(text: &str) -> Vec<CueRow> {
+    let rows = text.split('|').map(tokenize).collect::<Vec<_>>();
+    let subtitle_style = rows
+        .iter()
+        .flatten()
+        .filter_map(|token| match token {
+            Token::SubtitleStyle(style) => Some(*style),
+            Token::Text(_) | Token::RowStyle(_) => None,
+        })
+        .fold(CueStyle::default(), CueStyle::union);
+    rows.iter()
+        .map(|tokens| {
+            let mut row = CueRow::default();
+            let mut style = subtitle_style;
+            for token in tokens {
+                match token {
+                    Token::Text(text) => row.push(text, style),
+                    Token::RowStyle(row_style) => style = style.union(*row_style),
+                    Token::SubtitleStyle(_) => {}
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// Split the text of a row in texts and style tags.
+fn tokenize(row: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = row;
+    if let Some(text) = rest.strip_prefix('/') {
+        tokens.push(Token::RowStyle(CueStyle {
+            italic: true,
+            ..CueStyle::default()
+        }));
+        rest = text;
+    }
+    while let Some(first) = rest.chars().next() {
+        if let Some((code, value, text)) = control_code(rest) {
+            match code {
+                'y' => tokens.push(Token::RowStyle(parse_style(value))),
+                'Y' => tokens.push(Token::SubtitleStyle(parse_style(value))),
+                _ => {}
+            }
+            rest = text;
+            continue;
+        }
+        let first_len = first.len_utf8();
+        let end = rest[first_len..]
+            .find('{')
+            .map_or(rest.len(), |idx| idx + first_len);
+        tokens.push(Token::Text(&rest[..end]));
+        rest = &rest[end..];
+    }
+    tokens
+}
+
+/// Split a `{c:value}` control code at the start of a text, from the rest of the text.
+fn control_code(text: &str) -> Option<(char, &str, &str)> {
+    let (code, rest) = text.strip_prefix('{')?.split_once('}')?;
+    let (name, value) = code.split_once(':')?;
+    let mut chars = name.chars();
+    let name = chars.next().filter(char::is_ascii_alphabetic)?;
+    chars.next().is_none().then_some((name, value, rest))
+}
+
+/// Parse the value of a style tag, like `bi` or `b,i`.
+fn parse_style(value: &str) -> CueStyle {
+    let mut style = CueStyle::default();
+    for c in value.chars() {
+        match c.to_ascii_lowercase() {
+            'b' => style.bold = true,
+            'i' => style.italic = true,
+            'u' => style.underline = true,
+            's' => style.strike_out = true,
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Get the value of a style tag.
+fn style_code(style: CueStyle) -> String {
+    [
+        (style.bold, 'b'),
+        (style.italic, 'i'),
+        (style.underline, 'u'),
+        (style.strike_out, 's'),
+    ]
+    .into_iter()
+    .filter_map(|(set, code)| set.then_some(code))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches2::assert_matches;
+
+    const EXAMPLE: &str = "{1}{1}25
+{25}{75}{Y:b}Hello|{y:i}world
+{100}{150}/Première {c:$0000ff}ligne|Second {y:u}line
+";
+
+    /// Start of `EXAMPLE` written at 23.976 frames per second.
+    const WRITTEN_START: &str = "{1}{1}23.976\n{24}{72}{y:b}Hello|{y:bi}world\n";
+
+    #[test]
+    fn parse_styles() {
+        let microdvd = MicroDvd::parse(EXAMPLE, None).unwrap();
+        assert!((microdvd.frame_rate - 25.).abs() < f64::EPSILON);
+        assert_eq!(microdvd.cues.len(), 2);
+
+        let cue = &microdvd.cues[0];
+        assert_eq!(
+            cue.time_span,
+            TimeSpan::new(TimePoint::from_msecs(1000), TimePoint::from_msecs(3000))
+        );
+        assert_eq!(cue.plain_text(), "Hello\nworld");
+        assert!(cue.rows[0].spans[0].style.bold);
+        assert!(!cue.rows[0].spans[0].style.italic);
+        assert!(cue.rows[1].spans[0].style.bold);
+        assert!(cue.rows[1].spans[0].style.italic);
+
+        let cue = &microdvd.cues[1];
+        assert_eq!(cue.plain_text(), "Première ligne\nSecond line");
+        assert_eq!(cue.rows[0].spans.len(), 1);
+        assert!(cue.rows[0].spans[0].style.italic);
+        assert_eq!(cue.rows[1].spans.len(), 2);
+        assert!(cue.rows[1].spans[1].style.underline);
+    }
+
+    #[test]
+    fn frame_rate() {
+        let content = "{24}{48}Text";
+        assert_matches!(
+            MicroDvd::parse(content, None),
+            Err(TextError::MissingFrameRate)
+        );
+        let microdvd = MicroDvd::parse(content, Some(24.)).unwrap();
+        assert_eq!(microdvd.cues[0].time_span.end, TimePoint::from_msecs(2000));
+        assert_matches!(
+            MicroDvd::parse("{1}{1}25\n{24}Text", Some(24.)),
+            Err(TextError::InvalidTiming { line: 2, .. })
+        );
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut microdvd = MicroDvd::parse(EXAMPLE, None).unwrap();
+        microdvd.frame_rate = 23.976;
+        let mut content = Vec::new();
+        microdvd.write(&mut content).unwrap();
+        let content = String::from_utf8(content).unwrap();
+        assert!(content.starts_with(WRITTEN_START));
+
+        let read = MicroDvd::parse(&content, Some(25.)).unwrap();
+        assert!((read.frame_rate - 23.976).abs() < f64::EPSILON);
+        assert_eq!(read.cues.len(), 2);
+        for (read, cue) in read.cues.iter().zip(&microdvd.cues) {
+            assert_eq!(read.plain_text(), cue.plain_text());
+            assert!((read.time_span.start.msecs() - cue.time_span.start.msecs()).abs() < 42);
+        }
+        assert!(read.cues[0].rows[1].spans[0].style.italic);
+    }
+}
@@ -0,0 +1,87 @@
+//! Simple text subtitle formats: `MicroDVD`, `SubViewer`, `SBV` and `LRC`.
+//!
+//! The formats share the [`Cue`] model: rows of styled spans, with a time span and an
+//! optional position. Only `MicroDVD` has style tags, the other formats contain plain text.
+//! The cues of the other subtitle formats can be converted to this model.
+//!
+//! - `MicroDVD` (`*.sub`): `{start}{end}text` lines, with times in frames.
+//! - `SubViewer 2` (`*.sub`): an `[INFORMATION]` header, then `HH:MM:SS.cc` time spans.
+//! - `SBV` (`*.sbv`): the `YouTube` format, with `H:MM:SS.mmm` time spans.
+//! - `LRC` (`*.lrc`): lyrics, with only the start time of each line.
+mod cue;
+mod lrc;
+mod microdvd;
+mod sbv;
+mod subviewer;
+mod timestamp;
+
+pub use cue::{Cue, CueAlign, CuePosition, CueRow, CueSpan, CueStyle};
+pub use lrc::Lrc;
+pub use microdvd::{is_microdvd_file, MicroDvd};
+pub use sbv::Sbv;
+pub use subviewer::SubViewer;
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Error for simple text formats handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TextError {
+    /// If an error happen during the file opening.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("failed to read the subtitles content")]
+    Read(#[source] io::Error),
+
+    /// A line doesn't contain the expected timing.
+    #[error("line {line}: invalid timing '{value}'")]
+    InvalidTiming {
+        /// Line number (starting at 1).
+        line: usize,
+        /// Invalid line.
+        value: String,
+    },
+
+    /// A `MicroDVD` content has no frame rate header, and no frame rate was provided.
+    #[error("missing MicroDVD frame rate")]
+    MissingFrameRate,
+}
+
+/// Read the content of a file, replacing invalid `UTF-8` sequences.
+fn read_file(path: &Path) -> Result<String, TextError> {
+    let content = fs::read(path).map_err(|source| TextError::Io {
+        source,
+        path: path.into(),
+    })?;
+    Ok(decode(&content))
+}
+
+/// Read the content of a `reader`, replacing invalid `UTF-8` sequences.
+fn read_stream(mut reader: impl Read) -> Result<String, TextError> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content).map_err(TextError::Read)?;
+    Ok(decode(&content))
+}
+
+/// Decode a content as `UTF-8`, without its byte order mark.
+pub(crate) fn decode(content: &[u8]) -> String {
+    let content = String::from_utf8_lossy(content);
+    content.trim_start_matches('\u{feff}').to_owned()
+}
+
+/// Iterate over the lines of a content, with their number (starting at 1).
+fn numbered_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    (1..).zip(content.lines())
+}
@@ -0,0 +1,151 @@
+//! `SBV` subtitles, from `YouTube`: `H:MM:SS.mmm,H:MM:SS.mmm` time spans followed by the
+//! rows of the subtitle.
+
+use super::{
+    cue::plain_cues,
+    numbered_lines, read_file, read_stream,
+    timestamp::{parse_time_span, Timestamp},
+    Cue, CueRow, TextError,
+};
+use crate::time::TimeSpan;
+use std::{
+    io::{self, Read},
+    path::Path,
+};
+
+/// Content of a `SBV` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sbv {
+    /// Subtitles, in file order.
+    pub cues: Vec<Cue>,
+}
+
+impl Sbv {
+    /// Create a `SBV` content from subtitles.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)]) -> Self {
+        Self {
+            cues: plain_cues(subtitles),
+        }
+    }
+
+    /// Read a `SBV` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Io` if not able to read the file.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TextError> {
+        Self::parse(&read_file(path.as_ref())?)
+    }
+
+    /// Read a `SBV` content from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Read` if not able to read from the `reader`.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, TextError> {
+        Self::parse(&read_stream(reader)?)
+    }
+
+    /// Parse a `SBV` content.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::InvalidTiming` if a subtitle doesn't start with a time span.
+    pub fn parse(content: &str) -> Result<Self, TextError> {
+        let mut cues = Vec::new();
+        let mut current: Option<Cue> = None;
+        for (line_num, line) in numbered_lines(content) {
+            let line = line.trim_end();
+            if line.trim().is_empty() {
+                cues.extend(current.take());
+            } else if let Some(cue) = &mut current {
+                cue.rows.push(CueRow::plain(line));
+            } else {
+                let time_span = parse_time_span(line).ok_or_else(|| TextError::InvalidTiming {
+                    line: line_num,
+                    value: line.into(),
+                })?;
+                current = Some(Cue::plain(time_span, ""));
+            }
+        }
+        cues.extend(current);
+        Ok(Self { cues })
+    }
+
+    /// Write the content in the `SBV` format.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `io::Error` if writing fails.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        let timestamp = |time| Timestamp {
+            time,
+            hour_digits: Some(1),
+            fraction_digits: 3,
+        };
+        for (idx, cue) in self.cues.iter().enumerate() {
+            if idx > 0 {
+                writeln!(writer)?;
+            }
+            let start = timestamp(cue.time_span.start);
+            let end = timestamp(cue.time_span.end);
+            writeln!(writer, "{start},{end}\n{}", cue.plain_text())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::TimePoint;
+    use assert_matches2::assert_matches;
+
+    #[test]
+    fn write_and_read_back() {
+        let subtitles = [
+            (
+                TimeSpan::new(TimePoint::from_msecs(599), TimePoint::from_msecs(4160)),
+                ">> ALICE: Hi, my name is Alice\nand this is John".to_owned(),
+            ),
+            (
+                TimeSpan::new(
+                    TimePoint::from_msecs(4160),
+                    TimePoint::from_msecs(3_606_770),
+                ),
+                ">> JOHN: and we're the owners".to_owned(),
+            ),
+        ];
+        let mut content = Vec::new();
+        Sbv::from_subtitles(&subtitles).write(&mut content).unwrap();
+        let content = String::from_utf8(content).unwrap();
+        assert_eq!(
+            content,
+            "0:00:00.599,0:00:04.160
+>> ALICE: Hi, my name is Alice
+and this is John
+
+0:00:04.160,1:00:06.770
+>> JOHN: and we're the owners
+"
+        );
+
+        let sbv = Sbv::parse(&content).unwrap();
+        assert_eq!(sbv.cues.len(), 2);
+        for (cue, (time_span, text)) in sbv.cues.iter().zip(&subtitles) {
+            assert_eq!(cue.time_span, *time_span);
+            assert_eq!(cue.plain_text(), *text);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_matches!(
+            Sbv::parse("0:00:00.599,0:00:04.160\nText\n\nText"),
+            Err(TextError::InvalidTiming { line: 4, .. })
+        );
+    }
+}
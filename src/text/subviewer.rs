@@ -0,0 +1,176 @@
+//! `SubViewer 2` subtitles: an `[INFORMATION]` header, then `HH:MM:SS.cc,HH:MM:SS.cc`
+//! time spans followed by the text of the subtitle, with its rows separated by `[br]`.
+
+use super::{
+    cue::plain_cues,
+    numbered_lines, read_file, read_stream,
+    timestamp::{parse_time_span, Timestamp},
+    Cue, CueRow, TextError,
+};
+use crate::time::TimeSpan;
+use std::{
+    io::{self, Read},
+    path::Path,
+};
+
+/// Start of the information section.
+const INFORMATION: &str = "[INFORMATION]";
+/// End of the information section.
+const END_INFORMATION: &str = "[END INFORMATION]";
+/// Start of the subtitles.
+const SUBTITLE: &str = "[SUBTITLE]";
+/// Separator of the rows of a subtitle.
+const LINE_BREAK: &str = "[br]";
+
+/// Content of a `SubViewer 2` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubViewer {
+    /// Tags of the `[INFORMATION]` section with their values, like `("TITLE", "Title")`.
+    pub info: Vec<(String, String)>,
+    /// Subtitles, in file order.
+    pub cues: Vec<Cue>,
+}
+
+impl SubViewer {
+    /// Create a `SubViewer` content from subtitles, without information.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)]) -> Self {
+        Self {
+            info: Vec::new(),
+            cues: plain_cues(subtitles),
+        }
+    }
+
+    /// Read a `SubViewer` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Io` if not able to read the file.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TextError> {
+        Self::parse(&read_file(path.as_ref())?)
+    }
+
+    /// Read a `SubViewer` content from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::Read` if not able to read from the `reader`.
+    /// Will return an other `TextError` if the content is not valid.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, TextError> {
+        Self::parse(&read_stream(reader)?)
+    }
+
+    /// Parse a `SubViewer` content.
+    ///
+    /// The lines starting with `[` outside of the information section, like the style
+    /// lines, are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TextError::InvalidTiming` if a subtitle doesn't start with a time span.
+    pub fn parse(content: &str) -> Result<Self, TextError> {
+        let mut info = Vec::new();
+        let mut cues = Vec::new();
+        let mut current: Option<Cue> = None;
+        let mut in_info = false;
+        for (line_num, line) in numbered_lines(content) {
+            let line = line.trim();
+            if line.is_empty() {
+                cues.extend(current.take());
+            } else if let Some(cue) = &mut current {
+                let line = line.replace("[BR]", LINE_BREAK);
+                cue.rows
+                    .extend(line.split(LINE_BREAK).map(|row| CueRow::plain(row.trim())));
+            } else if let Some(tag) = line.strip_prefix('[') {
+                if line.eq_ignore_ascii_case(INFORMATION) {
+                    in_info = true;
+                } else if line.eq_ignore_ascii_case(END_INFORMATION) {
+                    in_info = false;
+                } else if let Some((tag, value)) = tag.split_once(']').filter(|_| in_info) {
+                    info.push((tag.to_owned(), value.trim().to_owned()));
+                }
+            } else {
+                let time_span = parse_time_span(line).ok_or_else(|| TextError::InvalidTiming {
+                    line: line_num,
+                    value: line.into(),
+                })?;
+                current = Some(Cue::plain(time_span, ""));
+            }
+        }
+        cues.extend(current);
+        Ok(Self { info, cues })
+    }
+
+    /// Write the content in the `SubViewer 2` format.
+    ///
+    /// # Errors
+    ///
+    /// Will return an `io::Error` if writing fails.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        writeln!(writer, "{INFORMATION}")?;
+        for (tag, value) in &self.info {
+            writeln!(writer, "[{tag}]{value}")?;
+        }
+        writeln!(writer, "{END_INFORMATION}\n{SUBTITLE}")?;
+
+        let timestamp = |time| Timestamp {
+            time,
+            hour_digits: Some(2),
+            fraction_digits: 2,
+        };
+        for cue in &self.cues {
+            let start = timestamp(cue.time_span.start);
+            let end = timestamp(cue.time_span.end);
+            let rows = cue.rows.iter().map(CueRow::text).collect::<Vec<_>>();
+            writeln!(writer, "{start},{end}\n{}\n", rows.join(LINE_BREAK))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::TimePoint;
+
+    const EXAMPLE: &str = "[INFORMATION]
+[TITLE]Xenogears
+[AUTHOR]Subtitler
+[DELAY]0
+[END INFORMATION]
+[SUBTITLE]
+[COLF]&HFFFFFF,[STYLE]bd,[SIZE]18,[FONT]Arial
+00:00:41.00,00:00:44.40
+The Age of Gods was closing.[br]Eternity had come to an end.
+
+00:00:55.00,00:00:58.40
+The heavens shook
+";
+
+    #[test]
+    fn parse_and_write() {
+        let subviewer = SubViewer::parse(EXAMPLE).unwrap();
+        assert_eq!(subviewer.info.len(), 3);
+        assert_eq!(
+            subviewer.info[0],
+            ("TITLE".to_owned(), "Xenogears".to_owned())
+        );
+        assert_eq!(subviewer.cues.len(), 2);
+        assert_eq!(
+            subviewer.cues[0].time_span,
+            TimeSpan::new(TimePoint::from_msecs(41_000), TimePoint::from_msecs(44_400))
+        );
+        assert_eq!(
+            subviewer.cues[0].plain_text(),
+            "The Age of Gods was closing.\nEternity had come to an end."
+        );
+        assert_eq!(subviewer.cues[1].plain_text(), "The heavens shook");
+
+        let mut content = Vec::new();
+        subviewer.write(&mut content).unwrap();
+        let content = String::from_utf8(content).unwrap();
+        assert_eq!(SubViewer::parse(&content).unwrap(), subviewer);
+        assert!(content.ends_with("00:00:55.00,00:00:58.40\nThe heavens shook\n\n"));
+    }
+}
@@ -0,0 +1,115 @@
+//! Timestamps of the simple text formats, like `0:01:02.345` or `01:02.34`.
+
+use crate::time::{TimePoint, TimeSpan};
+use std::fmt;
+
+/// Parse a timestamp in `[H:]MM:SS[.fraction]` format.
+///
+/// The hours are optional and the minutes are not limited without them, as in `LRC`.
+/// The fraction can have any number of digits.
+pub(super) fn parse_time(value: &str) -> Option<TimePoint> {
+    let value = value.trim();
+    let (clock, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let number = |value: &str| {
+        (!value.is_empty() && value.bytes().all(|c| c.is_ascii_digit()))
+            .then(|| value.parse::<i64>().ok())
+            .flatten()
+    };
+    let mut parts = clock.rsplit(':');
+    let secs = number(parts.next()?)?;
+    let mins = number(parts.next()?)?;
+    let hours = parts.next().map(number);
+    if parts.next().is_some() || secs >= 60 || (hours.is_some() && mins >= 60) {
+        return None;
+    }
+    let hours = hours.unwrap_or(Some(0))?;
+    let fraction_msecs = if fraction.is_empty() {
+        0
+    } else {
+        number(fraction)?.checked_mul(1000)?
+            / 10_i64.checked_pow(u32::try_from(fraction.len()).ok()?)?
+    };
+    let msecs = hours
+        .checked_mul(60)?
+        .checked_add(mins)?
+        .checked_mul(60)?
+        .checked_add(secs)?
+        .checked_mul(1000)?
+        .checked_add(fraction_msecs)?;
+    Some(TimePoint::from_msecs(msecs))
+}
+
+/// Parse a `start,end` time span, as in `SubViewer` and `SBV`.
+pub(super) fn parse_time_span(line: &str) -> Option<TimeSpan> {
+    let (start, end) = line.split_once(',')?;
+    Some(TimeSpan::new(parse_time(start)?, parse_time(end)?))
+}
+
+/// Display of a `TimePoint` as a timestamp.
+///
+/// The time is rounded to the fraction digits, and negative times are written as zero.
+pub(super) struct Timestamp {
+    /// Time to display.
+    pub time: TimePoint,
+    /// Minimum number of digits of the hours, or `None` to display only the minutes.
+    pub hour_digits: Option<usize>,
+    /// Number of digits of the fraction of second, from `0` to `3`.
+    pub fraction_digits: u32,
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = 10_i64.pow(3 - self.fraction_digits);
+        let per_sec = 10_i64.pow(self.fraction_digits);
+        let fractions = self.time.msecs().max(0).saturating_add(unit / 2) / unit;
+        let secs = fractions / per_sec;
+        if let Some(width) = self.hour_digits {
+            write!(f, "{:0width$}:{:02}:", secs / 3600, secs / 60 % 60)?;
+        } else {
+            write!(f, "{:02}:", secs / 60)?;
+        }
+        write!(f, "{:02}", secs % 60)?;
+        if self.fraction_digits > 0 {
+            let width = self.fraction_digits as usize;
+            write!(f, ".{:0width$}", fractions % per_sec)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(
+            parse_time("0:01:02.345"),
+            Some(TimePoint::from_msecs(62_345))
+        );
+        assert_eq!(
+            parse_time("75:02.3"),
+            Some(TimePoint::from_msecs(4_502_300))
+        );
+        assert_eq!(
+            parse_time("01:00:02"),
+            Some(TimePoint::from_msecs(3_602_000))
+        );
+        assert_eq!(parse_time("1:60:00.00"), None);
+        assert_eq!(parse_time("00:61.00"), None);
+        assert_eq!(parse_time("00:1a.00"), None);
+
+        let timestamp = |time, hour_digits, fraction_digits| {
+            Timestamp {
+                time: TimePoint::from_msecs(time),
+                hour_digits,
+                fraction_digits,
+            }
+            .to_string()
+        };
+        assert_eq!(timestamp(3_662_345, Some(1), 3), "1:01:02.345");
+        assert_eq!(timestamp(3_662_345, Some(2), 2), "01:01:02.35");
+        assert_eq!(timestamp(4_502_300, None, 2), "75:02.30");
+        assert_eq!(timestamp(-40, None, 2), "00:00.00");
+    }
+}
@@ -0,0 +1,530 @@
+//! Cleanup passes on subtitles timing.
+//!
+//! Subtitles produced from `OCR`, or converted from an other format, often overlap,
+//! flash for a few frames, or butt up against each other with zero gap.
+//! [`cleanup_timing`] apply a configurable set of passes to fix these issues
+//! and report every change made.
+use super::{TimePoint, TimeSpan};
+
+/// Access to the timing (and optionally the text) of a subtitle, used by the cleanup passes.
+pub trait TimedSubtitle {
+    /// Time span of the subtitle.
+    fn time_span(&self) -> TimeSpan;
+
+    /// Replace the time span of the subtitle.
+    fn set_time_span(&mut self, time_span: TimeSpan);
+
+    /// Text of the subtitle, if it has one. Used to check the reading speed.
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    /// Merge the content of `next` (the following subtitle) into `self`.
+    /// Return `false` if the content can't be merged.
+    fn merge_content(&mut self, _next: &Self) -> bool {
+        false
+    }
+}
+
+impl TimedSubtitle for TimeSpan {
+    fn time_span(&self) -> TimeSpan {
+        *self
+    }
+    fn set_time_span(&mut self, time_span: TimeSpan) {
+        *self = time_span;
+    }
+    fn merge_content(&mut self, _next: &Self) -> bool {
+        true
+    }
+}
+
+impl TimedSubtitle for (TimeSpan, String) {
+    fn time_span(&self) -> TimeSpan {
+        self.0
+    }
+    fn set_time_span(&mut self, time_span: TimeSpan) {
+        self.0 = time_span;
+    }
+    fn text(&self) -> Option<&str> {
+        Some(self.1.as_str())
+    }
+    fn merge_content(&mut self, next: &Self) -> bool {
+        if self.1 != next.1 {
+            self.1.push('\n');
+            self.1.push_str(&next.1);
+        }
+        true
+    }
+}
+
+/// How overlapping subtitles are fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapFix {
+    /// Overlaps are kept as is.
+    Keep,
+    /// The end of the first subtitle is moved to the start of the next one.
+    Truncate,
+    /// Overlapping subtitles are merged in one subtitle, if the content allow it.
+    /// Otherwise, the first subtitle is truncated.
+    Merge,
+}
+
+/// Options of the timing cleanup passes.
+///
+/// Durations are in milliseconds, a pass with a `None` value is disabled.
+/// A pass with a negative duration is skipped with a warning.
+/// [`TimePoint::from_frames`] can be used to express a duration in frames.
+#[derive(Debug, Clone, Copy)]
+pub struct TimingCleanupOpt {
+    /// How to fix overlapping subtitles.
+    pub overlap: OverlapFix,
+    /// Minimum duration of a subtitle.
+    pub min_duration: Option<i64>,
+    /// Maximum duration of a subtitle.
+    pub max_duration: Option<i64>,
+    /// Minimum gap between two consecutive subtitles.
+    pub min_gap: Option<i64>,
+    /// Maximum reading speed, in characters per second, of text subtitles.
+    /// The pass is skipped if the value is not finite and strictly positive.
+    pub max_cps: Option<f64>,
+}
+
+// Implement [`Default`] for [`TimingCleanupOpt`] with only the overlaps truncated.
+impl Default for TimingCleanupOpt {
+    fn default() -> Self {
+        Self {
+            overlap: OverlapFix::Truncate,
+            min_duration: None,
+            max_duration: None,
+            min_gap: None,
+            max_cps: None,
+        }
+    }
+}
+
+/// Kind of change made by a cleanup pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimingChangeKind {
+    /// The end of the subtitle was moved to the start of the next one.
+    OverlapTruncated,
+    /// The next subtitle was merged into this one.
+    OverlapMerged,
+    /// The subtitle was too long and was shortened.
+    MaxDurationCapped,
+    /// The subtitle was too short and was extended.
+    MinDurationExtended,
+    /// The subtitle was extended to reduce its reading speed.
+    ReadingSpeedExtended,
+    /// The reading speed (in characters per second) is still too high, timing is unchanged.
+    ReadingSpeedTooHigh(f64),
+    /// The end of the subtitle was moved to keep the minimum gap with the next one.
+    GapEnforced,
+}
+
+/// A change made on a subtitle by a cleanup pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingChange {
+    /// Index of the subtitle in the cleaned list.
+    pub index: usize,
+    /// Kind of the change.
+    pub kind: TimingChangeKind,
+    /// Time span before the change.
+    pub before: TimeSpan,
+    /// Time span after the change.
+    pub after: TimeSpan,
+}
+
+/// Apply the timing cleanup passes on a list of subtitles.
+///
+/// Subtitles are first sorted by start time, then the passes are applied in this order:
+/// overlaps, maximum duration, minimum duration, reading speed and minimum gap.
+/// Extensions never go beyond the start of the next subtitle minus the minimum gap.
+///
+/// Return the list of all changes made.
+#[profiling::function]
+pub fn cleanup_timing<T>(subtitles: &mut Vec<T>, opt: &TimingCleanupOpt) -> Vec<TimingChange>
+where
+    T: TimedSubtitle,
+{
+    let mut changes = Vec::new();
+    subtitles.sort_by_key(|sub| sub.time_span().start);
+
+    let min_duration = valid_duration(opt.min_duration, "minimum duration");
+    let max_duration = valid_duration(opt.max_duration, "maximum duration");
+    let min_gap = valid_duration(opt.min_gap, "minimum gap");
+
+    fix_overlaps(subtitles, opt.overlap, &mut changes);
+    if let Some(max_duration) = max_duration {
+        cap_duration(subtitles, max_duration, &mut changes);
+    }
+    if let Some(min_duration) = min_duration {
+        extend_duration(subtitles, min_duration, min_gap, &mut changes);
+    }
+    if let Some(max_cps) = opt.max_cps.filter(|max_cps| {
+        let valid = is_valid_cps(*max_cps);
+        if !valid {
+            log::warn!("Invalid maximum reading speed `{max_cps}`, reading speed pass skipped.");
+        }
+        valid
+    }) {
+        check_reading_speed(subtitles, max_cps, min_gap, &mut changes);
+    }
+    if let Some(min_gap) = min_gap {
+        enforce_gap(subtitles, min_gap, &mut changes);
+    }
+
+    changes
+}
+
+// Keep a duration option only if it's not negative, the pass is skipped otherwise.
+fn valid_duration(duration: Option<i64>, name: &str) -> Option<i64> {
+    duration.filter(|duration| {
+        let valid = *duration >= 0;
+        if !valid {
+            log::warn!("Invalid {name} `{duration}`, pass skipped.");
+        }
+        valid
+    })
+}
+
+// Update the time span of a subtitle and report the change.
+fn apply_change<T: TimedSubtitle>(
+    subtitles: &mut [T],
+    index: usize,
+    after: TimeSpan,
+    kind: TimingChangeKind,
+    changes: &mut Vec<TimingChange>,
+) {
+    let before = subtitles[index].time_span();
+    if before != after {
+        subtitles[index].set_time_span(after);
+        changes.push(TimingChange {
+            index,
+            kind,
+            before,
+            after,
+        });
+    }
+}
+
+// Latest end time allowed for the subtitle at `index`, regarding the next subtitle.
+fn end_limit<T: TimedSubtitle>(subtitles: &[T], index: usize, min_gap: Option<i64>) -> i64 {
+    subtitles.get(index + 1).map_or(i64::MAX, |next| {
+        next.time_span()
+            .start
+            .msecs()
+            .saturating_sub(min_gap.unwrap_or(0))
+    })
+}
+
+fn fix_overlaps<T: TimedSubtitle>(
+    subtitles: &mut Vec<T>,
+    overlap: OverlapFix,
+    changes: &mut Vec<TimingChange>,
+) {
+    if overlap == OverlapFix::Keep {
+        return;
+    }
+
+    let mut idx = 1;
+    while idx < subtitles.len() {
+        let prev = subtitles[idx - 1].time_span();
+        let current = subtitles[idx].time_span();
+        if prev.overlaps(&current) {
+            let (before, after) = subtitles.split_at_mut(idx);
+            let merged = overlap == OverlapFix::Merge && before[idx - 1].merge_content(&after[0]);
+            if merged {
+                let after = TimeSpan::new(prev.start, prev.end.max(current.end));
+                subtitles.remove(idx);
+                subtitles[idx - 1].set_time_span(after);
+                // Always reported, even if the time span is unchanged, as the content changed.
+                changes.push(TimingChange {
+                    index: idx - 1,
+                    kind: TimingChangeKind::OverlapMerged,
+                    before: prev,
+                    after,
+                });
+                // Don't go to next subtitle, the merged one can overlap the following.
+                continue;
+            }
+            apply_change(
+                subtitles,
+                idx - 1,
+                TimeSpan::new(prev.start, current.start),
+                TimingChangeKind::OverlapTruncated,
+                changes,
+            );
+        }
+        idx += 1;
+    }
+}
+
+fn cap_duration<T: TimedSubtitle>(
+    subtitles: &mut [T],
+    max_duration: i64,
+    changes: &mut Vec<TimingChange>,
+) {
+    for idx in 0..subtitles.len() {
+        let time = subtitles[idx].time_span();
+        if time.duration_msecs() > max_duration {
+            let end = TimePoint::from_msecs(time.start.msecs().saturating_add(max_duration));
+            apply_change(
+                subtitles,
+                idx,
+                TimeSpan::new(time.start, end),
+                TimingChangeKind::MaxDurationCapped,
+                changes,
+            );
+        }
+    }
+}
+
+fn extend_duration<T: TimedSubtitle>(
+    subtitles: &mut [T],
+    min_duration: i64,
+    min_gap: Option<i64>,
+    changes: &mut Vec<TimingChange>,
+) {
+    for idx in 0..subtitles.len() {
+        let time = subtitles[idx].time_span();
+        if time.duration_msecs() < min_duration {
+            let wanted_end = time.start.msecs().saturating_add(min_duration);
+            let end = wanted_end
+                .min(end_limit(subtitles, idx, min_gap))
+                .max(time.end.msecs());
+            apply_change(
+                subtitles,
+                idx,
+                TimeSpan::new(time.start, TimePoint::from_msecs(end)),
+                TimingChangeKind::MinDurationExtended,
+                changes,
+            );
+        }
+    }
+}
+
+// Number of characters taken into account for reading speed.
+fn nb_read_chars(text: &str) -> usize {
+    text.chars().filter(|c| *c != '\n' && *c != '\r').count()
+}
+
+/// Compute the reading speed of a text in characters per second.
+/// Line breaks are not counted as characters.
+#[must_use]
+pub fn reading_speed(text: &str, time: TimeSpan) -> f64 {
+    reading_speed_of(nb_read_chars(text), time)
+}
+
+fn reading_speed_of(nb_chars: usize, time: TimeSpan) -> f64 {
+    let duration = time.duration_msecs();
+    if duration <= 0 {
+        f64::INFINITY
+    } else {
+        cast::f64(nb_chars) * 1000. / cast::f64(duration)
+    }
+}
+
+/// Check the maximum reading speed can be used to compute a duration.
+fn is_valid_cps(max_cps: f64) -> bool {
+    max_cps.is_finite() && max_cps > 0.
+}
+
+fn check_reading_speed<T: TimedSubtitle>(
+    subtitles: &mut [T],
+    max_cps: f64,
+    min_gap: Option<i64>,
+    changes: &mut Vec<TimingChange>,
+) {
+    for idx in 0..subtitles.len() {
+        let time = subtitles[idx].time_span();
+        let Some(nb_chars) = subtitles[idx].text().map(nb_read_chars) else {
+            continue;
+        };
+        if reading_speed_of(nb_chars, time) <= max_cps {
+            continue;
+        }
+
+        let needed_duration =
+            cast::i64((cast::f64(nb_chars) * 1000. / max_cps).ceil()).unwrap_or(i64::MAX);
+        let end = (time.start.msecs().saturating_add(needed_duration))
+            .min(end_limit(subtitles, idx, min_gap))
+            .max(time.end.msecs());
+        let new_time = TimeSpan::new(time.start, TimePoint::from_msecs(end));
+        apply_change(
+            subtitles,
+            idx,
+            new_time,
+            TimingChangeKind::ReadingSpeedExtended,
+            changes,
+        );
+
+        let new_cps = reading_speed_of(nb_chars, new_time);
+        if new_cps > max_cps {
+            changes.push(TimingChange {
+                index: idx,
+                kind: TimingChangeKind::ReadingSpeedTooHigh(new_cps),
+                before: new_time,
+                after: new_time,
+            });
+        }
+    }
+}
+
+fn enforce_gap<T: TimedSubtitle>(
+    subtitles: &mut [T],
+    min_gap: i64,
+    changes: &mut Vec<TimingChange>,
+) {
+    for idx in 0..subtitles.len().saturating_sub(1) {
+        let time = subtitles[idx].time_span();
+        let limit = end_limit(subtitles, idx, Some(min_gap));
+        if time.end.msecs() > limit {
+            let end = limit.max(time.start.msecs());
+            apply_change(
+                subtitles,
+                idx,
+                TimeSpan::new(time.start, TimePoint::from_msecs(end)),
+                TimingChangeKind::GapEnforced,
+                changes,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: i64, end: i64) -> TimeSpan {
+        TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end))
+    }
+
+    #[test]
+    fn truncate_overlaps() {
+        let mut subs = vec![span(1000, 3000), span(0, 500), span(2500, 4000)];
+        let changes = cleanup_timing(&mut subs, &TimingCleanupOpt::default());
+        assert_eq!(subs, [span(0, 500), span(1000, 2500), span(2500, 4000)]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].index, 1);
+        assert_eq!(changes[0].kind, TimingChangeKind::OverlapTruncated);
+        assert_eq!(changes[0].before, span(1000, 3000));
+    }
+
+    #[test]
+    fn merge_overlaps() {
+        let mut subs = vec![
+            (span(0, 2000), String::from("Hello")),
+            (span(1500, 3000), String::from("World")),
+            (span(4000, 5000), String::from("!")),
+        ];
+        let opt = TimingCleanupOpt {
+            overlap: OverlapFix::Merge,
+            ..TimingCleanupOpt::default()
+        };
+        let changes = cleanup_timing(&mut subs, &opt);
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0], (span(0, 3000), String::from("Hello\nWorld")));
+        assert_eq!(changes[0].kind, TimingChangeKind::OverlapMerged);
+    }
+
+    #[test]
+    fn durations_and_gap() {
+        let mut subs = vec![span(0, 200), span(1000, 12000), span(12010, 14000)];
+        let opt = TimingCleanupOpt {
+            min_duration: Some(1000),
+            max_duration: Some(7000),
+            min_gap: Some(TimePoint::from_frames(2, 25.).msecs()),
+            ..TimingCleanupOpt::default()
+        };
+        let changes = cleanup_timing(&mut subs, &opt);
+        assert_eq!(subs, [span(0, 920), span(1000, 8000), span(12010, 14000)]);
+        let kinds = changes.iter().map(|change| change.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                TimingChangeKind::MaxDurationCapped,
+                TimingChangeKind::MinDurationExtended
+            ]
+        );
+    }
+
+    #[test]
+    fn reading_speed_check() {
+        let mut subs = vec![
+            (span(0, 1000), String::from("A quite long text to read")),
+            (span(1500, 2000), String::from("Too long text for the time")),
+            (span(2100, 5000), String::from("Ok")),
+        ];
+        let opt = TimingCleanupOpt {
+            max_cps: Some(20.),
+            ..TimingCleanupOpt::default()
+        };
+        let changes = cleanup_timing(&mut subs, &opt);
+        assert_eq!(subs[0].0, span(0, 1250));
+        assert_eq!(subs[1].0, span(1500, 2100));
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].kind, TimingChangeKind::ReadingSpeedExtended);
+        assert_eq!(changes[1].kind, TimingChangeKind::ReadingSpeedExtended);
+        assert!(matches!(
+            changes[2].kind,
+            TimingChangeKind::ReadingSpeedTooHigh(cps) if cps > 20.
+        ));
+    }
+
+    #[test]
+    fn invalid_reading_speed_skipped() {
+        for max_cps in [0., -5., f64::NAN, f64::INFINITY] {
+            let mut subs = vec![(span(0, 100), String::from("A quite long text to read"))];
+            let opt = TimingCleanupOpt {
+                max_cps: Some(max_cps),
+                ..TimingCleanupOpt::default()
+            };
+            let changes = cleanup_timing(&mut subs, &opt);
+            assert!(changes.is_empty());
+            assert_eq!(subs[0].0, span(0, 100));
+        }
+    }
+
+    #[test]
+    fn negative_durations_skipped() {
+        let mut subs = vec![span(0, 1000), span(1000, 2000)];
+        let opt = TimingCleanupOpt {
+            min_duration: Some(-1),
+            max_duration: Some(-500),
+            min_gap: Some(-100),
+            ..TimingCleanupOpt::default()
+        };
+        let changes = cleanup_timing(&mut subs, &opt);
+        assert!(changes.is_empty());
+        assert_eq!(subs, [span(0, 1000), span(1000, 2000)]);
+    }
+
+    #[test]
+    fn extreme_times_do_not_overflow() {
+        let mut subs = vec![
+            span(i64::MIN, i64::MIN + 10),
+            span(i64::MIN + 20, i64::MIN + 30),
+            span(i64::MAX - 10, i64::MAX),
+        ];
+        let opt = TimingCleanupOpt {
+            max_duration: Some(i64::MAX),
+            min_gap: Some(i64::MAX),
+            ..TimingCleanupOpt::default()
+        };
+        cleanup_timing(&mut subs, &opt);
+        assert_eq!(subs[0], span(i64::MIN, i64::MIN));
+        assert_eq!(subs[2], span(i64::MAX - 10, i64::MAX));
+    }
+
+    #[test]
+    fn tiny_reading_speed_does_not_overflow() {
+        let mut subs = vec![(span(0, 100), String::from("Text"))];
+        let opt = TimingCleanupOpt {
+            max_cps: Some(f64::MIN_POSITIVE),
+            ..TimingCleanupOpt::default()
+        };
+        let changes = cleanup_timing(&mut subs, &opt);
+        assert_eq!(changes[0].kind, TimingChangeKind::ReadingSpeedExtended);
+        assert!(subs[0].0.end.msecs() > 100);
+    }
+}
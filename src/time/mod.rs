@@ -1,6 +1,11 @@
 //! Subtitle Time management
+mod cleanup;
 mod time_point;
 mod time_span;
 
+pub use cleanup::{
+    cleanup_timing, reading_speed, OverlapFix, TimedSubtitle, TimingChange, TimingChangeKind,
+    TimingCleanupOpt,
+};
 pub use time_point::TimePoint;
 pub use time_span::TimeSpan;
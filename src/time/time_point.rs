@@ -24,6 +24,17 @@ impl TimePoint {
         Self(msecs)
     }
 
+    /// Create a `TimePoint` from a number of frames at the given frame rate.
+    ///
+    /// # Panics
+    ///
+    /// Will panics if the resulting time is to big to be store as millisecond in a [`i64`].
+    #[must_use]
+    #[expect(clippy::cast_precision_loss)]
+    pub fn from_frames(frames: i64, fps: f64) -> Self {
+        Self::from_secs(frames as f64 / fps)
+    }
+
     /// Convert to seconds
     #[must_use]
     #[expect(clippy::cast_precision_loss)]
@@ -103,6 +114,12 @@ mod tests {
         assert_eq!(TimePoint(142), TimePoint::from_secs(0.142_75));
     }
 
+    #[test]
+    fn time_point_from_frames() {
+        assert_eq!(TimePoint::from_frames(2, 25.), TimePoint::from_msecs(80));
+        assert_eq!(TimePoint::from_frames(24, 24.), TimePoint::from_msecs(1000));
+    }
+
     #[test]
     fn time_point_msecs() {
         const TIME: i64 = 62487;
@@ -16,6 +16,18 @@ impl TimeSpan {
     pub const fn new(start: TimePoint, end: TimePoint) -> Self {
         Self { start, end }
     }
+
+    /// Duration of the span in milliseconds.
+    #[must_use]
+    pub const fn duration_msecs(&self) -> i64 {
+        self.end.msecs() - self.start.msecs()
+    }
+
+    /// Check if the span overlap an other span.
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
 }
 
 impl Debug for TimeSpan {
@@ -64,4 +76,14 @@ mod tests {
         assert_ne!(time_span_0_1, time_span_0_2);
         assert_ne!(time_span_0_2, time_span_1_2);
     }
+
+    #[test]
+    fn time_span_duration_and_overlap() {
+        let time_span_0_1 = TimeSpan::new(TimePoint::from_msecs(0), TimePoint::from_msecs(1340));
+        let time_span_1_2 = TimeSpan::new(TimePoint::from_msecs(1245), TimePoint::from_msecs(2340));
+        let time_span_2_3 = TimeSpan::new(TimePoint::from_msecs(2340), TimePoint::from_msecs(3000));
+        assert_eq!(time_span_0_1.duration_msecs(), 1340);
+        assert!(time_span_0_1.overlaps(&time_span_1_2));
+        assert!(!time_span_1_2.overlaps(&time_span_2_3));
+    }
 }
@@ -0,0 +1,677 @@
+//! Read `PGS` and `DVB` subtitles from `MPEG-TS` and Blu-ray `M2TS` streams.
+//!
+//! On Blu-ray discs, the `PGS` segments are carried in `PES` packets of
+//! transport streams. The demuxer finds the `PGS` streams through the Program
+//! Association and Program Map tables, and rebuilds their content in the `.sup`
+//! format, which can be written to a file or decoded with [`SupParser`].
+//!
+//! In broadcast recordings, the `DVB` subtitle streams are found from the subtitling
+//! descriptor of the `PMT`, and their `PES` packets are decoded with [`DvbParser`].
+//!
+//! ## Example code
+//!
+//! ```no_run
+//! use subtile::{pgs::DecodeTimeOnly, ts};
+//!
+//! for track in ts::open_pgs_tracks("00001.m2ts").unwrap() {
+//!     println!("PID {:#x} ({:?})", track.pid(), track.language());
+//!     std::fs::write(format!("{:x}.sup", track.pid()), track.sup_data()).unwrap();
+//!     for time_span in track.parser::<DecodeTimeOnly>() {
+//!         println!("{:?}", time_span.unwrap());
+//!     }
+//! }
+//! ```
+//!
+//! [`DvbParser`]: crate::dvb::DvbParser
+
+mod psi;
+
+use crate::{
+    dvb::{DvbDecoder, DvbPacket, DvbParser},
+    pgs::{PgsDecoder, SupParser, SupWriter},
+};
+use log::warn;
+use psi::{parse_pat, parse_pmt, ElementaryStream};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, Cursor, ErrorKind, Read},
+    iter::Cloned,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    slice,
+};
+use thiserror::Error;
+
+/// Size of a `MPEG-TS` packet.
+const TS_PACKET_SIZE: usize = 188;
+/// Size of the timestamp header added before each packet in `M2TS` streams.
+const M2TS_HEADER_SIZE: usize = 4;
+/// Size of the data read to detect the packets format.
+const DETECT_LEN: u64 = 2 * (TS_PACKET_SIZE + M2TS_HEADER_SIZE) as u64 + 1;
+/// Sync byte at the start of each packet.
+const SYNC_BYTE: u8 = 0x47;
+/// `PID` of the Program Association Table.
+const PAT_PID: u16 = 0x0000;
+/// `PID` of the null packets, used for padding.
+const NULL_PID: u16 = 0x1fff;
+/// `stream_type` of `PGS` streams in the `PMT`.
+const PGS_STREAM_TYPE: u8 = 0x90;
+/// `stream_type` of private `PES` streams, which carry the `DVB` subtitles.
+const PRIVATE_PES_STREAM_TYPE: u8 = 0x06;
+/// `PID` range used by Blu-ray for `PGS` streams, when the `PMT` is missing.
+const PGS_DEFAULT_PIDS: RangeInclusive<u16> = 0x1200..=0x121f;
+
+/// Error for transport streams handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TsError {
+    /// Io error on a path.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("Io error while reading stream")]
+    Read(#[source] io::Error),
+
+    /// The stream doesn't start with a `MPEG-TS` or `M2TS` packet.
+    #[error("not a MPEG transport stream")]
+    NotTransportStream,
+
+    /// A packet doesn't start with the sync byte.
+    #[error("lost synchronization at packet {packet}")]
+    LostSync {
+        /// Index of the packet in the stream
+        packet: u64,
+    },
+}
+
+/// A `PGS` subtitle stream extracted from a transport stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgsTrack {
+    pid: u16,
+    language: Option<String>,
+    sup: Vec<u8>,
+}
+
+impl PgsTrack {
+    /// `PID` of the stream in the transport stream.
+    #[must_use]
+    pub const fn pid(&self) -> u16 {
+        self.pid
+    }
+
+    /// Language of the stream, as declared in the `PMT`.
+    #[must_use]
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Content of the stream in `.sup` format.
+    #[must_use]
+    pub fn sup_data(&self) -> &[u8] {
+        &self.sup
+    }
+
+    /// Get the content of the stream in `.sup` format.
+    #[must_use]
+    pub fn into_sup_data(self) -> Vec<u8> {
+        self.sup
+    }
+
+    /// Create a parser of the subtitles of the stream.
+    #[must_use]
+    pub fn parser<Decoder: PgsDecoder>(&self) -> SupParser<Cursor<&[u8]>, Decoder> {
+        SupParser::new(Cursor::new(self.sup.as_slice()))
+    }
+}
+
+/// A `DVB` subtitle stream extracted from a transport stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DvbTrack {
+    pid: u16,
+    language: Option<String>,
+    pages: Option<(u16, u16)>,
+    packets: Vec<DvbPacket>,
+}
+
+impl DvbTrack {
+    /// `PID` of the stream in the transport stream.
+    #[must_use]
+    pub const fn pid(&self) -> u16 {
+        self.pid
+    }
+
+    /// Language of the stream, as declared in the `PMT`.
+    #[must_use]
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Composition page and ancillary page of the stream, as declared in the `PMT`.
+    #[must_use]
+    pub const fn pages(&self) -> Option<(u16, u16)> {
+        self.pages
+    }
+
+    /// `PES` packets of the stream.
+    #[must_use]
+    pub fn packets(&self) -> &[DvbPacket] {
+        &self.packets
+    }
+
+    /// Create a parser of the subtitles of the stream, limited to the declared pages.
+    #[must_use]
+    pub fn parser<Decoder: DvbDecoder>(
+        &self,
+    ) -> DvbParser<Cloned<slice::Iter<'_, DvbPacket>>, Decoder> {
+        let parser = DvbParser::new(self.packets.iter().cloned());
+        match self.pages {
+            Some((composition, ancillary)) => parser.with_pages(composition, ancillary),
+            None => parser,
+        }
+    }
+}
+
+/// Content of a `PES` packet.
+pub(crate) struct Pes<'a> {
+    /// Presentation timestamp, in 90 kHz units.
+    pub pts: u64,
+    /// Decoding timestamp, in 90 kHz units, if present.
+    pub dts: Option<u64>,
+    /// Payload of the packet.
+    pub payload: &'a [u8],
+}
+
+/// Parse a 33 bits timestamp of a `PES` header.
+fn parse_timestamp(bytes: &[u8]) -> u64 {
+    (u64::from(bytes[0] >> 1 & 0x07) << 30)
+        | (u64::from(bytes[1]) << 22)
+        | (u64::from(bytes[2] >> 1) << 15)
+        | (u64::from(bytes[3]) << 7)
+        | u64::from(bytes[4] >> 1)
+}
+
+/// Parse a `PES` packet, return the timestamps and the payload.
+pub(crate) fn pes_payload(pes: &[u8]) -> Option<Pes<'_>> {
+    if pes.get(..3)? != [0, 0, 1] {
+        return None;
+    }
+    let length = usize::from(u16::from_be_bytes([*pes.get(4)?, *pes.get(5)?]));
+    let pes = if length == 0 {
+        pes
+    } else {
+        pes.get(..6 + length)?
+    };
+    // `PTS_DTS_flags`: `0b10` for the `PTS` only, `0b11` for the `PTS` and the `DTS`.
+    let pts_dts_flags = pes.get(7)? >> 6;
+    let header_len = usize::from(*pes.get(8)?);
+    let pts = if pts_dts_flags & 0b10 != 0 {
+        parse_timestamp(pes.get(9..14)?)
+    } else {
+        0
+    };
+    let dts = if pts_dts_flags == 0b11 {
+        Some(parse_timestamp(pes.get(14..19)?))
+    } else {
+        None
+    };
+    Some(Pes {
+        pts,
+        dts,
+        payload: pes.get(9 + header_len..)?,
+    })
+}
+
+/// Check if the continuity counter `counter` of a packet follows the counter `previous`
+/// of the previous packet of the same `PID`. A repeated packet keeps the same counter.
+const fn is_continuous(previous: u8, counter: u8) -> bool {
+    counter == previous || counter == (previous + 1) & 0x0f
+}
+
+/// Detect the size of the packets: with or without the `M2TS` header.
+fn detect_header_size(start: &[u8]) -> Option<usize> {
+    [0, M2TS_HEADER_SIZE].into_iter().find(|header| {
+        let packet_size = TS_PACKET_SIZE + header;
+        start.get(*header) == Some(&SYNC_BYTE)
+            && start
+                .get(header + packet_size)
+                .map_or(true, |byte| *byte == SYNC_BYTE)
+    })
+}
+
+/// Read a full packet, return `false` at the end of the stream.
+fn read_packet<R: Read>(reader: &mut R, packet: &mut [u8]) -> Result<bool, TsError> {
+    match reader.read_exact(packet) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(TsError::Read(err)),
+    }
+}
+
+/// `PES` packets of the demuxed streams, by `PID`.
+type PesPackets = BTreeMap<u16, Vec<Vec<u8>>>;
+
+/// Extract the `PES` packets of the streams of a `MPEG-TS` or `M2TS` stream.
+///
+/// The streams are selected from the `PMT` with `select`. If no `PMT` is present,
+/// the packets with a `PID` in `default_pids` are extracted.
+/// Return the selected streams of the `PMT`, if any, and the `PES` packets.
+fn demux<R, S>(
+    reader: R,
+    select: S,
+    default_pids: Option<&RangeInclusive<u16>>,
+) -> Result<(Option<Vec<ElementaryStream>>, PesPackets), TsError>
+where
+    R: Read,
+    S: Fn(&ElementaryStream) -> bool,
+{
+    let mut reader = BufReader::new(reader);
+
+    // Read the start of the stream to detect the packet format.
+    let mut start = Vec::new();
+    (&mut reader)
+        .take(DETECT_LEN)
+        .read_to_end(&mut start)
+        .map_err(TsError::Read)?;
+    let header_size = detect_header_size(&start).ok_or(TsError::NotTransportStream)?;
+    let mut reader = Cursor::new(start).chain(reader);
+
+    let mut pmt_pids = Vec::new();
+    let mut streams: Option<Vec<ElementaryStream>> = None;
+    let mut pes_packets = PesPackets::new();
+    let mut assembling = BTreeMap::<u16, Vec<u8>>::new();
+    let mut counters = BTreeMap::<u16, u8>::new();
+    let mut packet = vec![0; header_size + TS_PACKET_SIZE];
+    let mut index = 0;
+    while read_packet(&mut reader, &mut packet)? {
+        let packet = &packet[header_size..];
+        if packet[0] != SYNC_BYTE {
+            return Err(TsError::LostSync { packet: index });
+        }
+        index += 1;
+
+        let unit_start = packet[1] & 0x40 != 0;
+        let pid = u16::from_be_bytes([packet[1], packet[2]]) & 0x1fff;
+        let adaptation = packet[3] & 0x20 != 0;
+        let has_payload = packet[3] & 0x10 != 0;
+        if !has_payload || pid == NULL_PID {
+            continue;
+        }
+        let counter = packet[3] & 0x0f;
+        // The `discontinuity_indicator` signals an expected discontinuity.
+        let discontinuity =
+            adaptation && packet[4] > 0 && packet.get(5).is_some_and(|flags| flags & 0x80 != 0);
+        if let Some(previous) = counters.insert(pid, counter) {
+            if !discontinuity && !is_continuous(previous, counter) {
+                warn!(
+                    "Continuity counter discontinuity on PID {pid:#x} at packet {}: {previous} then {counter}",
+                    index - 1
+                );
+            }
+        }
+        let payload_start = if adaptation {
+            5 + usize::from(packet[4])
+        } else {
+            4
+        };
+        let Some(payload) = packet.get(payload_start..) else {
+            continue;
+        };
+
+        if pid == PAT_PID && unit_start {
+            if let Some(pids) = parse_pat(payload) {
+                pmt_pids = pids;
+            }
+        } else if pmt_pids.contains(&pid) && unit_start {
+            if let Some(pmt_streams) = parse_pmt(payload) {
+                let streams = streams.get_or_insert_with(Vec::new);
+                for stream in pmt_streams {
+                    if select(&stream) && !streams.contains(&stream) {
+                        streams.push(stream);
+                    }
+                }
+            }
+        } else if streams.as_ref().map_or_else(
+            || default_pids.is_some_and(|pids| pids.contains(&pid)),
+            |streams| streams.iter().any(|stream| stream.pid == pid),
+        ) {
+            let pes = assembling.entry(pid).or_default();
+            if unit_start {
+                if !pes.is_empty() {
+                    pes_packets
+                        .entry(pid)
+                        .or_default()
+                        .push(std::mem::take(pes));
+                }
+            } else if pes.is_empty() {
+                // Continuation of a packet which start is missing.
+                continue;
+            }
+            pes.extend_from_slice(payload);
+        }
+    }
+    for (pid, pes) in assembling {
+        let packets = pes_packets.entry(pid).or_default();
+        if !pes.is_empty() {
+            packets.push(pes);
+        }
+    }
+
+    // Only keep the streams declared in the `PMT`, if present.
+    if let Some(streams) = &streams {
+        pes_packets.retain(|pid, _| streams.iter().any(|stream| stream.pid == *pid));
+    }
+    Ok((streams, pes_packets))
+}
+
+/// Extract all the `PGS` streams of a `MPEG-TS` or `M2TS` stream.
+///
+/// The streams are found from the `PMT`. If no `PMT` is present, the packets
+/// with the `PID` used by Blu-ray for `PGS` are extracted.
+///
+/// # Errors
+///
+/// Will return `TsError::NotTransportStream` if the stream doesn't start with a packet.
+/// Will return `TsError::LostSync` if a packet doesn't start with the sync byte.
+/// Will return `TsError::Read` if reading failed.
+pub fn read_pgs_tracks<R: Read>(reader: R) -> Result<Vec<PgsTrack>, TsError> {
+    let (streams, pes_packets) = demux(
+        reader,
+        |stream| stream.stream_type == PGS_STREAM_TYPE,
+        Some(&PGS_DEFAULT_PIDS),
+    )?;
+    Ok(pes_packets
+        .into_iter()
+        .map(|(pid, packets)| {
+            let mut writer = SupWriter::new(Vec::new());
+            for pes in packets {
+                if let Some(Pes { pts, dts, payload }) = pes_payload(&pes) {
+                    // The timestamps are truncated to 32 bits, as in `.sup` files.
+                    let truncate = |timestamp: u64| {
+                        u32::try_from(timestamp & u64::from(u32::MAX)).unwrap_or_default()
+                    };
+                    if let Err(err) = writer.write_segments_with_dts(
+                        truncate(pts),
+                        dts.map_or(0, truncate),
+                        payload,
+                    ) {
+                        warn!("Skipping the end of a PES packet of PID 0x{pid:x}: {err}");
+                    }
+                } else {
+                    warn!("Skipping invalid PES packet of {} bytes", pes.len());
+                }
+            }
+            PgsTrack {
+                pid,
+                language: stream_language(streams.as_deref(), pid),
+                sup: writer.into_inner(),
+            }
+        })
+        .collect())
+}
+
+/// Extract all the `DVB` subtitle streams of a `MPEG-TS` stream.
+///
+/// The streams are found from the subtitling descriptors of the `PMT`.
+///
+/// # Errors
+///
+/// Will return `TsError::NotTransportStream` if the stream doesn't start with a packet.
+/// Will return `TsError::LostSync` if a packet doesn't start with the sync byte.
+/// Will return `TsError::Read` if reading failed.
+pub fn read_dvb_tracks<R: Read>(reader: R) -> Result<Vec<DvbTrack>, TsError> {
+    let (streams, pes_packets) = demux(
+        reader,
+        |stream| stream.stream_type == PRIVATE_PES_STREAM_TYPE && stream.subtitling.is_some(),
+        None,
+    )?;
+    let streams = streams.unwrap_or_default();
+    Ok(pes_packets
+        .into_iter()
+        .map(|(pid, packets)| {
+            let stream = streams.iter().find(|stream| stream.pid == pid);
+            DvbTrack {
+                pid,
+                language: stream.and_then(|stream| stream.language.clone()),
+                pages: stream
+                    .and_then(|stream| stream.subtitling)
+                    .map(|subtitling| (subtitling.composition_page, subtitling.ancillary_page)),
+                packets: packets
+                    .iter()
+                    .filter_map(|pes| {
+                        let packet = DvbPacket::from_pes(pes);
+                        if packet.is_none() {
+                            warn!("Skipping invalid PES packet of {} bytes", pes.len());
+                        }
+                        packet
+                    })
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
+/// Language of a stream, as declared in the `PMT`.
+fn stream_language(streams: Option<&[ElementaryStream]>, pid: u16) -> Option<String> {
+    streams?
+        .iter()
+        .find(|stream| stream.pid == pid)
+        .and_then(|stream| stream.language.clone())
+}
+
+/// Extract all the `PGS` streams of a `MPEG-TS` or `M2TS` file.
+///
+/// # Errors
+///
+/// Will return `TsError::Io` if not able to open the file.
+/// Will return the errors of [`read_pgs_tracks`].
+pub fn open_pgs_tracks<P: AsRef<Path>>(path: P) -> Result<Vec<PgsTrack>, TsError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| TsError::Io {
+        source,
+        path: path.into(),
+    })?;
+    read_pgs_tracks(file)
+}
+
+/// Extract all the `DVB` subtitle streams of a `MPEG-TS` file.
+///
+/// # Errors
+///
+/// Will return `TsError::Io` if not able to open the file.
+/// Will return the errors of [`read_dvb_tracks`].
+pub fn open_dvb_tracks<P: AsRef<Path>>(path: P) -> Result<Vec<DvbTrack>, TsError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| TsError::Io {
+        source,
+        path: path.into(),
+    })?;
+    read_dvb_tracks(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgs::{DecodeTimeImage, DecodeTimeOnly};
+    use assert_matches2::assert_matches;
+    use std::fs;
+
+    fn expected_times() -> Vec<crate::time::TimeSpan> {
+        SupParser::<BufReader<File>, DecodeTimeOnly>::from_file("./fixtures/only_one.sup")
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn extract_pgs_from_m2ts() {
+        let tracks = open_pgs_tracks("./fixtures/only_one.m2ts").unwrap();
+        assert_eq!(tracks.len(), 1);
+        let track = &tracks[0];
+        assert_eq!(track.pid(), 0x1200);
+        assert_eq!(track.language(), Some("fra"));
+
+        let times = track
+            .parser::<DecodeTimeOnly>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(times, expected_times());
+        let images = track
+            .parser::<DecodeTimeImage>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(images[0].1.position(), (985, 779));
+    }
+
+    #[test]
+    fn extract_pgs_from_ts() {
+        // Remove the `M2TS` header of each packet, and the tables.
+        let data = fs::read("./fixtures/only_one.m2ts").unwrap();
+        let ts = data
+            .chunks_exact(M2TS_HEADER_SIZE + TS_PACKET_SIZE)
+            .map(|packet| &packet[M2TS_HEADER_SIZE..])
+            .filter(|packet| u16::from_be_bytes([packet[1], packet[2]]) & 0x1fff != PAT_PID)
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        let tracks = read_pgs_tracks(ts.as_slice()).unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].language(), None);
+        let times = tracks[0]
+            .parser::<DecodeTimeOnly>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(times, expected_times());
+    }
+
+    /// Build a `TS` packet, padded with stuffing bytes.
+    fn ts_packet(pid: u16, payload: &[u8]) -> Vec<u8> {
+        let [high, low] = pid.to_be_bytes();
+        let mut packet = vec![SYNC_BYTE, 0x40 | high, low, 0x10];
+        packet.extend(payload);
+        packet.resize(TS_PACKET_SIZE, 0xff);
+        packet
+    }
+
+    /// Build a `PES` packet with a `PTS`.
+    fn pes(pts: u64, payload: &[u8]) -> Vec<u8> {
+        pes_with_dts(pts, None, payload)
+    }
+
+    /// Build a `PES` packet with a `PTS` and an optional `DTS`.
+    fn pes_with_dts(pts: u64, dts: Option<u64>, payload: &[u8]) -> Vec<u8> {
+        let byte = |value: u64| u8::try_from(value & 0xff).unwrap();
+        let timestamp = |prefix: u8, value: u64| {
+            [
+                prefix | 0x01 | byte((value >> 29) & 0x0e),
+                byte(value >> 22),
+                byte((value >> 14) & 0xfe) | 1,
+                byte(value >> 7),
+                byte((value << 1) & 0xfe) | 1,
+            ]
+        };
+        let header_len = if dts.is_some() { 10 } else { 5 };
+        let length = u16::try_from(3 + header_len + payload.len()).unwrap();
+        let mut pes = vec![0, 0, 1, 0xbd];
+        pes.extend(length.to_be_bytes());
+        if let Some(dts) = dts {
+            pes.extend([0x80, 0xc0, 10]);
+            pes.extend(timestamp(0x30, pts));
+            pes.extend(timestamp(0x10, dts));
+        } else {
+            pes.extend([0x80, 0x80, 5]);
+            pes.extend(timestamp(0x20, pts));
+        }
+        pes.extend(payload);
+        pes
+    }
+
+    #[test]
+    fn skip_truncated_pgs_segments() {
+        // A truncated segment, then a complete `END` segment.
+        let mut ts = ts_packet(0x1200, &pes(1000, &[0x16, 0, 10, 1]));
+        ts.extend(ts_packet(0x1200, &pes(2000, &[0x80, 0, 0])));
+
+        let tracks = read_pgs_tracks(ts.as_slice()).unwrap();
+        assert_eq!(tracks.len(), 1);
+        let mut expected = b"PG".to_vec();
+        expected.extend(2000_u32.to_be_bytes());
+        expected.extend(0_u32.to_be_bytes());
+        expected.extend([0x80, 0, 0]);
+        assert_eq!(tracks[0].sup, expected);
+    }
+
+    #[test]
+    fn extract_dvb_from_ts() {
+        use crate::dvb::{tests::packets, DecodeTimeOnly};
+
+        // `PAT` with the program `1` described by the `PMT` on `PID` `0x100`.
+        let pat = [
+            0, 0x00, 0xb0, 13, 0, 1, 0xc1, 0, 0, 0, 1, 0xe1, 0x00, 0, 0, 0, 0,
+        ];
+        // `PMT` with a `DVB` subtitle stream on `PID` `0x150`, for the pages `1`.
+        let pmt = [
+            0, 0x02, 0xb0, 28, 0, 1, 0xc1, 0, 0, 0xe1, 0x50, 0xf0, 0, 0x06, 0xe1, 0x50, 0xf0, 10,
+            0x59, 8, b'd', b'e', b'u', 0x10, 0, 1, 0, 1, 0, 0, 0, 0,
+        ];
+        let mut ts = ts_packet(PAT_PID, &pat);
+        ts.extend(ts_packet(0x100, &pmt));
+        for packet in packets() {
+            ts.extend(ts_packet(0x150, &pes(packet.pts, &packet.payload)));
+        }
+
+        let tracks = read_dvb_tracks(ts.as_slice()).unwrap();
+        assert_eq!(tracks.len(), 1);
+        let track = &tracks[0];
+        assert_eq!(track.pid(), 0x150);
+        assert_eq!(track.language(), Some("deu"));
+        assert_eq!(track.pages(), Some((1, 1)));
+        assert_eq!(track.packets(), packets());
+        let times = track
+            .parser::<DecodeTimeOnly>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(times.len(), 1);
+    }
+
+    #[test]
+    fn parse_pes_timestamps() {
+        let packet = pes(0x1_2345_6789, b"data");
+        let parsed = pes_payload(&packet).unwrap();
+        assert_eq!(
+            (parsed.pts, parsed.dts, parsed.payload),
+            (0x1_2345_6789, None, b"data".as_slice())
+        );
+
+        let packet = pes_with_dts(0x1_2345_6789, Some(0x1_2345_0000), b"x");
+        let parsed = pes_payload(&packet).unwrap();
+        assert_eq!(
+            (parsed.pts, parsed.dts, parsed.payload),
+            (0x1_2345_6789, Some(0x1_2345_0000), b"x".as_slice())
+        );
+    }
+
+    #[test]
+    fn continuity_counter() {
+        assert!(is_continuous(3, 4));
+        assert!(is_continuous(15, 0));
+        assert!(is_continuous(7, 7));
+        assert!(!is_continuous(3, 5));
+        assert!(!is_continuous(0, 15));
+    }
+
+    #[test]
+    fn reject_other_files() {
+        assert_matches!(
+            open_pgs_tracks("./fixtures/only_one.sup"),
+            Err(TsError::NotTransportStream)
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Parsing of the Program Specific Information tables (`PAT` and `PMT`).
+
+/// `table_id` of the Program Association Table.
+const PAT_TABLE_ID: u8 = 0x00;
+/// `table_id` of the Program Map Table.
+const PMT_TABLE_ID: u8 = 0x02;
+/// Size of the section header, before the table content.
+const SECTION_HEADER_LEN: usize = 8;
+/// Size of the `CRC` at the end of the sections.
+const CRC_LEN: usize = 4;
+/// Tag of the `ISO 639` language descriptor.
+const LANGUAGE_DESCRIPTOR_TAG: u8 = 0x0a;
+/// Tag of the `DVB` subtitling descriptor.
+const SUBTITLING_DESCRIPTOR_TAG: u8 = 0x59;
+
+/// First entry of the subtitling descriptor of a `DVB` subtitle stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subtitling {
+    /// Page of the segments of the subtitles.
+    pub composition_page: u16,
+    /// Page of the segments shared by several subtitle streams.
+    pub ancillary_page: u16,
+}
+
+/// An elementary stream declared in a `PMT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementaryStream {
+    /// Type of the stream.
+    pub stream_type: u8,
+    /// `PID` of the packets of the stream.
+    pub pid: u16,
+    /// Language of the stream, if declared.
+    pub language: Option<String>,
+    /// Subtitling descriptor of `DVB` subtitle streams.
+    pub subtitling: Option<Subtitling>,
+}
+
+/// Get the content of a table section in a packet payload starting a section.
+fn section(payload: &[u8], table_id: u8) -> Option<&[u8]> {
+    let pointer = usize::from(*payload.first()?);
+    let section = payload.get(1 + pointer..)?;
+    if *section.first()? != table_id {
+        return None;
+    }
+    let length = usize::from(u16::from_be_bytes([*section.get(1)?, *section.get(2)?]) & 0x0fff);
+    section.get(SECTION_HEADER_LEN..(3 + length).checked_sub(CRC_LEN)?)
+}
+
+/// Read a 13 bits `PID`.
+fn pid(data: &[u8]) -> u16 {
+    u16::from_be_bytes([data[0], data[1]]) & 0x1fff
+}
+
+/// Parse a `PAT`, return the `PID` of the `PMT` of each program.
+pub fn parse_pat(payload: &[u8]) -> Option<Vec<u16>> {
+    let programs = section(payload, PAT_TABLE_ID)?;
+    Some(
+        programs
+            .chunks_exact(4)
+            // Program 0 is the network information table.
+            .filter(|program| program[..2] != [0, 0])
+            .map(|program| pid(&program[2..]))
+            .collect(),
+    )
+}
+
+/// Parse a `PMT`, return the elementary streams of the program.
+pub fn parse_pmt(payload: &[u8]) -> Option<Vec<ElementaryStream>> {
+    let table = section(payload, PMT_TABLE_ID)?;
+    let program_info_len =
+        usize::from(u16::from_be_bytes([*table.get(2)?, *table.get(3)?]) & 0x0fff);
+    let mut data = table.get(4 + program_info_len..)?;
+
+    let mut streams = Vec::new();
+    while data.len() >= 5 {
+        let info_len = usize::from(u16::from_be_bytes([data[3], data[4]]) & 0x0fff);
+        let descriptors = data.get(5..5 + info_len)?;
+        let subtitling = descriptor(descriptors, SUBTITLING_DESCRIPTOR_TAG)
+            .filter(|content| content.len() >= 8)
+            .map(|content| Subtitling {
+                composition_page: u16::from_be_bytes([content[4], content[5]]),
+                ancillary_page: u16::from_be_bytes([content[6], content[7]]),
+            });
+        let language = descriptor(descriptors, LANGUAGE_DESCRIPTOR_TAG)
+            .or_else(|| descriptor(descriptors, SUBTITLING_DESCRIPTOR_TAG))
+            .and_then(|content| String::from_utf8(content.get(..3)?.to_vec()).ok());
+        streams.push(ElementaryStream {
+            stream_type: data[0],
+            pid: pid(&data[1..]),
+            language,
+            subtitling,
+        });
+        data = &data[5 + info_len..];
+    }
+    Some(streams)
+}
+
+/// Find the content of a descriptor of an elementary stream.
+fn descriptor(mut descriptors: &[u8], searched_tag: u8) -> Option<&[u8]> {
+    while descriptors.len() >= 2 {
+        let (tag, len) = (descriptors[0], usize::from(descriptors[1]));
+        let content = descriptors.get(2..2 + len)?;
+        if tag == searched_tag {
+            return Some(content);
+        }
+        descriptors = &descriptors[2 + len..];
+    }
+    None
+}
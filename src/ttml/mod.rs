@@ -0,0 +1,1224 @@
+//! Timed Text Markup Language (`TTML`) functionality, with the `IMSC1` profiles and `DFXP`.
+//!
+//! Documents are read with their styles, regions and paragraphs, and can be written
+//! for the `IMSC1` Text Profile, or for the `IMSC1` Image Profile with `PNG` images
+//! embedded in the document.
+//!
+//! Specification: <https://www.w3.org/TR/ttml-imsc1.0.1/>
+mod style;
+mod time;
+
+pub use style::{format_color, parse_color, DisplayAlign, TextAlign, TextStyle};
+pub use time::{TimePointTtml, TimingParams};
+
+use crate::{
+    content::{Area, AreaValues, Size},
+    text::{self, CueAlign, CuePosition, CueRow, CueStyle},
+    time::{TimePoint, TimeSpan},
+};
+use base64::Engine as _;
+use image::{ImageBuffer, ImageFormat, PixelWithColorType, Rgb, Rgba};
+use roxmltree::Node;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+};
+use style::styling_attribute;
+use thiserror::Error;
+
+/// Namespace of `TTML` elements.
+const TT_NS: &str = "http://www.w3.org/ns/ttml";
+/// Namespace of `TTML` parameter attributes.
+const TTP_NS: &str = "http://www.w3.org/ns/ttml#parameter";
+/// Namespace of `TTML` styling attributes.
+const TTS_NS: &str = "http://www.w3.org/ns/ttml#styling";
+/// Namespace of the `SMPTE-TT` extensions, used for images.
+const SMPTE_NS: &str = "http://www.smpte-ra.org/schemas/2052-1/2010/smpte-tt";
+/// Namespace of the `xml:*` attributes.
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+/// Profile designator of the `IMSC1` Text Profile.
+const IMSC1_TEXT_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1/text";
+/// Profile designator of the `IMSC1` Image Profile.
+const IMSC1_IMAGE_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1/image";
+
+/// Error for `TTML` handling.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TtmlError {
+    /// If an error happen during the file opening.
+    #[error("Io error on '{path}'")]
+    Io {
+        /// Source error
+        source: io::Error,
+        /// Path of the file we tried to read
+        path: PathBuf,
+    },
+
+    /// Io error while reading a stream.
+    #[error("failed to read the document")]
+    Read(#[source] io::Error),
+
+    /// The content is not a valid `XML` document.
+    #[error("invalid XML")]
+    Xml(#[from] roxmltree::Error),
+
+    /// The root element is not a `<tt>` element.
+    #[error("the document is not a TTML document")]
+    NotTtml,
+
+    /// A time expression is invalid.
+    #[error("line {line}: invalid time expression '{value}'")]
+    InvalidTime {
+        /// Line number of the element (starting at 1).
+        line: u32,
+        /// Time expression.
+        value: String,
+    },
+
+    /// The end of a paragraph or of an image can't be resolved.
+    #[error("line {line}: missing end time")]
+    MissingEnd {
+        /// Line number of the element (starting at 1).
+        line: u32,
+    },
+
+    /// An embedded image is referenced, but not defined.
+    #[error("line {line}: missing image '{id}'")]
+    MissingImage {
+        /// Line number of the element (starting at 1).
+        line: u32,
+        /// Id of the image.
+        id: String,
+    },
+
+    /// An embedded image is not valid `Base64` data.
+    #[error("line {line}: invalid Base64 image data")]
+    InvalidImage {
+        /// Line number of the element (starting at 1).
+        line: u32,
+    },
+
+    /// Failed to encode an image in `PNG`.
+    #[error("failed to encode image")]
+    EncodeImage(#[source] image::ImageError),
+}
+
+/// A length of the layout (`tts:origin`, `tts:extent`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    /// Percentage of the root container size.
+    Percent(f64),
+    /// Number of pixels.
+    Pixels(f64),
+}
+
+impl Length {
+    /// Parse a length like `10%` or `100px`. Other units are not supported.
+    fn parse(value: &str) -> Option<Self> {
+        if let Some(percent) = value.strip_suffix('%') {
+            percent.parse().ok().map(Self::Percent)
+        } else {
+            value.strip_suffix("px")?.parse().ok().map(Self::Pixels)
+        }
+    }
+
+    /// Parse a pair of lengths, like `10% 80%`.
+    fn parse_pair(value: &str) -> Option<[Self; 2]> {
+        let mut values = value.split_whitespace().map(Self::parse);
+        let pair = [values.next()??, values.next()??];
+        values.next().is_none().then_some(pair)
+    }
+
+    /// Get the length in pixels, with `reference` the size of the root container.
+    fn pixels(self, reference: usize) -> f64 {
+        match self {
+            #[expect(clippy::cast_precision_loss)]
+            Self::Percent(percent) => percent * reference as f64 / 100.,
+            Self::Pixels(pixels) => pixels,
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percent(percent) => write!(f, "{percent}%"),
+            Self::Pixels(pixels) => write!(f, "{pixels}px"),
+        }
+    }
+}
+
+/// A named style of the `<styling>` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    /// Id of the style.
+    pub id: String,
+    /// Properties of the style, including the properties of the referenced styles.
+    pub style: TextStyle,
+}
+
+/// A region of the `<layout>` section, where the content is displayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// Id of the region.
+    pub id: String,
+    /// Position of the top left corner (`tts:origin`).
+    pub origin: Option<[Length; 2]>,
+    /// Size of the region (`tts:extent`).
+    pub extent: Option<[Length; 2]>,
+    /// Vertical alignment of the content (`tts:displayAlign`).
+    pub display_align: Option<DisplayAlign>,
+    /// Style properties of the region, inherited by its content.
+    pub style: TextStyle,
+}
+
+impl Region {
+    /// Get the area of the region in pixels, for a root container of size `frame_size`.
+    #[must_use]
+    pub fn area(&self, frame_size: Size) -> Option<Area> {
+        let [x, y] = self.origin?;
+        let [w, h] = self.extent?;
+        let x1 = x.pixels(frame_size.w).round();
+        let y1 = y.pixels(frame_size.h).round();
+        let x2 = x1 + w.pixels(frame_size.w).round() - 1.;
+        let y2 = y1 + h.pixels(frame_size.h).round() - 1.;
+        Area::try_from(AreaValues {
+            x1: cast::u16(x1).ok()?,
+            y1: cast::u16(y1).ok()?,
+            x2: cast::u16(x2).ok()?,
+            y2: cast::u16(y2).ok()?,
+        })
+        .ok()
+    }
+
+    /// Write the region element.
+    fn write(&self, out: &mut impl io::Write) -> Result<(), io::Error> {
+        write!(out, r#"<region xml:id="{}""#, escape(&self.id))?;
+        if let Some([x, y]) = self.origin {
+            write!(out, r#" tts:origin="{x} {y}""#)?;
+        }
+        if let Some([w, h]) = self.extent {
+            write!(out, r#" tts:extent="{w} {h}""#)?;
+        }
+        if let Some(align) = self.display_align {
+            write!(out, r#" tts:displayAlign="{}""#, align.as_str())?;
+        }
+        self.style.write_attributes(out)?;
+        writeln!(out, "/>")
+    }
+}
+
+/// A part of the text of a paragraph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inline {
+    /// Text, with the styles of its `<span>` elements.
+    Span(Span),
+    /// A line break (`<br/>`).
+    LineBreak,
+}
+
+/// A text with its style.
+///
+/// Nested `<span>` elements are flattened: the style references and properties of the
+/// outer spans are applied before the ones of the inner spans.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    /// Text, with the whitespaces collapsed.
+    pub text: String,
+    /// Ids of the referenced styles.
+    pub styles: Vec<String>,
+    /// Inline style properties.
+    pub style: TextStyle,
+}
+
+/// Content of a cue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CueContent {
+    /// Text of a paragraph (`<p>`).
+    Text(Vec<Inline>),
+    /// `PNG` image embedded in the document (`IMSC1` Image Profile).
+    Image(Vec<u8>),
+    /// Reference to an external image file.
+    ImageFile(String),
+}
+
+/// A subtitle of the document, a paragraph or an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// Time span of the cue, in absolute time.
+    pub time_span: TimeSpan,
+    /// Id of the region of the cue.
+    pub region: Option<String>,
+    /// Ids of the referenced styles, including the ones of the parent elements.
+    pub styles: Vec<String>,
+    /// Inline style properties, including the ones of the parent elements.
+    pub style: TextStyle,
+    /// Content of the cue.
+    pub content: CueContent,
+}
+
+impl Cue {
+    /// Get the text of a text cue, with line breaks as `\n`. Return `None` for images.
+    #[must_use]
+    pub fn plain_text(&self) -> Option<String> {
+        let CueContent::Text(inlines) = &self.content else {
+            return None;
+        };
+        Some(
+            inlines
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Span(span) => span.text.as_str(),
+                    Inline::LineBreak => "\n",
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Convert to the shared text model, with the inline style properties only.
+/// Use [`Document::text_cues`] to also apply the referenced styles and the region.
+/// Image cues have no rows.
+impl From<&Cue> for text::Cue {
+    fn from(cue: &Cue) -> Self {
+        text_cue(cue, None)
+    }
+}
+
+/// Convert a cue to the shared text model, with the styles and regions of `document`.
+fn text_cue(cue: &Cue, document: Option<&Document>) -> text::Cue {
+    // Properties of the referenced styles, then the inline properties, over `base`.
+    let resolve = |ids: &[String], inline: &TextStyle, base: &TextStyle| {
+        let mut style = base.clone();
+        for named in ids.iter().filter_map(|id| document?.style(id)) {
+            style.merge(&named.style);
+        }
+        style.merge(inline);
+        style
+    };
+    let region = document
+        .zip(cue.region.as_deref())
+        .and_then(|(document, id)| document.region(id));
+    let cue_style = resolve(
+        &cue.styles,
+        &cue.style,
+        &region
+            .map(|region| region.style.clone())
+            .unwrap_or_default(),
+    );
+
+    let mut rows = Vec::new();
+    if let CueContent::Text(inlines) = &cue.content {
+        let mut row = CueRow::default();
+        for inline in inlines {
+            match inline {
+                Inline::Span(span) => {
+                    let style = resolve(&span.styles, &span.style, &cue_style);
+                    row.push(
+                        &span.text,
+                        CueStyle {
+                            bold: style.bold.unwrap_or_default(),
+                            italic: style.italic.unwrap_or_default(),
+                            underline: style.underline.unwrap_or_default(),
+                            strike_out: false,
+                            color: style
+                                .color
+                                .map(|Rgba([red, green, blue, _])| Rgb([red, green, blue])),
+                        },
+                    );
+                }
+                Inline::LineBreak => rows.push(std::mem::take(&mut row)),
+            }
+        }
+        rows.push(row);
+    }
+
+    // Vertical position of the region origin.
+    let line = region.and_then(|region| {
+        let [_, y] = region.origin?;
+        let percent = match (y, document?.extent) {
+            (Length::Percent(percent), _) => percent,
+            (Length::Pixels(_), Some(extent)) => y.pixels(extent.h) * 100. / cast::f64(extent.h),
+            (Length::Pixels(_), None) => return None,
+        };
+        cast::u8(percent.round()).ok()
+    });
+    let align = cue_style.text_align.map(|align| match align {
+        TextAlign::Left | TextAlign::Start => CueAlign::Left,
+        TextAlign::Center => CueAlign::Center,
+        TextAlign::Right | TextAlign::End => CueAlign::Right,
+    });
+    text::Cue {
+        time_span: cue.time_span,
+        position: CuePosition { line, align },
+        rows,
+    }
+}
+
+/// Inherited values while parsing the body of a document.
+#[derive(Clone, Default)]
+struct Context {
+    begin: TimePoint,
+    end: Option<TimePoint>,
+    region: Option<String>,
+    styles: Vec<String>,
+    style: TextStyle,
+}
+
+/// A `TTML` document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    /// Language of the document (`xml:lang`).
+    pub lang: Option<String>,
+    /// Size of the root container in pixels (`tts:extent` of `<tt>`).
+    pub extent: Option<Size>,
+    /// Parameters used for times in frames or ticks.
+    pub timing: TimingParams,
+    /// Named styles.
+    pub styles: Vec<Style>,
+    /// Regions of the layout.
+    pub regions: Vec<Region>,
+    /// Subtitles, in document order.
+    pub cues: Vec<Cue>,
+}
+
+impl Document {
+    /// Create a text document from subtitles, displayed centered at the bottom of the screen.
+    #[must_use]
+    pub fn from_subtitles(subtitles: &[(TimeSpan, String)]) -> Self {
+        let region = Region {
+            id: "bottom".into(),
+            origin: Some([Length::Percent(10.), Length::Percent(10.)]),
+            extent: Some([Length::Percent(80.), Length::Percent(80.)]),
+            display_align: Some(DisplayAlign::After),
+            style: TextStyle {
+                text_align: Some(TextAlign::Center),
+                ..TextStyle::default()
+            },
+        };
+        let cues = subtitles
+            .iter()
+            .map(|(time_span, text)| {
+                let mut inlines = Vec::new();
+                for (idx, line) in text.lines().enumerate() {
+                    if idx > 0 {
+                        inlines.push(Inline::LineBreak);
+                    }
+                    inlines.push(Inline::Span(Span {
+                        text: line.into(),
+                        ..Span::default()
+                    }));
+                }
+                Cue {
+                    time_span: *time_span,
+                    region: Some(region.id.clone()),
+                    styles: Vec::new(),
+                    style: TextStyle::default(),
+                    content: CueContent::Text(inlines),
+                }
+            })
+            .collect();
+        Self {
+            regions: vec![region],
+            cues,
+            ..Self::default()
+        }
+    }
+
+    /// Create an `IMSC1` Image Profile document from subtitle images, each displayed
+    /// at its `area` on a root container of size `frame_size`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TtmlError::EncodeImage` if an image can't be encoded in `PNG`.
+    pub fn from_images<'a, P, I>(frame_size: Size, images: I) -> Result<Self, TtmlError>
+    where
+        P: PixelWithColorType<Subpixel = u8> + 'a,
+        I: IntoIterator<Item = (TimeSpan, Area, &'a ImageBuffer<P, Vec<u8>>)>,
+    {
+        let mut document = Self {
+            extent: Some(frame_size),
+            ..Self::default()
+        };
+        for (idx, (time_span, area, image)) in images.into_iter().enumerate() {
+            let mut png = Cursor::new(Vec::new());
+            image
+                .write_to(&mut png, ImageFormat::Png)
+                .map_err(TtmlError::EncodeImage)?;
+            let region = Region {
+                id: format!("r{}", idx + 1),
+                origin: Some([
+                    Length::Pixels(f64::from(area.left())),
+                    Length::Pixels(f64::from(area.top())),
+                ]),
+                extent: Some([
+                    Length::Pixels(f64::from(area.width())),
+                    Length::Pixels(f64::from(area.height())),
+                ]),
+                display_align: None,
+                style: TextStyle::default(),
+            };
+            document.cues.push(Cue {
+                time_span,
+                region: Some(region.id.clone()),
+                styles: Vec::new(),
+                style: TextStyle::default(),
+                content: CueContent::Image(png.into_inner()),
+            });
+            document.regions.push(region);
+        }
+        Ok(document)
+    }
+
+    /// Read a `TTML` or `DFXP` file.
+    ///
+    /// Invalid `UTF-8` sequences are replaced, as with the other text formats.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TtmlError::Io` if not able to read the file.
+    /// Will return an other `TtmlError` if the content is not a valid document.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TtmlError> {
+        let path = path.as_ref();
+        let content = fs::read(path).map_err(|source| TtmlError::Io {
+            source,
+            path: path.into(),
+        })?;
+        Self::parse(&text::decode(&content))
+    }
+
+    /// Read a document from a `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TtmlError::Read` if failed to read from `reader`.
+    /// Will return an other `TtmlError` if the content is not a valid document.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, TtmlError> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(TtmlError::Read)?;
+        Self::parse(&text::decode(&content))
+    }
+
+    /// Parse the content of a document.
+    ///
+    /// Timing attributes of `<span>` elements are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TtmlError::Xml` or `TtmlError::NotTtml` if the content is not a `TTML` document.
+    /// Will return an other `TtmlError` if a time or an image of the document is invalid.
+    pub fn parse(content: &str) -> Result<Self, TtmlError> {
+        let xml = roxmltree::Document::parse(content)?;
+        let root = xml.root_element();
+        if root.tag_name().name() != "tt" {
+            return Err(TtmlError::NotTtml);
+        }
+
+        let mut document = Self {
+            lang: root.attribute((XML_NS, "lang")).map(Into::into),
+            extent: styling_attribute(root, "extent").and_then(pixel_size),
+            timing: timing_params(root),
+            ..Self::default()
+        };
+
+        let mut images = HashMap::new();
+        for node in root.descendants().filter(Node::is_element) {
+            let parent = node.parent_element().map(|parent| parent.tag_name().name());
+            match (parent, node.tag_name().name()) {
+                (Some("styling"), "style") => {
+                    let style = document.referenced_style(node);
+                    document.styles.push(Style {
+                        id: id(node).unwrap_or_default().into(),
+                        style,
+                    });
+                }
+                (Some("layout"), "region") => {
+                    document.regions.push(Region {
+                        id: id(node).unwrap_or_default().into(),
+                        origin: styling_attribute(node, "origin").and_then(Length::parse_pair),
+                        extent: styling_attribute(node, "extent").and_then(Length::parse_pair),
+                        display_align: styling_attribute(node, "displayAlign")
+                            .and_then(DisplayAlign::parse),
+                        style: document.referenced_style(node),
+                    });
+                }
+                (_, "image") if node.tag_name().namespace() == Some(SMPTE_NS) => {
+                    if let Some(id) = id(node) {
+                        images.insert(id, node);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(body) = root
+            .children()
+            .find(|node| node.tag_name().name() == "body")
+        {
+            document.parse_container(&xml, body, &Context::default(), &images)?;
+        }
+        Ok(document)
+    }
+
+    /// Get the style properties of the styles referenced by an element, with the
+    /// properties of the element applied over them.
+    fn referenced_style(&self, node: Node<'_, '_>) -> TextStyle {
+        let mut style = TextStyle::default();
+        for reference in style_references(node) {
+            if let Some(referenced) = self.styles.iter().find(|style| style.id == reference) {
+                style.merge(&referenced.style);
+            }
+        }
+        style.merge(&TextStyle::from_node(node));
+        style
+    }
+
+    /// Parse a `<body>` or `<div>` element, and its content.
+    fn parse_container(
+        &mut self,
+        xml: &roxmltree::Document<'_>,
+        node: Node<'_, '_>,
+        parent: &Context,
+        images: &HashMap<&str, Node<'_, '_>>,
+    ) -> Result<(), TtmlError> {
+        let context = self.context(xml, node, parent)?;
+        if let Some(image) = node
+            .attributes()
+            .find(|attribute| {
+                attribute.namespace() == Some(SMPTE_NS) && attribute.name() == "backgroundImage"
+            })
+            .map(|attribute| attribute.value())
+        {
+            let image = if let Some(id) = image.strip_prefix('#') {
+                let data = images.get(id).ok_or_else(|| TtmlError::MissingImage {
+                    line: line(xml, node),
+                    id: id.into(),
+                })?;
+                CueContent::Image(decode_image(xml, *data)?)
+            } else {
+                CueContent::ImageFile(image.into())
+            };
+            self.push_cue(xml, node, &context, image)?;
+        }
+
+        for child in node.children().filter(Node::is_element) {
+            match child.tag_name().name() {
+                "div" => self.parse_container(xml, child, &context, images)?,
+                "p" => {
+                    let context = self.context(xml, child, &context)?;
+                    let mut inlines = Vec::new();
+                    parse_inlines(child, &Span::default(), &mut inlines);
+                    self.push_cue(
+                        xml,
+                        child,
+                        &context,
+                        CueContent::Text(normalize_space(inlines)),
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the inherited values of an element.
+    fn context(
+        &self,
+        xml: &roxmltree::Document<'_>,
+        node: Node<'_, '_>,
+        parent: &Context,
+    ) -> Result<Context, TtmlError> {
+        let time = |name| {
+            node.attribute(name)
+                .map(|value| {
+                    self.timing
+                        .parse_time(value)
+                        .ok_or_else(|| TtmlError::InvalidTime {
+                            line: line(xml, node),
+                            value: value.into(),
+                        })
+                })
+                .transpose()
+        };
+        let offset = |time: TimePoint| {
+            TimePoint::from_msecs(parent.begin.msecs().saturating_add(time.msecs()))
+        };
+
+        let begin = time("begin")?.map_or(parent.begin, offset);
+        let end = if let Some(end) = time("end")? {
+            Some(offset(end))
+        } else if let Some(duration) = time("dur")? {
+            Some(TimePoint::from_msecs(
+                begin.msecs().saturating_add(duration.msecs()),
+            ))
+        } else {
+            parent.end
+        };
+
+        let mut styles = parent.styles.clone();
+        styles.extend(style_references(node).map(Into::into));
+        let mut style = parent.style.clone();
+        style.merge(&TextStyle::from_node(node));
+        Ok(Context {
+            begin,
+            end,
+            region: node
+                .attribute("region")
+                .map(Into::into)
+                .or_else(|| parent.region.clone()),
+            styles,
+            style,
+        })
+    }
+
+    /// Add a cue, with the inherited values of its element.
+    fn push_cue(
+        &mut self,
+        xml: &roxmltree::Document<'_>,
+        node: Node<'_, '_>,
+        inherited: &Context,
+        content: CueContent,
+    ) -> Result<(), TtmlError> {
+        let end = inherited.end.ok_or(TtmlError::MissingEnd {
+            line: line(xml, node),
+        })?;
+        self.cues.push(Cue {
+            time_span: TimeSpan::new(inherited.begin, end),
+            region: inherited.region.clone(),
+            styles: inherited.styles.clone(),
+            style: inherited.style.clone(),
+            content,
+        });
+        Ok(())
+    }
+
+    /// Get a style by id.
+    #[must_use]
+    pub fn style(&self, id: &str) -> Option<&Style> {
+        self.styles.iter().find(|style| style.id == id)
+    }
+
+    /// Convert the cues to the shared text model, with their referenced styles and regions.
+    #[must_use]
+    pub fn text_cues(&self) -> Vec<text::Cue> {
+        self.cues
+            .iter()
+            .map(|cue| text_cue(cue, Some(self)))
+            .collect()
+    }
+
+    /// Get a region by id.
+    #[must_use]
+    pub fn region(&self, id: &str) -> Option<&Region> {
+        self.regions.iter().find(|region| region.id == id)
+    }
+
+    /// Write the document. It's an `IMSC1` Image Profile document if it contains images,
+    /// else an `IMSC1` Text Profile document.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing in `writer` return an `Err`.
+    pub fn write(&self, writer: &mut impl io::Write) -> Result<(), io::Error> {
+        let has_images = self
+            .cues
+            .iter()
+            .any(|cue| !matches!(cue.content, CueContent::Text(_)));
+
+        let out = writer;
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        write!(
+            out,
+            r#"<tt xmlns="{TT_NS}" xmlns:ttp="{TTP_NS}" xmlns:tts="{TTS_NS}""#
+        )?;
+        if has_images {
+            write!(
+                out,
+                r#" xmlns:smpte="{SMPTE_NS}" ttp:profile="{IMSC1_IMAGE_PROFILE}""#
+            )?;
+        } else {
+            write!(out, r#" ttp:profile="{IMSC1_TEXT_PROFILE}""#)?;
+        }
+        if let Some(lang) = &self.lang {
+            write!(out, r#" xml:lang="{}""#, escape(lang))?;
+        }
+        if let Some(extent) = self.extent {
+            write!(out, r#" tts:extent="{}px {}px""#, extent.w, extent.h)?;
+        }
+        writeln!(out, ">\n<head>")?;
+
+        if has_images {
+            writeln!(out, "<metadata>")?;
+            for (idx, cue) in self.cues.iter().enumerate() {
+                if let CueContent::Image(png) = &cue.content {
+                    writeln!(
+                        out,
+                        r#"<smpte:image imagetype="PNG" encoding="Base64" xml:id="img{idx}">{}</smpte:image>"#,
+                        base64::engine::general_purpose::STANDARD.encode(png)
+                    )?;
+                }
+            }
+            writeln!(out, "</metadata>")?;
+        }
+        writeln!(out, "<styling>")?;
+        for style in &self.styles {
+            write!(out, r#"<style xml:id="{}""#, escape(&style.id))?;
+            style.style.write_attributes(out)?;
+            writeln!(out, "/>")?;
+        }
+        writeln!(out, "</styling>\n<layout>")?;
+        for region in &self.regions {
+            region.write(out)?;
+        }
+        writeln!(out, "</layout>\n</head>\n<body>")?;
+
+        let mut in_text_div = false;
+        for (idx, cue) in self.cues.iter().enumerate() {
+            let is_text = matches!(cue.content, CueContent::Text(_));
+            if is_text != in_text_div {
+                writeln!(out, "{}", if is_text { "<div>" } else { "</div>" })?;
+                in_text_div = is_text;
+            }
+            cue.write(out, idx)?;
+        }
+        if in_text_div {
+            writeln!(out, "</div>")?;
+        }
+        writeln!(out, "</body>\n</tt>")
+    }
+}
+
+impl Cue {
+    /// Write the element of the cue, with `idx` the index of the cue in the document.
+    fn write(&self, out: &mut impl io::Write, idx: usize) -> Result<(), io::Error> {
+        let element = if matches!(self.content, CueContent::Text(_)) {
+            "p"
+        } else {
+            "div"
+        };
+        write!(
+            out,
+            r#"<{element} begin="{}" end="{}""#,
+            TimePointTtml::from(self.time_span.start),
+            TimePointTtml::from(self.time_span.end)
+        )?;
+        if let Some(region) = &self.region {
+            write!(out, r#" region="{}""#, escape(region))?;
+        }
+        write_style_references(out, &self.styles)?;
+        self.style.write_attributes(out)?;
+        match &self.content {
+            CueContent::Text(inlines) => {
+                write!(out, ">")?;
+                for inline in inlines {
+                    match inline {
+                        Inline::LineBreak => write!(out, "<br/>")?,
+                        Inline::Span(span) if span.styles.is_empty() && span.style.is_empty() => {
+                            write!(out, "{}", escape(&span.text))?;
+                        }
+                        Inline::Span(span) => {
+                            write!(out, "<span")?;
+                            write_style_references(out, &span.styles)?;
+                            span.style.write_attributes(out)?;
+                            write!(out, ">{}</span>", escape(&span.text))?;
+                        }
+                    }
+                }
+                writeln!(out, "</p>")
+            }
+            CueContent::Image(_) => {
+                writeln!(out, r##" smpte:backgroundImage="#img{idx}"/>"##)
+            }
+            CueContent::ImageFile(file) => {
+                writeln!(out, r#" smpte:backgroundImage="{}"/>"#, escape(file))
+            }
+        }
+    }
+}
+
+/// Write the `style` attribute, if there are referenced styles.
+fn write_style_references(out: &mut impl io::Write, styles: &[String]) -> Result<(), io::Error> {
+    if styles.is_empty() {
+        return Ok(());
+    }
+    write!(out, r#" style="{}""#, escape(&styles.join(" ")))
+}
+
+/// Escape a text for `XML` content or attribute value.
+fn escape(value: &str) -> Cow<'_, str> {
+    if value.contains(['&', '<', '>', '"', '\'']) {
+        Cow::Owned(
+            value
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&apos;"),
+        )
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Get the `xml:id` of an element.
+fn id<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attribute((XML_NS, "id"))
+}
+
+/// Get the ids of the styles referenced by an element.
+fn style_references<'a>(node: Node<'a, '_>) -> impl Iterator<Item = &'a str> {
+    node.attribute("style")
+        .unwrap_or_default()
+        .split_whitespace()
+}
+
+/// Get the line of an element in the document.
+fn line(xml: &roxmltree::Document<'_>, node: Node<'_, '_>) -> u32 {
+    xml.text_pos_at(node.range().start).row
+}
+
+/// Get the value of a parameter attribute (`ttp:*`) of an element.
+fn parameter_attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attribute| {
+            attribute.name() == name
+                && attribute
+                    .namespace()
+                    .is_some_and(|namespace| namespace.ends_with("#parameter"))
+        })
+        .map(|attribute| attribute.value().trim())
+}
+
+/// Read the timing parameters of the root element.
+fn timing_params(root: Node<'_, '_>) -> TimingParams {
+    let frame_rate = parameter_attribute(root, "frameRate").and_then(|rate| rate.parse().ok());
+    let multiplier = parameter_attribute(root, "frameRateMultiplier")
+        .and_then(|multiplier| {
+            let (numerator, denominator) = multiplier.split_once(char::is_whitespace)?;
+            let numerator: f64 = numerator.trim().parse().ok()?;
+            let denominator: f64 = denominator.trim().parse().ok()?;
+            (denominator > 0.).then(|| numerator / denominator)
+        })
+        .unwrap_or(1.);
+    let sub_frame_rate = parameter_attribute(root, "subFrameRate")
+        .and_then(|rate| rate.parse().ok())
+        .unwrap_or(1);
+    let tick_rate = parameter_attribute(root, "tickRate")
+        .and_then(|rate| rate.parse().ok())
+        .or_else(|| frame_rate.map(|rate| rate * f64::from(sub_frame_rate)))
+        .unwrap_or(1.);
+    TimingParams {
+        frame_rate: frame_rate.unwrap_or(30.) * multiplier,
+        sub_frame_rate,
+        tick_rate,
+    }
+}
+
+/// Parse a size in pixels, like `1920px 1080px`.
+fn pixel_size(value: &str) -> Option<Size> {
+    let (w, h) = value.split_once(char::is_whitespace)?;
+    Some(Size {
+        w: w.trim().strip_suffix("px")?.parse().ok()?,
+        h: h.trim().strip_suffix("px")?.parse().ok()?,
+    })
+}
+
+/// Decode the `Base64` content of an `smpte:image` element.
+fn decode_image(xml: &roxmltree::Document<'_>, node: Node<'_, '_>) -> Result<Vec<u8>, TtmlError> {
+    let data: String = node.text().unwrap_or_default().split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()
+        .ok_or_else(|| TtmlError::InvalidImage {
+            line: line(xml, node),
+        })
+}
+
+/// Collect the text of a paragraph or a span, with the style of the enclosing spans.
+fn parse_inlines(node: Node<'_, '_>, parent: &Span, inlines: &mut Vec<Inline>) {
+    for child in node.children() {
+        if child.is_text() {
+            inlines.push(Inline::Span(Span {
+                text: collapse_whitespace(child.text().unwrap_or_default()),
+                ..parent.clone()
+            }));
+        } else if child.is_element() {
+            match child.tag_name().name() {
+                "br" => inlines.push(Inline::LineBreak),
+                "span" => {
+                    let mut span = parent.clone();
+                    span.styles.extend(style_references(child).map(Into::into));
+                    span.style.merge(&TextStyle::from_node(child));
+                    parse_inlines(child, &span, inlines);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Replace each sequence of whitespaces by a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut previous_space = false;
+    for c in text.chars() {
+        let space = c.is_whitespace();
+        if !(space && previous_space) {
+            collapsed.push(if space { ' ' } else { c });
+        }
+        previous_space = space;
+    }
+    collapsed
+}
+
+/// Remove the spaces at the start and end of lines, and between spans, then remove empty spans.
+fn normalize_space(mut inlines: Vec<Inline>) -> Vec<Inline> {
+    let mut line_start = true;
+    for inline in &mut inlines {
+        match inline {
+            Inline::LineBreak => line_start = true,
+            Inline::Span(span) => {
+                if line_start {
+                    span.text = span.text.trim_start().into();
+                }
+                if !span.text.is_empty() {
+                    line_start = span.text.ends_with(' ');
+                }
+            }
+        }
+    }
+    let mut line_end = true;
+    for inline in inlines.iter_mut().rev() {
+        match inline {
+            Inline::LineBreak => line_end = true,
+            Inline::Span(span) => {
+                if line_end {
+                    span.text = span.text.trim_end().into();
+                }
+                line_end = line_end && span.text.is_empty();
+            }
+        }
+    }
+    inlines.retain(|inline| !matches!(inline, Inline::Span(span) if span.text.is_empty()));
+    inlines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches2::assert_matches;
+    use image::{Rgba, RgbaImage};
+
+    const DOCUMENT: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter"
+    xmlns:tts="http://www.w3.org/ns/ttml#styling" xml:lang="fr"
+    ttp:frameRate="25" ttp:tickRate="1000">
+  <head>
+    <styling>
+      <style xml:id="base" tts:color="white" tts:fontFamily="proportionalSansSerif"/>
+      <style xml:id="italic" style="base" tts:fontStyle="italic"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 20%"
+          tts:displayAlign="after" tts:textAlign="center"/>
+    </layout>
+  </head>
+  <body region="bottom" style="base">
+    <div begin="10s">
+      <p begin="00:00:01.000" end="00:00:02.500">
+        Hello
+        <br/>
+        <span style="italic">big <span tts:color="#ff0000">red</span></span> world!
+      </p>
+      <p begin="00:00:03:05" dur="1500t" tts:textAlign="left">Frames &amp; ticks</p>
+    </div>
+  </body>
+</tt>"##;
+
+    #[test]
+    fn parse_document() {
+        let document = Document::parse(DOCUMENT).unwrap();
+        assert_eq!(document.lang.as_deref(), Some("fr"));
+
+        let italic = document.style("italic").unwrap();
+        assert_eq!(italic.style.italic, Some(true));
+        assert_eq!(italic.style.color, Some(Rgba([255, 255, 255, 255])));
+        let region = document.region("bottom").unwrap();
+        assert_eq!(region.display_align, Some(DisplayAlign::After));
+        assert_eq!(region.style.text_align, Some(TextAlign::Center));
+        assert_eq!(
+            region.area(Size { w: 1920, h: 1080 }),
+            Area::try_from(AreaValues {
+                x1: 192,
+                y1: 864,
+                x2: 1727,
+                y2: 1079
+            })
+            .ok()
+        );
+
+        assert_eq!(document.cues.len(), 2);
+        let first = &document.cues[0];
+        assert_eq!(
+            first.time_span,
+            TimeSpan::new(TimePoint::from_msecs(11_000), TimePoint::from_msecs(12_500))
+        );
+        assert_eq!(first.region.as_deref(), Some("bottom"));
+        assert_eq!(first.styles, ["base"]);
+        assert_eq!(first.plain_text().unwrap(), "Hello\nbig red world!");
+        assert_matches!(&first.content, CueContent::Text(inlines));
+        assert_matches!(&inlines[3], Inline::Span(red));
+        assert_eq!(red.text, "red");
+        assert_eq!(red.styles, ["italic"]);
+        assert_eq!(red.style.color, Some(Rgba([255, 0, 0, 255])));
+
+        let second = &document.cues[1];
+        assert_eq!(
+            second.time_span,
+            TimeSpan::new(TimePoint::from_msecs(13_200), TimePoint::from_msecs(14_700))
+        );
+        assert_eq!(second.style.text_align, Some(TextAlign::Left));
+        assert_eq!(second.plain_text().unwrap(), "Frames & ticks");
+    }
+
+    #[test]
+    fn convert_to_text_cues() {
+        let document = Document::parse(DOCUMENT).unwrap();
+        let cues = document.text_cues();
+        assert_eq!(cues.len(), 2);
+        let first = &cues[0];
+        assert_eq!(first.time_span, document.cues[0].time_span);
+        assert_eq!(first.position.line, Some(80));
+        assert_eq!(first.position.align, Some(CueAlign::Center));
+        assert_eq!(first.plain_text(), document.cues[0].plain_text().unwrap());
+        let spans = &first.rows[1].spans;
+        assert!(spans[0].style.italic);
+        assert_eq!(spans[0].style.color, Some(Rgb([255, 255, 255])));
+        assert!(spans[1].style.italic);
+        assert_eq!(spans[1].style.color, Some(Rgb([255, 0, 0])));
+        assert_eq!(cues[1].position.align, Some(CueAlign::Left));
+
+        // Without the document, only the inline properties are applied.
+        let inline = text::Cue::from(&document.cues[0]);
+        assert_eq!(inline.position, CuePosition::default());
+        assert!(!inline.rows[1].spans[0].style.italic);
+        assert_eq!(inline.rows[1].spans[1].style.color, Some(Rgb([255, 0, 0])));
+    }
+
+    #[test]
+    fn parse_dfxp_document() {
+        let document = Document::parse(
+            r#"<tt xmlns="http://www.w3.org/2006/10/ttaf1" xmlns:tts="http://www.w3.org/2006/10/ttaf1#style">
+  <body><div>
+    <p begin="1.5s" end="2s" tts:fontStyle="italic">Old<br/>format</p>
+  </div></body>
+</tt>"#,
+        )
+        .unwrap();
+        let cue = &document.cues[0];
+        assert_eq!(
+            cue.time_span,
+            TimeSpan::new(TimePoint::from_msecs(1500), TimePoint::from_msecs(2000))
+        );
+        assert_eq!(cue.style.italic, Some(true));
+        assert_eq!(cue.plain_text().unwrap(), "Old\nformat");
+    }
+
+    #[test]
+    fn parse_extreme_times_and_invalid_utf8() {
+        let document = Document::from_reader(
+            b"<tt><body><div begin=\"9000000000000000s\">
+<p begin=\"9000000000000000s\" dur=\"9000000000000000s\">Caf\xe9</p>
+</div></body></tt>"
+                .as_slice(),
+        )
+        .unwrap();
+        let cue = &document.cues[0];
+        assert_eq!(cue.time_span.start, TimePoint::from_msecs(i64::MAX));
+        assert_eq!(cue.time_span.end, TimePoint::from_msecs(i64::MAX));
+        assert_eq!(cue.plain_text().unwrap(), "Caf\u{fffd}");
+    }
+
+    #[test]
+    fn parse_invalid_document() {
+        assert_matches!(Document::parse("<tt"), Err(TtmlError::Xml(_)));
+        assert_matches!(Document::parse("<html/>"), Err(TtmlError::NotTtml));
+        assert_matches!(
+            Document::parse("<tt>\n<body><p begin=\"x\" end=\"1s\"/></body></tt>"),
+            Err(TtmlError::InvalidTime { line: 2, value })
+        );
+        assert_eq!(value, "x");
+        assert_matches!(
+            Document::parse("<tt><body><p begin=\"1s\">Text</p></body></tt>"),
+            Err(TtmlError::MissingEnd { line: 1 })
+        );
+    }
+
+    #[test]
+    fn write_text_document() {
+        let time_span = TimeSpan::new(TimePoint::from_msecs(1000), TimePoint::from_msecs(2500));
+        let mut document = Document::from_subtitles(&[(time_span, "Hello <you>\nworld".into())]);
+        document.lang = Some("en".into());
+
+        let mut output = Vec::new();
+        document.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(IMSC1_TEXT_PROFILE));
+        assert!(output.contains(
+            r#"<p begin="00:00:01.000" end="00:00:02.500" region="bottom">Hello &lt;you&gt;<br/>world</p>"#
+        ));
+        assert_eq!(Document::parse(&output).unwrap(), document);
+    }
+
+    #[test]
+    fn write_document_round_trip() {
+        let document = Document::parse(DOCUMENT).unwrap();
+        let mut output = Vec::new();
+        document.write(&mut output).unwrap();
+        let parsed = Document::parse(&String::from_utf8(output).unwrap()).unwrap();
+        assert_eq!(parsed.cues, document.cues);
+        assert_eq!(parsed.styles, document.styles);
+        assert_eq!(parsed.regions, document.regions);
+    }
+
+    #[test]
+    fn write_image_document() {
+        let image = RgbaImage::from_pixel(4, 2, Rgba([255, 255, 255, 255]));
+        let area = Area::try_from(AreaValues {
+            x1: 100,
+            y1: 400,
+            x2: 103,
+            y2: 401,
+        })
+        .unwrap();
+        let time_span = TimeSpan::new(TimePoint::from_msecs(500), TimePoint::from_msecs(1500));
+        let frame_size = Size { w: 720, h: 480 };
+        let document = Document::from_images(frame_size, [(time_span, area, &image)]).unwrap();
+
+        let mut output = Vec::new();
+        document.write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(IMSC1_IMAGE_PROFILE));
+        assert!(output.contains(r#"tts:extent="720px 480px""#));
+        assert!(output
+            .contains(r#"<region xml:id="r1" tts:origin="100px 400px" tts:extent="4px 2px"/>"#));
+
+        let parsed = Document::parse(&output).unwrap();
+        assert_eq!(parsed.extent, Some(frame_size));
+        let cue = &parsed.cues[0];
+        assert_eq!(cue.time_span, time_span);
+        assert_eq!(
+            parsed
+                .region(cue.region.as_deref().unwrap())
+                .unwrap()
+                .area(frame_size),
+            Some(area)
+        );
+        assert_matches!(&cue.content, CueContent::Image(png));
+        let decoded = image::load_from_memory(png).unwrap().to_rgba8();
+        assert_eq!(decoded, image);
+    }
+}
@@ -0,0 +1,282 @@
+//! Styling attributes (`tts:*`) of `TTML` documents.
+
+use image::Rgba;
+use roxmltree::Node;
+use std::io;
+
+/// Suffix of the styling namespaces, for `TTML` and the older `DFXP` drafts.
+const STYLING_NS_SUFFIXES: [&str; 2] = ["#styling", "#style"];
+
+/// Horizontal alignment of the text (`tts:textAlign`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    /// Aligned on the left.
+    Left,
+    /// Centered.
+    Center,
+    /// Aligned on the right.
+    Right,
+    /// Aligned on the start of the line (left for left-to-right text).
+    Start,
+    /// Aligned on the end of the line (right for left-to-right text).
+    End,
+}
+
+impl TextAlign {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Center => "center",
+            Self::Right => "right",
+            Self::Start => "start",
+            Self::End => "end",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "left" => Some(Self::Left),
+            "center" => Some(Self::Center),
+            "right" => Some(Self::Right),
+            "start" => Some(Self::Start),
+            "end" => Some(Self::End),
+            _ => None,
+        }
+    }
+}
+
+/// Vertical alignment of the content in a region (`tts:displayAlign`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayAlign {
+    /// Aligned on the top of the region.
+    Before,
+    /// Centered in the region.
+    Center,
+    /// Aligned on the bottom of the region.
+    After,
+}
+
+impl DisplayAlign {
+    pub(super) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Before => "before",
+            Self::Center => "center",
+            Self::After => "after",
+        }
+    }
+
+    pub(super) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "before" => Some(Self::Before),
+            "center" => Some(Self::Center),
+            "after" => Some(Self::After),
+            _ => None,
+        }
+    }
+}
+
+/// Styling properties of an element.
+///
+/// All properties are optional, a missing property is inherited from the parent element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    /// Color of the text (`tts:color`).
+    pub color: Option<Rgba<u8>>,
+    /// Color of the background (`tts:backgroundColor`).
+    pub background_color: Option<Rgba<u8>>,
+    /// If the text is italic (`tts:fontStyle`).
+    pub italic: Option<bool>,
+    /// If the text is bold (`tts:fontWeight`).
+    pub bold: Option<bool>,
+    /// If the text is underlined (`tts:textDecoration`).
+    pub underline: Option<bool>,
+    /// Horizontal alignment of the text (`tts:textAlign`).
+    pub text_align: Option<TextAlign>,
+    /// Font family (`tts:fontFamily`).
+    pub font_family: Option<String>,
+    /// Font size, as written in the document, like `100%` or `1c` (`tts:fontSize`).
+    pub font_size: Option<String>,
+}
+
+impl TextStyle {
+    /// Read the styling attributes of an element. Invalid values are ignored.
+    pub(super) fn from_node(node: Node<'_, '_>) -> Self {
+        Self {
+            color: styling_attribute(node, "color").and_then(parse_color),
+            background_color: styling_attribute(node, "backgroundColor").and_then(parse_color),
+            italic: styling_attribute(node, "fontStyle").map(|value| value != "normal"),
+            bold: styling_attribute(node, "fontWeight").map(|value| value == "bold"),
+            underline: styling_attribute(node, "textDecoration").and_then(|value| match value {
+                "underline" => Some(true),
+                "noUnderline" | "none" => Some(false),
+                _ => None,
+            }),
+            text_align: styling_attribute(node, "textAlign").and_then(TextAlign::parse),
+            font_family: styling_attribute(node, "fontFamily").map(Into::into),
+            font_size: styling_attribute(node, "fontSize").map(Into::into),
+        }
+    }
+
+    /// If no property is defined.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the properties defined in `other` over the properties of `self`.
+    pub fn merge(&mut self, other: &Self) {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field.clone_from(&other.$field);
+                })*
+            };
+        }
+        merge!(
+            color,
+            background_color,
+            italic,
+            bold,
+            underline,
+            text_align,
+            font_family,
+            font_size
+        );
+    }
+
+    /// Write the properties as `tts:*` attributes.
+    pub(super) fn write_attributes(&self, out: &mut impl io::Write) -> Result<(), io::Error> {
+        let mut attributes = Vec::new();
+        if let Some(color) = self.color {
+            attributes.push(("color", format_color(color)));
+        }
+        if let Some(color) = self.background_color {
+            attributes.push(("backgroundColor", format_color(color)));
+        }
+        if let Some(italic) = self.italic {
+            attributes.push(("fontStyle", if italic { "italic" } else { "normal" }.into()));
+        }
+        if let Some(bold) = self.bold {
+            attributes.push(("fontWeight", if bold { "bold" } else { "normal" }.into()));
+        }
+        if let Some(underline) = self.underline {
+            let decoration = if underline { "underline" } else { "none" };
+            attributes.push(("textDecoration", decoration.into()));
+        }
+        if let Some(align) = self.text_align {
+            attributes.push(("textAlign", align.as_str().into()));
+        }
+        if let Some(family) = &self.font_family {
+            attributes.push(("fontFamily", family.clone()));
+        }
+        if let Some(size) = &self.font_size {
+            attributes.push(("fontSize", size.clone()));
+        }
+        attributes
+            .iter()
+            .try_for_each(|(name, value)| write!(out, r#" tts:{name}="{}""#, super::escape(value)))
+    }
+}
+
+/// Get the value of a styling attribute (`tts:*`) of an element.
+pub(super) fn styling_attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attribute| {
+            attribute.name() == name
+                && attribute.namespace().is_some_and(|namespace| {
+                    STYLING_NS_SUFFIXES
+                        .iter()
+                        .any(|suffix| namespace.ends_with(suffix))
+                })
+        })
+        .map(|attribute| attribute.value().trim())
+}
+
+/// Parse a `TTML` color: `#rrggbb`, `#rrggbbaa`, `rgb(r,g,b)`, `rgba(r,g,b,a)` or a named color.
+#[must_use]
+pub fn parse_color(value: &str) -> Option<Rgba<u8>> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let component = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Rgba([component(0)?, component(2)?, component(4)?, 255])),
+            8 => Some(Rgba([
+                component(0)?,
+                component(2)?,
+                component(4)?,
+                component(6)?,
+            ])),
+            _ => None,
+        };
+    }
+    if let Some(args) = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+    {
+        let components = args
+            .strip_suffix(')')?
+            .split(',')
+            .map(|component| component.trim().parse().ok())
+            .collect::<Option<Vec<u8>>>()?;
+        return match components[..] {
+            [r, g, b] if value.starts_with("rgb(") => Some(Rgba([r, g, b, 255])),
+            [r, g, b, a] if value.starts_with("rgba(") => Some(Rgba([r, g, b, a])),
+            _ => None,
+        };
+    }
+    let [r, g, b, a] = match value {
+        "transparent" => [0, 0, 0, 0],
+        "black" => [0, 0, 0, 255],
+        "silver" => [192, 192, 192, 255],
+        "gray" | "grey" => [128, 128, 128, 255],
+        "white" => [255, 255, 255, 255],
+        "maroon" => [128, 0, 0, 255],
+        "red" => [255, 0, 0, 255],
+        "purple" => [128, 0, 128, 255],
+        "fuchsia" | "magenta" => [255, 0, 255, 255],
+        "green" => [0, 128, 0, 255],
+        "lime" => [0, 255, 0, 255],
+        "olive" => [128, 128, 0, 255],
+        "yellow" => [255, 255, 0, 255],
+        "navy" => [0, 0, 128, 255],
+        "blue" => [0, 0, 255, 255],
+        "teal" => [0, 128, 128, 255],
+        "aqua" | "cyan" => [0, 255, 255, 255],
+        _ => return None,
+    };
+    Some(Rgba([r, g, b, a]))
+}
+
+/// Format a color as `#rrggbb`, or `#rrggbbaa` if not opaque.
+#[must_use]
+pub fn format_color(color: Rgba<u8>) -> String {
+    let Rgba([r, g, b, a]) = color;
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(parse_color("#ff8000"), Some(Rgba([255, 128, 0, 255])));
+        assert_eq!(parse_color("#FF800080"), Some(Rgba([255, 128, 0, 128])));
+        assert_eq!(parse_color("rgb(1, 2, 3)"), Some(Rgba([1, 2, 3, 255])));
+        assert_eq!(parse_color("rgba(1,2,3,4)"), Some(Rgba([1, 2, 3, 4])));
+        assert_eq!(parse_color("yellow"), Some(Rgba([255, 255, 0, 255])));
+        assert_eq!(parse_color("rgb(1,2,3,4)"), None);
+        assert_eq!(parse_color("#fff"), None);
+        assert_eq!(parse_color("unknown"), None);
+    }
+
+    #[test]
+    fn format_colors() {
+        assert_eq!(format_color(Rgba([255, 128, 0, 255])), "#ff8000");
+        assert_eq!(format_color(Rgba([0, 0, 0, 0])), "#00000000");
+    }
+}
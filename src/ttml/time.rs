@@ -0,0 +1,158 @@
+//! Time expressions of `TTML` documents.
+
+use crate::time::TimePoint;
+use std::fmt;
+
+/// Parameters used to compute times expressed in frames or ticks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingParams {
+    /// Effective frame rate, with the `ttp:frameRateMultiplier` applied.
+    pub frame_rate: f64,
+    /// Number of sub-frames per frame (`ttp:subFrameRate`).
+    pub sub_frame_rate: u32,
+    /// Number of ticks per second (`ttp:tickRate`).
+    pub tick_rate: f64,
+}
+
+impl Default for TimingParams {
+    fn default() -> Self {
+        Self {
+            frame_rate: 30.,
+            sub_frame_rate: 1,
+            tick_rate: 1.,
+        }
+    }
+}
+
+impl TimingParams {
+    /// Parse a time expression, in clock format (`00:00:01.500`, `00:00:01:12`)
+    /// or offset format (`1.5s`, `36f`, `10000000t`).
+    #[must_use]
+    pub fn parse_time(&self, expression: &str) -> Option<TimePoint> {
+        let expression = expression.trim();
+        let secs = if expression.contains(':') {
+            self.clock_time(expression)?
+        } else {
+            self.offset_time(expression)?
+        };
+        Some(TimePoint::from_msecs(
+            cast::i64((secs * 1000.).round()).ok()?,
+        ))
+    }
+
+    /// Parse a clock time: `hours:minutes:seconds(.fraction|:frames(.sub-frames))`.
+    fn clock_time(&self, expression: &str) -> Option<f64> {
+        let mut parts = expression.splitn(4, ':');
+        let hours: u32 = integer(parts.next()?)?;
+        let minutes: u32 = integer(parts.next()?)?;
+        let seconds = parts.next()?;
+        let seconds = if let Some((secs, fraction)) = seconds.split_once('.') {
+            f64::from(integer::<u32>(secs)?) + fraction_value(fraction)?
+        } else {
+            f64::from(integer::<u32>(seconds)?)
+        };
+        let frames = parts.next().map_or(Some(0.), |frames| {
+            let (frames, sub_frames) = frames.split_once('.').unwrap_or((frames, "0"));
+            let sub_frames = f64::from(integer::<u32>(sub_frames)?);
+            Some(
+                (f64::from(integer::<u32>(frames)?)
+                    + sub_frames / f64::from(self.sub_frame_rate.max(1)))
+                    / self.frame_rate,
+            )
+        })?;
+        Some(f64::from(hours) * 3600. + f64::from(minutes) * 60. + seconds + frames)
+    }
+
+    /// Parse an offset time: a number followed by a metric (`h`, `m`, `s`, `ms`, `f` or `t`).
+    fn offset_time(&self, expression: &str) -> Option<f64> {
+        let split = expression
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&idx| idx > 0)?;
+        let (value, metric) = expression.split_at(split);
+        let value: f64 = value.parse().ok()?;
+        let factor = match metric {
+            "h" => 3600.,
+            "m" => 60.,
+            "s" => 1.,
+            "ms" => 0.001,
+            "f" => 1. / self.frame_rate,
+            "t" => 1. / self.tick_rate,
+            _ => return None,
+        };
+        Some(value * factor)
+    }
+}
+
+/// Parse an integer made only of digits.
+fn integer<T: std::str::FromStr>(value: &str) -> Option<T> {
+    (!value.is_empty() && value.bytes().all(|c| c.is_ascii_digit()))
+        .then(|| value.parse().ok())
+        .flatten()
+}
+
+/// Get the value of the decimal part of a number, from its digits.
+fn fraction_value(digits: &str) -> Option<f64> {
+    integer::<u64>(digits).and_then(|_| format!("0.{digits}").parse().ok())
+}
+
+/// Extend `TimePoint` for implement `TTML` specific `Display` (`HH:MM:SS.mmm`).
+///
+/// Negative times are written as zero.
+#[repr(transparent)]
+pub struct TimePointTtml(TimePoint);
+
+impl From<TimePoint> for TimePointTtml {
+    fn from(value: TimePoint) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for TimePointTtml {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        TimePoint::from_msecs(self.0.msecs().max(0)).fmt_separator(f, '.')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time_expressions() {
+        let params = TimingParams {
+            frame_rate: 25.,
+            sub_frame_rate: 2,
+            tick_rate: 10_000_000.,
+        };
+        let msecs = |expression| params.parse_time(expression).map(TimePoint::msecs);
+        assert_eq!(msecs("00:00:01.5"), Some(1500));
+        assert_eq!(msecs("01:02:03.456"), Some(3_723_456));
+        assert_eq!(msecs("00:00:01:12"), Some(1480));
+        assert_eq!(msecs("00:00:00:01.1"), Some(60));
+        assert_eq!(msecs("2.5s"), Some(2500));
+        assert_eq!(msecs("1.5m"), Some(90_000));
+        assert_eq!(msecs("0.5h"), Some(1_800_000));
+        assert_eq!(msecs("250ms"), Some(250));
+        assert_eq!(msecs("50f"), Some(2000));
+        assert_eq!(msecs("15000000t"), Some(1500));
+        assert_eq!(msecs("1.5"), None);
+        assert_eq!(msecs("s"), None);
+        assert_eq!(msecs("00:-1:00"), None);
+    }
+
+    #[test]
+    fn parse_drop_frame_rate() {
+        let params = TimingParams {
+            frame_rate: 30_000. / 1001.,
+            ..TimingParams::default()
+        };
+        assert_eq!(params.parse_time("30f"), Some(TimePoint::from_msecs(1001)));
+    }
+
+    #[test]
+    fn format_time() {
+        let format = |msecs| TimePointTtml(TimePoint::from_msecs(msecs)).to_string();
+        assert_eq!(format(3_723_456), "01:02:03.456");
+        assert_eq!(format(-5), "00:00:00.000");
+    }
+}
@@ -16,3 +16,18 @@ impl fmt::Debug for BytesFormatter<'_> {
         Ok(())
     }
 }
+
+/// Read all the content of an async `reader` in memory, for the `from_async_reader`
+/// constructors: the parsers are synchronous, so the content is buffered before parsing.
+#[cfg(feature = "tokio")]
+pub async fn read_all_async<R, E, F>(mut reader: R, to_error: F) -> Result<Vec<u8>, E>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    F: FnOnce(std::io::Error) -> E,
+{
+    use tokio::io::AsyncReadExt as _;
+
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await.map_err(to_error)?;
+    Ok(data)
+}
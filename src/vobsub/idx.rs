@@ -1,7 +1,7 @@
 //! Parse a file in `*.idx` format.
 
 use compact_str::CompactString;
-use log::trace;
+use log::{trace, warn};
 use regex::Regex;
 use std::{
     fmt, fs,
@@ -14,13 +14,18 @@ use super::{
     palette::{palette, DEFAULT_PALETTE},
     Palette, VobSubError,
 };
-use crate::{time::TimePoint, vobsub::IResultExt as _};
+use crate::{content::Size, time::TimePoint, vobsub::IResultExt as _};
 
 /// Lang of a subtitle as reported in `VobSub` idx file.
 #[derive(Debug, Clone)]
 pub struct Lang(CompactString);
 
 impl Lang {
+    /// Create a lang from a language code.
+    pub(super) fn from_code(code: &str) -> Self {
+        Self(code.into())
+    }
+
     #[allow(clippy::missing_const_for_fn)]
     pub fn lang(&self) -> &str {
         &self.0
@@ -61,8 +66,8 @@ impl fmt::Display for TimePointIdx {
 /// A `*.idx` file describing the subtitles in a `*.sub` file.
 #[derive(Debug)]
 pub struct Index {
-    // Frame size.
-    //size: Size,
+    /// Frame size.
+    size: Option<Size>,
     /// The colors used for the subtitles.
     palette: Palette,
     /// Lang of the subtitles
@@ -71,6 +76,17 @@ pub struct Index {
 
 const PALETTE_KEY: &str = "palette";
 const LANG_KEY: &str = "id";
+const SIZE_KEY: &str = "size";
+
+/// Parse a frame size value like `720x480`.
+fn parse_size(value: &str) -> Option<Size> {
+    value.split_once('x').and_then(|(w, h)| {
+        Some(Size {
+            w: w.trim().parse().ok()?,
+            h: h.trim().parse().ok()?,
+        })
+    })
+}
 
 impl Index {
     /// Open an `*.idx` file and the associated `*.sub` file.
@@ -90,18 +106,60 @@ impl Index {
         Self::read_index(input, &mkerr_idx)
     }
 
+    /// Read an `*.idx` file content from a buffered reader.
+    ///
+    /// # Errors
+    /// Will return [`VobSubError::Read`] if failed to read from `reader`.
+    /// Will return [`VobSubError::PaletteError`] if failed to parse the palette value.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, VobSubError> {
+        Self::parse(reader, &VobSubError::Read)
+    }
+
+    /// Read an `*.idx` file content from an in-memory buffer.
+    ///
+    /// # Errors
+    /// Will return [`VobSubError::PaletteError`] if failed to parse the palette value.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, VobSubError> {
+        Self::from_reader(data)
+    }
+
+    /// Read an `*.idx` file content from an async reader.
+    ///
+    /// The content is buffered in memory before parsing, see [`Index::from_bytes`].
+    ///
+    /// # Errors
+    /// Will return [`VobSubError::Read`] if failed to read from `reader`.
+    /// Will return [`VobSubError::PaletteError`] if failed to parse the palette value.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R>(reader: R) -> Result<Self, VobSubError>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let data = crate::util::read_all_async(reader, VobSubError::Read).await?;
+        Self::from_bytes(&data)
+    }
+
     /// Read the palette in `*.idx` file content
     ///
     /// # Errors
     /// Will return `VobSubError::MissingKey` if the palette key/value is not present
     /// Will return `VobSubError::PaletteError` if failed to read and parse palette value.
+    pub fn read_index<T, Err>(input: BufReader<T>, mkerr: &Err) -> Result<Self, VobSubError>
+    where
+        T: std::io::Read,
+        Err: Fn(io::Error) -> VobSubError,
+    {
+        Self::parse(input, mkerr)
+    }
+
+    /// Parse the `*.idx` content of `input`.
     ///
     /// # Panics
     /// Panic if the Regex creation failed
     #[profiling::function]
-    pub fn read_index<T, Err>(mut input: BufReader<T>, mkerr: &Err) -> Result<Self, VobSubError>
+    fn parse<R, Err>(mut input: R, mkerr: &Err) -> Result<Self, VobSubError>
     where
-        T: std::io::Read,
+        R: BufRead,
         Err: Fn(io::Error) -> VobSubError,
     {
         static KEY_VALUE: LazyLock<Regex> =
@@ -109,6 +167,7 @@ impl Index {
 
         let mut palette_val = None;
         let mut lang = None;
+        let mut size = None;
         let mut buf = String::with_capacity(256);
         while input.read_line(&mut buf).map_err(mkerr)? > 0 {
             let line = buf.trim_end();
@@ -127,6 +186,12 @@ impl Index {
                         //TODO: reporte missing lang ?
                         lang = Lang::try_from(val).ok();
                     }
+                    SIZE_KEY => {
+                        size = parse_size(val);
+                        if size.is_none() {
+                            warn!("Invalid idx frame size '{val}', ignored.");
+                        }
+                    }
                     _ => trace!("Unimplemented idx key: {key}"),
                 }
             }
@@ -136,13 +201,32 @@ impl Index {
         //TODO: report missing palette ?
         let palette = palette_val.unwrap_or(DEFAULT_PALETTE);
 
-        Ok(Self { palette, lang })
+        Ok(Self {
+            size,
+            palette,
+            lang,
+        })
     }
 
     /// Create an Index from a palette and sub data
     #[must_use]
     pub const fn init(palette: Palette, lang: Option<Lang>) -> Self {
-        Self { palette, lang }
+        Self {
+            size: None,
+            palette,
+            lang,
+        }
+    }
+
+    /// Get the frame size of the video, if present in the `*.idx` file.
+    #[must_use]
+    pub const fn size(&self) -> Option<Size> {
+        self.size
+    }
+
+    /// Set the frame size of the video.
+    pub(super) fn set_size(&mut self, size: Size) {
+        self.size = Some(size);
     }
 
     /// Get the palette associated with this `*.idx` file.
@@ -150,6 +234,13 @@ impl Index {
     pub const fn palette(&self) -> &Palette {
         &self.palette
     }
+    /// Replace the palette, for example with the palette of a [`PaletteNormalization`].
+    ///
+    /// [`PaletteNormalization`]: super::PaletteNormalization
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
     /// Get the lang associated with this `*.idx` file.
     #[must_use]
     pub const fn lang(&self) -> &Option<Lang> {
@@ -161,7 +252,7 @@ impl Index {
 mod tests {
     use image::Rgb;
 
-    use crate::vobsub::Index;
+    use crate::{content::Size, vobsub::Index};
 
     #[test]
     fn parse_index() {
@@ -169,8 +260,36 @@ mod tests {
 
         let idx = Index::open("./fixtures/example.idx").unwrap();
 
-        //assert_eq!(idx.size(), Size { w: 1920, h: 1080 });
+        assert_eq!(idx.size(), Some(Size { w: 1920, h: 1080 }));
         assert_eq!(idx.palette()[0], Rgb([0x00, 0x00, 0x00]));
         assert_eq!(idx.palette()[15], Rgb([0x11, 0xbb, 0xbb]));
     }
+
+    #[test]
+    fn parse_index_from_bytes() {
+        let data = std::fs::read("./fixtures/example.idx").unwrap();
+        let idx = Index::from_bytes(&data).unwrap();
+
+        assert_eq!(idx.size(), Some(Size { w: 1920, h: 1080 }));
+        assert_eq!(
+            idx.palette(),
+            Index::open("./fixtures/example.idx").unwrap().palette()
+        );
+    }
+
+    #[test]
+    fn parse_index_invalid_size() {
+        let data = b"size: 720 by 480\n";
+        let idx = Index::from_bytes(data).unwrap();
+        assert_eq!(idx.size(), None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn parse_index_from_async_reader() {
+        let data = std::fs::read("./fixtures/example.idx").unwrap();
+        let idx = Index::from_async_reader(data.as_slice()).await.unwrap();
+
+        assert_eq!(idx.size(), Some(Size { w: 1920, h: 1080 }));
+    }
 }
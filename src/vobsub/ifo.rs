@@ -0,0 +1,326 @@
+//! Read the subtitle information of a DVD title set from its `VTS_xx_0.IFO` file.
+//!
+//! For background, see [this documentation on the IFO format][ifo].
+//!
+//! [ifo]: http://dvd.sourceforge.net/dvdinfo/ifo.html
+
+use super::{idx::Lang, Index, Palette, VobSubError};
+use crate::{content::Size, image::ycrcb_to_rgb};
+use std::{fs, path::Path};
+
+/// Identifier at the start of a title set `IFO` file.
+const VTS_IDENTIFIER: &[u8] = b"DVDVIDEO-VTS";
+/// Size of a DVD sector.
+const SECTOR_SIZE: usize = 2048;
+/// Offset of the sector pointer to the title Program Chain Information table.
+const VTS_PGCI_OFFSET: usize = 0xcc;
+/// Offset of the video attributes of the title set.
+const VIDEO_ATTRIBUTES_OFFSET: usize = 0x200;
+/// Offset of the number of subpicture streams of the title set.
+const SUBPICTURE_COUNT_OFFSET: usize = 0x254;
+/// Offset of the subpicture streams attributes of the title set.
+const SUBPICTURE_ATTRIBUTES_OFFSET: usize = 0x256;
+/// Size of the attributes of a subpicture stream.
+const SUBPICTURE_ATTRIBUTES_SIZE: usize = 6;
+/// Maximum number of subpicture streams.
+const MAX_SUBPICTURE_STREAMS: usize = 32;
+/// Offset of the subpicture stream control in a Program Chain.
+const PGC_SUBPICTURE_CONTROL_OFFSET: usize = 0x1c;
+/// Offset of the color lookup table in a Program Chain.
+const PGC_PALETTE_OFFSET: usize = 0xa4;
+/// Subpicture code extension of forced subtitles.
+const FORCED_CODE_EXTENSION: u8 = 9;
+
+/// Substream ids of a subpicture stream for each display mode.
+///
+/// A subtitle stream can be encoded several times, adapted to the display mode.
+/// The ids are the substream ids of the packets in the `VOB` files (`0x20` to `0x3f`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubpictureVariants {
+    /// Substream for `4:3` display.
+    pub standard: Option<u8>,
+    /// Substream for `16:9` wide display.
+    pub wide: Option<u8>,
+    /// Substream for letterbox display.
+    pub letterbox: Option<u8>,
+    /// Substream for pan-scan display.
+    pub pan_scan: Option<u8>,
+}
+
+/// A subpicture (subtitle) stream of a title set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubpictureStream {
+    /// Index of the stream, as selected by the player.
+    pub index: u8,
+    /// `ISO 639` language code of the stream, if any.
+    pub language: Option<String>,
+    /// Code extension of the stream (1: normal, 2: large, 3: children, 9: forced, ...).
+    pub code_extension: u8,
+    /// Substreams of the stream for each display mode.
+    pub variants: SubpictureVariants,
+}
+
+impl SubpictureStream {
+    /// If the stream contains only forced subtitles.
+    #[must_use]
+    pub const fn is_forced(&self) -> bool {
+        self.code_extension == FORCED_CODE_EXTENSION
+    }
+}
+
+/// Subtitle information of a DVD title set.
+#[derive(Debug, Clone)]
+pub struct Ifo {
+    palette: Palette,
+    frame_size: Size,
+    streams: Vec<SubpictureStream>,
+}
+
+impl Ifo {
+    /// Read a `VTS_xx_0.IFO` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::Io` if not able to read the file.
+    /// Will return `VobSubError::InvalidIfo` if the file is not a valid title set `IFO`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, VobSubError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|source| VobSubError::Io {
+            source,
+            path: path.into(),
+        })?;
+        Self::from_bytes(&data)
+    }
+
+    /// Read the content of a `VTS_xx_0.IFO` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::InvalidIfo` if the data are not a valid title set `IFO`.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, VobSubError> {
+        if !data.starts_with(VTS_IDENTIFIER) {
+            return Err(VobSubError::InvalidIfo("missing title set identifier"));
+        }
+
+        let count = usize::from(read_u16(data, SUBPICTURE_COUNT_OFFSET)?);
+        if count > MAX_SUBPICTURE_STREAMS {
+            return Err(VobSubError::InvalidIfo("too many subpicture streams"));
+        }
+        let attributes = data
+            .get(
+                SUBPICTURE_ATTRIBUTES_OFFSET
+                    ..SUBPICTURE_ATTRIBUTES_OFFSET + count * SUBPICTURE_ATTRIBUTES_SIZE,
+            )
+            .ok_or(VobSubError::InvalidIfo("truncated subpicture attributes"))?;
+
+        let pgc = first_pgc(data)?;
+        let controls = pgc
+            .get(
+                PGC_SUBPICTURE_CONTROL_OFFSET
+                    ..PGC_SUBPICTURE_CONTROL_OFFSET + 4 * MAX_SUBPICTURE_STREAMS,
+            )
+            .ok_or(VobSubError::InvalidIfo("truncated program chain"))?;
+        let palette = pgc
+            .get(PGC_PALETTE_OFFSET..PGC_PALETTE_OFFSET + 4 * 16)
+            .ok_or(VobSubError::InvalidIfo("truncated program chain"))?;
+
+        let streams = attributes
+            .chunks_exact(SUBPICTURE_ATTRIBUTES_SIZE)
+            .zip(controls.chunks_exact(4))
+            .zip(0..)
+            .map(|((attributes, control), index)| SubpictureStream {
+                index,
+                language: (attributes[0] & 0b11 == 1)
+                    .then(|| String::from_utf8(attributes[2..4].to_vec()).ok())
+                    .flatten(),
+                code_extension: attributes[5],
+                variants: variants(control),
+            })
+            .collect();
+
+        Ok(Self {
+            palette: std::array::from_fn(|i| {
+                let entry = &palette[i * 4..];
+                ycrcb_to_rgb(entry[1], entry[2], entry[3])
+            }),
+            frame_size: frame_size(read_u16(data, VIDEO_ATTRIBUTES_OFFSET)?),
+            streams,
+        })
+    }
+
+    /// Color lookup table of the subtitles.
+    #[must_use]
+    pub const fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Size of the video frames.
+    #[must_use]
+    pub const fn frame_size(&self) -> Size {
+        self.frame_size
+    }
+
+    /// Subpicture streams of the title set.
+    #[must_use]
+    pub fn streams(&self) -> &[SubpictureStream] {
+        &self.streams
+    }
+
+    /// Create the [`Index`] of a subpicture stream, to use with the subtitles read from the `VOB` files.
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::InvalidIfo` if there is no stream with this `index`.
+    pub fn index(&self, index: u8) -> Result<Index, VobSubError> {
+        let stream = self
+            .streams
+            .iter()
+            .find(|stream| stream.index == index)
+            .ok_or(VobSubError::InvalidIfo(
+                "no subpicture stream with this index",
+            ))?;
+        let mut idx = Index::init(
+            self.palette,
+            stream.language.as_deref().map(Lang::from_code),
+        );
+        idx.set_size(self.frame_size);
+        Ok(idx)
+    }
+}
+
+/// Read a big endian `u16`.
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, VobSubError> {
+    data.get(offset..offset + 2)
+        .map(|value| u16::from_be_bytes([value[0], value[1]]))
+        .ok_or(VobSubError::InvalidIfo("truncated file"))
+}
+
+/// Read a big endian `u32` as `usize`.
+fn read_usize(data: &[u8], offset: usize) -> Result<usize, VobSubError> {
+    data.get(offset..offset + 4)
+        .and_then(|value| {
+            usize::try_from(u32::from_be_bytes([value[0], value[1], value[2], value[3]])).ok()
+        })
+        .ok_or(VobSubError::InvalidIfo("truncated file"))
+}
+
+/// Get the data of the first Program Chain of the title set.
+fn first_pgc(data: &[u8]) -> Result<&[u8], VobSubError> {
+    let pgci_start = read_usize(data, VTS_PGCI_OFFSET)?
+        .checked_mul(SECTOR_SIZE)
+        .ok_or(VobSubError::InvalidIfo("invalid program chain table"))?;
+    let pgci = data
+        .get(pgci_start..)
+        .ok_or(VobSubError::InvalidIfo("truncated program chain table"))?;
+    if read_u16(pgci, 0)? == 0 {
+        return Err(VobSubError::InvalidIfo("no program chain"));
+    }
+    // The first search pointer follows the 8 bytes table header.
+    let pgc_offset = read_usize(pgci, 8 + 4)?;
+    pgci.get(pgc_offset..)
+        .ok_or(VobSubError::InvalidIfo("truncated program chain"))
+}
+
+/// Read the substreams of a subpicture stream from its stream control.
+fn variants(control: &[u8]) -> SubpictureVariants {
+    // The stream is available only if the first bit is set.
+    if control[0] & 0x80 == 0 {
+        return SubpictureVariants::default();
+    }
+    let substream = |number: u8| Some(0x20 + (number & 0x1f));
+    SubpictureVariants {
+        standard: substream(control[0]),
+        wide: substream(control[1]),
+        letterbox: substream(control[2]),
+        pan_scan: substream(control[3]),
+    }
+}
+
+/// Get the frame size from the video attributes.
+const fn frame_size(attributes: u16) -> Size {
+    let pal = (attributes >> 12) & 0b11 == 1;
+    let height = if pal { 576 } else { 480 };
+    match (attributes >> 3) & 0b111 {
+        1 => Size { w: 704, h: height },
+        2 => Size { w: 352, h: height },
+        3 => Size {
+            w: 352,
+            h: height / 2,
+        },
+        _ => Size { w: 720, h: height },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    /// Build a minimal title set `IFO` with two subpicture streams.
+    fn ifo_data() -> Vec<u8> {
+        let mut data = vec![0; 3 * SECTOR_SIZE];
+        data[..VTS_IDENTIFIER.len()].copy_from_slice(VTS_IDENTIFIER);
+        // Program Chain Information table in sector 1.
+        data[VTS_PGCI_OFFSET..VTS_PGCI_OFFSET + 4].copy_from_slice(&1u32.to_be_bytes());
+        // PAL 16:9 720x576 video.
+        data[VIDEO_ATTRIBUTES_OFFSET] = 0x1c;
+        data[SUBPICTURE_COUNT_OFFSET + 1] = 2;
+        let attributes = SUBPICTURE_ATTRIBUTES_OFFSET;
+        data[attributes..attributes + 6].copy_from_slice(&[0x01, 0, b'f', b'r', 0, 1]);
+        data[attributes + 6..attributes + 12].copy_from_slice(&[0x01, 0, b'e', b'n', 0, 9]);
+
+        let pgci = SECTOR_SIZE;
+        data[pgci + 1] = 1;
+        let pgc_offset = 16u32;
+        data[pgci + 12..pgci + 16].copy_from_slice(&pgc_offset.to_be_bytes());
+        let pgc = pgci + 16;
+        let control = pgc + PGC_SUBPICTURE_CONTROL_OFFSET;
+        data[control..control + 4].copy_from_slice(&[0x80, 0x01, 0x02, 0x03]);
+        data[control + 4..control + 8].copy_from_slice(&[0x84, 0x04, 0x04, 0x04]);
+        let palette = pgc + PGC_PALETTE_OFFSET;
+        // Black, white, then gray.
+        data[palette..palette + 8].copy_from_slice(&[0, 16, 128, 128, 0, 235, 128, 128]);
+        for entry in data[palette + 8..palette + 64].chunks_exact_mut(4) {
+            entry.copy_from_slice(&[0, 126, 128, 128]);
+        }
+        data
+    }
+
+    #[test]
+    fn parse_ifo() {
+        let ifo = Ifo::from_bytes(&ifo_data()).unwrap();
+        assert_eq!(ifo.frame_size(), Size { w: 720, h: 576 });
+        assert_eq!(ifo.palette()[0], Rgb([0, 0, 0]));
+        assert_eq!(ifo.palette()[1], Rgb([255, 255, 255]));
+        assert_eq!(ifo.palette()[2], Rgb([128, 128, 128]));
+
+        let streams = ifo.streams();
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].language.as_deref(), Some("fr"));
+        assert!(!streams[0].is_forced());
+        assert_eq!(
+            streams[0].variants,
+            SubpictureVariants {
+                standard: Some(0x20),
+                wide: Some(0x21),
+                letterbox: Some(0x22),
+                pan_scan: Some(0x23),
+            }
+        );
+        assert_eq!(streams[1].language.as_deref(), Some("en"));
+        assert!(streams[1].is_forced());
+        assert_eq!(streams[1].variants.standard, Some(0x24));
+
+        let index = ifo.index(1).unwrap();
+        assert_eq!(index.lang().as_ref().map(Lang::lang), Some("en"));
+        assert_eq!(index.size(), Some(Size { w: 720, h: 576 }));
+        assert_eq!(index.palette(), ifo.palette());
+        assert!(ifo.index(2).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_ifo() {
+        assert!(Ifo::from_bytes(b"DVDVIDEO-VMG").is_err());
+        assert!(Ifo::from_bytes(&ifo_data()[..SECTOR_SIZE]).is_err());
+    }
+}
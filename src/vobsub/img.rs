@@ -1,7 +1,7 @@
 //! Run-length encoded image format for subtitles.
 
 use core::fmt::{self, Debug};
-use image::{ImageBuffer, Luma, Pixel, Rgb, Rgba};
+use image::{ImageBuffer, LumaA, Pixel, Rgb, Rgba};
 use iter_fixed::IntoIteratorFixed as _;
 use log::trace;
 use nom::{
@@ -13,10 +13,18 @@ use nom::{
 };
 use thiserror::Error;
 
-use super::{palette::PaletteLuma, IResultExt as _, NomError, VobSubError};
+use super::{
+    palette::{Palette, PaletteLuma},
+    IResultExt as _, NomError, VobSubError,
+};
 use crate::{
-    content::{Area, Size},
-    image::{ImageArea, ImageSize as _, ToImage, ToOcrImage, ToOcrImageOpt},
+    content::{Area, AreaValues, Size},
+    image::{
+        content_bounds, crop_indexed, prepare_ocr_image, remove_outline_colors, role_ocr_color,
+        roles_are_conclusive, CompareImage, ImageArea, ImageSize as _, IndexedImage,
+        IndexedTransform, PaletteRole, PalettedImage, PerceptualHash, ToImage, ToOcrImage,
+        ToOcrImageOpt,
+    },
     util::BytesFormatter,
 };
 
@@ -252,6 +260,51 @@ impl VobSubIndexedImage {
     pub fn raw_image(&self) -> &[u8] {
         self.raw_image.as_slice()
     }
+
+    /// Replace the 4 palette indexes and alpha of the subtitle.
+    pub(super) const fn with_colors(mut self, palette: [u8; 4], alpha: [u8; 4]) -> Self {
+        self.palette = palette;
+        self.alpha = alpha;
+        self
+    }
+
+    /// Crop the image to the bounding box of its visible pixels (with a non-zero alpha),
+    /// and adjust the area to match.
+    ///
+    /// As an [`Area`] is at least 2 pixels wide and high, a thinner content is extended
+    /// with a transparent column or row. Return `None` if the image has no visible pixel.
+    ///
+    /// # Panics
+    /// Will panic if the cropped area is invalid, which should not happen.
+    #[must_use]
+    pub fn crop_to_content(&self) -> Option<Self> {
+        let mut bounds = content_bounds(&self.raw_image, self.width(), |index| {
+            self.alpha[usize::from(index)]
+        })?;
+        if bounds.width < 2 {
+            bounds.x = bounds.x.min(self.width() - 2);
+            bounds.width = 2;
+        }
+        if bounds.height < 2 {
+            bounds.y = bounds.y.min(self.height() - 2);
+            bounds.height = 2;
+        }
+
+        let offset = |value: u32| u16::try_from(value).unwrap();
+        let area = Area::try_from(AreaValues {
+            x1: self.area.left() + offset(bounds.x),
+            y1: self.area.top() + offset(bounds.y),
+            x2: self.area.left() + offset(bounds.right() - 1),
+            y2: self.area.top() + offset(bounds.bottom() - 1),
+        })
+        .unwrap();
+        let raw_image = crop_indexed(&self.raw_image, self.width(), bounds);
+        Some(Self {
+            area,
+            raw_image,
+            ..*self
+        })
+    }
 }
 
 impl fmt::Debug for VobSubIndexedImage {
@@ -270,6 +323,102 @@ impl ImageArea for VobSubIndexedImage {
     }
 }
 
+/// Compare the images with the area, the palette, the alpha and the pixels indexes.
+/// The perceptual hash use the alpha of pixels as intensity.
+impl CompareImage for VobSubIndexedImage {
+    fn same_pixels(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn perceptual_hash(&self) -> PerceptualHash {
+        let pixels = self
+            .raw_image
+            .iter()
+            .map(|&idx| self.alpha[usize::from(idx)] * 0x11);
+        PerceptualHash::new(self.width(), self.height(), pixels)
+    }
+}
+
+/// The alpha of the 4 colors is scaled from 0-15 to 0-255.
+impl IndexedImage for VobSubIndexedImage {
+    fn indexes(&self) -> &[u8] {
+        &self.raw_image
+    }
+
+    fn index_alpha(&self, index: u8) -> u8 {
+        self.alpha
+            .get(usize::from(index))
+            .map_or(0, |alpha| alpha * 0x11)
+    }
+}
+
+/// A [`VobSubIndexedImage`] with the 16 colors palette of its `*.idx` file,
+/// which give access to the colors of the image. See [`VobSubIndexedImage::with_palette`].
+#[derive(Debug, Clone, Copy)]
+pub struct VobSubPalettedImage<'a> {
+    image: &'a VobSubIndexedImage,
+    palette: &'a Palette,
+}
+
+impl VobSubIndexedImage {
+    /// Associate the image with the 16 colors palette of the `*.idx` file.
+    #[must_use]
+    pub const fn with_palette<'a>(&'a self, palette: &'a Palette) -> VobSubPalettedImage<'a> {
+        VobSubPalettedImage {
+            image: self,
+            palette,
+        }
+    }
+}
+
+impl ImageArea for VobSubPalettedImage<'_> {
+    fn area(&self) -> Area {
+        self.image.area
+    }
+}
+
+impl IndexedImage for VobSubPalettedImage<'_> {
+    fn indexes(&self) -> &[u8] {
+        self.image.indexes()
+    }
+
+    fn index_alpha(&self, index: u8) -> u8 {
+        self.image.index_alpha(index)
+    }
+}
+
+/// The alpha of the 4 colors is scaled from 0-15 to 0-255.
+impl PalettedImage for VobSubPalettedImage<'_> {
+    fn palette_rgba(&self, index: u8) -> Rgba<u8> {
+        let slot = usize::from(index);
+        match (self.image.palette.get(slot), self.image.alpha.get(slot)) {
+            (Some(&palette_index), Some(&alpha)) => conv_to_rgba(
+                self.palette[usize::from(palette_index & 0x0F)],
+                alpha * 0x11,
+            ),
+            _ => Rgba([0, 0, 0, 0]),
+        }
+    }
+}
+
+impl IndexedTransform for VobSubIndexedImage {
+    fn screen_area(&self) -> Area {
+        self.area
+    }
+
+    fn with_screen_area(self, area: Area) -> Self {
+        Self { area, ..self }
+    }
+
+    fn with_pixels(&self, area: Area, indexes: Vec<u8>) -> Self {
+        Self {
+            area,
+            raw_image: indexes,
+            ..*self
+        }
+    }
+}
+
 impl From<VobSubRleImage<'_>> for VobSubIndexedImage {
     fn from(rle_image: VobSubRleImage) -> Self {
         let decompressed_image = decompress(rle_image.size(), rle_image.raw_data()).unwrap();
@@ -358,10 +507,15 @@ where
     }
 }
 
-/// A struct to convert [`VobSubIndexedImage`] to image for `OCR`
+/// A struct to convert [`VobSubIndexedImage`] to image for `OCR`.
+///
+/// Any visible and non black color is considered as text, unless roles are given with
+/// [`VobSubOcrImage::with_roles`], or detected with [`ToOcrImageOpt::palette_roles`] and
+/// conclusive (see [`roles_are_conclusive`]). Then the [`PaletteRole`] of the colors are used.
 pub struct VobSubOcrImage<'a> {
     indexed_img: &'a VobSubIndexedImage,
     palette: &'a PaletteLuma,
+    roles: Option<[PaletteRole; 4]>,
 }
 
 impl<'a> VobSubOcrImage<'a> {
@@ -371,24 +525,30 @@ impl<'a> VobSubOcrImage<'a> {
         Self {
             indexed_img,
             palette,
+            roles: None,
         }
     }
 
-    // Compute the output palette color
-    fn compute_palette_color(&self, opt: ToOcrImageOpt) -> [Luma<u8>; 4] {
-        const LUMA_BLACK: [u8; 1] = [0; 1];
+    /// Use the given role of the 4 colors of the subtitle to separate the text from
+    /// the background: only the pixels of the [`PaletteRole::Fill`] color are text, with
+    /// the outline if it's not removed.
+    ///
+    /// The roles can be detected on the subtitle with [`VobSubIndexedImage::palette_roles`],
+    /// or on the whole track with [`track_palette_roles`](super::track_palette_roles).
+    #[must_use]
+    pub const fn with_roles(mut self, roles: [PaletteRole; 4]) -> Self {
+        self.roles = Some(roles);
+        self
+    }
+
+    // Compute the luminance and alpha of the 4 colors of the subtitle.
+    fn compute_palette_luma_a(&self) -> [LumaA<u8>; 4] {
         self.indexed_img
             .palette()
             .into_iter_fixed()
             .zip(self.indexed_img.alpha())
             .map(|(&palette_idx, &alpha)| (self.palette[palette_idx as usize], alpha))
-            .map(|(luminance, alpha)| {
-                if alpha > 0 && luminance.0 > LUMA_BLACK {
-                    opt.text_color
-                } else {
-                    opt.background_color
-                }
-            })
+            .map(|(luminance, alpha)| LumaA([luminance.0[0], alpha * 0x11]))
             .collect()
     }
 }
@@ -398,18 +558,256 @@ impl ToOcrImage for VobSubOcrImage<'_> {
     fn image(&self, opt: &ToOcrImageOpt) -> image::GrayImage {
         let width = self.indexed_img.width();
         let height = self.indexed_img.height();
-        let border = opt.border;
-        let out_color_palette = self.compute_palette_color(*opt);
+        // The roles of the colors separate the text from the background if they are given,
+        // or detected when asked and conclusive.
+        let roles = self
+            .roles
+            .unwrap_or_else(|| self.indexed_img.palette_roles());
+        let palette_luma_a =
+            if self.roles.is_some() || (opt.palette_roles && roles_are_conclusive(&roles)) {
+                roles.map(|role| role_ocr_color(Some(&role), opt.remove_outline))
+            } else {
+                let mut palette_luma_a = self.compute_palette_luma_a();
+                if opt.remove_outline {
+                    remove_outline_colors(&mut palette_luma_a, &roles);
+                }
+                palette_luma_a
+            };
 
-        let image = ImageBuffer::from_fn(width + border * 2, height + border * 2, |x, y| {
-            if x < border || x >= width + border || y < border || y >= height + border {
-                opt.background_color
+        let source = ImageBuffer::from_fn(width, height, |x, y| {
+            let offset = y * width + x;
+            let sub_palette_idx = self.indexed_img.raw_image()[offset as usize] as usize;
+            palette_luma_a[sub_palette_idx]
+        });
+
+        // Any visible and non black color is considered as text.
+        let convert = |LumaA([luminance, alpha]): LumaA<u8>| {
+            if alpha > 0 && luminance > 0 {
+                opt.text_color
             } else {
-                let offset = (y - border) * width + (x - border);
-                let sub_palette_idx = self.indexed_img.raw_image()[offset as usize] as usize;
-                out_color_palette[sub_palette_idx]
+                opt.background_color
             }
-        });
+        };
+        prepare_ocr_image(source, convert, opt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::{IndexedScaleFilter, IndexedToImage};
+    use image::Luma;
+
+    #[test]
+    fn scale_to_smaller_frame() {
+        let area = Area::try_from(AreaValues {
+            x1: 960,
+            y1: 900,
+            x2: 963,
+            y2: 903,
+        })
+        .unwrap();
+        let raw_image = (0..16).map(|i| u8::from(i % 4 < 2)).collect();
+        let image = VobSubIndexedImage::new(area, [0, 1, 2, 3], [0, 15, 15, 15], raw_image);
+
+        let scaled = image
+            .scale_to_frame(
+                Size { w: 1920, h: 1080 },
+                Size { w: 960, h: 540 },
+                IndexedScaleFilter::Majority,
+            )
+            .unwrap()
+            .center_horizontally(Size { w: 960, h: 540 });
+        assert_eq!(scaled.area().size(), Size { w: 2, h: 2 });
+        assert_eq!((scaled.area().left(), scaled.area().top()), (479, 450));
+        assert_eq!(scaled.raw_image(), [1, 0, 1, 0]);
+        assert_eq!(scaled.palette(), image.palette());
+    }
+
+    #[test]
+    fn crop_transparent_margins() {
+        let area = Area::try_from(AreaValues {
+            x1: 100,
+            y1: 200,
+            x2: 103,
+            y2: 203,
+        })
+        .unwrap();
+        #[rustfmt::skip]
+        let raw_image = vec![
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 3, 0,
+            0, 0, 0, 0,
+        ];
+        let image = VobSubIndexedImage::new(area, [0, 1, 2, 3], [0, 15, 15, 15], raw_image);
+
+        let cropped = image.crop_to_content().unwrap();
+        assert_eq!((cropped.area().left(), cropped.area().top()), (102, 202));
+        assert_eq!(cropped.area().size(), Size { w: 2, h: 2 });
+        assert_eq!(cropped.raw_image(), [3, 0, 0, 0]);
+
+        let empty = VobSubIndexedImage::new(area, [0; 4], [0, 15, 15, 15], vec![0; 16]);
+        assert_eq!(empty.crop_to_content(), None);
+    }
+
+    #[test]
+    fn ocr_dark_text_with_bright_outline() {
+        let area = Area::try_from(AreaValues {
+            x1: 0,
+            y1: 0,
+            x2: 3,
+            y2: 3,
+        })
+        .unwrap();
+        #[rustfmt::skip]
+        let raw_image = vec![
+            1, 1, 1, 1,
+            1, 2, 2, 1,
+            1, 2, 2, 1,
+            1, 1, 1, 1,
+        ];
+        // Outline is white, text is dark gray.
+        let image = VobSubIndexedImage::new(area, [0, 1, 2, 0], [0, 15, 15, 0], raw_image);
+        let mut palette = [Luma([0]); 16];
+        palette[1] = Luma([255]);
+        palette[2] = Luma([40]);
+
+        let text_of = |opt: &ToOcrImageOpt| {
+            VobSubOcrImage::new(&image, &palette)
+                .with_roles(image.palette_roles())
+                .image(opt)
+                .pixels()
+                .map(|pixel| *pixel == opt.text_color)
+                .collect::<Vec<_>>()
+        };
+        let opt = ToOcrImageOpt::default().with_border(0);
+        assert!(text_of(&opt).iter().all(|&is_text| is_text));
+        let opt = opt.with_remove_outline(true);
+        assert_eq!(text_of(&opt), raw_image_is(&image, 2));
+    }
+
+    #[test]
+    fn ocr_remove_outline() {
+        let area = Area::try_from(AreaValues {
+            x1: 0,
+            y1: 0,
+            x2: 3,
+            y2: 3,
+        })
+        .unwrap();
+        #[rustfmt::skip]
+        let raw_image = vec![
+            1, 1, 1, 1,
+            1, 2, 2, 1,
+            1, 2, 2, 1,
+            1, 1, 1, 1,
+        ];
+        let image = VobSubIndexedImage::new(area, [0, 1, 2, 0], [0, 15, 15, 0], raw_image);
+        let mut palette = [Luma([0]); 16];
+        palette[1] = Luma([255]);
+        palette[2] = Luma([40]);
+
+        let opt = ToOcrImageOpt {
+            border: 0,
+            remove_outline: true,
+            ..Default::default()
+        };
+        let ocr_image = VobSubOcrImage::new(&image, &palette).image(&opt);
+        let text = ocr_image
+            .pixels()
+            .map(|pixel| *pixel == opt.text_color)
+            .collect::<Vec<_>>();
+        assert_eq!(text, raw_image_is(&image, 2));
+    }
+
+    #[test]
+    fn ocr_palette_roles_and_fallback() {
+        let area = Area::try_from(AreaValues {
+            x1: 0,
+            y1: 0,
+            x2: 3,
+            y2: 3,
+        })
+        .unwrap();
+        #[rustfmt::skip]
+        let raw_image = vec![
+            1, 1, 1, 1,
+            1, 2, 2, 1,
+            1, 2, 2, 1,
+            1, 1, 1, 1,
+        ];
+        let mut palette = [Luma([0]); 16];
+        palette[1] = Luma([255]);
+        palette[2] = Luma([40]);
+        let opt = ToOcrImageOpt::default()
+            .with_border(0)
+            .with_palette_roles(true)
+            .with_remove_outline(true);
+        let text_of = |image: &VobSubIndexedImage, opt: &ToOcrImageOpt| {
+            VobSubOcrImage::new(image, &palette)
+                .image(opt)
+                .pixels()
+                .map(|pixel| *pixel == opt.text_color)
+                .collect::<Vec<_>>()
+        };
+
+        // By default, the luminance is used: the bright outline is text.
+        let image = VobSubIndexedImage::new(area, [0, 1, 2, 0], [0, 15, 15, 0], raw_image);
+        let default_opt = ToOcrImageOpt::default().with_border(0);
+        assert!(text_of(&image, &default_opt).iter().all(|&is_text| is_text));
+
+        // Roles are detected without hand tuning: the bright outline is not text.
+        assert_eq!(text_of(&image, &opt), raw_image_is(&image, 2));
+
+        // Without outline, a few anti-aliasing pixels make the roles inconclusive,
+        // then any visible and non black color is text.
+        let mut smoothed = vec![2; 16];
+        smoothed[0] = 1;
+        smoothed[15] = 1;
+        let image = VobSubIndexedImage::new(area, [0, 2, 2, 0], [0, 15, 15, 0], smoothed);
+        assert!(!roles_are_conclusive(&image.palette_roles()));
+        let opt = opt.with_remove_outline(false);
+        assert!(text_of(&image, &opt).iter().all(|&is_text| is_text));
+    }
+
+    #[test]
+    fn paletted_image_to_rgba() {
+        let area = Area::try_from(AreaValues {
+            x1: 10,
+            y1: 20,
+            x2: 13,
+            y2: 21,
+        })
+        .unwrap();
+        let raw_image = vec![0, 1, 2, 3, 3, 2, 1, 0];
+        let image = VobSubIndexedImage::new(area, [0, 5, 6, 7], [0, 15, 8, 15], raw_image);
+        let mut palette = [Rgb([0, 0, 0]); 16];
+        palette[5] = Rgb([255, 0, 0]);
+        palette[6] = Rgb([0, 255, 0]);
+        palette[7] = Rgb([0, 0, 255]);
+
+        let paletted = image.with_palette(&palette);
+        assert_eq!(paletted.palette_rgba(2), Rgba([0, 255, 0, 0x88]));
+        assert_eq!(paletted.palette_rgba(4), Rgba([0, 0, 0, 0]));
+
+        let expected = VobSubToImage::new(&image, &palette, |color, alpha| {
+            conv_to_rgba(color, alpha * 0x11)
+        })
+        .to_image();
+        assert_eq!(IndexedToImage::rgba(&paletted).to_image(), expected);
+
+        let mut other_palette = palette;
+        other_palette[6] = Rgb([0, 128, 0]);
+        assert!(paletted.same_used_colors(&image.with_palette(&palette)));
+        assert!(!paletted.same_used_colors(&image.with_palette(&other_palette)));
+    }
+
+    fn raw_image_is(image: &VobSubIndexedImage, slot: u8) -> Vec<bool> {
         image
+            .raw_image()
+            .iter()
+            .map(|&index| index == slot)
+            .collect()
     }
 }
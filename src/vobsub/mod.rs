@@ -71,18 +71,23 @@
 
 mod decoder;
 mod idx;
+mod ifo;
 mod img;
 mod mpeg2;
+mod normalize;
 mod palette;
 mod probe;
 mod sub;
 
 pub use self::{
     idx::{Index, TimePointIdx},
-    img::{conv_to_rgba, VobSubIndexedImage, VobSubOcrImage, VobSubToImage},
+    ifo::{Ifo, SubpictureStream, SubpictureVariants},
+    img::{conv_to_rgba, VobSubIndexedImage, VobSubOcrImage, VobSubPalettedImage, VobSubToImage},
+    mpeg2::ps::PesPacketReader,
+    normalize::{track_palette_roles, PaletteNormalization, RoleColor, VobSubTrackRoles},
     palette::{palette, palette_rgb_to_luminance, Palette},
     probe::{is_idx_file, is_sub_file},
-    sub::{ErrorMissing, Sub},
+    sub::{decode_spu, ErrorMissing, Sub, VobsReader, VobsubParser},
 };
 
 use crate::content::ContentError;
@@ -186,6 +191,18 @@ pub enum VobSubError {
         /// Path of the file we tried to read
         path: PathBuf,
     },
+
+    /// The `IFO` data are invalid.
+    #[error("invalid IFO: {0}")]
+    InvalidIfo(&'static str),
+
+    /// Io error while reading a stream.
+    #[error("Io error while reading stream")]
+    Read(#[source] io::Error),
+
+    /// The substream id is not the id of a subpicture substream (`0x20` to `0x3f`).
+    #[error("0x{0:x} is not a subpicture substream id")]
+    InvalidSubstream(u8),
 }
 
 /// Error from `nom` handling
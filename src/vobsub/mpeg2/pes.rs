@@ -17,7 +17,7 @@ use nom::{
     IResult,
     Parser as _,
 };
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
 use super::clock::{clock, Clock};
 use crate::util::BytesFormatter;
@@ -223,7 +223,7 @@ pub struct Packet<'a> {
     pub header: Header,
     pub header_data: HeaderData,
     pub substream_id: u8,
-    pub data: &'a [u8],
+    pub data: Cow<'a, [u8]>,
 }
 
 impl fmt::Debug for Packet<'_> {
@@ -232,7 +232,7 @@ impl fmt::Debug for Packet<'_> {
             .field("header", &self.header)
             .field("header_data", &self.header_data)
             .field("substream_id", &self.substream_id)
-            .field("data", &BytesFormatter(self.data))
+            .field("data", &BytesFormatter(&self.data))
             .finish()
     }
 }
@@ -246,11 +246,24 @@ fn packet_helper(input: &[u8]) -> IResult<&[u8], Packet<'_>> {
             header,
             header_data,
             substream_id,
-            data,
+            data: Cow::Borrowed(data),
         },
     ))
 }
 
+impl Packet<'_> {
+    /// Copy the data of the packet, to not borrow the parsed input.
+    #[must_use]
+    pub fn into_owned(self) -> Packet<'static> {
+        Packet {
+            header: self.header,
+            header_data: self.header_data,
+            substream_id: self.substream_id,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
+
 pub fn packet(input: &[u8]) -> IResult<&[u8], Packet<'_>> {
     const PACKET_TAG: &[u8] = &[0x00, 0x00, 0x01, 0xbd];
     let packet_tag = tag_bytes(PACKET_TAG);
@@ -361,7 +374,7 @@ mod tests {
                 ..HeaderData::default()
             },
             substream_id: 0x20,
-            data: &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            data: Cow::Borrowed(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
         };
 
         assert_eq!(packet(input), IResult::Ok((&[0xff][..], expected)));
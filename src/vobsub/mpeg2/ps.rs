@@ -11,7 +11,7 @@ use nom::{
     bytes::complete::tag as tag_bytes,
     IResult, Parser as _,
 };
-use std::fmt;
+use std::{fmt, io::BufRead};
 
 use super::{
     clock::{clock_and_ext, Clock},
@@ -105,6 +105,20 @@ pub fn pes_packet(input: &[u8]) -> IResult<&[u8], PesPacket<'_>> {
     ))
 }
 
+impl PesPacket<'_> {
+    /// Copy the data of the packet, to not borrow the parsed input.
+    #[must_use]
+    pub fn into_owned(self) -> PesPacket<'static> {
+        PesPacket {
+            ps_header: self.ps_header,
+            pes_packet: self.pes_packet.into_owned(),
+        }
+    }
+}
+
+/// Start code of a Program Stream packet.
+const PS_START_CODE: [u8; 4] = [0x00, 0x00, 0x01, 0xba];
+
 /// An iterator over all the `PES` packets in an MPEG-2 Program Stream.
 pub struct PesPackets<'a> {
     /// The remaining input to parse.
@@ -117,7 +131,7 @@ impl<'a> Iterator for PesPackets<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             // Search for the start of a ProgramStream packet.
-            let needle = &[0x00, 0x00, 0x01, 0xba];
+            let needle = &PS_START_CODE;
             let start = self
                 .remaining
                 .windows(needle.len())
@@ -167,3 +181,112 @@ impl<'a> Iterator for PesPackets<'a> {
 pub const fn pes_packets(input: &[u8]) -> PesPackets<'_> {
     PesPackets { remaining: input }
 }
+
+/// Size of the Program Stream packet header, without stuffing bytes.
+const PS_HEADER_SIZE: usize = 14;
+/// Size of the start code and length of a `PES` packet.
+const PES_PREFIX_SIZE: usize = 6;
+/// Size of the chunks read from the reader.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// An iterator over all the `PES` packets in an MPEG-2 Program Stream read from a [`BufRead`].
+///
+/// Only the packet currently parsed is kept in memory, so the memory use doesn't depend on
+/// the size of the stream. Packets are the same as the ones parsed from data in memory.
+pub struct PesPacketReader<R> {
+    reader: R,
+    /// Data read but not parsed yet.
+    buffer: Vec<u8>,
+    /// If the end of the reader is reached.
+    eof: bool,
+}
+
+impl<R: BufRead> PesPacketReader<R> {
+    /// Create an iterator over the `PES` packets read from `reader`.
+    pub const fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// Read more data in the buffer, return `false` if the end of the reader is reached.
+    fn fill(&mut self) -> Result<bool, VobSubError> {
+        if self.eof {
+            return Ok(false);
+        }
+        let data = self.reader.fill_buf().map_err(VobSubError::Read)?;
+        let len = data.len().min(READ_CHUNK_SIZE);
+        self.buffer.extend_from_slice(&data[..len]);
+        self.reader.consume(len);
+        self.eof = len == 0;
+        Ok(!self.eof)
+    }
+
+    /// Size of the packet at the start of the buffer, if enough data are available to know it.
+    fn packet_size(&self) -> Option<usize> {
+        let stuffing = usize::from(*self.buffer.get(PS_HEADER_SIZE - 1)? & 0b111);
+        let pes_start = PS_HEADER_SIZE + stuffing;
+        let len = self
+            .buffer
+            .get(pes_start + 4..pes_start + PES_PREFIX_SIZE)?;
+        Some(pes_start + PES_PREFIX_SIZE + usize::from(u16::from_be_bytes([len[0], len[1]])))
+    }
+
+    fn next_packet(&mut self) -> Result<Option<PesPacket<'static>>, VobSubError> {
+        loop {
+            let start = self
+                .buffer
+                .windows(PS_START_CODE.len())
+                .position(|window| window == PS_START_CODE);
+            let Some(start) = start else {
+                // Keep the end of the buffer, which can be the beginning of a start code.
+                let keep = self.buffer.len().min(PS_START_CODE.len() - 1);
+                self.buffer.drain(..self.buffer.len() - keep);
+                if self.fill()? {
+                    continue;
+                }
+                trace!("Reached end of data");
+                return Ok(None);
+            };
+            self.buffer.drain(..start);
+
+            // Read until the whole packet is available, to parse it like a complete input.
+            if self
+                .packet_size()
+                .map_or(true, |size| self.buffer.len() < size)
+                && self.fill()?
+            {
+                continue;
+            }
+
+            match pes_packet(&self.buffer) {
+                IResult::Ok((remaining, packet)) => {
+                    trace!("Decoded packet {:?}", &packet);
+                    let packet = packet.into_owned();
+                    let consumed = self.buffer.len() - remaining.len();
+                    self.buffer.drain(..consumed);
+                    return Ok(Some(packet));
+                }
+                IResult::Err(nom::Err::Incomplete(needed)) => {
+                    self.buffer.clear();
+                    warn!("Incomplete packet, need: {needed:?}");
+                    return Err(VobSubError::PESPacket(NomError::IncompleteInput(needed)));
+                }
+                IResult::Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+                    debug!("Skipping packet {:?}", &err);
+                    self.buffer.drain(..PS_START_CODE.len());
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PesPacketReader<R> {
+    type Item = Result<PesPacket<'static>, VobSubError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet().transpose()
+    }
+}
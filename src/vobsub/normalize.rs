@@ -0,0 +1,230 @@
+//! Normalization of the colors of `VobSub` subtitles.
+
+use image::Rgb;
+
+use super::{img::VobSubIndexedImage, Palette};
+use crate::image::{detect_roles, ImageSize as _, PaletteRole, PaletteRoleStats};
+
+/// Entry of the palette and alpha used for a [`PaletteRole`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoleColor {
+    /// Index in the 16 colors [`Palette`].
+    pub palette_index: u8,
+    /// Alpha value, from 0 (transparent) to 15 (opaque).
+    pub alpha: u8,
+}
+
+/// Target colors of a palette normalization of `VobSub` subtitles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteNormalization {
+    /// The 16 colors palette, to use in place of the `*.idx` palette.
+    pub palette: Palette,
+    /// Color of the background (and unused entries).
+    pub background: RoleColor,
+    /// Color of the text fill.
+    pub fill: RoleColor,
+    /// Color of the text outline.
+    pub outline: RoleColor,
+    /// Color of the anti-aliasing pixels.
+    pub anti_alias: RoleColor,
+}
+
+// Implement [`Default`] for [`PaletteNormalization`] with white text, a black outline
+// and a gray anti-aliasing on a transparent background.
+impl Default for PaletteNormalization {
+    fn default() -> Self {
+        let mut palette = [Rgb([0, 0, 0]); 16];
+        palette[1] = Rgb([0xff, 0xff, 0xff]);
+        palette[3] = Rgb([0x80, 0x80, 0x80]);
+        let opaque = |palette_index| RoleColor {
+            palette_index,
+            alpha: 15,
+        };
+        Self {
+            palette,
+            background: RoleColor {
+                palette_index: 0,
+                alpha: 0,
+            },
+            fill: opaque(1),
+            outline: opaque(2),
+            anti_alias: opaque(3),
+        }
+    }
+}
+
+impl PaletteNormalization {
+    /// Color used for a role.
+    #[must_use]
+    pub const fn color(&self, role: PaletteRole) -> RoleColor {
+        match role {
+            PaletteRole::Unused | PaletteRole::Background => self.background,
+            PaletteRole::Fill => self.fill,
+            PaletteRole::Outline => self.outline,
+            PaletteRole::AntiAlias => self.anti_alias,
+        }
+    }
+}
+
+impl VobSubIndexedImage {
+    /// Detect the role of the 4 colors of the subtitle, from its pixels and alpha.
+    ///
+    /// See [`PaletteRoleStats::roles`](crate::image::PaletteRoleStats::roles).
+    #[must_use]
+    pub fn palette_roles(&self) -> [PaletteRole; 4] {
+        let alpha = self.alpha();
+        let roles = detect_roles(self.raw_image(), self.width(), 4, |index| {
+            alpha[usize::from(index)]
+        });
+        std::array::from_fn(|slot| roles[slot])
+    }
+
+    /// Rewrite the palette indexes and alpha of the 4 colors of the subtitle
+    /// with the colors of `normalization` matching `roles`.
+    #[must_use]
+    pub fn remap_palette(
+        self,
+        roles: &[PaletteRole; 4],
+        normalization: &PaletteNormalization,
+    ) -> Self {
+        let colors = roles.map(|role| normalization.color(role));
+        let palette = colors.map(|color| color.palette_index);
+        let alpha = colors.map(|color| color.alpha);
+        self.with_colors(palette, alpha)
+    }
+
+    /// Detect the role of the 4 colors of the subtitle, and rewrite them with
+    /// the colors of `normalization`.
+    ///
+    /// The palette of `normalization` should be used in place of the `*.idx` palette
+    /// to display or export the normalized subtitles.
+    #[must_use]
+    pub fn normalize_palette(self, normalization: &PaletteNormalization) -> Self {
+        let roles = self.palette_roles();
+        self.remap_palette(&roles, normalization)
+    }
+}
+
+/// Role of the colors of a `VobSub` track, see [`track_palette_roles`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VobSubTrackRoles {
+    /// Role of each color, indexed by [`color_key`].
+    roles: Vec<PaletteRole>,
+}
+
+/// Key of a color of a subtitle, from its palette index and alpha.
+const fn color_key(palette_index: u8, alpha: u8) -> u8 {
+    (palette_index & 0x0F) << 4 | (alpha & 0x0F)
+}
+
+impl VobSubTrackRoles {
+    /// Role of the 4 colors of a subtitle of the track.
+    #[must_use]
+    pub fn roles(&self, image: &VobSubIndexedImage) -> [PaletteRole; 4] {
+        std::array::from_fn(|slot| {
+            let key = color_key(image.palette()[slot], image.alpha()[slot]);
+            self.roles[usize::from(key)]
+        })
+    }
+}
+
+/// Detect the role of the colors used by the subtitles of a track.
+///
+/// A color is identified by its palette index and its alpha. The pixels of all subtitles are
+/// analysed together, which is more reliable than on each subtitle, as a color can be rare or
+/// missing in some subtitles. See [`PaletteRoleStats::roles`].
+#[profiling::function]
+pub fn track_palette_roles<'a, I>(images: I) -> VobSubTrackRoles
+where
+    I: IntoIterator<Item = &'a VobSubIndexedImage>,
+{
+    let mut stats = PaletteRoleStats::new(usize::from(u8::MAX) + 1);
+    for image in images {
+        let keys = image
+            .raw_image()
+            .iter()
+            .map(|&slot| {
+                let slot = usize::from(slot);
+                color_key(image.palette()[slot], image.alpha()[slot])
+            })
+            .collect::<Vec<_>>();
+        stats.add_image(&keys, image.width(), |key| key & 0x0F);
+    }
+    VobSubTrackRoles {
+        roles: stats.roles(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{Area, AreaValues};
+
+    #[test]
+    fn normalize_yellow_text() {
+        let area = Area::try_from(AreaValues {
+            x1: 0,
+            y1: 0,
+            x2: 5,
+            y2: 4,
+        })
+        .unwrap();
+        #[rustfmt::skip]
+        let raw_image = vec![
+            2, 2, 2, 2, 2, 2,
+            2, 3, 3, 3, 3, 2,
+            2, 3, 3, 3, 3, 2,
+            2, 3, 3, 3, 3, 2,
+            2, 2, 2, 2, 2, 2,
+        ];
+        let image = VobSubIndexedImage::new(area, [7, 0, 12, 10], [15, 0, 15, 15], raw_image);
+        let roles = image.palette_roles();
+        assert_eq!(
+            roles,
+            [
+                PaletteRole::Unused,
+                PaletteRole::Unused,
+                PaletteRole::Outline,
+                PaletteRole::Fill,
+            ]
+        );
+
+        let normalized = image.normalize_palette(&PaletteNormalization::default());
+        assert_eq!(normalized.palette(), &[0, 0, 2, 1]);
+        assert_eq!(normalized.alpha(), &[0, 0, 15, 15]);
+    }
+
+    #[test]
+    fn roles_of_track() {
+        let area = Area::try_from(AreaValues {
+            x1: 0,
+            y1: 0,
+            x2: 3,
+            y2: 3,
+        })
+        .unwrap();
+        #[rustfmt::skip]
+        let outlined = vec![
+            1, 1, 1, 1,
+            1, 2, 2, 1,
+            1, 2, 2, 1,
+            1, 1, 1, 1,
+        ];
+        let first = VobSubIndexedImage::new(area, [0, 5, 9, 0], [0, 15, 15, 0], outlined);
+        // Second subtitle use the colors in others slots, without outline.
+        let second = VobSubIndexedImage::new(area, [9, 0, 0, 0], [15, 0, 0, 0], vec![0; 16]);
+
+        let track_roles = track_palette_roles([&first, &second]);
+        assert_eq!(
+            track_roles.roles(&second),
+            [
+                PaletteRole::Fill,
+                PaletteRole::Unused,
+                PaletteRole::Unused,
+                PaletteRole::Unused,
+            ]
+        );
+        assert_eq!(track_roles.roles(&first)[1], PaletteRole::Outline);
+        assert_eq!(track_roles.roles(&first)[2], PaletteRole::Fill);
+    }
+}
@@ -4,16 +4,18 @@ use super::VobSubError;
 use std::{fs, io::Read as _, path::Path};
 
 /// Internal helper function which looks for "magic" bytes at the start of
-/// a file.
+/// a file. A file shorter than the magic bytes doesn't match.
 fn has_magic(path: &Path, magic: &[u8]) -> Result<bool, VobSubError> {
     let mkerr = |source| VobSubError::Io {
         source,
         path: path.into(),
     };
 
-    let mut f = fs::File::open(path).map_err(mkerr)?;
-    let mut bytes = vec![0; magic.len()];
-    f.read_exact(&mut bytes).map_err(mkerr)?;
+    let f = fs::File::open(path).map_err(mkerr)?;
+    let mut bytes = Vec::with_capacity(magic.len());
+    f.take(magic.len() as u64)
+        .read_to_end(&mut bytes)
+        .map_err(mkerr)?;
     Ok(magic == &bytes[..])
 }
 
@@ -28,7 +30,8 @@ pub fn is_idx_file<P: AsRef<Path>>(path: P) -> Result<bool, VobSubError> {
 /// Does the specified path appear to point to a `*.sub` file?
 ///
 /// Note that this may (or may not) return false positives for certain
-/// MPEG-2 related formats.
+/// MPEG-2 related formats. Text `*.sub` files, like `MicroDVD` ones (see
+/// [`is_microdvd_file`](crate::text::is_microdvd_file)), don't match.
 ///
 /// # Errors
 ///
@@ -40,6 +43,7 @@ pub fn is_sub_file<P: AsRef<Path>>(path: P) -> Result<bool, VobSubError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::text::is_microdvd_file;
 
     #[test]
     fn probe_idx_files() {
@@ -51,5 +55,12 @@ mod tests {
     fn probe_sub_files() {
         assert!(is_sub_file("./fixtures/tiny.sub").unwrap());
         assert!(!is_sub_file("./fixtures/tiny.idx").unwrap());
+        assert!(!is_sub_file("./fixtures/tiny-microdvd.sub").unwrap());
+    }
+
+    #[test]
+    fn probe_microdvd_files() {
+        assert!(is_microdvd_file("./fixtures/tiny-microdvd.sub").unwrap());
+        assert!(!is_microdvd_file("./fixtures/tiny.sub").unwrap());
     }
 }
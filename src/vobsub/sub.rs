@@ -27,7 +27,14 @@ use nom::{
     IResult, Parser as _,
 };
 use std::{
-    cmp::Ordering, fmt::Debug, fs, iter::FusedIterator, marker::PhantomData, path::Path,
+    cmp::Ordering,
+    fmt::Debug,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops::Range,
+    path::Path,
     slice::from_ref,
 };
 use thiserror::Error;
@@ -400,6 +407,19 @@ where
     Ok(result)
 }
 
+/// Decode a subtitle from a `SPU` packet, as stored without Program Stream in containers
+/// like `Matroska`. The times of the subtitle are relative to `base_time` in seconds.
+///
+/// # Errors
+///
+/// Will return an error if the packet is incomplete or invalid.
+pub fn decode_spu(
+    data: &[u8],
+    base_time: f64,
+) -> Result<(TimeSpan, VobSubIndexedImage), VobSubError> {
+    subtitle::<(TimeSpan, VobSubIndexedImage), _>(data, base_time)
+}
+
 /// Like `?` and `try!`, but assume that we're working with
 /// `Option<Result<T, E>>` instead of `Result<T, E>`, and pass through
 /// `None`.
@@ -413,10 +433,32 @@ macro_rules! try_iter {
     };
 }
 
+/// Data of a `*.sub` file.
+enum SubData {
+    /// Data read in memory.
+    Owned(Vec<u8>),
+    /// File mapped in memory.
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl SubData {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            #[cfg(feature = "mmap")]
+            Self::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// Range of the substream ids used by subtitle streams, in private stream 1.
+const SUBPICTURE_SUBSTREAMS: Range<u8> = 0x20..0x40;
+
 /// Store the content of a `*.sub` file.
 pub struct Sub {
     /// Our compressed subtitle data.
-    data: Vec<u8>,
+    data: SubData,
 }
 impl Sub {
     /// Init a `Sub` from a file path.
@@ -432,23 +474,133 @@ impl Sub {
             source,
             path: path.as_ref().to_path_buf(),
         })?;
-        Ok(Self { data })
+        Ok(Self {
+            data: SubData::Owned(data),
+        })
+    }
+
+    /// Init a `Sub` from the content of a `*.sub` file.
+    #[must_use]
+    pub fn from_bytes(data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            data: SubData::Owned(data.into()),
+        }
+    }
+
+    /// Init a `Sub` by reading all the content of `reader`.
+    ///
+    /// To parse subtitles without loading all the data in memory, use [`Sub::subtitles_from_reader`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::Read` if failed to read from `reader`.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, VobSubError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(VobSubError::Read)?;
+        Ok(Self::from_bytes(data))
+    }
+
+    /// Init a `Sub` by reading all the content of an async `reader`.
+    ///
+    /// The content is buffered in memory before parsing, see [`Sub::from_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::Read` if failed to read from `reader`.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R>(reader: R) -> Result<Self, VobSubError>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let data = crate::util::read_all_async(reader, VobSubError::Read).await?;
+        Ok(Self::from_bytes(data))
+    }
+
+    /// Init a `Sub` by mapping the file from `path` in memory, instead of reading it.
+    ///
+    /// The file must not be modified while the `Sub` is alive.
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::Io` if not able to open or map the file from `path`.
+    #[cfg(feature = "mmap")]
+    pub fn open_mmap<P>(path: P) -> Result<Self, VobSubError>
+    where
+        P: AsRef<Path>,
+    {
+        let to_error = |source| VobSubError::Io {
+            source,
+            path: path.as_ref().to_path_buf(),
+        };
+        let file = fs::File::open(path.as_ref()).map_err(to_error)?;
+        // SAFETY: the file is only read, and the caller is warned to not modify it while mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(to_error)?;
+        Ok(Self {
+            data: SubData::Mapped(mmap),
+        })
     }
 
     /// Iterate over the subtitles associated with this `*.idx` file.
     #[must_use]
-    #[allow(clippy::missing_const_for_fn)]
     pub fn subtitles<D>(&self) -> VobsubParser<'_, D> {
-        VobsubParser::new(&self.data)
+        VobsubParser::new(self.data.as_slice())
+    }
+
+    /// Iterate over the subtitles of a `*.sub` stream read from `reader`.
+    ///
+    /// Data are read incrementally, only the subtitle currently parsed is kept in memory.
+    /// This allows to parse large files, or data from a pipe.
+    #[must_use]
+    pub const fn subtitles_from_reader<D, R>(
+        reader: R,
+    ) -> VobsubParser<'static, D, ps::PesPacketReader<R>>
+    where
+        R: BufRead,
+    {
+        VobsubParser::from_reader(reader)
+    }
+
+    /// Iterate over the subtitles of the `VOB` files of a title set (like `VTS_01_1.VOB`,
+    /// `VTS_01_2.VOB`, ...), read one after another as a single Program Stream.
+    ///
+    /// The files are chained and read incrementally, like with [`Sub::subtitles_from_reader`].
+    /// Use [`VobsubParser::with_substream`] to select the subtitle stream to parse.
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::Io` if not able to open one of the files.
+    pub fn subtitles_from_vobs<D, I, P>(
+        paths: I,
+    ) -> Result<VobsubParser<'static, D, ps::PesPacketReader<VobsReader>>, VobSubError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut reader: Box<dyn Read + Send> = Box::new(io::empty());
+        for path in paths {
+            let path = path.as_ref();
+            let file = File::open(path).map_err(|source| VobSubError::Io {
+                source,
+                path: path.to_path_buf(),
+            })?;
+            reader = Box::new(reader.chain(file));
+        }
+        Ok(VobsubParser::from_reader(BufReader::new(reader)))
     }
 }
 
-/// An internal iterator over subtitles.  These subtitles may not have a
-/// valid `end_time`, so we'll try to fix them up before letting the user
-/// see them.
-pub struct VobsubParser<'a, Decoder> {
-    pes_packets: ps::PesPackets<'a>,
-    phantom_data: PhantomData<Decoder>,
+/// Reader of the chained `VOB` files of a title set, see [`Sub::subtitles_from_vobs`].
+pub type VobsReader = BufReader<Box<dyn Read + Send>>;
+
+/// An iterator over subtitles, parsed from the `PES` packets of a Program Stream.
+///
+/// These subtitles may not have a valid `end_time`, so we'll try to fix them up
+/// before letting the user see them.
+pub struct VobsubParser<'a, Decoder, Packets = ps::PesPackets<'a>> {
+    pes_packets: Packets,
+    /// Substream id of the selected subtitle stream, all subtitle streams if `None`.
+    substream: Option<u8>,
+    phantom_data: PhantomData<(&'a (), Decoder)>,
 }
 
 impl<'a, Decoder> VobsubParser<'a, Decoder> {
@@ -458,16 +610,72 @@ impl<'a, Decoder> VobsubParser<'a, Decoder> {
     pub const fn new(input: &'a [u8]) -> Self {
         Self {
             pes_packets: ps::pes_packets(input),
+            substream: None,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<Decoder, R: BufRead> VobsubParser<'static, Decoder, ps::PesPacketReader<R>> {
+    /// To parse a `vobsub` (.sub) stream read from `reader`.
+    /// Return an iterator over the subtitles in this data stream.
+    #[must_use]
+    pub const fn from_reader(reader: R) -> Self {
+        Self {
+            pes_packets: ps::PesPacketReader::new(reader),
+            substream: None,
             phantom_data: PhantomData,
         }
     }
+}
+
+impl<'a, Decoder, Packets> VobsubParser<'a, Decoder, Packets>
+where
+    Packets: Iterator<Item = Result<ps::PesPacket<'a>, VobSubError>>,
+{
+    /// Select the subtitle stream to parse, from its substream id (`0x20` to `0x3f`).
+    ///
+    /// `*.sub` files contain only one stream, but `VOB` files can contain several
+    /// subtitle streams, each with a substream per display mode, see [`SubpictureVariants`].
+    /// Without selection, the subtitles of all streams are returned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `VobSubError::InvalidSubstream` if `substream_id` is not a subpicture substream.
+    ///
+    /// [`SubpictureVariants`]: super::SubpictureVariants
+    pub fn with_substream(mut self, substream_id: u8) -> Result<Self, VobSubError> {
+        if !SUBPICTURE_SUBSTREAMS.contains(&substream_id) {
+            return Err(VobSubError::InvalidSubstream(substream_id));
+        }
+        self.substream = Some(substream_id);
+        Ok(self)
+    }
+
+    /// Check if a packet of `substream_id` is a packet of the selected subtitle stream.
+    fn is_selected(&self, substream_id: u8) -> bool {
+        self.substream.map_or_else(
+            || SUBPICTURE_SUBSTREAMS.contains(&substream_id),
+            |selected| selected == substream_id,
+        )
+    }
 
     // Read all pes_packets needed to parse a subtitle.
     fn next_sub_packet(&mut self) -> Option<Result<(f64, Vec<u8>), VobSubError>> {
         profiling::scope!("VobsubParser next_sub_packet");
 
         // Get the `PES` packet containing the first chunk of our subtitle.
-        let first: ps::PesPacket = try_iter!(self.pes_packets.next());
+        // Packets of other streams (audio in a `VOB` file) are skipped.
+        let first: ps::PesPacket = loop {
+            let packet: ps::PesPacket = try_iter!(self.pes_packets.next());
+            if self.is_selected(packet.pes_packet.substream_id) {
+                break packet;
+            }
+            trace!(
+                "Skipping packet of substream 0x{:x}",
+                packet.pes_packet.substream_id
+            );
+        };
 
         // Fetch useful information from our first packet.
         let Some(pts_dts) = first.pes_packet.header_data.pts_dts else {
@@ -484,7 +692,7 @@ impl<'a, Decoder> VobsubParser<'a, Decoder> {
         let wanted =
             (usize::from(first.pes_packet.data[0]) << 8) | usize::from(first.pes_packet.data[1]);
         let mut sub_packet = Vec::with_capacity(wanted);
-        sub_packet.extend_from_slice(first.pes_packet.data);
+        sub_packet.extend_from_slice(&first.pes_packet.data);
 
         // Keep fetching more packets until we have enough.
         while sub_packet.len() < wanted {
@@ -502,7 +710,7 @@ impl<'a, Decoder> VobsubParser<'a, Decoder> {
             }
 
             // Add the extra bytes to our buffer.
-            sub_packet.extend_from_slice(next.pes_packet.data);
+            sub_packet.extend_from_slice(&next.pes_packet.data);
         }
 
         // Check to make sure we didn't get too _many_ bytes.  Again, this
@@ -517,9 +725,32 @@ impl<'a, Decoder> VobsubParser<'a, Decoder> {
         }
         Some(Ok((base_time, sub_packet)))
     }
+
+    /// Decode all the remaining subtitles, with the images decoded in parallel.
+    ///
+    /// The packets are read sequentially, then the subtitles are parsed and their images
+    /// decoded in parallel. The subtitles are returned in the stream order.
+    #[cfg(feature = "rayon")]
+    pub fn par_decode(mut self) -> Vec<Result<(TimeSpan, VobSubIndexedImage), VobSubError>> {
+        use rayon::prelude::*;
+
+        profiling::scope!("VobsubParser par_decode");
+        let packets = std::iter::from_fn(|| self.next_sub_packet()).collect::<Vec<_>>();
+        packets
+            .into_par_iter()
+            .map(|packet| {
+                packet.and_then(|(base_time, sub_packet)| {
+                    subtitle::<(TimeSpan, VobSubIndexedImage), _>(&sub_packet, base_time)
+                })
+            })
+            .collect()
+    }
 }
 
-impl<D> Iterator for VobsubParser<'_, D> {
+impl<'a, D, P> Iterator for VobsubParser<'a, D, P>
+where
+    P: Iterator<Item = Result<ps::PesPacket<'a>, VobSubError>>,
+{
     type Item = Result<(TimeSpan, VobSubIndexedImage), VobSubError>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -532,7 +763,10 @@ impl<D> Iterator for VobsubParser<'_, D> {
         Some(subtitle)
     }
 }
-impl<D> FusedIterator for VobsubParser<'_, D> {}
+impl<'a, D, P> FusedIterator for VobsubParser<'a, D, P> where
+    P: Iterator<Item = Result<ps::PesPacket<'a>, VobSubError>>
+{
+}
 
 #[cfg(test)]
 mod tests {
@@ -694,4 +928,129 @@ mod tests {
             .unwrap();
         assert_eq!(tiny, split);
     }
+
+    #[test]
+    fn parse_subtitles_from_reader() {
+        for path in [
+            "./fixtures/example.sub",
+            "./fixtures/tiny.sub",
+            "./fixtures/tiny-split.sub",
+        ] {
+            let sub = Sub::open(path).unwrap();
+            let expected = sub
+                .subtitles::<TimeSpan>()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            // Use a small buffer, to have packets split between reads.
+            let file = fs::File::open(path).unwrap();
+            let reader = std::io::BufReader::with_capacity(100, file);
+            let streamed = Sub::subtitles_from_reader::<TimeSpan, _>(reader)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(streamed, expected);
+        }
+    }
+
+    #[test]
+    fn parse_subtitles_from_bytes() {
+        let expected = Sub::open("./fixtures/tiny.sub")
+            .unwrap()
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let data = fs::read("./fixtures/tiny.sub").unwrap();
+        let from_bytes = Sub::from_bytes(data.as_slice())
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(from_bytes, expected);
+        let from_reader = Sub::from_reader(data.as_slice())
+            .unwrap()
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(from_reader, expected);
+    }
+
+    #[test]
+    fn parse_subtitles_from_vob() {
+        let expected = Sub::open("./fixtures/tiny.sub")
+            .unwrap()
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let vob = || Sub::subtitles_from_vobs::<TimeSpan, _, _>(["./fixtures/tiny.vob"]).unwrap();
+
+        // Video, navigation and audio packets are skipped.
+        let all = vob().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(all.len(), 2);
+
+        let first = vob()
+            .with_substream(0x20)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(first, expected);
+
+        let second = vob()
+            .with_substream(0x21)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(second.len(), 1);
+        assert!(vob().with_substream(0x22).unwrap().next().is_none());
+
+        // The files are read one after another.
+        let twice = Sub::subtitles_from_vobs::<TimeSpan, _, _>([
+            "./fixtures/tiny.vob",
+            "./fixtures/tiny.vob",
+        ])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(twice.len(), 4);
+
+        assert!(matches!(
+            vob().with_substream(0x80),
+            Err(VobSubError::InvalidSubstream(0x80))
+        ));
+        assert!(matches!(
+            Sub::subtitles_from_vobs::<TimeSpan, _, _>(["./fixtures/missing.vob"]),
+            Err(VobSubError::Io { .. })
+        ));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parse_subtitles_in_parallel() {
+        let sub = Sub::open("./fixtures/example.sub").unwrap();
+        let expected = sub
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let decoded = sub
+            .subtitles::<TimeSpan>()
+            .par_decode()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn parse_subtitles_from_mmap() {
+        let expected = Sub::open("./fixtures/example.sub")
+            .unwrap()
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mapped = Sub::open_mmap("./fixtures/example.sub")
+            .unwrap()
+            .subtitles::<TimeSpan>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(mapped, expected);
+    }
 }